@@ -0,0 +1,194 @@
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+use crate::collision::Hitbox;
+use crate::display::DisplaySettings;
+use crate::food::FoodKind;
+use crate::snake::{Snake, SnakeSegment};
+use crate::ui::{AnimationTimer, PendingTexture, SpriteTextures, FOOD_FRAME_SECONDS};
+
+// Pixels across the generated food circle, and of the dark rim around it
+const FOOD_CIRCLE_SIZE: u32 = 64;
+const FOOD_CIRCLE_RIM: f32 = 8.0;
+// The head is ringed in white and every body segment in black, so the head
+// stands out from the body whatever the colors
+const HEAD_OUTLINE_WIDTH: f32 = 3.0;
+const HEAD_OUTLINE_COLOR: Color = Color::WHITE;
+const BODY_OUTLINE_WIDTH: f32 = 2.0;
+const BODY_OUTLINE_COLOR: Color = Color::BLACK;
+// Just behind the sprite it outlines, in front of a shield's outline
+const CONTRAST_OUTLINE_Z: f32 = -0.05;
+
+// The colorblind mode tells the food and the snake apart by more than their
+// color. The food is drawn as a circle with a dark rim instead of a square,
+// and the head and the body get outlines of opposite brightness. Switched on
+// the menu and kept in the `DisplaySettings`, it applies to what is already
+// on screen right away.
+pub struct AccessibilityPlugin;
+
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(switch_food_shape)
+            .add_system(show_contrast_outlines);
+    }
+}
+
+// An outline behind a head or a body segment, only there in colorblind mode
+#[derive(Component)]
+pub struct ContrastOutline;
+
+// Put on a snake part once it has its `ContrastOutline`
+#[derive(Component)]
+pub struct ContrastOutlined;
+
+// A white disc with a dark rim on a see-through square, tinted with the
+// food's color like the sprite sheet is
+pub fn food_circle_image() -> Image {
+    let size = FOOD_CIRCLE_SIZE;
+    let radius = size as f32 / 2.0;
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let offset = Vec2::new(x as f32 + 0.5, y as f32 + 0.5) - radius;
+            let distance = offset.length();
+            let pixel = if distance > radius {
+                [0, 0, 0, 0]
+            } else if distance > radius - FOOD_CIRCLE_RIM {
+                [0, 0, 0, 255]
+            } else {
+                [255, 255, 255, 255]
+            };
+            data.extend_from_slice(&pixel);
+        }
+    }
+    Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+// Gives food its sprite: the pulsing sprite sheet normally, the circle in
+// colorblind mode. Whichever it had before is taken off.
+pub fn insert_food_sprite(
+    entity: &mut EntityCommands,
+    color: Color,
+    size: Vec2,
+    textures: &SpriteTextures,
+    colorblind: bool,
+) {
+    if colorblind {
+        entity
+            .remove::<TextureAtlasSprite>()
+            .remove::<Handle<TextureAtlas>>()
+            .remove::<AnimationTimer>()
+            .remove::<PendingTexture>()
+            .insert(Sprite {
+                color,
+                custom_size: Some(size),
+                ..default()
+            })
+            .insert(textures.food_circle.clone());
+    } else {
+        entity
+            .remove::<Sprite>()
+            .remove::<Handle<Image>>()
+            .insert(TextureAtlasSprite {
+                color,
+                custom_size: Some(size),
+                ..default()
+            })
+            .insert(textures.food.clone())
+            .insert(AnimationTimer(Timer::from_seconds(
+                FOOD_FRAME_SECONDS,
+                true,
+            )))
+            .insert(PendingTexture);
+    }
+}
+
+// Swaps the sprite of the food already out when the mode is switched. New
+// food gets the right one from `add_sprites`.
+pub fn switch_food_shape(
+    mut commands: Commands,
+    settings: Res<DisplaySettings>,
+    textures: Res<SpriteTextures>,
+    mut applied: Local<bool>,
+    query: Query<(Entity, &Hitbox, AnyOf<(&Sprite, &TextureAtlasSprite)>), With<FoodKind>>,
+) {
+    if settings.colorblind == *applied {
+        return;
+    }
+    *applied = settings.colorblind;
+
+    for (food, hitbox, (sprite, atlas_sprite)) in &query {
+        let color = match (sprite, atlas_sprite) {
+            (Some(sprite), _) => sprite.color,
+            (None, Some(sprite)) => sprite.color,
+            (None, None) => continue,
+        };
+        insert_food_sprite(
+            &mut commands.entity(food),
+            color,
+            **hitbox,
+            &textures,
+            settings.colorblind,
+        );
+    }
+}
+
+// Outlines every head and body segment while the mode is on, the ones that
+// appear later too, and takes the outlines off again when it is switched off
+pub fn show_contrast_outlines(
+    mut commands: Commands,
+    settings: Res<DisplaySettings>,
+    part_query: Query<
+        (Entity, &Hitbox, Option<&Snake>),
+        (
+            Or<(With<Snake>, With<SnakeSegment>)>,
+            Without<ContrastOutlined>,
+        ),
+    >,
+    outlined_query: Query<Entity, With<ContrastOutlined>>,
+    outline_query: Query<Entity, With<ContrastOutline>>,
+) {
+    if !settings.colorblind {
+        for outline in &outline_query {
+            commands.entity(outline).despawn_recursive();
+        }
+        for part in &outlined_query {
+            commands.entity(part).remove::<ContrastOutlined>();
+        }
+        return;
+    }
+
+    for (part, hitbox, head) in &part_query {
+        let (width, color) = if head.is_some() {
+            (HEAD_OUTLINE_WIDTH, HEAD_OUTLINE_COLOR)
+        } else {
+            (BODY_OUTLINE_WIDTH, BODY_OUTLINE_COLOR)
+        };
+        commands
+            .entity(part)
+            .insert(ContrastOutlined)
+            .with_children(|parent| {
+                parent
+                    .spawn_bundle(SpriteBundle {
+                        sprite: Sprite {
+                            color,
+                            custom_size: Some(**hitbox + 2.0 * width),
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(0.0, 0.0, CONTRAST_OUTLINE_Z),
+                        ..default()
+                    })
+                    .insert(ContrastOutline);
+            });
+    }
+}
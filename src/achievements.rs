@@ -0,0 +1,157 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::collision::{grow_snake, FoodEaten, SnakeDied};
+use crate::config::GameConfig;
+use crate::game::{lose_life, FixedUpdate, GameTick};
+use crate::locale::Locale;
+use crate::replay::ReplayPlayer;
+use crate::snake::{SegmentOrder, Snake};
+use crate::state::GameState;
+
+// Foods worth eating in one game for `Achievement::Hungry`
+pub const HUNGRY_FOODS: usize = 10;
+// Segments including the head for `Achievement::LongSnake`
+pub const LONG_SNAKE_LENGTH: usize = 25;
+// Seconds of a single run for `Achievement::Survivor`
+pub const SURVIVOR_SECONDS: f32 = 180.0;
+// A death sooner than this into a run unlocks `Achievement::Unlucky`
+pub const UNLUCKY_SECONDS: f32 = 2.0;
+
+// Unlocks the achievements as they are earned. Replays don't count, they only
+// show what was already played.
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Achievements>()
+            .init_resource::<AchievementProgress>()
+            .add_event::<AchievementUnlocked>()
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(FixedUpdate)
+                    .with_system(track_achievements.after(grow_snake).after(lose_life)),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::Playing).with_system(reset_achievement_progress),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Achievement {
+    Hungry,
+    LongSnake,
+    Survivor,
+    Unlucky,
+}
+
+impl Achievement {
+    // In the order the achievements screen lists them
+    pub const ALL: [Achievement; 4] = [
+        Achievement::Hungry,
+        Achievement::LongSnake,
+        Achievement::Survivor,
+        Achievement::Unlucky,
+    ];
+
+    // What its text is kept under in the `Locale`
+    pub fn key(self) -> &'static str {
+        match self {
+            Achievement::Hungry => "achievement.hungry",
+            Achievement::LongSnake => "achievement.long_snake",
+            Achievement::Survivor => "achievement.survivor",
+            Achievement::Unlucky => "achievement.unlucky",
+        }
+    }
+
+    pub fn title(self, locale: &Locale) -> String {
+        locale.get(self.key()).to_string()
+    }
+
+    pub fn description(self, locale: &Locale) -> String {
+        let key = format!("{}.description", self.key());
+        match self {
+            Achievement::Hungry => locale.text(&key, &[("count", &HUNGRY_FOODS)]),
+            Achievement::LongSnake => locale.text(&key, &[("length", &LONG_SNAKE_LENGTH)]),
+            Achievement::Survivor => locale.text(
+                &key,
+                &[("minutes", &format!("{:.0}", SURVIVOR_SECONDS / 60.0))],
+            ),
+            Achievement::Unlucky => {
+                locale.text(&key, &[("seconds", &format!("{UNLUCKY_SECONDS:.0}"))])
+            }
+        }
+    }
+}
+
+// The achievements unlocked so far, in the order they were. Kept on disk by
+// the `PersistencePlugin`.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Achievements {
+    pub unlocked: Vec<Achievement>,
+}
+
+impl Achievements {
+    pub fn is_unlocked(&self, achievement: Achievement) -> bool {
+        self.unlocked.contains(&achievement)
+    }
+}
+
+// Sent once for every achievement, when it is first unlocked
+pub struct AchievementUnlocked {
+    pub achievement: Achievement,
+}
+
+// What the current game has done towards the achievements so far that
+// can't be read off the game itself
+#[derive(Default)]
+pub struct AchievementProgress {
+    pub foods: usize,
+}
+
+pub fn reset_achievement_progress(mut progress: ResMut<AchievementProgress>) {
+    *progress = AchievementProgress::default();
+}
+
+pub fn track_achievements(
+    mut food_events: EventReader<FoodEaten>,
+    mut died_events: EventReader<SnakeDied>,
+    mut achievements: ResMut<Achievements>,
+    mut progress: ResMut<AchievementProgress>,
+    mut unlocked_events: EventWriter<AchievementUnlocked>,
+    snake_query: Query<&SegmentOrder, With<Snake>>,
+    player: Option<Res<ReplayPlayer>>,
+    tick: Res<GameTick>,
+    config: Res<GameConfig>,
+) {
+    if player.is_some() {
+        return;
+    }
+    progress.foods += food_events
+        .iter()
+        .filter(|event| event.kind.grows())
+        .count();
+    let seconds = **tick as f32 * config.time_step;
+    let longest = snake_query
+        .iter()
+        .map(|segments| segments.len() + 1)
+        .max()
+        .unwrap_or(0);
+    let died = died_events.iter().count() > 0;
+
+    let earned = [
+        (Achievement::Hungry, progress.foods >= HUNGRY_FOODS),
+        (Achievement::LongSnake, longest >= LONG_SNAKE_LENGTH),
+        (Achievement::Survivor, seconds >= SURVIVOR_SECONDS),
+        (Achievement::Unlucky, died && seconds <= UNLUCKY_SECONDS),
+    ];
+    // Only touched for a new unlock, since every change is saved to disk
+    for (achievement, earned) in earned {
+        if earned && !achievements.is_unlocked(achievement) {
+            achievements.unlocked.push(achievement);
+            unlocked_events.send(AchievementUnlocked { achievement });
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use bevy::{audio::AudioSink, prelude::*};
+
+use crate::collision::FoodEaten;
+use crate::config::GameConfig;
+use crate::input::InitialsEntry;
+use crate::state::GameState;
+
+// Plays the sound effects and the music. Needs the `AudioPlugin` from
+// `DefaultPlugins`.
+pub struct SnakeAudioPlugin;
+
+impl Plugin for SnakeAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Muted>()
+            .add_startup_system(load_sounds)
+            .add_system(play_chomp)
+            .add_system(toggle_mute)
+            .add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(play_crash))
+            .add_system_set(SystemSet::on_enter(GameState::Menu).with_system(play_menu_music))
+            .add_system_set(SystemSet::on_exit(GameState::Menu).with_system(stop_music))
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(play_game_music))
+            .add_system_set(SystemSet::on_exit(GameState::Playing).with_system(stop_music));
+    }
+}
+
+// The sound effects, loaded once at startup
+pub struct SoundEffects {
+    pub chomp: Handle<AudioSource>,
+    pub crash: Handle<AudioSource>,
+}
+
+// The music tracks and the sink of the one playing, if any
+pub struct MusicController {
+    pub menu: Handle<AudioSource>,
+    pub game: Handle<AudioSource>,
+    pub sink: Option<Handle<AudioSink>>,
+}
+
+// Silences the music and the sound effects, toggled with M
+#[derive(Default, Deref, DerefMut)]
+pub struct Muted(pub bool);
+
+impl Muted {
+    // The volume to play at given the configured one
+    pub fn volume(&self, volume: f32) -> f32 {
+        if self.0 {
+            0.0
+        } else {
+            volume
+        }
+    }
+}
+
+pub fn load_sounds(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(SoundEffects {
+        chomp: asset_server.load("sounds/chomp.wav"),
+        crash: asset_server.load("sounds/crash.wav"),
+    });
+    commands.insert_resource(MusicController {
+        menu: asset_server.load("sounds/menu_music.wav"),
+        game: asset_server.load("sounds/game_music.wav"),
+        sink: None,
+    });
+}
+
+// Every `play` gets its own sink, so chomps in quick succession overlap
+// instead of cutting each other off
+pub fn play_chomp(
+    mut food_events: EventReader<FoodEaten>,
+    audio: Res<Audio>,
+    sounds: Res<SoundEffects>,
+    config: Res<GameConfig>,
+    muted: Res<Muted>,
+) {
+    for _ in food_events.iter() {
+        audio.play_with_settings(
+            sounds.chomp.clone(),
+            PlaybackSettings::ONCE.with_volume(muted.volume(config.master_volume)),
+        );
+    }
+}
+
+pub fn play_crash(
+    audio: Res<Audio>,
+    sounds: Res<SoundEffects>,
+    config: Res<GameConfig>,
+    muted: Res<Muted>,
+) {
+    audio.play_with_settings(
+        sounds.crash.clone(),
+        PlaybackSettings::ONCE.with_volume(muted.volume(config.master_volume)),
+    );
+}
+
+pub fn play_menu_music(
+    audio: Res<Audio>,
+    sinks: Res<Assets<AudioSink>>,
+    mut music: ResMut<MusicController>,
+    config: Res<GameConfig>,
+    muted: Res<Muted>,
+) {
+    let sink = play_music(
+        &audio,
+        music.menu.clone(),
+        muted.volume(config.music_volume),
+    );
+    music.sink = Some(sinks.get_handle(sink));
+}
+
+pub fn play_game_music(
+    audio: Res<Audio>,
+    sinks: Res<Assets<AudioSink>>,
+    mut music: ResMut<MusicController>,
+    config: Res<GameConfig>,
+    muted: Res<Muted>,
+) {
+    let sink = play_music(
+        &audio,
+        music.game.clone(),
+        muted.volume(config.music_volume),
+    );
+    music.sink = Some(sinks.get_handle(sink));
+}
+
+fn play_music(audio: &Audio, track: Handle<AudioSource>, volume: f32) -> Handle<AudioSink> {
+    audio.play_with_settings(track, PlaybackSettings::LOOP.with_volume(volume))
+}
+
+pub fn stop_music(sinks: Res<Assets<AudioSink>>, mut music: ResMut<MusicController>) {
+    if let Some(sink) = music.sink.take().and_then(|sink| sinks.get(&sink)) {
+        sink.stop();
+    }
+}
+
+// Changes the volume of the music right away, sound effects already playing
+// are short enough to be left alone
+pub fn toggle_mute(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut muted: ResMut<Muted>,
+    sinks: Res<Assets<AudioSink>>,
+    music: Res<MusicController>,
+    config: Res<GameConfig>,
+    initials: Option<Res<InitialsEntry>>,
+) {
+    // M is just a letter while typing initials
+    let typing = initials.is_some_and(|initials| initials.is_some());
+    if typing || !keyboard_input.just_pressed(KeyCode::M) {
+        return;
+    }
+
+    **muted = !**muted;
+    if let Some(sink) = music.sink.as_ref().and_then(|sink| sinks.get(sink)) {
+        sink.set_volume(muted.volume(config.music_volume));
+    }
+}
@@ -0,0 +1,83 @@
+use bevy::prelude::*;
+
+use crate::config::GameConfig;
+use crate::theme::Theme;
+use crate::walls::ArenaBounds;
+
+// Behind the snake, the food and the walls, which all sit at z 0
+const CHECKERBOARD_Z: f32 = -1.0;
+// How much further back than usual the camera sees, for the checkerboard
+pub const BACKGROUND_DEPTH: f32 = 2.0;
+
+// Shades the area inside the walls in a checkerboard of grid cells, so the
+// snake's position is easy to read
+pub struct CheckerboardPlugin;
+
+impl Plugin for CheckerboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(spawn_checkerboard);
+    }
+}
+
+// One square of the checkerboard
+#[derive(Component)]
+pub struct CheckerboardTile;
+
+// The center, size and shade of every square. They are
+// `GameConfig::snake_size` across and centered on the cells, so they line up
+// with the grid the snake moves on. The ones along the walls are cut off
+// where the walls start, if the arena isn't a whole number of cells across.
+pub fn checkerboard_tiles(config: &GameConfig, bounds: &ArenaBounds) -> Vec<(Vec2, Vec2, bool)> {
+    let (inner_min, inner_max) = bounds.inside(Vec2::ZERO);
+    let cell = config.snake_size;
+    let first = (inner_min / cell).round().as_ivec2();
+    let last = (inner_max / cell).round().as_ivec2();
+
+    let mut tiles = Vec::new();
+    for y in first.y..=last.y {
+        for x in first.x..=last.x {
+            let center = IVec2::new(x, y).as_vec2() * cell;
+            let min = (center - cell / 2.0).max(inner_min);
+            let max = (center + cell / 2.0).min(inner_max);
+            if min.cmplt(max).all() {
+                tiles.push(((min + max) / 2.0, max - min, (x + y).rem_euclid(2) == 1));
+            }
+        }
+    }
+    tiles
+}
+
+// Lays the checkerboard out again whenever the arena changes size or the
+// theme changes, the first time included
+pub fn spawn_checkerboard(
+    mut commands: Commands,
+    bounds: Res<ArenaBounds>,
+    config: Res<GameConfig>,
+    theme: Res<Theme>,
+    tile_query: Query<Entity, With<CheckerboardTile>>,
+) {
+    if !bounds.is_changed() && !theme.is_changed() {
+        return;
+    }
+
+    for tile in &tile_query {
+        commands.entity(tile).despawn();
+    }
+    for (center, size, alternate) in checkerboard_tiles(&config, &bounds) {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: if alternate {
+                        theme.grid_alt
+                    } else {
+                        theme.grid
+                    },
+                    custom_size: Some(size),
+                    ..default()
+                },
+                transform: Transform::from_translation(center.extend(CHECKERBOARD_Z)),
+                ..default()
+            })
+            .insert(CheckerboardTile);
+    }
+}
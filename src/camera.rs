@@ -0,0 +1,114 @@
+use bevy::{prelude::*, render::camera::ScalingMode};
+use rand::Rng;
+
+use crate::collision::GameOverEvent;
+use crate::config::GameConfig;
+use crate::ui::WINDOW_MARGIN;
+use crate::walls::ArenaBounds;
+
+const SHAKE_SECONDS: f32 = 0.35;
+// Largest offset of the camera in units, reached right when the shake starts
+const SHAKE_MAGNITUDE: f32 = 8.0;
+
+// Shakes the camera when the snake dies
+pub struct CameraShakePlugin;
+
+impl Plugin for CameraShakePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraShake>()
+            .add_system(start_camera_shake)
+            .add_system(shake_camera.after(start_camera_shake));
+    }
+}
+
+// Zooms the camera out whenever the window is too small for the arena, so
+// the walls are always in sight, centered between bars of empty space. The
+// HUD is laid out by the UI, so it keeps its size either way.
+pub struct ArenaCameraPlugin;
+
+impl Plugin for ArenaCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(fit_camera_to_arena);
+    }
+}
+
+pub struct CameraShake {
+    pub remaining: Timer,
+    pub magnitude: f32,
+    // Where the camera was before the shake started, put back once it ends
+    pub origin: Option<Vec3>,
+}
+
+impl Default for CameraShake {
+    fn default() -> Self {
+        let mut remaining = Timer::from_seconds(SHAKE_SECONDS, false);
+        // Not shaking until something starts it
+        remaining.tick(remaining.duration());
+        CameraShake {
+            remaining,
+            magnitude: SHAKE_MAGNITUDE,
+            origin: None,
+        }
+    }
+}
+
+pub fn start_camera_shake(
+    mut game_over_events: EventReader<GameOverEvent>,
+    mut shake: ResMut<CameraShake>,
+    config: Res<GameConfig>,
+) {
+    if game_over_events.iter().count() == 0 || !config.screen_shake {
+        return;
+    }
+    // Starting over while already shaking keeps the original `origin`
+    shake.remaining.reset();
+}
+
+// The jitter shrinks as the shake runs out, and is computed from `origin`
+// every frame so it can't drift
+pub fn shake_camera(
+    time: Res<Time>,
+    mut shake: ResMut<CameraShake>,
+    mut camera_query: Query<&mut Transform, With<Camera2d>>,
+) {
+    let Ok(mut transform) = camera_query.get_single_mut() else {
+        return;
+    };
+
+    if shake.remaining.finished() {
+        if let Some(origin) = shake.origin.take() {
+            transform.translation = origin;
+        }
+        return;
+    }
+
+    shake.remaining.tick(time.delta());
+    let origin = *shake.origin.get_or_insert(transform.translation);
+    let strength = shake.magnitude * (1.0 - shake.remaining.percent());
+    let mut rng = rand::thread_rng();
+    let jitter = Vec2::new(rng.gen_range(-1.0..=1.0), rng.gen_range(-1.0..=1.0)) * strength;
+    transform.translation = origin + jitter.extend(0.0);
+}
+
+// The arena follows a resized window down to its smallest size, so this only
+// zooms out below that. The camera is centered on the arena, like the shake
+// expects it to be when it starts.
+pub fn fit_camera_to_arena(
+    bounds: Res<ArenaBounds>,
+    added_query: Query<(), Added<Camera2d>>,
+    mut camera_query: Query<(&mut OrthographicProjection, &mut Transform), With<Camera2d>>,
+) {
+    if !bounds.is_changed() && added_query.is_empty() {
+        return;
+    }
+    // The same room around the arena `build_app` sizes the window with
+    let room = bounds.thickness + 2.0 * WINDOW_MARGIN;
+    let center = Vec2::new(bounds.left + bounds.right, bounds.bottom + bounds.top) / 2.0;
+    for (mut projection, mut transform) in &mut camera_query {
+        projection.scaling_mode = ScalingMode::Auto {
+            min_width: bounds.width() + room,
+            min_height: bounds.height() + room,
+        };
+        transform.translation = center.extend(transform.translation.z);
+    }
+}
@@ -0,0 +1,107 @@
+use crate::config::GameConfig;
+
+pub const USAGE: &str = "\
+Usage: snake [OPTIONS]
+
+Options:
+    --width <UNITS>     Width of the arena between the side walls
+    --height <UNITS>    Height of the arena between the top and bottom walls
+    --speed <UNITS>     How far the snake moves per second
+    --seed <NUMBER>     Seed for the food placement, a new one every run when left out
+    --casual            Keep the snake inside the walls instead of dying on them
+    --bounce            Bounce the snake off the walls instead of dying on them
+    --continuous        Let the snake glide freely instead of moving cell by cell
+    --level <NAME>      Play on a map from assets/levels, or on the map file at NAME
+    --replay <PATH>     Watch a recorded run, hold F to fast-forward
+    --debug-colliders   Outline the collision boxes, F4 toggles them too
+    --runs <PATH>       Add every finished run to the CSV file at PATH
+    --no-telemetry      Don't keep the finished runs in a file at all
+    --help              Print this message";
+
+// Settings given on the command line, each overriding `config.ron`
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CliArgs {
+    pub width: Option<f32>,
+    pub height: Option<f32>,
+    pub speed: Option<f32>,
+    pub seed: Option<u64>,
+    pub level: Option<String>,
+    pub replay: Option<String>,
+    pub runs: Option<String>,
+    pub no_telemetry: bool,
+    pub help: bool,
+}
+
+impl CliArgs {
+    // Parses the arguments that follow the program name. The error says
+    // which argument was wrong, without the usage text.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<CliArgs, String> {
+        let mut parsed = CliArgs::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--width" => parsed.width = Some(positive(&arg, args.next())?),
+                "--height" => parsed.height = Some(positive(&arg, args.next())?),
+                "--speed" => parsed.speed = Some(positive(&arg, args.next())?),
+                "--seed" => {
+                    let value = value(&arg, args.next())?;
+                    let seed = value
+                        .parse()
+                        .map_err(|_| format!("`{arg}` expects a whole number, got `{value}`"))?;
+                    parsed.seed = Some(seed);
+                }
+                "--level" => parsed.level = Some(value(&arg, args.next())?),
+                "--replay" => parsed.replay = Some(value(&arg, args.next())?),
+                "--runs" => parsed.runs = Some(value(&arg, args.next())?),
+                "--no-telemetry" => parsed.no_telemetry = true,
+                // Read on their own by `WallMode`, `MovementMode` and
+                // `ColliderDebug`
+                "--casual" | "--bounce" | "--continuous" | "--debug-colliders" => {}
+                "--help" | "-h" => parsed.help = true,
+                _ => return Err(format!("unknown argument `{arg}`")),
+            }
+        }
+        Ok(parsed)
+    }
+
+    // Resizes the arena around the origin and sets the speed, the seed and
+    // where the runs go. The speed is given in units per second, so in grid
+    // mode it is turned into cells.
+    pub fn apply(&self, config: &mut GameConfig) {
+        if self.seed.is_some() {
+            config.seed = self.seed;
+        }
+        if self.runs.is_some() {
+            config.runs_file = self.runs.clone();
+        }
+        if self.no_telemetry {
+            config.record_runs = false;
+        }
+        if let Some(width) = self.width {
+            config.left_wall = -width / 2.0;
+            config.right_wall = width / 2.0;
+        }
+        if let Some(height) = self.height {
+            config.bottom_wall = -height / 2.0;
+            config.top_wall = height / 2.0;
+        }
+        if let Some(speed) = self.speed {
+            config.snake_speed = speed;
+            config.grid_steps_per_second = speed / config.snake_size.x;
+        }
+    }
+}
+
+fn value(flag: &str, value: Option<String>) -> Result<String, String> {
+    value.ok_or_else(|| format!("`{flag}` needs a value"))
+}
+
+fn positive(flag: &str, value_arg: Option<String>) -> Result<f32, String> {
+    let value = value(flag, value_arg)?;
+    match value.parse::<f32>() {
+        Ok(number) if number.is_finite() && number > 0.0 => Ok(number),
+        _ => Err(format!(
+            "`{flag}` expects a number greater than zero, got `{value}`"
+        )),
+    }
+}
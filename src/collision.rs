@@ -0,0 +1,833 @@
+use bevy::{
+    prelude::*,
+    sprite::collide_aabb::{collide, Collision},
+};
+use rand::Rng;
+
+use crate::config::GameConfig;
+use crate::crates::{smashing, Crate, CrateHit, Hammer, HAMMER_SMASHES};
+use crate::food::{
+    free_food_position, BonusCountdown, Food, FoodKind, GameRng, Magnet, SpawnAnimation,
+    BONUS_FOOD_EVERY, BONUS_FOOD_POINTS, BONUS_FOOD_SCALE, BONUS_FOOD_SECONDS, GHOST_CHANCE,
+    GHOST_SECONDS, GOLDEN_FOOD_CHANCE, GOLDEN_FOOD_FACTOR, GOLDEN_FOOD_SECONDS, HAMMER_CHANCE,
+    MAGNET_CHANCE, MAGNET_SECONDS, POISON_FOOD_CHANCE, POISON_FOOD_SEGMENTS, SHIELD_CHANCE,
+    SLOW_MOTION_CHANCE, SLOW_MOTION_FACTOR, SLOW_MOTION_SECONDS,
+};
+use crate::game::{Combo, FixedUpdate, ScoreMultiplier, Scoreboard};
+use crate::hunter::Hunter;
+use crate::portal::{Portal, Teleported};
+use crate::snake::{
+    cell_center, grid_cell, point_along_trail, reverse_trail, Boost, Direction, DirectionQueue,
+    GameSpeed, GhostMode, MovementMode, NextDirection, Player, SegmentOrder, Shield, Snake,
+    SnakeSegment, SnakeTrail, SpeedModifier, SpeedRamp, Velocity,
+};
+use crate::spawning::{spawn_bonus_food, spawn_food, spawn_food_of_kind, spawn_segment};
+use crate::walls::{ArenaBounds, InteriorWall, Obstacle, WallLocation, WallMode};
+
+// What the snakes run into: food, their own and each other's bodies, the
+// walls and the obstacles
+pub struct CollisionPlugin;
+
+impl Plugin for CollisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<FoodEaten>()
+            .add_event::<WallHit>()
+            .add_event::<CollisionEvent>()
+            .add_event::<FatalCollision>()
+            .add_event::<SnakeDied>()
+            .add_event::<GameOverEvent>()
+            .init_resource::<LastCollision>()
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(FixedUpdate)
+                    .with_system(check_for_collisions)
+                    .with_system(bounce_off_walls.after(check_for_collisions))
+                    .with_system(
+                        check_for_self_collision
+                            .after(check_for_collisions)
+                            .after(bounce_off_walls),
+                    )
+                    .with_system(use_shield.after(check_for_self_collision))
+                    .with_system(score_food.after(check_for_collisions))
+                    .with_system(grow_snake.after(check_for_collisions))
+                    .with_system(shrink_snake.after(check_for_collisions))
+                    .with_system(respawn_food.after(grow_snake).after(shrink_snake)),
+            );
+    }
+}
+
+#[derive(Component)]
+pub struct Collider;
+
+// The size of the box the snake, the food and the walls collide with. The
+// sprites are drawn at the same size.
+#[derive(Component, Clone, Copy, Deref, DerefMut)]
+pub struct Hitbox(pub Vec2);
+
+// Sent when a head reaches a piece of food, before it is despawned. `snake`
+// is the head that ate it.
+pub struct FoodEaten {
+    pub snake: Entity,
+    pub food: Entity,
+    pub position: Vec3,
+    pub kind: FoodKind,
+}
+
+// Sent every tick the `snake` head touches a wall, whether or not it is
+// lethal. `side` is the side of the wall the head hit.
+pub struct WallHit {
+    pub snake: Entity,
+    pub side: Collision,
+}
+
+// Sent every tick a `snake` head touches anything it collides with, with the
+// side of it the head touched. Walls are touched by a ghost going through
+// them too. Whatever comes of it is sent on its own, as a `FoodEaten`,
+// `WallHit` or `FatalCollision`.
+#[derive(Debug)]
+pub enum CollisionEvent {
+    Food {
+        snake: Entity,
+        food: Entity,
+        side: Collision,
+    },
+    Wall {
+        snake: Entity,
+        wall: Entity,
+        side: Collision,
+    },
+    // An obstacle, a wall inside the arena or the hunter
+    Obstacle {
+        snake: Entity,
+        obstacle: Entity,
+        side: Collision,
+    },
+}
+
+// What a snake died of, or what ended the run when the time ran out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeathCause {
+    // One of the arena's walls, outside of any level
+    Wall(WallLocation),
+    // An obstacle or an interior wall
+    Obstacle,
+    // Its own body, or another snake
+    SelfCollision,
+    // Poison eaten with no tail left to give up
+    Poison,
+    // Under a ring of the shrinking arena
+    Crushed,
+    // Caught by the `Hunter`
+    Hunter,
+    // The hunger meter ran out, see `Hunger`
+    Starved,
+    TimeUp,
+}
+
+impl DeathCause {
+    pub fn label(self) -> &'static str {
+        match self {
+            DeathCause::Wall(_) => "wall",
+            DeathCause::Obstacle => "obstacle",
+            DeathCause::SelfCollision => "self_collision",
+            DeathCause::Poison => "poison",
+            DeathCause::Crushed => "crushed",
+            DeathCause::Hunter => "hunter",
+            DeathCause::Starved => "starved",
+            DeathCause::TimeUp => "time_up",
+        }
+    }
+}
+
+// Sent when the `snake` head runs into something that kills it, so a
+// `Shield` can be used up first
+pub struct FatalCollision {
+    pub snake: Entity,
+    pub cause: DeathCause,
+}
+
+// Sent when the `snake` head dies, so a life can be used up before the run
+// ends, see `lose_life`
+pub struct SnakeDied {
+    pub snake: Entity,
+    pub cause: DeathCause,
+}
+
+// Sent when the `snake` head dies with no lives left
+pub struct GameOverEvent {
+    pub snake: Entity,
+    pub cause: DeathCause,
+}
+
+// What `collide` last reported, for the collider outlines to show
+#[derive(Default)]
+pub struct LastCollision(pub Option<(Entity, Collision)>);
+
+// `Collision` can be neither copied nor cloned
+fn same_side(side: &Collision) -> Collision {
+    match side {
+        Collision::Left => Collision::Left,
+        Collision::Right => Collision::Right,
+        Collision::Top => Collision::Top,
+        Collision::Bottom => Collision::Bottom,
+        Collision::Inside => Collision::Inside,
+    }
+}
+
+// Whether a `size` box moving from `start` to `end` in a tick touches the
+// `target_size` box at `target`, and on which side of it. Checked along the
+// whole way, so a fast box can't skip over the target in between two ticks.
+pub fn swept_collide(
+    start: Vec3,
+    end: Vec3,
+    size: Vec2,
+    target: Vec3,
+    target_size: Vec2,
+) -> Option<Collision> {
+    if let Some(collision) = collide(end, size, target, target_size) {
+        return Some(collision);
+    }
+
+    // The box's center against the target grown by half of the box
+    let half = (size + target_size) / 2.0;
+    let (min, max) = (target.truncate() - half, target.truncate() + half);
+    let (start, motion) = (start.truncate(), (end - start).truncate());
+    let (mut enter, mut exit) = (0.0_f32, 1.0_f32);
+    let mut entered_on = None;
+    for axis in 0..2 {
+        if motion[axis] == 0.0 {
+            if start[axis] <= min[axis] || start[axis] >= max[axis] {
+                return None;
+            }
+            continue;
+        }
+        let near = (min[axis] - start[axis]) / motion[axis];
+        let far = (max[axis] - start[axis]) / motion[axis];
+        let (near, far) = (near.min(far), near.max(far));
+        if near > enter {
+            enter = near;
+            entered_on = Some(axis);
+        }
+        exit = exit.min(far);
+        if enter >= exit {
+            return None;
+        }
+    }
+    // Starting out inside was already caught the tick before
+    match entered_on? {
+        0 if motion.x > 0.0 => Some(Collision::Left),
+        0 => Some(Collision::Right),
+        _ if motion.y > 0.0 => Some(Collision::Bottom),
+        _ => Some(Collision::Top),
+    }
+}
+
+// The smallest push along one axis that takes a `size` box at `position`
+// out of the `target_size` box at `target`, and the side of the target it
+// comes out on. Boxes that only touch don't overlap. On an exact corner,
+// where the boxes overlap as far both ways, the box is pushed out sideways.
+pub fn resolve_overlap(
+    position: Vec3,
+    size: Vec2,
+    target: Vec3,
+    target_size: Vec2,
+) -> Option<(Vec2, Collision)> {
+    let offset = (position - target).truncate();
+    let overlap = (size + target_size) / 2.0 - offset.abs();
+    if overlap.x <= 0.0 || overlap.y <= 0.0 {
+        return None;
+    }
+    Some(if overlap.x <= overlap.y {
+        if offset.x < 0.0 {
+            (Vec2::new(-overlap.x, 0.0), Collision::Left)
+        } else {
+            (Vec2::new(overlap.x, 0.0), Collision::Right)
+        }
+    } else if offset.y < 0.0 {
+        (Vec2::new(0.0, -overlap.y), Collision::Bottom)
+    } else {
+        (Vec2::new(0.0, overlap.y), Collision::Top)
+    })
+}
+
+// Free movement checks the way the head came along this tick, see
+// `swept_collide`. A head moving freely into a wall is pushed back out of it
+// by `resolve_overlap` before it is decided whether that kills it, so the
+// next wall is checked from where the head ends up. A head that just came
+// out of a portal is checked from the exit. On the grid the head stays on
+// its cell.
+pub fn check_for_collisions(
+    mut snake_query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &Hitbox,
+            Option<&Velocity>,
+            &Boost,
+            Option<&Hammer>,
+        ),
+        With<Snake>,
+    >,
+    collider_query: Query<
+        (
+            Entity,
+            &Transform,
+            &Hitbox,
+            Option<&FoodKind>,
+            Option<&Obstacle>,
+            Option<&InteriorWall>,
+            Option<&SpawnAnimation>,
+            Option<&WallLocation>,
+            Option<&Hunter>,
+            Option<&Crate>,
+        ),
+        (With<Collider>, Without<Snake>),
+    >,
+    config: Res<GameConfig>,
+    wall_mode: Res<WallMode>,
+    movement_mode: Res<MovementMode>,
+    ghost: Res<GhostMode>,
+    teleported: Res<Teleported>,
+    mut collision_events: EventWriter<CollisionEvent>,
+    mut food_events: EventWriter<FoodEaten>,
+    mut wall_events: EventWriter<WallHit>,
+    mut fatal_events: EventWriter<FatalCollision>,
+    mut crate_events: EventWriter<CrateHit>,
+    mut last_collision: ResMut<LastCollision>,
+) {
+    // Two heads reaching the same food in the same tick, only the first eats it
+    let mut eaten = Vec::new();
+    for (snake, mut snake_transform, snake_hitbox, velocity, boost, hammer) in &mut snake_query {
+        let smashing = smashing(boost, hammer);
+        let mut end = snake_transform.translation;
+        let start = match velocity {
+            Some(velocity) if !teleported.contains(&snake) => {
+                end - (**velocity * config.time_step).extend(0.0)
+            }
+            _ => end,
+        };
+        for (
+            collider_entity,
+            transform,
+            hitbox,
+            maybe_food,
+            maybe_obstacle,
+            maybe_interior,
+            spawning,
+            location,
+            maybe_hunter,
+            maybe_crate,
+        ) in &collider_query
+        {
+            // Food still popping up isn't there to run into yet, and a hunter
+            // that crashed is gone by the end of the tick
+            if spawning.is_some_and(|animation| !animation.edible())
+                || maybe_hunter.is_some_and(|hunter| hunter.crashed)
+            {
+                continue;
+            }
+            let collision =
+                swept_collide(start, end, **snake_hitbox, transform.translation, **hitbox);
+            let hazard =
+                maybe_obstacle.is_some() || maybe_interior.is_some() || maybe_hunter.is_some();
+            if let Some(side) = &collision {
+                last_collision.0 = Some((collider_entity, same_side(side)));
+                let side = same_side(side);
+                collision_events.send(if maybe_food.is_some() {
+                    CollisionEvent::Food {
+                        snake,
+                        food: collider_entity,
+                        side,
+                    }
+                } else if hazard {
+                    CollisionEvent::Obstacle {
+                        snake,
+                        obstacle: collider_entity,
+                        side,
+                    }
+                } else {
+                    CollisionEvent::Wall {
+                        snake,
+                        wall: collider_entity,
+                        side,
+                    }
+                });
+            }
+            if let Some(kind) = maybe_food {
+                // On the grid the food is only eaten when the head is right on its cell
+                let reached = if *movement_mode == MovementMode::Grid {
+                    grid_cell(&config, snake_transform.translation)
+                        == grid_cell(&config, transform.translation)
+                } else {
+                    collision.is_some()
+                };
+                if reached && !eaten.contains(&collider_entity) {
+                    eaten.push(collider_entity);
+                    food_events.send(FoodEaten {
+                        snake,
+                        food: collider_entity,
+                        position: transform.translation,
+                        kind: *kind,
+                    });
+                }
+            } else if let (Some(_), Some(hammer)) = (maybe_crate, smashing) {
+                // A crate gives way to a smashing head instead of killing it
+                if collision.is_some() {
+                    crate_events.send(CrateHit {
+                        snake,
+                        crate_entity: collider_entity,
+                        hammer,
+                    });
+                }
+            } else if hazard {
+                if collision.is_some() {
+                    let cause = if maybe_hunter.is_some() {
+                        DeathCause::Hunter
+                    } else {
+                        DeathCause::Obstacle
+                    };
+                    fatal_events.send(FatalCollision { snake, cause });
+                }
+            } else if ghost.is_active() {
+                // Anything else the snake can collide with is a wall, which a
+                // ghost goes right through
+            } else if let Some(mut side) = collision {
+                if let Some((push, out)) =
+                    resolve_overlap(end, **snake_hitbox, transform.translation, **hitbox)
+                {
+                    side = out;
+                    if *movement_mode == MovementMode::Continuous {
+                        end += push.extend(0.0);
+                    }
+                }
+                let wall = location.copied().or_else(|| WallLocation::hit_on(&side));
+                wall_events.send(WallHit { snake, side });
+                if *wall_mode == WallMode::Lethal {
+                    fatal_events.send(FatalCollision {
+                        snake,
+                        cause: DeathCause::Wall(wall.unwrap_or(WallLocation::Left)),
+                    });
+                }
+            }
+        }
+        if end != snake_transform.translation {
+            snake_transform.translation = end;
+        }
+    }
+}
+
+// On bouncy walls, turns the part of the velocity taking a head into the
+// wall it hit around, and puts the head back inside
+pub fn bounce_off_walls(
+    mut wall_events: EventReader<WallHit>,
+    mut snake_query: Query<
+        (
+            &mut Transform,
+            &mut Velocity,
+            &mut Direction,
+            &mut NextDirection,
+        ),
+        With<Snake>,
+    >,
+    config: Res<GameConfig>,
+    bounds: Res<ArenaBounds>,
+    wall_mode: Res<WallMode>,
+) {
+    if *wall_mode != WallMode::Bounce {
+        return;
+    }
+
+    for event in wall_events.iter() {
+        let Ok((mut transform, mut velocity, mut direction, mut next_direction)) =
+            snake_query.get_mut(event.snake)
+        else {
+            continue;
+        };
+        // `side` is the side of the wall the head reached into
+        match event.side {
+            Collision::Left if velocity.x > 0.0 => velocity.x = -velocity.x,
+            Collision::Right if velocity.x < 0.0 => velocity.x = -velocity.x,
+            Collision::Bottom if velocity.y > 0.0 => velocity.y = -velocity.y,
+            Collision::Top if velocity.y < 0.0 => velocity.y = -velocity.y,
+            _ => {}
+        }
+        if **velocity != Vec2::ZERO {
+            *direction = Direction::from_vec2(**velocity);
+            **next_direction = *direction;
+        }
+        let (min, max) = bounds.inside(config.snake_size);
+        let inside = transform.translation.truncate().clamp(min, max);
+        transform.translation = inside.extend(transform.translation.z);
+    }
+}
+
+// Despawns the eaten food and counts it for whoever ate it, see
+// `award_points`. Food worth points also speeds the snakes up, poison
+// takes a point off instead. The pickups aren't worth anything, they only
+// start their power-up.
+pub fn score_food(
+    mut commands: Commands,
+    mut food_events: EventReader<FoodEaten>,
+    mut scoreboard: ResMut<Scoreboard>,
+    (mut multiplier, mut combo): (ResMut<ScoreMultiplier>, ResMut<Combo>),
+    mut speed: ResMut<GameSpeed>,
+    (mut modifier, mut ghost, mut magnet): (
+        ResMut<SpeedModifier>,
+        ResMut<GhostMode>,
+        ResMut<Magnet>,
+    ),
+    mut snake_query: Query<(&Player, &mut Shield, &mut Hammer), With<Snake>>,
+    ramp: Res<SpeedRamp>,
+    config: Res<GameConfig>,
+) {
+    for event in food_events.iter() {
+        commands.entity(event.food).despawn();
+        let Ok((player, mut shield, mut hammer)) = snake_query.get_mut(event.snake) else {
+            continue;
+        };
+        let score = scoreboard.score_mut(*player);
+        if let Some(points) = award_points(event.kind, &mut multiplier, &mut combo) {
+            *score += points;
+            speed.ramp_up(*score, &ramp);
+            continue;
+        }
+        match event.kind {
+            FoodKind::Poison => {
+                *score = score.saturating_sub(1);
+            }
+            FoodKind::SlowMotion => {
+                modifier.slow_down(SLOW_MOTION_FACTOR, SLOW_MOTION_SECONDS);
+            }
+            FoodKind::Ghost => {
+                ghost.start(GHOST_SECONDS);
+            }
+            FoodKind::Magnet => {
+                magnet.start(config.magnet_radius, MAGNET_SECONDS);
+            }
+            FoodKind::Shield => {
+                **shield = (**shield + 1).min(config.max_shields);
+            }
+            FoodKind::Hammer => {
+                **hammer += HAMMER_SMASHES;
+            }
+            FoodKind::Normal | FoodKind::Golden | FoodKind::Bonus => {}
+        }
+    }
+}
+
+// The points eating `kind` scores, times the `ScoreMultiplier` and the
+// `Combo` it extends, or `None` for the food that isn't worth any. Golden
+// food counts before it starts the multiplier.
+pub fn award_points(
+    kind: FoodKind,
+    multiplier: &mut ScoreMultiplier,
+    combo: &mut Combo,
+) -> Option<usize> {
+    let points = match kind {
+        FoodKind::Normal | FoodKind::Golden => 1,
+        FoodKind::Bonus => BONUS_FOOD_POINTS,
+        _ => return None,
+    };
+    combo.extend();
+    let points = points * multiplier.factor * combo.factor();
+    if kind == FoodKind::Golden {
+        multiplier.start(GOLDEN_FOOD_FACTOR, GOLDEN_FOOD_SECONDS);
+    }
+    Some(points)
+}
+
+// Adds a segment at the end of the eater's tail for every food eaten that
+// grows it
+pub fn grow_snake(
+    mut commands: Commands,
+    mut food_events: EventReader<FoodEaten>,
+    mut snake_query: Query<(&Player, &mut SegmentOrder, &SnakeTrail), With<Snake>>,
+    config: Res<GameConfig>,
+) {
+    for event in food_events.iter().filter(|event| event.kind.grows()) {
+        let Ok((player, mut segments, trail)) = snake_query.get_mut(event.snake) else {
+            continue;
+        };
+        let tail_position =
+            point_along_trail(trail, (segments.len() + 1) as f32 * config.snake_size.x);
+        let segment = spawn_segment(&mut commands, &config, *player, tail_position);
+        segments.push(segment);
+    }
+}
+
+// Takes `POISON_FOOD_SEGMENTS` off the end of the tail for every poison food
+// eaten. A head that has no tail left to give up dies.
+pub fn shrink_snake(
+    mut commands: Commands,
+    mut food_events: EventReader<FoodEaten>,
+    mut snake_query: Query<&mut SegmentOrder, With<Snake>>,
+    mut died_events: EventWriter<SnakeDied>,
+) {
+    for event in food_events
+        .iter()
+        .filter(|event| event.kind == FoodKind::Poison)
+    {
+        let Ok(mut segments) = snake_query.get_mut(event.snake) else {
+            continue;
+        };
+        if segments.is_empty() {
+            died_events.send(SnakeDied {
+                snake: event.snake,
+                cause: DeathCause::Poison,
+            });
+            continue;
+        }
+        for _ in 0..POISON_FOOD_SEGMENTS {
+            if let Some(segment) = segments.pop() {
+                commands.entity(segment).despawn_recursive();
+            }
+        }
+    }
+}
+
+// Puts a new piece of food in the arena for every normal one eaten, so there
+// are always `simultaneous_food` of them. One that found no free spot is
+// placed on a later tick, the arena is never left without food for good.
+// Now and then a poison food, a golden one or a pickup comes along with it,
+// and every `BONUS_FOOD_EVERY` a bonus food. They are all placed here so they
+// keep clear of each other. Poison is never the only food out, since it is
+// only ever added next to the normal ones.
+pub fn respawn_food(
+    mut commands: Commands,
+    mut food_events: EventReader<FoodEaten>,
+    snake_query: Query<(&Transform, &Hitbox, &SegmentOrder, &SnakeTrail), With<Snake>>,
+    food_query: Query<(Entity, &Transform, &Hitbox, &FoodKind), With<Food>>,
+    mut countdown: ResMut<BonusCountdown>,
+    segment_query: Query<(&Transform, &Hitbox), With<SnakeSegment>>,
+    obstacle_query: Query<
+        (&Transform, &Hitbox),
+        Or<(With<Obstacle>, With<InteriorWall>, With<Portal>)>,
+    >,
+    crate_query: Query<(), With<Crate>>,
+    config: Res<GameConfig>,
+    bounds: Res<ArenaBounds>,
+    movement_mode: Res<MovementMode>,
+    mut rng: ResMut<GameRng>,
+) {
+    let events: Vec<&FoodEaten> = food_events.iter().collect();
+    let eaten: Vec<Entity> = events.iter().map(|event| event.food).collect();
+    let normal_left = food_query
+        .iter()
+        .filter(|(food, .., kind)| **kind == FoodKind::Normal && !eaten.contains(food))
+        .count();
+    if events.is_empty() && normal_left >= config.simultaneous_food {
+        return;
+    }
+
+    // The new food must not land on any part of a snake, including the
+    // segments `grow_snake` just added, which aren't spawned yet, nor on the
+    // food that is left
+    let mut occupied = Vec::new();
+    for (transform, hitbox, segments, trail) in &snake_query {
+        occupied.push((transform.translation, **hitbox));
+        for (index, segment) in segments.iter().enumerate() {
+            occupied.push(match segment_query.get(*segment) {
+                Ok((transform, hitbox)) => (transform.translation, **hitbox),
+                Err(_) => {
+                    let distance = (index + 1) as f32 * config.snake_size.x;
+                    (point_along_trail(trail, distance), config.snake_size)
+                }
+            });
+        }
+    }
+    occupied.extend(
+        obstacle_query
+            .iter()
+            .map(|(transform, hitbox)| (transform.translation, **hitbox)),
+    );
+    let left = food_query.iter().filter(|(food, ..)| !eaten.contains(food));
+    let mut kinds_left = Vec::new();
+    for (_, transform, hitbox, kind) in left {
+        occupied.push((transform.translation, **hitbox));
+        kinds_left.push(*kind);
+    }
+
+    // Each replacement also keeps clear of the ones placed before it
+    let normal_eaten = events
+        .iter()
+        .filter(|event| event.kind == FoodKind::Normal)
+        .count();
+    for _ in normal_left..config.simultaneous_food {
+        if let Some(food_position) =
+            free_food_position(&mut rng.rng, &config, &bounds, *movement_mode, &occupied)
+        {
+            spawn_food(&mut commands, &config, food_position);
+            occupied.push((food_position, config.food_size));
+        }
+    }
+
+    // One of each at a time is plenty
+    let extras = [
+        (FoodKind::Poison, POISON_FOOD_CHANCE),
+        (FoodKind::Golden, GOLDEN_FOOD_CHANCE),
+        (FoodKind::SlowMotion, SLOW_MOTION_CHANCE),
+        (FoodKind::Ghost, GHOST_CHANCE),
+        (FoodKind::Shield, SHIELD_CHANCE),
+        (FoodKind::Magnet, MAGNET_CHANCE),
+        (FoodKind::Hammer, HAMMER_CHANCE),
+    ];
+    for (kind, chance) in extras {
+        if normal_eaten == 0 || kinds_left.contains(&kind) || !rng.rng.gen_bool(chance) {
+            continue;
+        }
+        // Nothing to break
+        if kind == FoodKind::Hammer && crate_query.is_empty() {
+            continue;
+        }
+        if let Some(position) =
+            free_food_position(&mut rng.rng, &config, &bounds, *movement_mode, &occupied)
+        {
+            spawn_food_of_kind(&mut commands, &config, position, kind);
+            occupied.push((position, config.food_size));
+        }
+    }
+
+    **countdown = countdown.saturating_sub(normal_eaten);
+    // The countdown waits at zero while the last bonus is still out
+    if **countdown > 0 || kinds_left.contains(&FoodKind::Bonus) {
+        return;
+    }
+    // Checked at the size of normal food, so everything is grown by how much
+    // bigger the bonus is
+    let margin = config.food_size * (BONUS_FOOD_SCALE - 1.0);
+    let padded: Vec<(Vec3, Vec2)> = occupied
+        .iter()
+        .map(|(center, size)| (*center, *size + margin))
+        .collect();
+    if let Some(bonus_position) =
+        free_food_position(&mut rng.rng, &config, &bounds, *movement_mode, &padded)
+    {
+        spawn_bonus_food(&mut commands, &config, bonus_position, BONUS_FOOD_SECONDS);
+        **countdown = BONUS_FOOD_EVERY;
+    }
+}
+
+// A head biting its own body, or running into another snake. Another snake's
+// body is as deadly as its own, and two heads meeting kill each other.
+pub fn check_for_self_collision(
+    head_query: Query<(Entity, &Transform, &Hitbox, &SegmentOrder), With<Snake>>,
+    segment_query: Query<(&Transform, &Hitbox), With<SnakeSegment>>,
+    config: Res<GameConfig>,
+    movement_mode: Res<MovementMode>,
+    mut fatal_events: EventWriter<FatalCollision>,
+) {
+    // On the grid a head only bites what shares its cell
+    let touches = |head: (&Transform, &Hitbox), other: (&Transform, &Hitbox)| match *movement_mode {
+        MovementMode::Grid => {
+            grid_cell(&config, head.0.translation) == grid_cell(&config, other.0.translation)
+        }
+        MovementMode::Continuous => {
+            collide(head.0.translation, **head.1, other.0.translation, **other.1).is_some()
+        }
+    };
+    // When moving freely the segment right behind a head always touches it,
+    // so it can't be bitten
+    let skipped = match *movement_mode {
+        MovementMode::Grid => 0,
+        MovementMode::Continuous => 1,
+    };
+
+    for (snake, head_transform, head_hitbox, _) in &head_query {
+        let head = (head_transform, head_hitbox);
+        let bitten = head_query
+            .iter()
+            .any(|(other, other_transform, other_hitbox, segments)| {
+                let own = other == snake;
+                let head_on = !own && touches(head, (other_transform, other_hitbox));
+                head_on
+                    || segments
+                        .iter()
+                        .skip(if own { skipped } else { 0 })
+                        .filter_map(|segment| segment_query.get(*segment).ok())
+                        .any(|segment| touches(head, segment))
+            });
+        if bitten {
+            fatal_events.send(FatalCollision {
+                snake,
+                cause: DeathCause::SelfCollision,
+            });
+        }
+    }
+}
+
+// Kills a snake on a fatal collision, unless it holds a shield. Then
+// the shield is used up and the snake turns around: the end of the tail
+// becomes the head, one cell further back, heading away from the rest of the
+// body. A lone head just bounces back a cell. Runs before anything could
+// despawn the snake.
+pub fn use_shield(
+    mut fatal_events: EventReader<FatalCollision>,
+    mut head_query: Query<
+        (
+            &mut Transform,
+            &mut Direction,
+            &mut NextDirection,
+            Option<&mut Velocity>,
+            &mut Shield,
+            &SegmentOrder,
+            &mut SnakeTrail,
+            &mut DirectionQueue,
+        ),
+        With<Snake>,
+    >,
+    config: Res<GameConfig>,
+    movement_mode: Res<MovementMode>,
+    mut died_events: EventWriter<SnakeDied>,
+) {
+    // Hitting several things at once still only takes one shield
+    let mut hit: Vec<(Entity, DeathCause)> = Vec::new();
+    for event in fatal_events.iter() {
+        if hit.iter().all(|(snake, _)| *snake != event.snake) {
+            hit.push((event.snake, event.cause));
+        }
+    }
+
+    for (snake, cause) in hit {
+        let Ok((
+            mut transform,
+            mut direction,
+            mut next_direction,
+            velocity,
+            mut shield,
+            segments,
+            mut trail,
+            mut queue,
+        )) = head_query.get_mut(snake)
+        else {
+            continue;
+        };
+        if **shield == 0 {
+            died_events.send(SnakeDied { snake, cause });
+            continue;
+        }
+        **shield -= 1;
+
+        let length = (segments.len() + 1) as f32 * config.snake_size.x;
+        let Some(mut head) = reverse_trail(&mut trail, length) else {
+            continue;
+        };
+        if *movement_mode == MovementMode::Grid {
+            head = cell_center(&config, grid_cell(&config, head));
+            if let Some(front) = trail.front_mut() {
+                *front = head;
+            }
+        }
+        let heading = trail
+            .get(1)
+            .map(|behind| (head - *behind).truncate())
+            .filter(|heading| *heading != Vec2::ZERO)
+            .map_or(direction.opposite(), Direction::from_vec2);
+        transform.translation = head;
+        *direction = heading;
+        **next_direction = heading;
+        if let Some(mut velocity) = velocity {
+            **velocity = direction.cell_offset().as_vec2() * velocity.length();
+        }
+        queue.clear();
+    }
+}
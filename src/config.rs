@@ -0,0 +1,297 @@
+use std::{fmt, fs, io, path::Path};
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+// Where the tuning values are read from, relative to the working directory
+pub const CONFIG_PATH: &str = "config.ron";
+
+// The tuning values for the arena, the snake and the food. Every field is
+// optional in `config.ron`, missing ones keep their default.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    // Seconds between two gameplay ticks
+    pub time_step: f32,
+    // Cells per second the head moves in grid mode at its base speed
+    pub grid_steps_per_second: f32,
+    // Units per second the head moves in continuous mode at its base speed
+    pub snake_speed: f32,
+    // The size of the head and of every body segment, and of a grid cell
+    pub snake_size: Vec2,
+    pub snake_color: Color,
+    // The second snake in versus
+    pub player_two_color: Color,
+    // What the bodies of the two darken to towards the tip of the tail
+    pub snake_tail_color: Color,
+    pub player_two_tail_color: Color,
+    pub food_size: Vec2,
+    pub food_color: Color,
+    // The bonus food showing up every few foods
+    pub bonus_food_color: Color,
+    // The poison food that shrinks the snake
+    pub poison_food_color: Color,
+    // The rare golden food that multiplies the score for a while
+    pub golden_food_color: Color,
+    // The slow motion pickup, also the color of its icon in the HUD
+    pub slow_motion_color: Color,
+    // The ghost pickup and its countdown in the HUD
+    pub ghost_color: Color,
+    // The shield pickup and the outline around a shielded head
+    pub shield_color: Color,
+    // How many shields the snake can hold at once
+    pub max_shields: usize,
+    // The magnet pickup, and how close food has to be for it to be pulled
+    pub magnet_color: Color,
+    pub magnet_radius: f32,
+    // How many pieces of food are in the arena at once
+    pub simultaneous_food: usize,
+    // A harder twist: the food slowly wanders around the arena, see `Wander`
+    pub wandering_food: bool,
+    // Normal food left uneaten for too long goes and turns up somewhere
+    // else, see `FoodLifetime`
+    pub expiring_food: bool,
+    // A pair of portals in the open arena, see `Portal`
+    pub portals: bool,
+    // A hunter comes after the snake now and then, see `Hunter`
+    pub hunter: bool,
+    // The snake starves unless it keeps eating, see `Hunger`
+    pub hunger: bool,
+    // The walls are centered on these coordinates, each pair as far from the
+    // origin on either side. The game goes by the `ArenaConfig` made from
+    // them.
+    pub left_wall: f32,
+    pub right_wall: f32,
+    pub bottom_wall: f32,
+    pub top_wall: f32,
+    pub wall_thickness: f32,
+    pub wall_color: Color,
+    // The two shades of the checkerboard inside the walls, one cell per square
+    pub grid_color: Color,
+    pub grid_alt_color: Color,
+    // How many blocks are scattered inside the open arena every run, and the
+    // most grid cells each of them spans in either direction
+    pub obstacle_count: usize,
+    pub obstacle_max_size: u32,
+    pub obstacle_color: Color,
+    // How many crates are scattered inside the open arena, see `Crate`, and
+    // the colors of the crates and of the hammer pickup that breaks them
+    pub crate_count: usize,
+    pub crate_color: Color,
+    pub hammer_color: Color,
+    // How many patches of ice are scattered inside the open arena, see
+    // `IcePatch`
+    pub ice_count: usize,
+    // Makes the food placement the same every run, `--seed` takes precedence
+    pub seed: Option<u64>,
+    // Loudness of the sound effects, from 0 (silent) to 1
+    pub master_volume: f32,
+    // Loudness of the background music, from 0 (silent) to 1
+    pub music_volume: f32,
+    // Shakes the camera when the snake dies, off for anyone bothered by motion
+    pub screen_shake: bool,
+    // Starts the menu with three lives picked instead of one
+    pub three_lives: bool,
+    // Counts down again before the snake moves on after a pause
+    pub countdown_on_resume: bool,
+    // Pauses the game when the window loses focus, off to keep playing in
+    // the background
+    pub pause_on_focus_loss: bool,
+    // Adds every finished run to the runs file, off with `--no-telemetry`
+    pub record_runs: bool,
+    // Where the runs file is, `runs.csv` in the data folder when left out
+    pub runs_file: Option<String>,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            time_step: 1.0 / 60.0,
+            grid_steps_per_second: 8.0,
+            snake_speed: 700.0,
+            snake_size: Vec2::new(20.0, 20.0),
+            snake_color: Color::rgb(0.1, 0.7, 0.1),
+            player_two_color: Color::rgb(0.2, 0.4, 0.9),
+            snake_tail_color: Color::rgb(0.03, 0.3, 0.03),
+            player_two_tail_color: Color::rgb(0.07, 0.15, 0.4),
+            food_size: Vec2::new(20.0, 20.0),
+            food_color: Color::rgb(0.9, 0.3, 0.3),
+            bonus_food_color: Color::rgb(1.0, 0.75, 0.1),
+            poison_food_color: Color::rgb(0.6, 0.1, 0.7),
+            golden_food_color: Color::rgb(1.0, 0.95, 0.4),
+            slow_motion_color: Color::rgb(0.3, 0.6, 1.0),
+            ghost_color: Color::rgb(0.85, 0.85, 1.0),
+            shield_color: Color::rgb(0.2, 0.9, 0.9),
+            max_shields: 1,
+            magnet_color: Color::rgb(0.9, 0.2, 0.3),
+            magnet_radius: 150.0,
+            simultaneous_food: 1,
+            wandering_food: false,
+            expiring_food: false,
+            portals: false,
+            hunter: false,
+            hunger: false,
+            left_wall: -450.0,
+            right_wall: 450.0,
+            bottom_wall: -300.0,
+            top_wall: 300.0,
+            wall_thickness: 10.0,
+            wall_color: Color::rgb(0.8, 0.8, 0.8),
+            grid_color: Color::rgb(0.36, 0.36, 0.36),
+            grid_alt_color: Color::rgb(0.4, 0.4, 0.4),
+            obstacle_count: 5,
+            obstacle_max_size: 3,
+            obstacle_color: Color::rgb(0.55, 0.35, 0.2),
+            crate_count: 0,
+            crate_color: Color::rgb(0.8, 0.6, 0.3),
+            hammer_color: Color::rgb(0.6, 0.6, 0.65),
+            ice_count: 0,
+            seed: None,
+            master_volume: 1.0,
+            music_volume: 0.5,
+            screen_shake: true,
+            three_lives: false,
+            countdown_on_resume: false,
+            pause_on_focus_loss: true,
+            record_runs: true,
+            runs_file: None,
+        }
+    }
+}
+
+impl GameConfig {
+    // Reads and checks the config at `path`, falling back to the defaults
+    // when there is no such file
+    pub fn load(path: impl AsRef<Path>) -> Result<GameConfig, ConfigError> {
+        let config = match fs::read_to_string(path) {
+            Ok(contents) => ron::from_str(&contents).map_err(ConfigError::Parse)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => GameConfig::default(),
+            Err(err) => return Err(ConfigError::Read(err)),
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    // Rejects values the game can't be played with, such as walls in the wrong
+    // order or an arena too small to hold the snake
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let positive = [
+            ("time_step", self.time_step),
+            ("grid_steps_per_second", self.grid_steps_per_second),
+            ("snake_speed", self.snake_speed),
+            ("snake_size.x", self.snake_size.x),
+            ("snake_size.y", self.snake_size.y),
+            ("food_size.x", self.food_size.x),
+            ("food_size.y", self.food_size.y),
+            ("magnet_radius", self.magnet_radius),
+        ];
+        for (name, value) in positive {
+            if !(value.is_finite() && value > 0.0) {
+                return Err(ConfigError::Invalid(format!(
+                    "`{name}` must be greater than zero, got {value}"
+                )));
+            }
+        }
+        if !(self.wall_thickness.is_finite() && self.wall_thickness >= 0.0) {
+            return Err(ConfigError::Invalid(format!(
+                "`wall_thickness` must not be negative, got {}",
+                self.wall_thickness
+            )));
+        }
+        if self.simultaneous_food == 0 {
+            return Err(ConfigError::Invalid(
+                "`simultaneous_food` must be at least one".to_string(),
+            ));
+        }
+        if self.obstacle_max_size == 0 {
+            return Err(ConfigError::Invalid(
+                "`obstacle_max_size` must be at least one cell".to_string(),
+            ));
+        }
+        let volumes = [
+            ("master_volume", self.master_volume),
+            ("music_volume", self.music_volume),
+        ];
+        for (name, value) in volumes {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(ConfigError::Invalid(format!(
+                    "`{name}` must be between 0 and 1, got {value}"
+                )));
+            }
+        }
+        let walls = [
+            ("left_wall", self.left_wall),
+            ("right_wall", self.right_wall),
+            ("bottom_wall", self.bottom_wall),
+            ("top_wall", self.top_wall),
+        ];
+        for (name, value) in walls {
+            if !value.is_finite() {
+                return Err(ConfigError::Invalid(format!(
+                    "`{name}` must be a finite number, got {value}"
+                )));
+            }
+        }
+        if self.left_wall >= self.right_wall {
+            return Err(ConfigError::Invalid(format!(
+                "`left_wall` ({}) must be less than `right_wall` ({})",
+                self.left_wall, self.right_wall
+            )));
+        }
+        if self.bottom_wall >= self.top_wall {
+            return Err(ConfigError::Invalid(format!(
+                "`bottom_wall` ({}) must be less than `top_wall` ({})",
+                self.bottom_wall, self.top_wall
+            )));
+        }
+        // The arena is centered on the origin
+        if self.left_wall != -self.right_wall {
+            return Err(ConfigError::Invalid(format!(
+                "`left_wall` ({}) must be as far from the origin as `right_wall` ({})",
+                self.left_wall, self.right_wall
+            )));
+        }
+        if self.bottom_wall != -self.top_wall {
+            return Err(ConfigError::Invalid(format!(
+                "`bottom_wall` ({}) must be as far from the origin as `top_wall` ({})",
+                self.bottom_wall, self.top_wall
+            )));
+        }
+
+        // Only half of each wall reaches into the arena
+        let inner_size = Vec2::new(
+            self.right_wall - self.left_wall,
+            self.top_wall - self.bottom_wall,
+        ) - self.wall_thickness;
+        let largest = self.snake_size.max(self.food_size);
+        if inner_size.x < largest.x || inner_size.y < largest.y {
+            return Err(ConfigError::Invalid(format!(
+                "the arena ({} x {} inside the walls) is too small for the snake and the food",
+                inner_size.x, inner_size.y
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    // The file exists but couldn't be read
+    Read(io::Error),
+    // The file isn't valid RON, or has fields of the wrong type
+    Parse(ron::error::SpannedError),
+    // The values were read but make no sense together
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Read(err) => write!(f, "could not read the config: {err}"),
+            ConfigError::Parse(err) => write!(f, "could not parse the config: {err}"),
+            ConfigError::Invalid(reason) => write!(f, "invalid config: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
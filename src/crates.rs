@@ -0,0 +1,179 @@
+use bevy::prelude::*;
+use bevy::sprite::collide_aabb::collide;
+use bevy::utils::HashSet;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::collision::check_for_collisions;
+use crate::config::GameConfig;
+use crate::game::{FixedUpdate, Scoreboard};
+use crate::snake::{cell_center, grid_cell, Boost, Player, Snake};
+use crate::walls::{grid_bounds, reachable_cells, ArenaBounds};
+
+// How many ticks of smashing a crate takes
+pub const CRATE_HITS: u8 = 1;
+// What breaking a crate is worth
+pub const CRATE_POINTS: usize = 2;
+// How many crates a hammer pickup breaks
+pub const HAMMER_SMASHES: usize = 3;
+// How many free cells are tried for each crate
+const CRATE_SPAWN_ATTEMPTS: usize = 32;
+
+// The crates scattered over the arena, and the snakes smashing them
+pub struct CratePlugin;
+
+impl Plugin for CratePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CrateHit>()
+            .add_event::<CrateBroken>()
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(FixedUpdate)
+                    .with_system(smash_crates.after(check_for_collisions)),
+            );
+    }
+}
+
+// An obstacle that can be broken. A snake boosting, or with a `Hammer`,
+// smashes it instead of dying on it. It is an `Obstacle` as well, so
+// everything that keeps clear of obstacles keeps clear of crates.
+#[derive(Component)]
+pub struct Crate {
+    pub hits_remaining: u8,
+}
+
+// Crates a snake can still break without boosting, from hammer pickups
+#[derive(Component, Default, Deref, DerefMut)]
+pub struct Hammer(pub usize);
+
+// Sent by `check_for_collisions` for a smashing head in a crate. `hammer`
+// is whether it was the hammer doing it rather than the boost.
+pub struct CrateHit {
+    pub snake: Entity,
+    pub crate_entity: Entity,
+    pub hammer: bool,
+}
+
+// The crate at `position` broke, for the particles
+pub struct CrateBroken {
+    pub snake: Entity,
+    pub position: Vec3,
+}
+
+// Free cells for `count` crates, away from the rows and columns of the
+// `starts` like the obstacles. Everything `blocked` (given as center and
+// size) is kept clear of, and no crate is put where it would cut any part of
+// the arena off from the first start, so the food can't be boxed in by them.
+pub fn random_crates(
+    rng: &mut impl Rng,
+    config: &GameConfig,
+    bounds: &ArenaBounds,
+    count: usize,
+    blocked: &[(Vec3, Vec2)],
+    starts: &[Vec3],
+) -> Vec<Vec3> {
+    let (min, max) = grid_bounds(config, bounds);
+    let starts: Vec<IVec2> = starts
+        .iter()
+        .map(|start| grid_cell(config, *start))
+        .collect();
+    let Some(first) = starts.first().copied() else {
+        return Vec::new();
+    };
+    let mut walls: HashSet<IVec2> = (min.x..=max.x)
+        .flat_map(|x| (min.y..=max.y).map(move |y| IVec2::new(x, y)))
+        .filter(|cell| {
+            blocked.iter().any(|(center, size)| {
+                collide(
+                    cell_center(config, *cell),
+                    config.snake_size,
+                    *center,
+                    *size,
+                )
+                .is_some()
+            })
+        })
+        .collect();
+    let mut open = reachable_cells(min, max, &walls, first);
+    let candidates: Vec<IVec2> = open
+        .iter()
+        .copied()
+        .filter(|cell| {
+            !starts
+                .iter()
+                .any(|start| start.x == cell.x || start.y == cell.y)
+        })
+        .collect();
+
+    let mut crates = Vec::new();
+    for _ in 0..count {
+        for _ in 0..CRATE_SPAWN_ATTEMPTS {
+            let Some(cell) = candidates.choose(rng).copied() else {
+                return crates;
+            };
+            if walls.contains(&cell) {
+                continue;
+            }
+            walls.insert(cell);
+            let still_open = reachable_cells(min, max, &walls, first);
+            if still_open.len() + 1 == open.len() {
+                open = still_open;
+                crates.push(cell_center(config, cell));
+                break;
+            }
+            walls.remove(&cell);
+        }
+    }
+    crates
+}
+
+// Takes a hit off every crate a smashing head is in. A crate out of hits
+// breaks, is worth `CRATE_POINTS` to whoever broke it, and uses up one of
+// their hammer's smashes if it wasn't the boost.
+pub fn smash_crates(
+    mut commands: Commands,
+    mut hit_events: EventReader<CrateHit>,
+    mut broken_events: EventWriter<CrateBroken>,
+    mut crate_query: Query<(&Transform, &mut Crate)>,
+    mut snake_query: Query<(&Player, &mut Hammer), With<Snake>>,
+    mut scoreboard: ResMut<Scoreboard>,
+) {
+    for event in hit_events.iter() {
+        let Ok((transform, mut smashed)) = crate_query.get_mut(event.crate_entity) else {
+            continue;
+        };
+        // Already broken by the other snake this tick
+        if smashed.hits_remaining == 0 {
+            continue;
+        }
+        smashed.hits_remaining -= 1;
+        if smashed.hits_remaining > 0 {
+            continue;
+        }
+        commands.entity(event.crate_entity).despawn();
+        broken_events.send(CrateBroken {
+            snake: event.snake,
+            position: transform.translation,
+        });
+        let Ok((player, mut hammer)) = snake_query.get_mut(event.snake) else {
+            continue;
+        };
+        *scoreboard.score_mut(*player) += CRATE_POINTS;
+        if event.hammer {
+            **hammer = hammer.saturating_sub(1);
+        }
+    }
+}
+
+// Whether a snake smashes the crates it runs into rather than dying on
+// them, and if so whether with its hammer. The boost comes first, so the
+// hammer is kept for later.
+pub fn smashing(boost: &Boost, hammer: Option<&Hammer>) -> Option<bool> {
+    if boost.active {
+        Some(false)
+    } else if hammer.is_some_and(|hammer| **hammer > 0) {
+        Some(true)
+    } else {
+        None
+    }
+}
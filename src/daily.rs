@@ -0,0 +1,74 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::game::Difficulty;
+
+// Every daily challenge is played with these presets, whatever is picked on
+// the menu, with lethal walls and a single life
+pub const DAILY_DIFFICULTY: Difficulty = Difficulty::Normal;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+// A day of the calendar, in UTC so everyone gets the same one at the same
+// time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DailyDate {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl DailyDate {
+    pub fn today() -> DailyDate {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        DailyDate::from_days_since_epoch((seconds / SECONDS_PER_DAY) as i64)
+    }
+
+    // The date `days` after 1970-01-01, by Howard Hinnant's `civil_from_days`
+    pub fn from_days_since_epoch(days: i64) -> DailyDate {
+        let shifted = days + 719_468;
+        let era = shifted.div_euclid(146_097);
+        let day_of_era = shifted.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        // Months counted from March, so the leap day comes last
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        } as u32;
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        DailyDate { year, month, day }
+    }
+
+    // What the day's best score is kept under, like "2024-03-09"
+    pub fn key(&self) -> String {
+        format!("{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+
+    // The seed of the day's run. Only integer arithmetic with fixed widths
+    // goes into it, so it comes out the same on every platform.
+    pub fn seed(&self) -> u64 {
+        let date = self.year as u64 * 10_000 + self.month as u64 * 100 + self.day as u64;
+        // SplitMix64's finalizer, so neighbouring days get unrelated seeds
+        let mut seed = date.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        seed = (seed ^ (seed >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        seed = (seed ^ (seed >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        seed ^ (seed >> 31)
+    }
+}
+
+// The day whose challenge is being played, set when it is picked on the menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DailyChallenge(pub DailyDate);
+
+impl Default for DailyChallenge {
+    fn default() -> Self {
+        DailyChallenge(DailyDate::today())
+    }
+}
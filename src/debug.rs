@@ -0,0 +1,400 @@
+use bevy::diagnostic::{Diagnostics, EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin};
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+use bevy::ui::update::UI_Z_STEP;
+use bevy::utils::HashSet;
+
+use crate::collision::{Collider, CollisionEvent, Hitbox, LastCollision};
+use crate::food::FoodKind;
+use crate::game::GameTick;
+use crate::snake::Snake;
+
+// How often the numbers are refreshed, so they can be read while they change
+const REFRESH_SECONDS: f32 = 0.25;
+const OVERLAY_FONT_SIZE: f32 = 16.0;
+const OVERLAY_PADDING: Val = Val::Px(5.0);
+const OVERLAY_BACKGROUND: Color = Color::rgba(0.0, 0.0, 0.0, 0.6);
+const OVERLAY_TEXT_COLOR: Color = Color::rgb(1.0, 1.0, 0.4);
+// Bevy stacks the UI by the order it finds the root nodes in, so the overlay
+// is lifted above everything else once the layout is done
+const OVERLAY_Z: f32 = 500.0;
+// The collider outlines, thin and see-through so the sprites still show, and
+// above everything in the arena
+const OUTLINE_WIDTH: f32 = 1.5;
+const OUTLINE_Z: f32 = 100.0;
+const OUTLINE_SNAKE_COLOR: Color = Color::rgba(0.2, 1.0, 0.2, 0.7);
+const OUTLINE_FOOD_COLOR: Color = Color::rgba(1.0, 1.0, 0.2, 0.7);
+const OUTLINE_COLLIDER_COLOR: Color = Color::rgba(1.0, 0.2, 0.2, 0.7);
+const LABEL_FONT_SIZE: f32 = 14.0;
+// Room between the top of the box and the label naming the side
+const LABEL_OFFSET: f32 = 10.0;
+
+// F3 toggles a small overlay with the frame rate, the gameplay ticks actually
+// run per second and the number of entities, in every state
+pub struct DebugOverlayPlugin;
+
+impl Plugin for DebugOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(FrameTimeDiagnosticsPlugin)
+            .add_plugin(EntityCountDiagnosticsPlugin)
+            .init_resource::<DebugOverlay>()
+            .add_system(toggle_debug_overlay)
+            .add_system(update_debug_overlay.after(toggle_debug_overlay))
+            .add_system_to_stage(CoreStage::Last, lift_debug_overlay);
+    }
+}
+
+pub struct DebugOverlay {
+    pub enabled: bool,
+    pub refresh: Timer,
+    // The `GameTick` and the time at the last refresh, to work out the rate
+    // the ticks came at since
+    pub last_tick: u64,
+    pub last_seconds: f64,
+}
+
+impl Default for DebugOverlay {
+    fn default() -> Self {
+        DebugOverlay {
+            enabled: false,
+            refresh: Timer::from_seconds(REFRESH_SECONDS, true),
+            last_tick: 0,
+            last_seconds: 0.0,
+        }
+    }
+}
+
+// Only there while the overlay is enabled
+#[derive(Component)]
+pub struct DebugOverlayNode;
+
+#[derive(Component)]
+pub struct DebugOverlayText;
+
+pub fn toggle_debug_overlay(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    asset_server: Res<AssetServer>,
+    mut overlay: ResMut<DebugOverlay>,
+    node_query: Query<Entity, With<DebugOverlayNode>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F3) {
+        return;
+    }
+    overlay.enabled = !overlay.enabled;
+    if !overlay.enabled {
+        for entity in &node_query {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: OVERLAY_PADDING,
+                    right: OVERLAY_PADDING,
+                    ..default()
+                },
+                padding: UiRect::all(OVERLAY_PADDING),
+                ..default()
+            },
+            color: OVERLAY_BACKGROUND.into(),
+            ..default()
+        })
+        .insert(DebugOverlayNode)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                        font_size: OVERLAY_FONT_SIZE,
+                        color: OVERLAY_TEXT_COLOR,
+                    },
+                ))
+                .insert(DebugOverlayNode)
+                .insert(DebugOverlayText);
+        });
+}
+
+// A tick rate below `1 / time_step` while playing means the frames can't keep
+// up. Outside of a game and while paused no ticks run, so it reads 0. The
+// ticks are counted while the overlay is hidden too, so it has a rate to show
+// as soon as it is enabled.
+pub fn update_debug_overlay(
+    time: Res<Time>,
+    diagnostics: Res<Diagnostics>,
+    tick: Res<GameTick>,
+    mut overlay: ResMut<DebugOverlay>,
+    mut text_query: Query<&mut Text, With<DebugOverlayText>>,
+) {
+    overlay.refresh.tick(time.delta());
+    if !overlay.refresh.just_finished() {
+        return;
+    }
+
+    let now = time.seconds_since_startup();
+    let elapsed = now - overlay.last_seconds;
+    // A new game starts counting from 0 again
+    let ticks = tick.saturating_sub(overlay.last_tick);
+    let tick_rate = if elapsed > 0.0 {
+        ticks as f64 / elapsed
+    } else {
+        0.0
+    };
+    overlay.last_tick = **tick;
+    overlay.last_seconds = now;
+    if !overlay.enabled {
+        return;
+    }
+
+    let fps = diagnostics
+        .get(FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.average())
+        .unwrap_or(0.0);
+    let entities = diagnostics
+        .get(EntityCountDiagnosticsPlugin::ENTITY_COUNT)
+        .and_then(|count| count.value())
+        .unwrap_or(0.0);
+    let tick = **tick;
+    for mut text in &mut text_query {
+        text.sections[0].value =
+            format!("FPS {fps:.0}\nTick {tick}\nTicks/s {tick_rate:.0}\nEntities {entities:.0}");
+    }
+}
+
+// Runs after the transforms are propagated, right before they are drawn.
+// The text sits just above its background, the way bevy would have put it.
+pub fn lift_debug_overlay(
+    mut node_query: Query<
+        (&mut GlobalTransform, Option<&DebugOverlayText>),
+        With<DebugOverlayNode>,
+    >,
+) {
+    for (mut transform, text) in &mut node_query {
+        transform.translation_mut().z = if text.is_some() {
+            OVERLAY_Z + UI_Z_STEP
+        } else {
+            OVERLAY_Z
+        };
+    }
+}
+
+// F4, or starting with `--debug-colliders`, outlines every box
+// `check_for_collisions` hands to `collide`: the heads, the walls, the
+// obstacles and the food. The side `collide` last reported is written next to
+// the box it was reported for, and every contact is logged. Only sprites of
+// its own are spawned, the gameplay entities are never touched.
+pub struct ColliderDebugPlugin;
+
+impl Plugin for ColliderDebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ColliderDebug>()
+            .add_system(toggle_collider_debug)
+            .add_system(log_collisions)
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                draw_collider_outlines.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+#[derive(Default)]
+pub struct ColliderDebug {
+    pub enabled: bool,
+}
+
+impl ColliderDebug {
+    pub fn from_args() -> ColliderDebug {
+        ColliderDebug {
+            enabled: std::env::args().any(|arg| arg == "--debug-colliders"),
+        }
+    }
+}
+
+// One edge of the box around `target`, indexing into `outline_edges`
+#[derive(Component)]
+pub struct OutlineEdge {
+    pub target: Entity,
+    pub edge: usize,
+}
+
+// Names the side of the last collision, above the box it was reported for
+#[derive(Component)]
+pub struct ColliderSideLabel;
+
+pub fn toggle_collider_debug(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut debug: ResMut<ColliderDebug>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F4) {
+        debug.enabled = !debug.enabled;
+    }
+}
+
+// Tells the food, the walls and the obstacles the heads touch apart
+pub fn log_collisions(
+    debug: Res<ColliderDebug>,
+    mut collision_events: EventReader<CollisionEvent>,
+) {
+    for event in collision_events.iter() {
+        if !debug.enabled {
+            continue;
+        }
+        match event {
+            CollisionEvent::Food { snake, food, side } => {
+                info!("{snake:?} reached food {food:?} from its {side:?} side");
+            }
+            CollisionEvent::Wall { snake, wall, side } => {
+                info!("{snake:?} hit the {side:?} side of wall {wall:?}");
+            }
+            CollisionEvent::Obstacle {
+                snake,
+                obstacle,
+                side,
+            } => {
+                info!("{snake:?} hit the {side:?} side of obstacle {obstacle:?}");
+            }
+        }
+    }
+}
+
+fn outline_color(is_snake: bool, is_food: bool) -> Color {
+    if is_snake {
+        OUTLINE_SNAKE_COLOR
+    } else if is_food {
+        OUTLINE_FOOD_COLOR
+    } else {
+        OUTLINE_COLLIDER_COLOR
+    }
+}
+
+// The edges of a `size` box as offsets from its center and their sizes
+fn outline_edges(size: Vec2) -> [(Vec2, Vec2); 4] {
+    let half = size / 2.0;
+    let horizontal = Vec2::new(size.x + OUTLINE_WIDTH, OUTLINE_WIDTH);
+    let vertical = Vec2::new(OUTLINE_WIDTH, size.y + OUTLINE_WIDTH);
+    [
+        (Vec2::new(0.0, half.y), horizontal),
+        (Vec2::new(0.0, -half.y), horizontal),
+        (Vec2::new(-half.x, 0.0), vertical),
+        (Vec2::new(half.x, 0.0), vertical),
+    ]
+}
+
+fn edge_transform(target: &Transform, offset: Vec2) -> Transform {
+    Transform::from_translation((target.translation.truncate() + offset).extend(OUTLINE_Z))
+}
+
+// Runs once the frame's movement is done, so the outlines never lag behind.
+// The edges are moved onto the boxes every frame, and spawned for boxes that
+// don't have them yet or despawned once their box is gone.
+pub fn draw_collider_outlines(
+    mut commands: Commands,
+    debug: Res<ColliderDebug>,
+    last_collision: Res<LastCollision>,
+    asset_server: Res<AssetServer>,
+    target_query: Query<
+        (
+            Entity,
+            &Transform,
+            &Hitbox,
+            Option<&Snake>,
+            Option<&FoodKind>,
+        ),
+        Or<(With<Snake>, With<Collider>)>,
+    >,
+    mut edge_query: Query<(Entity, &OutlineEdge, &mut Transform, &mut Sprite), Without<Hitbox>>,
+    mut label_query: Query<
+        (Entity, &mut Transform, &mut Text),
+        (
+            With<ColliderSideLabel>,
+            Without<Hitbox>,
+            Without<OutlineEdge>,
+        ),
+    >,
+    mut outlined: Local<HashSet<Entity>>,
+) {
+    if !debug.enabled {
+        for (entity, ..) in &edge_query {
+            commands.entity(entity).despawn();
+        }
+        for (entity, ..) in &label_query {
+            commands.entity(entity).despawn();
+        }
+        outlined.clear();
+        return;
+    }
+
+    for (entity, edge, mut transform, mut sprite) in &mut edge_query {
+        let Ok((_, target, hitbox, ..)) = target_query.get(edge.target) else {
+            commands.entity(entity).despawn();
+            outlined.remove(&edge.target);
+            continue;
+        };
+        let (offset, size) = outline_edges(**hitbox)[edge.edge];
+        *transform = edge_transform(target, offset);
+        sprite.custom_size = Some(size);
+    }
+
+    for (entity, transform, hitbox, snake, food) in &target_query {
+        if !outlined.insert(entity) {
+            continue;
+        }
+        let color = outline_color(snake.is_some(), food.is_some());
+        for (edge, (offset, size)) in outline_edges(**hitbox).into_iter().enumerate() {
+            let transform = edge_transform(transform, offset);
+            commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color,
+                        custom_size: Some(size),
+                        ..default()
+                    },
+                    transform,
+                    // Spawned after the transforms are propagated this frame
+                    global_transform: transform.into(),
+                    ..default()
+                })
+                .insert(OutlineEdge {
+                    target: entity,
+                    edge,
+                });
+        }
+    }
+
+    let labelled = last_collision.0.as_ref().and_then(|(collider, side)| {
+        let (_, transform, hitbox, ..) = target_query.get(*collider).ok()?;
+        let above = Vec2::new(0.0, hitbox.y / 2.0 + LABEL_OFFSET);
+        Some((edge_transform(transform, above), format!("{side:?}")))
+    });
+    match (labelled, label_query.get_single_mut()) {
+        (Some((transform, side)), Ok((_, mut label_transform, mut text))) => {
+            *label_transform = transform;
+            text.sections[0].value = side;
+        }
+        (Some((transform, side)), Err(_)) => {
+            commands
+                .spawn_bundle(Text2dBundle {
+                    text: Text::from_section(
+                        side,
+                        TextStyle {
+                            font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                            font_size: LABEL_FONT_SIZE,
+                            color: OVERLAY_TEXT_COLOR,
+                        },
+                    )
+                    .with_alignment(TextAlignment::CENTER),
+                    transform,
+                    global_transform: transform.into(),
+                    ..default()
+                })
+                .insert(ColliderSideLabel);
+        }
+        (None, Ok((entity, ..))) => commands.entity(entity).despawn(),
+        (None, Err(_)) => {}
+    }
+}
@@ -0,0 +1,74 @@
+use bevy::prelude::*;
+use bevy::window::WindowMode;
+use serde::{Deserialize, Serialize};
+
+use crate::locale::Language;
+use crate::theme::{SnakeSwatch, ThemeName};
+
+// F11 switches between the window and borderless fullscreen. Whichever it was
+// left in is what the game opens in the next time.
+pub struct DisplayPlugin;
+
+impl Plugin for DisplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DisplaySettings>()
+            .add_startup_system(apply_display_settings)
+            .add_system(toggle_fullscreen);
+    }
+}
+
+// Kept on disk by the `PersistencePlugin`
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplaySettings {
+    pub fullscreen: bool,
+    // The size of the window before it went fullscreen, given back to it on
+    // the way out. `None` until it has been fullscreen once.
+    pub windowed_size: Option<(f32, f32)>,
+    // Picked on the menu, see `Theme`
+    pub theme: ThemeName,
+    // The first player's colors, over the theme's
+    pub snake_swatch: SnakeSwatch,
+    // See `AccessibilityPlugin`
+    pub colorblind: bool,
+    // The text of the UI is in, see `Locale`
+    pub language: Language,
+    // Set once the `TutorialPlugin`'s steps were played through or skipped
+    pub tutorial_seen: bool,
+}
+
+// The window is already open by the time the settings are read, so a game
+// closed in fullscreen goes back to it here
+pub fn apply_display_settings(settings: Res<DisplaySettings>, mut windows: ResMut<Windows>) {
+    let Some(window) = windows.get_primary_mut() else {
+        return;
+    };
+    if settings.fullscreen {
+        window.set_mode(WindowMode::BorderlessFullscreen);
+    }
+}
+
+pub fn toggle_fullscreen(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut settings: ResMut<DisplaySettings>,
+    mut windows: ResMut<Windows>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F11) {
+        return;
+    }
+    let Some(window) = windows.get_primary_mut() else {
+        return;
+    };
+    if window.mode() == WindowMode::Windowed {
+        settings.windowed_size = Some((window.width(), window.height()));
+        settings.fullscreen = true;
+        window.set_mode(WindowMode::BorderlessFullscreen);
+        return;
+    }
+
+    settings.fullscreen = false;
+    window.set_mode(WindowMode::Windowed);
+    if let Some((width, height)) = settings.windowed_size {
+        window.set_resolution(width, height);
+    }
+}
@@ -0,0 +1,601 @@
+use std::time::Duration;
+
+use bevy::{
+    prelude::*,
+    sprite::collide_aabb::{collide, Collision},
+};
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::collision::{check_for_collisions, respawn_food, score_food, FoodEaten, Hitbox};
+use crate::config::GameConfig;
+use crate::game::{spawn_gameplay, FixedUpdate};
+use crate::portal::Portal;
+use crate::savegame::restore_saved_game;
+use crate::snake::{
+    apply_velocity, cell_center, move_segments, MovementMode, Snake, SnakeSegment, Velocity,
+};
+use crate::spawning::spawn_food;
+use crate::state::GameState;
+use crate::walls::{grid_bounds, ArenaBounds, InteriorWall, Obstacle};
+
+// New food grows from nothing to its full size, a little past it and back
+pub const FOOD_SPAWN_SECONDS: f32 = 0.25;
+const FOOD_SPAWN_OVERSHOOT: f32 = 1.2;
+// Food that small or smaller can't be eaten yet
+pub const FOOD_SPAWN_EDIBLE_SCALE: f32 = 0.8;
+// How many random spots are tried before searching the free cells one by one
+const FOOD_SPAWN_ATTEMPTS: usize = 32;
+// A bonus food shows up after this many normal ones are eaten
+pub const BONUS_FOOD_EVERY: usize = 5;
+pub const BONUS_FOOD_POINTS: usize = 5;
+pub const BONUS_FOOD_SECONDS: f32 = 5.0;
+// How much bigger than normal food the bonus is drawn and collides
+pub const BONUS_FOOD_SCALE: f32 = 1.5;
+// How likely a poison food shows up whenever a normal one is eaten
+pub const POISON_FOOD_CHANCE: f64 = 0.2;
+// Tail segments lost to a poison food
+pub const POISON_FOOD_SEGMENTS: usize = 2;
+// Golden food is rarer, and multiplies the points of everything eaten after
+// it for a while
+pub const GOLDEN_FOOD_CHANCE: f64 = 0.05;
+pub const GOLDEN_FOOD_FACTOR: usize = 3;
+pub const GOLDEN_FOOD_SECONDS: f32 = 10.0;
+// The slow motion pickup halves the speed for a while, for when the ramp
+// gets too much
+pub const SLOW_MOTION_CHANCE: f64 = 0.08;
+pub const SLOW_MOTION_FACTOR: f32 = 0.5;
+pub const SLOW_MOTION_SECONDS: f32 = 8.0;
+// The ghost pickup lets the snake through the outer walls for a while
+pub const GHOST_CHANCE: f64 = 0.05;
+pub const GHOST_SECONDS: f32 = 6.0;
+// The shield pickup forgives the next fatal collision
+pub const SHIELD_CHANCE: f64 = 0.05;
+// The magnet pickup pulls nearby food towards the head for a while
+pub const MAGNET_CHANCE: f64 = 0.05;
+pub const MAGNET_SECONDS: f32 = 8.0;
+// Units per second the food drifts, well below the snake's speed
+pub const MAGNET_SPEED: f32 = 120.0;
+// The hammer pickup breaks crates without boosting, and only turns up while
+// there are any
+pub const HAMMER_CHANCE: f64 = 0.05;
+// How long expiring food stays where it is, and how much of that it blinks
+// through at the end
+pub const FOOD_LIFETIME_SECONDS: f32 = 12.0;
+pub const FOOD_BLINK_SECONDS: f32 = 3.0;
+// Units per second wandering food goes, and the seconds it keeps a heading
+pub const WANDER_SPEED: f32 = 40.0;
+pub const WANDER_MIN_SECONDS: f32 = 1.0;
+pub const WANDER_MAX_SECONDS: f32 = 2.0;
+
+// The food and where it gets placed. Eating it is up to `check_for_collisions`.
+pub struct FoodPlugin;
+
+impl Plugin for FoodPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameRng>()
+            .init_resource::<BonusCountdown>()
+            .init_resource::<Magnet>()
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(FixedUpdate)
+                    .with_system(grow_spawned_food.before(check_for_collisions))
+                    .with_system(expire_bonus_food.after(respawn_food))
+                    .with_system(relocate_expired_food.after(respawn_food))
+                    .with_system(pull_food.after(move_segments).before(check_for_collisions))
+                    .with_system(wander_food.after(grow_spawned_food).before(apply_velocity))
+                    .with_system(decay_magnet.after(score_food)),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::Playing)
+                    .with_system(
+                        reset_bonus_countdown
+                            .after(spawn_gameplay)
+                            .before(restore_saved_game),
+                    )
+                    .with_system(
+                        reset_magnet
+                            .after(spawn_gameplay)
+                            .before(restore_saved_game),
+                    ),
+            );
+    }
+}
+
+// Where the food placement gets its randomness from. Every run is seeded
+// afresh, so with the same seed and the same input the food lands in the
+// same places every time.
+pub struct GameRng {
+    // Given on the command line or in the config, otherwise every run rolls
+    // its own seed
+    pub fixed_seed: Option<u64>,
+    // The seed the current run started from
+    pub seed: u64,
+    // The generator behind `StdRng`, used directly so a saved game can put
+    // it back where it was
+    pub rng: ChaCha12Rng,
+}
+
+impl GameRng {
+    pub fn new(fixed_seed: Option<u64>) -> GameRng {
+        let mut rng = GameRng {
+            fixed_seed,
+            seed: 0,
+            rng: ChaCha12Rng::seed_from_u64(0),
+        };
+        rng.reseed();
+        rng
+    }
+
+    // Starts the sequence over for a new run
+    pub fn reseed(&mut self) {
+        self.seed_with(self.fixed_seed.unwrap_or_else(rand::random));
+    }
+
+    // Starts the sequence of `seed` for a run that has to use it, whatever
+    // the fixed seed is
+    pub fn seed_with(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = ChaCha12Rng::seed_from_u64(seed);
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        GameRng::new(None)
+    }
+}
+
+#[derive(Component)]
+pub struct Food;
+
+// What eating a food does. Every `Food` has one.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FoodKind {
+    // Worth a point and a segment, and replaced right away
+    Normal,
+    // See `BonusFood`
+    Bonus,
+    // Costs a point and `POISON_FOOD_SEGMENTS` segments, and ends the run
+    // when there is no tail left to lose. It isn't replaced when eaten.
+    Poison,
+    // Worth a point and a segment like normal food, then starts the
+    // `ScoreMultiplier`. It isn't replaced when eaten either.
+    Golden,
+    // Not food as such: eating it doesn't score or grow, it starts the
+    // `SpeedModifier`
+    SlowMotion,
+    // Starts the `GhostMode`, and is otherwise like `SlowMotion`
+    Ghost,
+    // Adds a `Shield`, up to `GameConfig::max_shields`
+    Shield,
+    // Starts the `Magnet`
+    Magnet,
+    // Adds `HAMMER_SMASHES` to the `Hammer`
+    Hammer,
+}
+
+impl FoodKind {
+    // Whether eating it adds a segment to the tail
+    pub fn grows(self) -> bool {
+        matches!(self, FoodKind::Normal | FoodKind::Bonus | FoodKind::Golden)
+    }
+}
+
+// Put on new food while it grows to its full size, see `spawn_pop_scale`.
+// The sprite is drawn at that scale, the `Hitbox` stays as it is.
+#[derive(Component, Deref, DerefMut)]
+pub struct SpawnAnimation(pub Timer);
+
+impl Default for SpawnAnimation {
+    fn default() -> Self {
+        SpawnAnimation(Timer::from_seconds(FOOD_SPAWN_SECONDS, false))
+    }
+}
+
+impl SpawnAnimation {
+    pub fn scale(&self) -> f32 {
+        spawn_pop_scale(self.percent())
+    }
+
+    // Only once it has grown that far can it be eaten, so the snake can't
+    // eat what can't be seen yet
+    pub fn edible(&self) -> bool {
+        self.finished() || self.scale() >= FOOD_SPAWN_EDIBLE_SCALE
+    }
+}
+
+// Back-out easing: from 0 at `t` = 0 to a little over 1, and back to 1 at
+// `t` = 1
+pub fn spawn_pop_scale(t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0) - 1.0;
+    1.0 + (FOOD_SPAWN_OVERSHOOT + 1.0) * t.powi(3) + FOOD_SPAWN_OVERSHOOT * t.powi(2)
+}
+
+// Food worth `BONUS_FOOD_POINTS` that goes away by itself when `timer` runs
+// out. It isn't replaced when eaten.
+#[derive(Component)]
+pub struct BonusFood {
+    pub timer: Timer,
+}
+
+impl BonusFood {
+    pub fn remaining_secs(&self) -> f32 {
+        self.timer.duration().as_secs_f32() - self.timer.elapsed_secs()
+    }
+}
+
+// Normal foods left to eat until the next bonus food
+#[derive(Deref, DerefMut)]
+pub struct BonusCountdown(pub usize);
+
+impl Default for BonusCountdown {
+    fn default() -> Self {
+        BonusCountdown(BONUS_FOOD_EVERY)
+    }
+}
+
+// Put on normal food when `GameConfig::expiring_food` is on. Once it runs out
+// the food is put down somewhere else, see `relocate_expired_food`.
+#[derive(Component, Deref, DerefMut)]
+pub struct FoodLifetime(pub Timer);
+
+impl FoodLifetime {
+    pub fn remaining_secs(&self) -> f32 {
+        self.duration().as_secs_f32() - self.elapsed_secs()
+    }
+}
+
+impl Default for FoodLifetime {
+    fn default() -> Self {
+        FoodLifetime(Timer::from_seconds(FOOD_LIFETIME_SECONDS, false))
+    }
+}
+
+// Moves food that wasn't eaten in time to a free spot, as a new food with a
+// lifetime of its own. Runs after `respawn_food`, which still counted the old
+// one, so only this puts a replacement down. Food eaten this tick is worth
+// what it always is, and isn't moved.
+pub fn relocate_expired_food(
+    mut commands: Commands,
+    mut food_events: EventReader<FoodEaten>,
+    mut food_query: Query<(Entity, &mut FoodLifetime)>,
+    occupied_query: Query<
+        (&Transform, &Hitbox),
+        Or<(
+            With<Snake>,
+            With<SnakeSegment>,
+            With<Food>,
+            With<Obstacle>,
+            With<InteriorWall>,
+            With<Portal>,
+        )>,
+    >,
+    config: Res<GameConfig>,
+    bounds: Res<ArenaBounds>,
+    movement_mode: Res<MovementMode>,
+    mut rng: ResMut<GameRng>,
+) {
+    let eaten: Vec<Entity> = food_events.iter().map(|event| event.food).collect();
+    let mut expired = Vec::new();
+    for (food, mut lifetime) in &mut food_query {
+        if eaten.contains(&food) {
+            continue;
+        }
+        lifetime.tick(Duration::from_secs_f32(config.time_step));
+        if lifetime.finished() {
+            expired.push(food);
+        }
+    }
+    if expired.is_empty() {
+        return;
+    }
+
+    let mut occupied: Vec<(Vec3, Vec2)> = occupied_query
+        .iter()
+        .map(|(transform, hitbox)| (transform.translation, **hitbox))
+        .collect();
+    for food in expired {
+        commands.entity(food).despawn();
+        if let Some(position) =
+            free_food_position(&mut rng.rng, &config, &bounds, *movement_mode, &occupied)
+        {
+            spawn_food(&mut commands, &config, position);
+            occupied.push((position, config.food_size));
+        }
+    }
+}
+
+// Put on every food when `GameConfig::wandering_food` is on. The food keeps
+// its heading until `next_turn` runs out, then heads off somewhere new.
+#[derive(Component, Default)]
+pub struct Wander {
+    pub next_turn: Timer,
+}
+
+// Turns the wandering food every `WANDER_MIN_SECONDS` to `WANDER_MAX_SECONDS`,
+// all of it rolled from the `GameRng` so a seed wanders the same way every
+// time. Food heading into a wall or an obstacle bounces off it before it
+// gets there, and holds still until its next turn if it is boxed in. Food
+// still popping up stays put.
+pub fn wander_food(
+    mut food_query: Query<
+        (
+            &Transform,
+            &Hitbox,
+            &mut Velocity,
+            &mut Wander,
+            Option<&SpawnAnimation>,
+        ),
+        With<Food>,
+    >,
+    obstacle_query: Query<
+        (&Transform, &Hitbox),
+        (Or<(With<Obstacle>, With<InteriorWall>)>, Without<Food>),
+    >,
+    config: Res<GameConfig>,
+    bounds: Res<ArenaBounds>,
+    mut rng: ResMut<GameRng>,
+) {
+    for (transform, hitbox, mut velocity, mut wander, spawning) in &mut food_query {
+        if spawning.is_some() {
+            **velocity = Vec2::ZERO;
+            continue;
+        }
+        wander
+            .next_turn
+            .tick(Duration::from_secs_f32(config.time_step));
+        if wander.next_turn.finished() {
+            let angle = rng.rng.gen_range(0.0..std::f32::consts::TAU);
+            **velocity = Vec2::from_angle(angle) * WANDER_SPEED;
+            let seconds = rng.rng.gen_range(WANDER_MIN_SECONDS..=WANDER_MAX_SECONDS);
+            wander.next_turn = Timer::from_seconds(seconds, false);
+        }
+
+        let position = transform.translation;
+        let next = |velocity: Vec2| position + (velocity * config.time_step).extend(0.0);
+        let (min, max) = bounds.inside(**hitbox);
+        let ahead = next(**velocity).truncate();
+        if ahead.x < min.x || ahead.x > max.x {
+            velocity.x = -velocity.x;
+        }
+        if ahead.y < min.y || ahead.y > max.y {
+            velocity.y = -velocity.y;
+        }
+        for (obstacle, size) in &obstacle_query {
+            match collide(next(**velocity), **hitbox, obstacle.translation, **size) {
+                Some(Collision::Left) if velocity.x > 0.0 => velocity.x = -velocity.x,
+                Some(Collision::Right) if velocity.x < 0.0 => velocity.x = -velocity.x,
+                Some(Collision::Bottom) if velocity.y > 0.0 => velocity.y = -velocity.y,
+                Some(Collision::Top) if velocity.y < 0.0 => velocity.y = -velocity.y,
+                _ => {}
+            }
+        }
+
+        let ahead = next(**velocity);
+        let inside = ahead.truncate().clamp(min, max) == ahead.truncate();
+        let blocked = obstacle_query.iter().any(|(obstacle, size)| {
+            collide(ahead, **hitbox, obstacle.translation, **size).is_some()
+        });
+        if !inside || blocked {
+            **velocity = Vec2::ZERO;
+        }
+    }
+}
+
+// Takes the `SpawnAnimation` off once the food has grown to its full size
+pub fn grow_spawned_food(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut SpawnAnimation)>,
+    config: Res<GameConfig>,
+) {
+    for (food, mut animation) in &mut query {
+        animation.tick(Duration::from_secs_f32(config.time_step));
+        if animation.finished() {
+            commands.entity(food).remove::<SpawnAnimation>();
+        }
+    }
+}
+
+// Takes a bonus food away once its time is up, unless it was just eaten
+pub fn expire_bonus_food(
+    mut commands: Commands,
+    mut food_events: EventReader<FoodEaten>,
+    mut bonus_query: Query<(Entity, &mut BonusFood)>,
+    config: Res<GameConfig>,
+) {
+    let eaten: Vec<Entity> = food_events.iter().map(|event| event.food).collect();
+    for (entity, mut bonus) in &mut bonus_query {
+        if eaten.contains(&entity) {
+            continue;
+        }
+        bonus.timer.tick(Duration::from_secs_f32(config.time_step));
+        if bonus.timer.finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+pub fn reset_bonus_countdown(mut countdown: ResMut<BonusCountdown>) {
+    *countdown = BonusCountdown::default();
+}
+
+// While `remaining` runs, food within `radius` of the head drifts towards it.
+// Set by the magnet pickup; a second one starts the time over.
+pub struct Magnet {
+    pub radius: f32,
+    pub remaining: Timer,
+}
+
+impl Magnet {
+    pub fn start(&mut self, radius: f32, seconds: f32) {
+        self.radius = radius;
+        self.remaining = Timer::from_seconds(seconds, false);
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.radius > 0.0
+    }
+
+    pub fn remaining_secs(&self) -> f32 {
+        self.remaining.duration().as_secs_f32() - self.remaining.elapsed_secs()
+    }
+}
+
+impl Default for Magnet {
+    fn default() -> Self {
+        Magnet {
+            radius: 0.0,
+            remaining: Timer::default(),
+        }
+    }
+}
+
+// Moves the food in reach of the magnet straight at the closest head, without
+// overshooting it. Food stops at the walls and at anything else in its way;
+// the rest of the food, like a piece that was just put down further away,
+// stays where it is.
+pub fn pull_food(
+    head_query: Query<&Transform, (With<Snake>, Without<Food>)>,
+    mut food_query: Query<(&mut Transform, &Hitbox), With<Food>>,
+    obstacle_query: Query<
+        (&Transform, &Hitbox),
+        (Or<(With<Obstacle>, With<InteriorWall>)>, Without<Food>),
+    >,
+    magnet: Res<Magnet>,
+    config: Res<GameConfig>,
+    bounds: Res<ArenaBounds>,
+) {
+    if !magnet.is_active() {
+        return;
+    }
+    let heads: Vec<Vec2> = head_query
+        .iter()
+        .map(|head| head.translation.truncate())
+        .collect();
+
+    for (mut transform, hitbox) in &mut food_query {
+        let position = transform.translation.truncate();
+        let Some(head) = heads
+            .iter()
+            .copied()
+            .min_by(|a, b| position.distance(*a).total_cmp(&position.distance(*b)))
+        else {
+            return;
+        };
+        let distance = position.distance(head);
+        if distance > magnet.radius || distance == 0.0 {
+            continue;
+        }
+        let step = (MAGNET_SPEED * config.time_step).min(distance);
+        let (min, max) = bounds.inside(**hitbox);
+        let moved = (position + (head - position) / distance * step)
+            .clamp(min, max)
+            .extend(transform.translation.z);
+        let blocked = obstacle_query.iter().any(|(obstacle, size)| {
+            collide(moved, **hitbox, obstacle.translation, **size).is_some()
+        });
+        if !blocked {
+            transform.translation = moved;
+        }
+    }
+}
+
+// Turns the magnet off again once its time is up
+pub fn decay_magnet(mut magnet: ResMut<Magnet>, config: Res<GameConfig>) {
+    if !magnet.is_active() {
+        return;
+    }
+    magnet
+        .remaining
+        .tick(Duration::from_secs_f32(config.time_step));
+    if magnet.remaining.finished() {
+        *magnet = Magnet::default();
+    }
+}
+
+pub fn reset_magnet(mut magnet: ResMut<Magnet>) {
+    *magnet = Magnet::default();
+}
+
+// Picks a spot for the food so that the whole sprite lies inside the walls.
+// In grid mode the food is always placed on a grid cell.
+pub fn random_food_position(
+    rng: &mut impl Rng,
+    config: &GameConfig,
+    bounds: &ArenaBounds,
+    movement_mode: MovementMode,
+) -> Vec3 {
+    if movement_mode == MovementMode::Grid {
+        let (min, max) = grid_bounds(config, bounds);
+        let cell = IVec2::new(rng.gen_range(min.x..=max.x), rng.gen_range(min.y..=max.y));
+        return cell_center(config, cell);
+    }
+
+    let (min, max) = bounds.inside(config.food_size);
+    let x = rng.gen_range(min.x..max.x);
+    let y = rng.gen_range(min.y..max.y);
+    Vec3::new(x, y, 0.0)
+}
+
+// Every spot the food could be placed on without leaving gaps
+pub fn food_cells(
+    config: &GameConfig,
+    bounds: &ArenaBounds,
+    movement_mode: MovementMode,
+) -> Vec<Vec3> {
+    if movement_mode == MovementMode::Grid {
+        let (min, max) = grid_bounds(config, bounds);
+        return (min.x..=max.x)
+            .flat_map(|x| (min.y..=max.y).map(move |y| cell_center(config, IVec2::new(x, y))))
+            .collect();
+    }
+
+    let (min, max) = bounds.inside(config.food_size);
+    let columns = ((max.x - min.x) / config.food_size.x) as usize + 1;
+    let rows = ((max.y - min.y) / config.food_size.y) as usize + 1;
+
+    (0..columns)
+        .flat_map(|column| (0..rows).map(move |row| (column, row)))
+        .map(|(column, row)| {
+            Vec3::new(
+                min.x + column as f32 * config.food_size.x,
+                min.y + row as f32 * config.food_size.y,
+                0.0,
+            )
+        })
+        .collect()
+}
+
+// Picks a spot for the food that doesn't overlap any of the `occupied`
+// rectangles (given as center and size). A few random rolls are enough
+// while the arena is mostly empty; once the snake fills most of it, the
+// free cells are listed and one of them is picked.
+// Returns `None` when there is no room left at all.
+pub fn free_food_position(
+    rng: &mut impl Rng,
+    config: &GameConfig,
+    bounds: &ArenaBounds,
+    movement_mode: MovementMode,
+    occupied: &[(Vec3, Vec2)],
+) -> Option<Vec3> {
+    let is_free = |position: Vec3| {
+        occupied
+            .iter()
+            .all(|(center, size)| collide(position, config.food_size, *center, *size).is_none())
+    };
+
+    for _ in 0..FOOD_SPAWN_ATTEMPTS {
+        let position = random_food_position(rng, config, bounds, movement_mode);
+        if is_free(position) {
+            return Some(position);
+        }
+    }
+
+    let free_cells: Vec<Vec3> = food_cells(config, bounds, movement_mode)
+        .into_iter()
+        .filter(|position| is_free(*position))
+        .collect();
+    free_cells.choose(rng).copied()
+}
@@ -0,0 +1,1041 @@
+use std::time::Duration;
+
+use bevy::{ecs::schedule::ShouldRun, prelude::*, sprite::collide_aabb::collide, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::achievements::AchievementsPlugin;
+use crate::collision::{
+    grow_snake, score_food, shrink_snake, use_shield, CollisionPlugin, DeathCause, GameOverEvent,
+    Hitbox, SnakeDied,
+};
+use crate::config::GameConfig;
+use crate::crates::{random_crates, CratePlugin};
+use crate::daily::{DailyChallenge, DAILY_DIFFICULTY};
+use crate::food::{free_food_position, Food, FoodPlugin, GameRng};
+use crate::hunger::HungerPlugin;
+use crate::hunter::{Hunter, HunterPlugin};
+use crate::ice::{random_ice_patches, IcePatch, IcePlugin};
+use crate::level::{Campaign, CurrentLevel, LevelPlugin};
+use crate::portal::{random_portals, Portal, PortalPlugin};
+use crate::replay::ReplayPlugin;
+use crate::run_stats::RunStatsPlugin;
+use crate::savegame::{SaveGamePlugin, SavedGameSlot};
+use crate::snake::{
+    cell_center, grid_cell, Direction, DirectionQueue, Dying, GameSpeed, GhostMode, MovementMode,
+    NextDirection, Player, Respawning, SegmentOrder, Snake, SnakePlugin, SnakeSegment, SnakeTrail,
+    SpeedModifier, SpeedRamp, StepOrigin, StepProgress, Velocity, INITIAL_SNAKE_DIRECTION,
+    SNAKE_STARTING_POSITION,
+};
+use crate::spawning::{
+    spawn_crate, spawn_food, spawn_ice_patch, spawn_obstacle, spawn_portals, spawn_snake,
+};
+use crate::state::GameState;
+use crate::statistics::StatisticsPlugin;
+use crate::walls::{
+    grid_bounds, random_obstacles, ArenaBounds, ArenaPlugin, InteriorWall, Obstacle, WallMode,
+};
+
+// Number of foods eaten in the current game. `score` is player one's, the
+// only one outside of versus.
+#[derive(Default)]
+pub struct Scoreboard {
+    pub score: usize,
+    pub player_two: usize,
+}
+
+impl Scoreboard {
+    pub fn score_of(&self, player: Player) -> usize {
+        if player == Player::TWO {
+            self.player_two
+        } else {
+            self.score
+        }
+    }
+
+    pub fn score_mut(&mut self, player: Player) -> &mut usize {
+        if player == Player::TWO {
+            &mut self.player_two
+        } else {
+            &mut self.score
+        }
+    }
+}
+
+// What kind of game the next run is, picked on the main menu
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    // One snake, for the high scores and the leaderboard
+    #[default]
+    Classic,
+    // One snake going for the highest score before the `RoundTimer` runs out
+    TimeAttack,
+    // Two snakes on one keyboard, the first one to die loses the round
+    Versus,
+    // One snake in an arena that keeps closing in, see `ShrinkingArena`
+    Survival,
+    // One snake on the seed of the `DailyChallenge`, for the daily best
+    Daily,
+}
+
+// The lives a run starts with when three lives are picked on the menu,
+// otherwise the first death ends it
+pub const THREE_LIVES: usize = 3;
+
+// How many lives the next run starts with, 1 or `THREE_LIVES`. Picked on the
+// main menu, `GameConfig::three_lives` decides which it starts out as.
+#[derive(Clone, Copy, Deref, DerefMut)]
+pub struct StartingLives(pub usize);
+
+impl StartingLives {
+    pub fn from_config(config: &GameConfig) -> StartingLives {
+        StartingLives(if config.three_lives { THREE_LIVES } else { 1 })
+    }
+
+    pub fn toggle(&mut self) {
+        **self = if **self == 1 { THREE_LIVES } else { 1 };
+    }
+}
+
+// The lives left in the current run, including the one being played. Versus
+// rounds always have just the one.
+#[derive(Default, Deref, DerefMut)]
+pub struct Lives(pub usize);
+
+// What ended the last run, for the game over screen, and how long the snake
+// was then, counting the head. In versus it is the first snake to die.
+#[derive(Default)]
+pub struct LastDeath {
+    pub cause: Option<DeathCause>,
+    pub length: usize,
+}
+
+// How long a time attack run lasts
+pub const TIME_ATTACK_SECONDS: f32 = 120.0;
+
+// The time left in a time attack run.
+#[derive(Deref, DerefMut)]
+pub struct RoundTimer(pub Timer);
+
+impl RoundTimer {
+    pub fn remaining_secs(&self) -> f32 {
+        self.duration().as_secs_f32() - self.elapsed_secs()
+    }
+}
+
+impl Default for RoundTimer {
+    fn default() -> Self {
+        RoundTimer(Timer::from_seconds(TIME_ATTACK_SECONDS, false))
+    }
+}
+
+// The rounds each player won since versus was picked on the menu, and who
+// won the last one, `None` for a draw
+#[derive(Default)]
+pub struct VersusTally {
+    pub wins: [usize; 2],
+    pub last_winner: Option<Player>,
+}
+
+impl VersusTally {
+    // The survivor wins the round. Both snakes dying in the same tick is a
+    // draw.
+    pub fn record(&mut self, losers: &[Player]) {
+        self.last_winner = [Player::ONE, Player::TWO]
+            .into_iter()
+            .find(|player| !losers.contains(player));
+        if let Some(winner) = self.last_winner {
+            self.wins[*winner as usize] += 1;
+        }
+    }
+}
+
+// Every point scored is worth `factor` times as much until `remaining` runs
+// out. Set by eating a golden food.
+#[derive(Clone)]
+pub struct ScoreMultiplier {
+    pub factor: usize,
+    pub remaining: Timer,
+}
+
+impl ScoreMultiplier {
+    // Starts the window over, so a second golden food refreshes it instead
+    // of multiplying again
+    pub fn start(&mut self, factor: usize, seconds: f32) {
+        self.factor = factor;
+        self.remaining = Timer::from_seconds(seconds, false);
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.factor > 1
+    }
+
+    pub fn remaining_secs(&self) -> f32 {
+        self.remaining.duration().as_secs_f32() - self.remaining.elapsed_secs()
+    }
+}
+
+impl Default for ScoreMultiplier {
+    fn default() -> Self {
+        ScoreMultiplier {
+            factor: 1,
+            remaining: Timer::default(),
+        }
+    }
+}
+
+// How long after a food the next one still adds to the combo
+pub const COMBO_SECONDS: f32 = 4.0;
+// The most a combo multiplies the points of a food by
+pub const MAX_COMBO: usize = 5;
+
+// The foods worth points eaten one after the other, each within
+// `COMBO_SECONDS` of the last. The n-th of them scores n times, up to
+// `MAX_COMBO`. Shared by both snakes in versus.
+#[derive(Default, Clone)]
+pub struct Combo {
+    pub count: usize,
+    pub window: Timer,
+    // The longest combo of the run, for the game over screen
+    pub best: usize,
+}
+
+impl Combo {
+    // Counts another food and opens the window for the next one
+    pub fn extend(&mut self) {
+        self.count += 1;
+        self.best = self.best.max(self.count);
+        self.window = Timer::from_seconds(COMBO_SECONDS, false);
+    }
+
+    pub fn factor(&self) -> usize {
+        self.count.clamp(1, MAX_COMBO)
+    }
+
+    pub fn remaining_secs(&self) -> f32 {
+        self.window.duration().as_secs_f32() - self.window.elapsed_secs()
+    }
+}
+
+// The presets a game can be started with, picked on the main menu
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Difficulty {
+    // A slow snake that slides along the walls instead of dying on them
+    Easy,
+    #[default]
+    Normal,
+    // A fast snake that speeds up quickly, walls are always lethal
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    // The difficulty `steps` places further along `ALL`, wrapping around
+    pub fn cycle(self, steps: isize) -> Difficulty {
+        let count = Difficulty::ALL.len() as isize;
+        let index = Difficulty::ALL.iter().position(|d| *d == self).unwrap() as isize;
+        Difficulty::ALL[(index + steps).rem_euclid(count) as usize]
+    }
+
+    // What a run in `mode` is played on. The daily challenge has to be the
+    // same for everyone playing it that day, whatever they picked.
+    pub fn played_in(self, mode: GameMode) -> Difficulty {
+        if mode == GameMode::Daily {
+            DAILY_DIFFICULTY
+        } else {
+            self
+        }
+    }
+
+    // The speed multiplier a game starts with
+    pub fn starting_speed(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.25,
+        }
+    }
+
+    // How much faster than on normal the hunger meter drains
+    pub fn hunger_rate(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.5,
+        }
+    }
+
+    pub fn speed_ramp(self) -> SpeedRamp {
+        match self {
+            Difficulty::Easy => SpeedRamp {
+                increment: 0.05,
+                foods_per_step: 2,
+                max_multiplier: 1.5,
+            },
+            Difficulty::Normal => SpeedRamp::default(),
+            Difficulty::Hard => SpeedRamp {
+                increment: 0.2,
+                foods_per_step: 1,
+                max_multiplier: 3.0,
+            },
+        }
+    }
+
+    // Normal leaves the walls to the `--casual` and `--bounce` command line
+    // flags
+    pub fn wall_mode(self) -> WallMode {
+        match self {
+            Difficulty::Easy => WallMode::Casual,
+            Difficulty::Normal => WallMode::from_args(),
+            Difficulty::Hard => WallMode::Lethal,
+        }
+    }
+}
+
+// The best score reached on each difficulty and in each daily challenge,
+// kept on disk by the `PersistencePlugin`
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HighScores {
+    pub best: HashMap<Difficulty, usize>,
+    // By `DailyDate::key`, only for the days that were played
+    pub daily: HashMap<String, usize>,
+    // Whether the last finished run beat the previous best
+    #[serde(skip)]
+    pub last_was_best: bool,
+    // The best of the day before the last daily run, `None` on the first try
+    #[serde(skip)]
+    pub previous_daily: Option<usize>,
+}
+
+impl HighScores {
+    pub fn best(&self, difficulty: Difficulty) -> usize {
+        self.best.get(&difficulty).copied().unwrap_or(0)
+    }
+
+    pub fn record(&mut self, difficulty: Difficulty, score: usize) {
+        let best = self.best.entry(difficulty).or_insert(0);
+        self.last_was_best = score > *best;
+        *best = (*best).max(score);
+    }
+
+    pub fn daily_best(&self, key: &str) -> Option<usize> {
+        self.daily.get(key).copied()
+    }
+
+    // Only the best attempt of the day is kept. The first one always is.
+    pub fn record_daily(&mut self, key: &str, score: usize) {
+        self.previous_daily = self.daily_best(key);
+        self.last_was_best = self.previous_daily.is_none_or(|best| score > best);
+        let best = self.daily.entry(key.to_string()).or_insert(score);
+        *best = (*best).max(score);
+    }
+}
+
+// How many runs the leaderboard keeps
+pub const LEADERBOARD_SIZE: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub initials: String,
+    pub score: usize,
+    pub difficulty: Difficulty,
+}
+
+// The best runs on any difficulty, highest score first, kept on disk by the
+// `PersistencePlugin`
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Leaderboard {
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+impl Leaderboard {
+    // Whether a run with `score` would make it onto the table. A score tying
+    // the last entry of a full table doesn't, the earlier run keeps its place.
+    pub fn qualifies(&self, score: usize) -> bool {
+        score > 0
+            && (self.entries.len() < LEADERBOARD_SIZE
+                || score > self.entries[LEADERBOARD_SIZE - 1].score)
+    }
+
+    // Adds `entry` below every run with the same score and returns its place,
+    // or `None` if it doesn't qualify
+    pub fn insert(&mut self, entry: LeaderboardEntry) -> Option<usize> {
+        if !self.qualifies(entry.score) {
+            return None;
+        }
+        let index = self
+            .entries
+            .iter()
+            .position(|other| other.score < entry.score)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(index, entry);
+        self.entries.truncate(LEADERBOARD_SIZE);
+        Some(index)
+    }
+}
+
+// While set, none of the gameplay systems run
+#[derive(Default, Deref, DerefMut)]
+pub struct Paused(pub bool);
+
+// How long the 3-2-1 before the snake moves lasts
+pub const COUNTDOWN_SECONDS: f32 = 3.0;
+
+// Counts down before the snake moves: at the start of a run, after a lost
+// life and, with `GameConfig::countdown_on_resume`, after a pause. None of
+// the gameplay systems run until it is finished, but the steering input is
+// still queued. A fresh one is running, so a run never starts without it.
+#[derive(Deref, DerefMut)]
+pub struct Countdown(pub Timer);
+
+impl Default for Countdown {
+    fn default() -> Self {
+        Countdown(Timer::from_seconds(COUNTDOWN_SECONDS, false))
+    }
+}
+
+// The number of gameplay ticks since the run started, counted before any
+// other gameplay system runs. The first tick of a run is tick 1. Every timer
+// of the gameplay counts in these ticks rather than in real time, so it
+// stands still while the game is paused and plays out the same in a replay.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Deref, DerefMut)]
+pub struct GameTick(pub u64);
+
+// How many ticks run in the real time one `GameConfig::time_step` takes.
+// Fast-forwarding a replay raises it.
+#[derive(Deref, DerefMut)]
+pub struct TickSpeed(pub f32);
+
+impl Default for TickSpeed {
+    fn default() -> Self {
+        TickSpeed(1.0)
+    }
+}
+
+// The rules of the game: movement, collisions, food, scoring and the screens
+// a game moves through, each feature in a plugin of its own added below.
+// Doesn't need a window, so it also runs on top of `MinimalPlugins`, steered
+// through the `DirectionQueue` and `HeldDirections` on each head.
+// Uses the `GameConfig`, `WallMode`, `MovementMode` and `GameRng` already
+// inserted, or their defaults.
+pub struct SnakeGamePlugin;
+
+impl Plugin for SnakeGamePlugin {
+    fn build(&self, app: &mut App) {
+        let config = app.world.get_resource_or_insert_with(GameConfig::default);
+        let starting_lives = StartingLives::from_config(&config);
+
+        app.add_state(GameState::Menu)
+            .init_resource::<Scoreboard>()
+            .init_resource::<ScoreMultiplier>()
+            .init_resource::<Combo>()
+            .init_resource::<GameMode>()
+            .init_resource::<VersusTally>()
+            .init_resource::<RoundTimer>()
+            .init_resource::<DailyChallenge>()
+            .insert_resource(starting_lives)
+            .init_resource::<Lives>()
+            .init_resource::<LastDeath>()
+            .init_resource::<Paused>()
+            .init_resource::<Countdown>()
+            .init_resource::<Difficulty>()
+            .init_resource::<HighScores>()
+            .init_resource::<Leaderboard>()
+            .init_resource::<GameTick>()
+            .init_resource::<TickSpeed>()
+            .init_resource::<TickFraction>()
+            // Every plugin's fixed-timestep systems share this criteria, so
+            // they all tick together
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(fixed_tick.chain(while_playing).label(FixedUpdate))
+                    .with_system(count_tick)
+                    .with_system(decay_score_multiplier.after(score_food))
+                    .with_system(decay_combo.after(score_food))
+                    .with_system(count_down_round.after(count_tick))
+                    .with_system(
+                        lose_life
+                            .after(use_shield)
+                            .after(shrink_snake)
+                            .after(grow_snake),
+                    )
+                    .with_system(handle_game_over.after(lose_life)),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(spawn_gameplay))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(run_countdown)
+                    .with_system(play_death_animation),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Playing).with_system(despawn_gameplay))
+            .add_plugin(ArenaPlugin)
+            .add_plugin(LevelPlugin)
+            .add_plugin(SnakePlugin)
+            .add_plugin(FoodPlugin)
+            .add_plugin(CollisionPlugin)
+            .add_plugin(CratePlugin)
+            .add_plugin(PortalPlugin)
+            .add_plugin(HunterPlugin)
+            .add_plugin(IcePlugin)
+            .add_plugin(HungerPlugin)
+            .add_plugin(ReplayPlugin)
+            .add_plugin(SaveGamePlugin)
+            .add_plugin(AchievementsPlugin)
+            .add_plugin(StatisticsPlugin)
+            .add_plugin(RunStatsPlugin);
+    }
+}
+
+// How far the time is towards the next tick, from 0 to 1, for the render side
+// to draw what moves in between the ticks
+#[derive(Default, Deref, DerefMut)]
+pub struct TickFraction(pub f32);
+
+// The run criteria of the systems that advance the game by one tick
+#[derive(RunCriteriaLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FixedUpdate;
+
+// Works like bevy's `FixedTimestep`: runs the gameplay systems once for every
+// `time_step` of real time that passed, only scaled by the `TickSpeed`
+pub fn fixed_tick(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    speed: Res<TickSpeed>,
+    mut fraction: ResMut<TickFraction>,
+    mut accumulator: Local<f64>,
+    mut looping: Local<bool>,
+) -> ShouldRun {
+    if !*looping {
+        *accumulator += time.delta_seconds_f64() * **speed as f64;
+    }
+
+    let step = config.time_step as f64;
+    if *accumulator >= step {
+        *accumulator -= step;
+        *looping = true;
+        ShouldRun::YesAndCheckAgain
+    } else {
+        *looping = false;
+        **fraction = (*accumulator / step) as f32;
+        ShouldRun::No
+    }
+}
+
+// Every other gameplay system runs after it, so they all see the number of
+// the tick they run in
+pub fn count_tick(mut tick: ResMut<GameTick>) {
+    **tick += 1;
+}
+
+// Holds the fixed-timestep systems back unless a game is running, isn't
+// paused, is done counting down and no snake is dying. The timestep keeps
+// consuming the elapsed time in the meantime, so resuming doesn't run a
+// burst of catch-up ticks.
+pub fn while_playing(
+    In(should_run): In<ShouldRun>,
+    state: Res<State<GameState>>,
+    paused: Res<Paused>,
+    countdown: Res<Countdown>,
+    dying_query: Query<(), With<Dying>>,
+) -> ShouldRun {
+    if gameplay_running(&state, &paused, &countdown) && dying_query.is_empty() {
+        should_run
+    } else {
+        ShouldRun::No
+    }
+}
+
+// Whether the ticks are advancing the game at all
+pub fn gameplay_running(state: &State<GameState>, paused: &Paused, countdown: &Countdown) -> bool {
+    *state.current() == GameState::Playing && !**paused && countdown.finished()
+}
+
+// Counts the `Countdown` down in real time, standing still while paused. With
+// `countdown_on_resume` set, unpausing starts it over.
+pub fn run_countdown(
+    time: Res<Time>,
+    paused: Res<Paused>,
+    config: Res<GameConfig>,
+    mut countdown: ResMut<Countdown>,
+) {
+    if config.countdown_on_resume && paused.is_changed() && !paused.is_added() && !**paused {
+        *countdown = Countdown::default();
+    }
+    if !**paused {
+        countdown.tick(time.delta());
+    }
+}
+
+// Clears the arena once a run is over, and has the next one count down
+pub fn despawn_gameplay(
+    mut commands: Commands,
+    query: Query<
+        Entity,
+        Or<(
+            With<Snake>,
+            With<SnakeSegment>,
+            With<Food>,
+            With<Obstacle>,
+            With<InteriorWall>,
+            With<Portal>,
+            With<Hunter>,
+            With<IcePatch>,
+        )>,
+    >,
+    mut countdown: ResMut<Countdown>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+    *countdown = Countdown::default();
+}
+
+// Where the snakes of a run start and which way they head. Versus puts one
+// snake above the middle of the arena and the other below it, heading in
+// opposite directions.
+pub fn starting_positions(
+    config: &GameConfig,
+    bounds: &ArenaBounds,
+    mode: GameMode,
+) -> Vec<(Player, Vec3, Direction)> {
+    let heading = Direction::from_vec2(INITIAL_SNAKE_DIRECTION);
+    if mode != GameMode::Versus {
+        return vec![(Player::ONE, SNAKE_STARTING_POSITION, heading)];
+    }
+    let (min, max) = grid_bounds(config, bounds);
+    vec![
+        (
+            Player::ONE,
+            cell_center(config, IVec2::new(0, max.y / 2)),
+            heading,
+        ),
+        (
+            Player::TWO,
+            cell_center(config, IVec2::new(0, min.y / 2)),
+            heading.opposite(),
+        ),
+    ]
+}
+
+// Starts a fresh run: a new snake, or two in versus, and `simultaneous_food`
+// pieces of food, and obstacles or the walls of the level, and everything
+// left over from the previous run forgotten. Versus always plays the open
+// arena.
+pub fn spawn_gameplay(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    bounds: Res<ArenaBounds>,
+    mut rng: ResMut<GameRng>,
+    movement_mode: Res<MovementMode>,
+    (difficulty, mode, daily): (Res<Difficulty>, Res<GameMode>, Res<DailyChallenge>),
+    mut wall_mode: ResMut<WallMode>,
+    mut ramp: ResMut<SpeedRamp>,
+    mut paused: ResMut<Paused>,
+    (mut scoreboard, mut multiplier, mut combo, mut round_timer): (
+        ResMut<Scoreboard>,
+        ResMut<ScoreMultiplier>,
+        ResMut<Combo>,
+        ResMut<RoundTimer>,
+    ),
+    (mut speed, mut modifier, mut ghost): (
+        ResMut<GameSpeed>,
+        ResMut<SpeedModifier>,
+        ResMut<GhostMode>,
+    ),
+    (mut tick, mut lives, starting_lives, mut last_death): (
+        ResMut<GameTick>,
+        ResMut<Lives>,
+        Res<StartingLives>,
+        ResMut<LastDeath>,
+    ),
+    (slot, level, campaign): (Res<SavedGameSlot>, Res<CurrentLevel>, Res<Campaign>),
+) {
+    *tick = GameTick::default();
+    *last_death = LastDeath::default();
+    **paused = false;
+    // Later levels of the campaign keep the score and the lives left, and go
+    // faster
+    if campaign.0.as_ref().is_none_or(|level| level.index == 0) {
+        scoreboard.score = 0;
+        **lives = if matches!(*mode, GameMode::Versus | GameMode::Daily) {
+            1
+        } else {
+            **starting_lives
+        };
+    }
+    scoreboard.player_two = 0;
+    *multiplier = ScoreMultiplier::default();
+    *combo = Combo::default();
+    *round_timer = RoundTimer::default();
+    let stage_speed = campaign.0.as_ref().map_or(1.0, |level| level.stage().speed);
+    let difficulty = difficulty.played_in(*mode);
+    *speed = GameSpeed(difficulty.starting_speed() * stage_speed);
+    *modifier = SpeedModifier::default();
+    *ghost = GhostMode::default();
+    *ramp = difficulty.speed_ramp();
+    if *mode == GameMode::Daily {
+        *wall_mode = WallMode::Lethal;
+        rng.seed_with(daily.0.seed());
+    } else {
+        *wall_mode = difficulty.wall_mode();
+        rng.reseed();
+    }
+
+    // `restore_saved_game` spawns the saved snake and food instead
+    if slot.resuming {
+        return;
+    }
+    // A level is laid out by hand and places the first food itself, the open
+    // arena gets random obstacles
+    let layout = campaign
+        .layout(&level)
+        .filter(|_| *mode != GameMode::Versus);
+    let mut starts = starting_positions(&config, &bounds, *mode);
+    let mut occupied = match layout {
+        Some(level) => {
+            level.spawn_walls(&mut commands, &config);
+            level.spawn_crates(&mut commands, &config);
+            level.spawn_ice(&mut commands, &config);
+            let food_position = cell_center(&config, level.food);
+            spawn_food(&mut commands, &config, food_position);
+            let mut occupied = level.wall_blocks(&config);
+            occupied.extend(
+                level
+                    .crates
+                    .iter()
+                    .map(|cell| (cell_center(&config, *cell), config.snake_size)),
+            );
+            occupied.push((food_position, config.food_size));
+            starts[0].1 = cell_center(&config, level.start);
+            occupied
+        }
+        None => {
+            let obstacle_count = campaign
+                .0
+                .as_ref()
+                .map_or(config.obstacle_count, |level| level.stage().obstacles);
+            let positions: Vec<Vec3> = starts.iter().map(|(_, position, _)| *position).collect();
+            let obstacles =
+                random_obstacles(&mut rng.rng, &config, &bounds, obstacle_count, &positions);
+            for (position, size) in &obstacles {
+                spawn_obstacle(&mut commands, *position, *size);
+            }
+            let crates = random_crates(
+                &mut rng.rng,
+                &config,
+                &bounds,
+                config.crate_count,
+                &obstacles,
+                &positions,
+            );
+            let mut occupied = obstacles;
+            for position in crates {
+                spawn_crate(&mut commands, &config, position);
+                occupied.push((position, config.snake_size));
+            }
+            // The ice is only floor, nothing else has to keep clear of it
+            let ice = random_ice_patches(
+                &mut rng.rng,
+                &config,
+                &bounds,
+                config.ice_count,
+                &occupied,
+                &positions,
+            );
+            for (position, size) in ice {
+                spawn_ice_patch(&mut commands, position, size);
+            }
+            occupied
+        }
+    };
+    for (player, position, heading) in starts {
+        spawn_snake(
+            &mut commands,
+            &config,
+            *movement_mode,
+            *speed,
+            player,
+            position,
+            heading,
+        );
+        occupied.push((position, config.snake_size));
+    }
+    if config.portals && layout.is_none() {
+        if let Some(positions) = random_portals(&mut rng.rng, &config, &bounds, &occupied) {
+            spawn_portals(&mut commands, &config, positions);
+            occupied.push((positions.0, config.snake_size));
+            occupied.push((positions.1, config.snake_size));
+        }
+    }
+
+    let placed = usize::from(layout.is_some());
+    for _ in placed..config.simultaneous_food {
+        if let Some(food_position) =
+            free_food_position(&mut rng.rng, &config, &bounds, *movement_mode, &occupied)
+        {
+            spawn_food(&mut commands, &config, food_position);
+            occupied.push((food_position, config.food_size));
+        }
+    }
+}
+
+// Closes the multiplier window once its time is up
+pub fn decay_score_multiplier(mut multiplier: ResMut<ScoreMultiplier>, config: Res<GameConfig>) {
+    if !multiplier.is_active() {
+        return;
+    }
+    multiplier
+        .remaining
+        .tick(Duration::from_secs_f32(config.time_step));
+    if multiplier.remaining.finished() {
+        *multiplier = ScoreMultiplier::default();
+    }
+}
+
+// Like the multiplier, the window only runs while the game does
+pub fn decay_combo(mut combo: ResMut<Combo>, config: Res<GameConfig>) {
+    if combo.count == 0 {
+        return;
+    }
+    combo.window.tick(Duration::from_secs_f32(config.time_step));
+    if combo.window.finished() {
+        combo.count = 0;
+    }
+}
+
+// Ends the run in the tick the time attack clock runs out, whether or not
+// the snake is still alive
+pub fn count_down_round(
+    mut round_timer: ResMut<RoundTimer>,
+    mode: Res<GameMode>,
+    config: Res<GameConfig>,
+    mut last_death: ResMut<LastDeath>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if *mode != GameMode::TimeAttack || round_timer.finished() {
+        return;
+    }
+    round_timer.tick(Duration::from_secs_f32(config.time_step));
+    if round_timer.finished() {
+        last_death.cause = Some(DeathCause::TimeUp);
+        let _ = state.set(GameState::GameOver);
+    }
+}
+
+// The free cell closest to the middle of the arena, where a snake that lost
+// a life comes back. Anything in `occupied` (given as center and size) is
+// kept clear of.
+pub fn respawn_position(
+    config: &GameConfig,
+    bounds: &ArenaBounds,
+    occupied: &[(Vec3, Vec2)],
+) -> Vec3 {
+    let (min, max) = grid_bounds(config, bounds);
+    let center = grid_cell(config, SNAKE_STARTING_POSITION);
+    let mut cells: Vec<IVec2> = (min.x..=max.x)
+        .flat_map(|x| (min.y..=max.y).map(move |y| IVec2::new(x, y)))
+        .collect();
+    cells.sort_by_key(|cell| {
+        let offset = *cell - center;
+        offset.dot(offset)
+    });
+    cells
+        .into_iter()
+        .map(|cell| cell_center(config, cell))
+        .find(|position| {
+            occupied
+                .iter()
+                .all(|(other, size)| collide(*position, config.snake_size, *other, *size).is_none())
+        })
+        .unwrap_or(SNAKE_STARTING_POSITION)
+}
+
+// Takes a life from a snake that died. If it has another one, the body is
+// gone and the head comes back in the middle heading along
+// `INITIAL_SNAKE_DIRECTION`, and after a `Countdown` blinks for a moment
+// before it moves on. Without one left the run is over. A snake still
+// blinking can't die.
+pub fn lose_life(
+    mut commands: Commands,
+    mut died_events: EventReader<SnakeDied>,
+    mut lives: ResMut<Lives>,
+    mut head_query: Query<
+        (
+            &mut Transform,
+            &mut Direction,
+            &mut NextDirection,
+            &mut StepProgress,
+            Option<&mut Velocity>,
+            &mut SegmentOrder,
+            &mut SnakeTrail,
+            &mut DirectionQueue,
+            &mut StepOrigin,
+        ),
+        (With<Snake>, Without<Respawning>),
+    >,
+    blocker_query: Query<
+        (&Transform, &Hitbox),
+        (
+            Or<(
+                With<Food>,
+                With<Obstacle>,
+                With<InteriorWall>,
+                With<Portal>,
+                With<IcePatch>,
+            )>,
+            Without<Snake>,
+        ),
+    >,
+    config: Res<GameConfig>,
+    bounds: Res<ArenaBounds>,
+    (speed, modifier): (Res<GameSpeed>, Res<SpeedModifier>),
+    mut game_over_events: EventWriter<GameOverEvent>,
+    mut countdown: ResMut<Countdown>,
+) {
+    let mut died: Vec<(Entity, DeathCause)> = Vec::new();
+    for event in died_events.iter() {
+        if died.iter().all(|(snake, _)| *snake != event.snake) {
+            died.push((event.snake, event.cause));
+        }
+    }
+
+    for (snake, cause) in died {
+        let Ok((
+            mut transform,
+            mut direction,
+            mut next_direction,
+            mut progress,
+            velocity,
+            mut segments,
+            mut trail,
+            mut queue,
+            mut origin,
+        )) = head_query.get_mut(snake)
+        else {
+            continue;
+        };
+        **lives = lives.saturating_sub(1);
+        if **lives == 0 {
+            game_over_events.send(GameOverEvent { snake, cause });
+            continue;
+        }
+
+        for segment in segments.drain(..) {
+            commands.entity(segment).despawn_recursive();
+        }
+        let occupied: Vec<(Vec3, Vec2)> = blocker_query
+            .iter()
+            .map(|(blocker, hitbox)| (blocker.translation, **hitbox))
+            .collect();
+        transform.translation = respawn_position(&config, &bounds, &occupied);
+        **origin = transform.translation;
+        let heading = Direction::from_vec2(INITIAL_SNAKE_DIRECTION);
+        *direction = heading;
+        **next_direction = heading;
+        **progress = 0.0;
+        if let Some(mut velocity) = velocity {
+            **velocity =
+                heading.cell_offset().as_vec2() * config.snake_speed * **speed * modifier.factor;
+        }
+        trail.clear();
+        queue.clear();
+        commands.entity(snake).insert(Respawning::default());
+        *countdown = Countdown::default();
+    }
+}
+
+// Ends the run as soon as a snake is out of lives, a time attack run with
+// the score so far. A versus round counts towards the tally instead of the
+// high scores.
+// Time attack keeps no high scores, its runs don't compare with classic ones.
+// The game over screen waits for the snakes that died to play out `Dying`.
+pub fn handle_game_over(
+    mut commands: Commands,
+    mut game_over_events: EventReader<GameOverEvent>,
+    mut snake_query: Query<
+        (
+            &Player,
+            Option<&mut Velocity>,
+            &mut DirectionQueue,
+            &SegmentOrder,
+        ),
+        With<Snake>,
+    >,
+    scoreboard: Res<Scoreboard>,
+    (difficulty, mode, daily): (Res<Difficulty>, Res<GameMode>, Res<DailyChallenge>),
+    mut high_scores: ResMut<HighScores>,
+    mut tally: ResMut<VersusTally>,
+    mut last_death: ResMut<LastDeath>,
+) {
+    let mut losers: Vec<Player> = Vec::new();
+    for event in game_over_events.iter() {
+        let Ok((player, _, _, segments)) = snake_query.get(event.snake) else {
+            continue;
+        };
+        if last_death.cause.is_none() {
+            last_death.cause = Some(event.cause);
+            last_death.length = segments.len() + 1;
+        }
+        if !losers.contains(player) {
+            losers.push(*player);
+            commands
+                .entity(event.snake)
+                .insert(Dying::new(segments.len()));
+        }
+    }
+    if losers.is_empty() {
+        return;
+    }
+
+    match *mode {
+        GameMode::Classic => high_scores.record(*difficulty, scoreboard.score),
+        GameMode::Daily => high_scores.record_daily(&daily.0.key(), scoreboard.score),
+        GameMode::TimeAttack | GameMode::Survival => {}
+        GameMode::Versus => tally.record(&losers),
+    }
+    for (_, velocity, mut queue, _) in &mut snake_query {
+        queue.clear();
+        if let Some(mut velocity) = velocity {
+            **velocity = Vec2::ZERO;
+        }
+    }
+}
+
+// Plays out `Dying` in real time, and ends the run once every snake that
+// died is done. It stands still while paused.
+pub fn play_death_animation(
+    mut commands: Commands,
+    time: Res<Time>,
+    paused: Res<Paused>,
+    mut query: Query<(&mut Dying, &mut SegmentOrder)>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if query.is_empty() || **paused {
+        return;
+    }
+    let mut finished = true;
+    for (mut dying, mut segments) in &mut query {
+        dying.timer.tick(time.delta());
+        while segments.len() > dying.segments_due() {
+            if let Some(segment) = segments.pop() {
+                commands.entity(segment).despawn_recursive();
+            }
+        }
+        dying.segments_remaining = segments.len();
+        finished &= dying.timer.finished();
+    }
+    // Several deaths in the same frame only need one transition
+    if finished {
+        let _ = state.set(GameState::GameOver);
+    }
+}
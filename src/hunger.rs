@@ -0,0 +1,113 @@
+use bevy::prelude::*;
+
+use crate::collision::{check_for_collisions, DeathCause, FoodEaten, SnakeDied};
+use crate::config::GameConfig;
+use crate::food::FoodKind;
+use crate::game::{lose_life, spawn_gameplay, Difficulty, FixedUpdate, GameMode};
+use crate::snake::Snake;
+use crate::state::GameState;
+
+// How long a full meter lasts on normal
+pub const HUNGER_SECONDS: f32 = 20.0;
+// How much of the meter food that grows the snake fills back up, and the
+// bonus food
+pub const HUNGER_FOOD_REFILL: f32 = 0.5;
+pub const HUNGER_BONUS_REFILL: f32 = 1.0;
+// Below this part of the meter the HUD warns that the snake is starving
+pub const HUNGER_LOW: f32 = 0.25;
+
+// The snake starving unless it keeps eating, when `GameConfig::hunger` is
+// on. Versus has no meter, one would be shared by both snakes.
+pub struct HungerPlugin;
+
+impl Plugin for HungerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Hunger>()
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(FixedUpdate)
+                    .with_system(feed_hunger.after(check_for_collisions))
+                    .with_system(drain_hunger.after(feed_hunger).before(lose_life)),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::Playing)
+                    .with_system(reset_hunger.after(spawn_gameplay)),
+            );
+    }
+}
+
+// The hunger meter, in seconds of normal draining left out of `max`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hunger {
+    pub value: f32,
+    pub max: f32,
+}
+
+impl Default for Hunger {
+    fn default() -> Self {
+        Hunger {
+            value: HUNGER_SECONDS,
+            max: HUNGER_SECONDS,
+        }
+    }
+}
+
+impl Hunger {
+    // How full the meter is, from 0 to 1
+    pub fn fraction(&self) -> f32 {
+        self.value / self.max
+    }
+
+    pub fn is_low(&self) -> bool {
+        self.fraction() < HUNGER_LOW
+    }
+
+    // Fills `part` of the whole meter back up
+    pub fn refill(&mut self, part: f32) {
+        self.value = (self.value + part * self.max).min(self.max);
+    }
+}
+
+pub fn reset_hunger(mut hunger: ResMut<Hunger>) {
+    *hunger = Hunger::default();
+}
+
+// The food that grows the snake refills the meter by `HUNGER_FOOD_REFILL`,
+// the bonus food by `HUNGER_BONUS_REFILL`. The pickups don't.
+pub fn feed_hunger(mut food_events: EventReader<FoodEaten>, mut hunger: ResMut<Hunger>) {
+    for event in food_events.iter() {
+        let part = match event.kind {
+            FoodKind::Bonus => HUNGER_BONUS_REFILL,
+            kind if kind.grows() => HUNGER_FOOD_REFILL,
+            _ => continue,
+        };
+        hunger.refill(part);
+    }
+}
+
+// Empties the meter a tick at a time, faster on harder difficulties. An
+// empty meter starves the snake, and is full again for its next life.
+pub fn drain_hunger(
+    mut hunger: ResMut<Hunger>,
+    snake_query: Query<Entity, With<Snake>>,
+    config: Res<GameConfig>,
+    difficulty: Res<Difficulty>,
+    mode: Res<GameMode>,
+    mut died_events: EventWriter<SnakeDied>,
+) {
+    if !config.hunger || *mode == GameMode::Versus {
+        return;
+    }
+    let rate = difficulty.played_in(*mode).hunger_rate();
+    hunger.value = (hunger.value - config.time_step * rate).max(0.0);
+    if hunger.value > 0.0 {
+        return;
+    }
+    for snake in &snake_query {
+        died_events.send(SnakeDied {
+            snake,
+            cause: DeathCause::Starved,
+        });
+    }
+    *hunger = Hunger::default();
+}
@@ -0,0 +1,217 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::sprite::collide_aabb::collide;
+use rand::seq::SliceRandom;
+
+use crate::collision::{check_for_collisions, score_food, CollisionEvent, FoodEaten, Hitbox};
+use crate::config::GameConfig;
+use crate::food::{food_cells, Food, FoodKind, GameRng};
+use crate::game::{spawn_gameplay, Difficulty, FixedUpdate, GameMode, Scoreboard};
+use crate::snake::{
+    apply_velocity, GameSpeed, MovementMode, Player, Snake, SnakeSegment, SpeedModifier, Velocity,
+};
+use crate::spawning::spawn_hunter;
+use crate::state::GameState;
+use crate::walls::{ArenaBounds, Obstacle, Wall};
+
+// Normal foods eaten before a hunter comes out, and again after it is gone
+pub const HUNTER_AFTER_FOODS: usize = 10;
+// How long the hunter keeps lunging the same way before it looks for the
+// head again
+pub const HUNTER_RETARGET_SECONDS: f32 = 2.0;
+// The hunter's speed, as a part of the snake's
+pub const HUNTER_SPEED_FACTOR: f32 = 0.4;
+// It never comes out any closer than this to a head
+pub const HUNTER_SAFE_DISTANCE: f32 = 200.0;
+// What a hunter lunging into a wall is worth to the snake it was after
+pub const HUNTER_BONUS_POINTS: usize = 5;
+
+// A hazard chasing the snakes, when `GameConfig::hunter` is on. It is left
+// out of runs played on easy.
+pub struct HunterPlugin;
+
+impl Plugin for HunterPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HunterCountdown>()
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(FixedUpdate)
+                    .with_system(release_hunter.after(score_food))
+                    .with_system(steer_hunter.before(apply_velocity))
+                    .with_system(
+                        crash_hunter
+                            .after(apply_velocity)
+                            .before(check_for_collisions),
+                    )
+                    .with_system(retire_hunter.after(check_for_collisions)),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::Playing)
+                    .with_system(reset_hunter_countdown.after(spawn_gameplay)),
+            );
+    }
+}
+
+// The red square after the snakes. Every `HUNTER_RETARGET_SECONDS` it turns
+// towards the nearest head and lunges on that way, `chasing` the player
+// whose head that is.
+#[derive(Component)]
+pub struct Hunter {
+    pub heading: Vec2,
+    pub retarget: Timer,
+    pub chasing: Player,
+    // Set by `crash_hunter` right away, while its despawn waits for the end
+    // of the stage. `check_for_collisions` leaves a crashed hunter be.
+    pub crashed: bool,
+}
+
+// Normal foods left to eat until the next hunter comes out
+#[derive(Deref, DerefMut)]
+pub struct HunterCountdown(pub usize);
+
+impl Default for HunterCountdown {
+    fn default() -> Self {
+        HunterCountdown(HUNTER_AFTER_FOODS)
+    }
+}
+
+pub fn reset_hunter_countdown(mut countdown: ResMut<HunterCountdown>) {
+    *countdown = HunterCountdown::default();
+}
+
+// Counts the normal food down, and once it is all eaten lets a hunter out on
+// a free cell at least `HUNTER_SAFE_DISTANCE` from every head. The countdown
+// waits at zero while a hunter is out, or while there is nowhere to put one.
+pub fn release_hunter(
+    mut commands: Commands,
+    mut food_events: EventReader<FoodEaten>,
+    mut countdown: ResMut<HunterCountdown>,
+    hunter_query: Query<(), With<Hunter>>,
+    head_query: Query<&Transform, With<Snake>>,
+    blocker_query: Query<
+        (&Transform, &Hitbox),
+        Or<(With<SnakeSegment>, With<Obstacle>, With<Wall>, With<Food>)>,
+    >,
+    config: Res<GameConfig>,
+    bounds: Res<ArenaBounds>,
+    (difficulty, mode): (Res<Difficulty>, Res<GameMode>),
+    mut rng: ResMut<GameRng>,
+) {
+    let eaten = food_events
+        .iter()
+        .filter(|event| event.kind == FoodKind::Normal)
+        .count();
+    if !config.hunter || difficulty.played_in(*mode) == Difficulty::Easy {
+        return;
+    }
+    **countdown = countdown.saturating_sub(eaten);
+    if **countdown > 0 || !hunter_query.is_empty() {
+        return;
+    }
+
+    let free: Vec<Vec3> = food_cells(&config, &bounds, MovementMode::Grid)
+        .into_iter()
+        .filter(|cell| {
+            head_query
+                .iter()
+                .all(|head| head.translation.distance(*cell) >= HUNTER_SAFE_DISTANCE)
+                && blocker_query.iter().all(|(blocker, size)| {
+                    collide(*cell, config.snake_size, blocker.translation, **size).is_none()
+                })
+        })
+        .collect();
+    if let Some(position) = free.choose(&mut rng.rng) {
+        spawn_hunter(&mut commands, &config, *position);
+        **countdown = HUNTER_AFTER_FOODS;
+    }
+}
+
+// How fast the snake goes right now, in units per second, without the boost
+pub fn snake_speed(
+    config: &GameConfig,
+    movement_mode: MovementMode,
+    speed: GameSpeed,
+    modifier: &SpeedModifier,
+) -> f32 {
+    let base = match movement_mode {
+        MovementMode::Grid => config.grid_steps_per_second * config.snake_size.x,
+        MovementMode::Continuous => config.snake_speed,
+    };
+    base * *speed * modifier.factor
+}
+
+// Points the hunter at the nearest head whenever its `retarget` comes round,
+// and keeps it at `HUNTER_SPEED_FACTOR` of the snake's speed as that
+// changes.
+pub fn steer_hunter(
+    mut hunter_query: Query<(&Transform, &mut Hunter, &mut Velocity)>,
+    head_query: Query<(&Transform, &Player), With<Snake>>,
+    config: Res<GameConfig>,
+    movement_mode: Res<MovementMode>,
+    speed: Res<GameSpeed>,
+    modifier: Res<SpeedModifier>,
+) {
+    for (transform, mut hunter, mut velocity) in &mut hunter_query {
+        hunter
+            .retarget
+            .tick(Duration::from_secs_f32(config.time_step));
+        if hunter.heading == Vec2::ZERO || hunter.retarget.just_finished() {
+            let position = transform.translation;
+            let nearest = head_query.iter().min_by(|(a, _), (b, _)| {
+                let a = a.translation.distance_squared(position);
+                let b = b.translation.distance_squared(position);
+                a.total_cmp(&b)
+            });
+            if let Some((head, player)) = nearest {
+                hunter.heading = (head.translation - position).truncate().normalize_or_zero();
+                hunter.chasing = *player;
+            }
+        }
+        let hunter_speed =
+            snake_speed(&config, *movement_mode, *speed, &modifier) * HUNTER_SPEED_FACTOR;
+        let lunge = hunter.heading * hunter_speed;
+        if **velocity != lunge {
+            **velocity = lunge;
+        }
+    }
+}
+
+// A hunter lunging into a wall is done for, and the snake it was after gets
+// `HUNTER_BONUS_POINTS` for it
+pub fn crash_hunter(
+    mut commands: Commands,
+    mut hunter_query: Query<(Entity, &Transform, &Hitbox, &mut Hunter)>,
+    wall_query: Query<(&Transform, &Hitbox), With<Wall>>,
+    mut scoreboard: ResMut<Scoreboard>,
+) {
+    for (entity, transform, hitbox, mut hunter) in &mut hunter_query {
+        if hunter.crashed {
+            continue;
+        }
+        let crashed = wall_query.iter().any(|(wall, size)| {
+            collide(transform.translation, **hitbox, wall.translation, **size).is_some()
+        });
+        if crashed {
+            hunter.crashed = true;
+            commands.entity(entity).despawn();
+            *scoreboard.score_mut(hunter.chasing) += HUNTER_BONUS_POINTS;
+        }
+    }
+}
+
+// A hunter that caught a snake goes away with it, and the next one comes
+// `HUNTER_AFTER_FOODS` later
+pub fn retire_hunter(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    hunter_query: Query<(), With<Hunter>>,
+) {
+    for event in collision_events.iter() {
+        if let CollisionEvent::Obstacle { obstacle, .. } = event {
+            if hunter_query.contains(*obstacle) {
+                commands.entity(*obstacle).despawn();
+            }
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use bevy::prelude::*;
+use bevy::sprite::collide_aabb::collide;
+use rand::Rng;
+
+use crate::collision::Hitbox;
+use crate::config::GameConfig;
+use crate::game::{count_tick, FixedUpdate};
+use crate::snake::{move_snake, Snake};
+use crate::walls::{random_obstacles, ArenaBounds};
+
+// Below the portals, which can lie on the ice
+pub const ICE_Z: f32 = -0.6;
+
+// The slippery floor the snake can't turn on
+pub struct IcePlugin;
+
+impl Plugin for IcePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::new()
+                .with_run_criteria(FixedUpdate)
+                .with_system(detect_ice.after(count_tick).before(move_snake)),
+        );
+    }
+}
+
+// A patch of ice on the floor. It isn't a `Collider`, so
+// `check_for_collisions` never sees it: the snake slides over it rather than
+// hitting it.
+#[derive(Component)]
+pub struct IcePatch;
+
+// Whether the head is on ice right now. `move_snake` keeps the turns queued
+// while it is, and takes them once the head is off.
+#[derive(Component, Default, Deref, DerefMut)]
+pub struct Sliding(pub bool);
+
+// Rolls `count` patches of ice (given as center and size) the way
+// `random_obstacles` rolls the obstacles, so none of them crosses the row or
+// column `starts`, and the snakes never start on ice. Patches overlapping
+// anything `blocked` are left out.
+pub fn random_ice_patches(
+    rng: &mut impl Rng,
+    config: &GameConfig,
+    bounds: &ArenaBounds,
+    count: usize,
+    blocked: &[(Vec3, Vec2)],
+    starts: &[Vec3],
+) -> Vec<(Vec3, Vec2)> {
+    random_obstacles(rng, config, bounds, count, starts)
+        .into_iter()
+        .filter(|(center, size)| {
+            blocked
+                .iter()
+                .all(|(other, other_size)| collide(*center, *size, *other, *other_size).is_none())
+        })
+        .collect()
+}
+
+// Flags every head over a patch of ice as `Sliding`, before `move_snake`
+// would turn it
+pub fn detect_ice(
+    mut snake_query: Query<(&Transform, &Hitbox, &mut Sliding), With<Snake>>,
+    ice_query: Query<(&Transform, &Hitbox), With<IcePatch>>,
+) {
+    for (transform, hitbox, mut sliding) in &mut snake_query {
+        let on_ice = ice_query.iter().any(|(ice, size)| {
+            collide(transform.translation, **hitbox, ice.translation, **size).is_some()
+        });
+        if **sliding != on_ice {
+            **sliding = on_ice;
+        }
+    }
+}
@@ -0,0 +1,717 @@
+use bevy::ecs::schedule::StateError;
+use bevy::input::InputSystem;
+use bevy::window::WindowFocused;
+use bevy::{app::AppExit, ecs::schedule::ShouldRun, prelude::*, utils::HashMap};
+
+use crate::config::GameConfig;
+use crate::daily::{DailyChallenge, DailyDate};
+use crate::display::DisplaySettings;
+use crate::game::{
+    Difficulty, GameMode, Leaderboard, LeaderboardEntry, Paused, Scoreboard, StartingLives,
+    TickSpeed, VersusTally,
+};
+use crate::level::{Campaign, LevelState};
+use crate::replay::ReplayPlayer;
+use crate::savegame::{SaveAndQuit, SavedGameSlot};
+use crate::snake::{
+    move_snake, Boost, Direction, DirectionQueue, Dying, HeldDirections, NextDirection, Player,
+    SegmentOrder, Snake,
+};
+use crate::state::GameState;
+use crate::ui::{MenuItem, MenuSelection};
+use crate::walls::ArenaConfig;
+
+pub const INITIALS_LENGTH: usize = 3;
+// How many times faster a replay plays while F is held
+const REPLAY_FAST_FORWARD: f32 = 4.0;
+
+// How far off a cardinal direction the stick may lean and still count,
+// as the tangent of the angle (about 30 degrees)
+const STICK_MAX_SLANT: f32 = 0.58;
+
+// Which keys and gamepad buttons steer the snake. Several of them may share
+// a direction.
+pub struct ControlScheme {
+    pub bindings: Vec<(KeyCode, Direction)>,
+    // The keys of the second snake in versus, which the first one then
+    // can't use any more. The gamepads always steer the first snake.
+    pub player_two: Vec<(KeyCode, Direction)>,
+    pub gamepad_buttons: Vec<(GamepadButtonType, Direction)>,
+    // The keys held to boost, see `Boost`, and the ones of the second snake
+    // in versus
+    pub boost: Vec<KeyCode>,
+    pub player_two_boost: Vec<KeyCode>,
+    // How far the left stick has to be pushed before it steers, from 0 to 1
+    pub stick_dead_zone: f32,
+}
+
+impl Default for ControlScheme {
+    // Arrow keys and WASD
+    fn default() -> Self {
+        ControlScheme {
+            bindings: vec![
+                (KeyCode::Left, Direction::Left),
+                (KeyCode::Right, Direction::Right),
+                (KeyCode::Up, Direction::Up),
+                (KeyCode::Down, Direction::Down),
+                (KeyCode::A, Direction::Left),
+                (KeyCode::D, Direction::Right),
+                (KeyCode::W, Direction::Up),
+                (KeyCode::S, Direction::Down),
+            ],
+            player_two: vec![
+                (KeyCode::A, Direction::Left),
+                (KeyCode::D, Direction::Right),
+                (KeyCode::W, Direction::Up),
+                (KeyCode::S, Direction::Down),
+            ],
+            gamepad_buttons: vec![
+                (GamepadButtonType::DPadLeft, Direction::Left),
+                (GamepadButtonType::DPadRight, Direction::Right),
+                (GamepadButtonType::DPadUp, Direction::Up),
+                (GamepadButtonType::DPadDown, Direction::Down),
+            ],
+            boost: vec![KeyCode::LShift, KeyCode::RShift],
+            player_two_boost: vec![KeyCode::LShift],
+            stick_dead_zone: 0.5,
+        }
+    }
+}
+
+impl ControlScheme {
+    // Directions whose keys went down this frame, in binding order
+    pub fn just_pressed<'a>(
+        &'a self,
+        keyboard_input: &'a Input<KeyCode>,
+    ) -> impl Iterator<Item = Direction> + 'a {
+        self.bindings
+            .iter()
+            .filter(|(key, _)| keyboard_input.just_pressed(*key))
+            .map(|(_, direction)| *direction)
+    }
+
+    // The cardinal direction the stick points at, if it is pushed far enough
+    // and not at an ambiguous, diagonal angle
+    pub fn stick_direction(&self, stick: Vec2) -> Option<Direction> {
+        if stick.length() < self.stick_dead_zone {
+            return None;
+        }
+
+        let (major, minor) = if stick.x.abs() >= stick.y.abs() {
+            (stick.x.abs(), stick.y.abs())
+        } else {
+            (stick.y.abs(), stick.x.abs())
+        };
+        if minor > major * STICK_MAX_SLANT {
+            return None;
+        }
+        Some(Direction::from_vec2(stick))
+    }
+
+    // The keys that steer `player`'s snake in a game of `mode`
+    pub fn player_bindings(&self, player: Player, mode: GameMode) -> Vec<(KeyCode, Direction)> {
+        match mode {
+            GameMode::Versus if player == Player::TWO => self.player_two.clone(),
+            GameMode::Versus => self
+                .bindings
+                .iter()
+                .filter(|(key, _)| self.player_two.iter().all(|(taken, _)| taken != key))
+                .copied()
+                .collect(),
+            GameMode::Classic | GameMode::TimeAttack | GameMode::Survival | GameMode::Daily => {
+                self.bindings.clone()
+            }
+        }
+    }
+
+    // The keys that boost `player`'s snake, split up in versus the way
+    // `player_bindings` splits the steering
+    pub fn boost_keys(&self, player: Player, mode: GameMode) -> Vec<KeyCode> {
+        match mode {
+            GameMode::Versus if player == Player::TWO => self.player_two_boost.clone(),
+            GameMode::Versus => self
+                .boost
+                .iter()
+                .filter(|key| !self.player_two_boost.contains(key))
+                .copied()
+                .collect(),
+            GameMode::Classic | GameMode::TimeAttack | GameMode::Survival | GameMode::Daily => {
+                self.boost.clone()
+            }
+        }
+    }
+}
+
+// The initials being typed on the game over screen. `None` unless the run
+// made it onto the leaderboard and they haven't been entered yet.
+#[derive(Default, Deref, DerefMut)]
+pub struct InitialsEntry(pub Option<String>);
+
+// Whether the menu is asking to confirm quitting the game, see `confirm_quit`
+#[derive(Default, Deref, DerefMut)]
+pub struct QuitPrompt(pub bool);
+
+// Keyboard and gamepad controls, for the menus as well as the snake
+pub struct SnakeInputPlugin;
+
+impl Plugin for SnakeInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ControlScheme>()
+            .init_resource::<MenuSelection>()
+            .init_resource::<InitialsEntry>()
+            .init_resource::<QuitPrompt>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Menu)
+                    .with_system(confirm_quit.before(navigate_menu))
+                    .with_system(navigate_menu),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Menu).with_system(close_quit_prompt))
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(steering)
+                    .with_system(queue_direction_input.before(move_snake))
+                    .with_system(queue_gamepad_input.before(move_snake))
+                    .with_system(track_held_directions.before(move_snake))
+                    .with_system(track_boost.before(move_snake)),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(toggle_pause.before(queue_direction_input))
+                    .with_system(pause_on_focus_loss.after(toggle_pause))
+                    .with_system(request_save_and_quit)
+                    .with_system(fast_forward_replay),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::GameOver).with_system(start_initials_entry),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::GameOver)
+                    .with_system(enter_initials.before(restart_game))
+                    .with_system(restart_game),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::CampaignComplete).with_system(restart_game),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Achievements).with_system(back_to_menu))
+            .add_system_set(SystemSet::on_update(GameState::Statistics).with_system(back_to_menu))
+            .add_system(quick_restart)
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                skip_death_animation.after(InputSystem),
+            );
+    }
+}
+
+// Any key or button skips the snake's death straight to the game over
+// screen. Nothing else gets to react to the keys meanwhile, nor the ones
+// pressed to skip it.
+pub fn skip_death_animation(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut buttons: ResMut<Input<GamepadButton>>,
+    mut paused: ResMut<Paused>,
+    mut query: Query<&mut Dying>,
+) {
+    if query.is_empty() {
+        return;
+    }
+    if keyboard_input.get_just_pressed().next().is_some()
+        || buttons.get_just_pressed().next().is_some()
+    {
+        for mut dying in &mut query {
+            dying.skip();
+        }
+        **paused = false;
+    }
+    keyboard_input.clear();
+    buttons.clear();
+}
+
+// The player steers while a game is running, unless it is a replay, which
+// steers the snake by itself
+pub fn steering(state: Res<State<GameState>>, player: Option<Res<ReplayPlayer>>) -> ShouldRun {
+    if *state.current() == GameState::Playing && player.is_none() {
+        ShouldRun::Yes
+    } else {
+        ShouldRun::No
+    }
+}
+
+// Holding F speeds a replay up
+pub fn fast_forward_replay(
+    keyboard_input: Res<Input<KeyCode>>,
+    player: Option<Res<ReplayPlayer>>,
+    mut speed: ResMut<TickSpeed>,
+) {
+    let fast = player.is_some() && keyboard_input.pressed(KeyCode::F);
+    **speed = if fast { REPLAY_FAST_FORWARD } else { 1.0 };
+}
+
+// P or a gamepad's Start button pauses and resumes the game. Esc only
+// pauses, it is up to the `PauseMenuPlugin` what it does once paused.
+pub fn toggle_pause(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    mut paused: ResMut<Paused>,
+) {
+    let start_pressed = gamepads.iter().any(|gamepad| {
+        buttons.just_pressed(GamepadButton::new(*gamepad, GamepadButtonType::Start))
+    });
+    if keyboard_input.just_pressed(KeyCode::P)
+        || start_pressed
+        || (!**paused && keyboard_input.clear_just_pressed(KeyCode::Escape))
+    {
+        **paused = !**paused;
+    }
+}
+
+// Pauses the game when the window loses focus, unless
+// `GameConfig::pause_on_focus_loss` is off. Focusing the window again leaves
+// it paused, it only resumes with the pause key, so the click that brings the
+// window back doesn't set the snake going.
+pub fn pause_on_focus_loss(
+    mut focus_events: EventReader<WindowFocused>,
+    config: Res<GameConfig>,
+    mut paused: ResMut<Paused>,
+) {
+    let lost_focus = focus_events.iter().any(|event| !event.focused);
+    if lost_focus && config.pause_on_focus_loss && !**paused {
+        **paused = true;
+    }
+}
+
+// Q while paused saves the run and goes back to the menu
+pub fn request_save_and_quit(
+    keyboard_input: Res<Input<KeyCode>>,
+    paused: Res<Paused>,
+    mut save_events: EventWriter<SaveAndQuit>,
+) {
+    if **paused && keyboard_input.just_pressed(KeyCode::Q) {
+        save_events.send(SaveAndQuit);
+    }
+}
+
+// Esc on the menu asks before quitting the game. Enter then quits, Esc
+// stays. Either key is used up, and the menu doesn't react to any key while
+// it asks.
+pub fn confirm_quit(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut prompt: ResMut<QuitPrompt>,
+    mut app_exit_events: EventWriter<AppExit>,
+) {
+    if !**prompt {
+        if keyboard_input.clear_just_pressed(KeyCode::Escape) {
+            **prompt = true;
+        }
+        return;
+    }
+    if keyboard_input.clear_just_pressed(KeyCode::Return) {
+        app_exit_events.send(AppExit);
+    } else if keyboard_input.clear_just_pressed(KeyCode::Escape) {
+        **prompt = false;
+    }
+}
+
+pub fn close_quit_prompt(mut prompt: ResMut<QuitPrompt>) {
+    **prompt = false;
+}
+
+// Up and Down move through the menu, Enter picks the highlighted entry.
+// Left and Right, or Enter, change the difficulty, the lives, the theme, the
+// snake's color or the colorblind mode while they are highlighted.
+pub fn navigate_menu(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    controls: Res<ControlScheme>,
+    mut selection: ResMut<MenuSelection>,
+    mut difficulty: ResMut<Difficulty>,
+    mut state: ResMut<State<GameState>>,
+    mut app_exit_events: EventWriter<AppExit>,
+    mut slot: ResMut<SavedGameSlot>,
+    mut campaign: ResMut<Campaign>,
+    (mut mode, mut tally, mut daily): (
+        ResMut<GameMode>,
+        ResMut<VersusTally>,
+        ResMut<DailyChallenge>,
+    ),
+    mut starting_lives: ResMut<StartingLives>,
+    mut display: ResMut<DisplaySettings>,
+    (config, arena, prompt): (Res<GameConfig>, Res<ArenaConfig>, Res<QuitPrompt>),
+) {
+    if **prompt {
+        return;
+    }
+    let count = MenuItem::ALL.len();
+    let on_difficulty = MenuItem::ALL[**selection] == MenuItem::Difficulty;
+    let on_lives = MenuItem::ALL[**selection] == MenuItem::Lives;
+    let on_theme = MenuItem::ALL[**selection] == MenuItem::Theme;
+    let on_snake_color = MenuItem::ALL[**selection] == MenuItem::SnakeColor;
+    let on_colorblind = MenuItem::ALL[**selection] == MenuItem::Colorblind;
+    let on_language = MenuItem::ALL[**selection] == MenuItem::Language;
+    // Without a saved game there is nothing to continue, so it is skipped
+    let selectable =
+        |index: usize| MenuItem::ALL[index] != MenuItem::Continue || slot.saved.is_some();
+    for direction in controls.just_pressed(&keyboard_input) {
+        let step = match direction {
+            Direction::Up => count - 1,
+            Direction::Down => 1,
+            Direction::Left if on_difficulty => {
+                *difficulty = difficulty.cycle(-1);
+                continue;
+            }
+            Direction::Right if on_difficulty => {
+                *difficulty = difficulty.cycle(1);
+                continue;
+            }
+            Direction::Left | Direction::Right if on_lives => {
+                starting_lives.toggle();
+                continue;
+            }
+            Direction::Left if on_theme => {
+                display.theme = display.theme.cycle(-1);
+                continue;
+            }
+            Direction::Right if on_theme => {
+                display.theme = display.theme.cycle(1);
+                continue;
+            }
+            Direction::Left if on_snake_color => {
+                display.snake_swatch = display.snake_swatch.cycle(-1);
+                continue;
+            }
+            Direction::Right if on_snake_color => {
+                display.snake_swatch = display.snake_swatch.cycle(1);
+                continue;
+            }
+            Direction::Left | Direction::Right if on_colorblind => {
+                display.colorblind = !display.colorblind;
+                continue;
+            }
+            Direction::Left if on_language => {
+                display.language = display.language.cycle(-1);
+                continue;
+            }
+            Direction::Right if on_language => {
+                display.language = display.language.cycle(1);
+                continue;
+            }
+            _ => continue,
+        };
+        let mut index = (**selection + step) % count;
+        while !selectable(index) {
+            index = (index + step) % count;
+        }
+        **selection = index;
+    }
+
+    if keyboard_input.clear_just_pressed(KeyCode::Return) {
+        match MenuItem::ALL[**selection] {
+            MenuItem::Continue => {
+                slot.resuming = slot.saved.is_some();
+                *mode = GameMode::Classic;
+                let _ = state.set(GameState::Playing);
+            }
+            MenuItem::Play => {
+                **campaign = None;
+                *mode = GameMode::Classic;
+                let _ = state.set(GameState::Playing);
+            }
+            MenuItem::Daily => {
+                **campaign = None;
+                *mode = GameMode::Daily;
+                *daily = DailyChallenge(DailyDate::today());
+                let _ = state.set(GameState::Playing);
+            }
+            MenuItem::TimeAttack => {
+                **campaign = None;
+                *mode = GameMode::TimeAttack;
+                let _ = state.set(GameState::Playing);
+            }
+            MenuItem::Survival => {
+                **campaign = None;
+                *mode = GameMode::Survival;
+                let _ = state.set(GameState::Playing);
+            }
+            MenuItem::Campaign => {
+                **campaign = Some(LevelState::new(0, &config, &arena));
+                *mode = GameMode::Classic;
+                let _ = state.set(GameState::Playing);
+            }
+            MenuItem::Versus => {
+                **campaign = None;
+                *mode = GameMode::Versus;
+                *tally = VersusTally::default();
+                let _ = state.set(GameState::Playing);
+            }
+            MenuItem::Difficulty => *difficulty = difficulty.cycle(1),
+            MenuItem::Lives => starting_lives.toggle(),
+            MenuItem::Theme => display.theme = display.theme.cycle(1),
+            MenuItem::SnakeColor => display.snake_swatch = display.snake_swatch.cycle(1),
+            MenuItem::Colorblind => display.colorblind = !display.colorblind,
+            MenuItem::Language => display.language = display.language.cycle(1),
+            MenuItem::Achievements => {
+                let _ = state.set(GameState::Achievements);
+            }
+            MenuItem::Statistics => {
+                let _ = state.set(GameState::Statistics);
+            }
+            MenuItem::Tutorial => {
+                display.tutorial_seen = false;
+                **campaign = None;
+                *mode = GameMode::Classic;
+                let _ = state.set(GameState::Playing);
+            }
+            MenuItem::Quit => app_exit_events.send(AppExit),
+        }
+    }
+}
+
+// The key that leaves the screen is consumed, so the next screen doesn't
+// react to the same press in this frame (Esc would also ask to quit on the
+// menu)
+pub fn restart_game(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if keyboard_input.clear_just_pressed(KeyCode::Return) {
+        let _ = state.set(GameState::Playing);
+    } else if keyboard_input.clear_just_pressed(KeyCode::Escape) {
+        let _ = state.set(GameState::Menu);
+    }
+}
+
+// Enter or Esc go back from a screen opened on the menu
+pub fn back_to_menu(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if keyboard_input.clear_just_pressed(KeyCode::Return)
+        || keyboard_input.clear_just_pressed(KeyCode::Escape)
+    {
+        let _ = state.set(GameState::Menu);
+    }
+}
+
+// Only classic runs make it onto the leaderboard
+pub fn start_initials_entry(
+    scoreboard: Res<Scoreboard>,
+    leaderboard: Res<Leaderboard>,
+    mode: Res<GameMode>,
+    mut initials: ResMut<InitialsEntry>,
+) {
+    **initials =
+        (*mode == GameMode::Classic && leaderboard.qualifies(scoreboard.score)).then(String::new);
+}
+
+// Letters and digits are typed in upper case and Backspace takes them back.
+// Enter adds the run to the leaderboard once all the initials are in, Esc
+// leaves it off. Either way the key isn't seen by `restart_game`.
+pub fn enter_initials(
+    mut received_characters: EventReader<ReceivedCharacter>,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut initials: ResMut<InitialsEntry>,
+    mut leaderboard: ResMut<Leaderboard>,
+    scoreboard: Res<Scoreboard>,
+    difficulty: Res<Difficulty>,
+) {
+    let typed: Vec<char> = received_characters.iter().map(|event| event.char).collect();
+    let Some(entry) = initials.0.as_mut() else {
+        return;
+    };
+
+    for character in typed {
+        if character.is_ascii_alphanumeric() && entry.len() < INITIALS_LENGTH {
+            entry.push(character.to_ascii_uppercase());
+        }
+    }
+    if keyboard_input.clear_just_pressed(KeyCode::Back) {
+        entry.pop();
+    }
+
+    if keyboard_input.clear_just_pressed(KeyCode::Escape) {
+        **initials = None;
+    } else if entry.len() == INITIALS_LENGTH && keyboard_input.clear_just_pressed(KeyCode::Return) {
+        leaderboard.insert(LeaderboardEntry {
+            initials: entry.clone(),
+            score: scoreboard.score,
+            difficulty: *difficulty,
+        });
+        **initials = None;
+    } else {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+    }
+}
+
+// R, or a gamepad's Select button, throws the current run away and starts
+// over, whether the snake is still alive or not
+pub fn quick_restart(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    initials: Res<InitialsEntry>,
+    mut state: ResMut<State<GameState>>,
+    mut campaign: ResMut<Campaign>,
+    config: Res<GameConfig>,
+    arena: Res<ArenaConfig>,
+) {
+    // R is just a letter while typing initials
+    if initials.is_some() {
+        return;
+    }
+    let select_pressed = gamepads.iter().any(|gamepad| {
+        buttons.just_pressed(GamepadButton::new(*gamepad, GamepadButtonType::Select))
+    });
+    if !(keyboard_input.clear_just_pressed(KeyCode::R) || select_pressed) {
+        return;
+    }
+
+    let _ = match state.current() {
+        GameState::Playing => restart_run(&mut state, &mut campaign, &config, &arena),
+        GameState::GameOver => state.set(GameState::Playing),
+        GameState::Menu
+        | GameState::LevelComplete
+        | GameState::CampaignComplete
+        | GameState::Achievements
+        | GameState::Statistics => Ok(()),
+    };
+}
+
+// Restarting `Playing` goes through the same clean up and spawning as
+// leaving and entering it, and the campaign starts over from its first level
+pub fn restart_run(
+    state: &mut State<GameState>,
+    campaign: &mut Campaign,
+    config: &GameConfig,
+    arena: &ArenaConfig,
+) -> Result<(), StateError> {
+    campaign.rewind(config, arena);
+    state.restart()
+}
+
+// Records every steering key press, so quick double turns made between two
+// moves aren't lost. Presses that wouldn't change anything, turns straight
+// back into the body and presses beyond the queue's capacity are dropped.
+// Each snake only hears its own player's keys.
+pub fn queue_direction_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    controls: Res<ControlScheme>,
+    mode: Res<GameMode>,
+    mut query: Query<(&Player, &NextDirection, &SegmentOrder, &mut DirectionQueue), With<Snake>>,
+    paused: Res<Paused>,
+) {
+    if **paused {
+        return;
+    }
+
+    for (player, next_direction, segments, mut queue) in &mut query {
+        for (key, turn) in controls.player_bindings(*player, *mode) {
+            if keyboard_input.just_pressed(key) {
+                queue.push_turn(turn, **next_direction, !segments.is_empty());
+            }
+        }
+    }
+}
+
+// Keeps the held directions in the order their keys went down, so the one
+// pressed last can win
+pub fn track_held_directions(
+    keyboard_input: Res<Input<KeyCode>>,
+    controls: Res<ControlScheme>,
+    mode: Res<GameMode>,
+    mut query: Query<(&Player, &mut HeldDirections), With<Snake>>,
+) {
+    for (player, mut held) in &mut query {
+        let bindings = controls.player_bindings(*player, *mode);
+        let pressed = |direction: Direction| {
+            bindings
+                .iter()
+                .any(|(key, bound)| *bound == direction && keyboard_input.pressed(*key))
+        };
+        held.retain(|direction| pressed(*direction));
+        for direction in [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ] {
+            if pressed(direction) && !held.contains(&direction) {
+                held.push(direction);
+            }
+        }
+    }
+}
+
+// Whether each snake's boost key is held, `drain_boost` decides whether
+// that boosts it
+pub fn track_boost(
+    keyboard_input: Res<Input<KeyCode>>,
+    controls: Res<ControlScheme>,
+    mode: Res<GameMode>,
+    mut query: Query<(&Player, &mut Boost), With<Snake>>,
+) {
+    for (player, mut boost) in &mut query {
+        let held = controls
+            .boost_keys(*player, *mode)
+            .iter()
+            .any(|key| keyboard_input.pressed(*key));
+        if boost.held != held {
+            boost.held = held;
+        }
+    }
+}
+
+// Feeds the D-pad and the left stick of every connected gamepad into the
+// same queue as the keyboard. The stick only counts once it is pushed past
+// the dead zone close to one of the four cardinal directions, and has to
+// leave that direction before it can queue it again.
+pub fn queue_gamepad_input(
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut gamepad_events: EventReader<GamepadEvent>,
+    controls: Res<ControlScheme>,
+    mut query: Query<(&Player, &NextDirection, &SegmentOrder, &mut DirectionQueue), With<Snake>>,
+    paused: Res<Paused>,
+    mut stick_directions: Local<HashMap<Gamepad, Direction>>,
+) {
+    // A controller plugged back in starts from a centered stick
+    for event in gamepad_events.iter() {
+        if let GamepadEventType::Disconnected = event.event_type {
+            stick_directions.remove(&event.gamepad);
+        }
+    }
+
+    if **paused {
+        return;
+    }
+
+    let Some((_, next_direction, segments, mut queue)) = query
+        .iter_mut()
+        .find(|(player, ..)| **player == Player::ONE)
+    else {
+        return;
+    };
+    for gamepad in gamepads.iter() {
+        for (button_type, turn) in &controls.gamepad_buttons {
+            if buttons.just_pressed(GamepadButton::new(*gamepad, *button_type)) {
+                queue.push_turn(*turn, **next_direction, !segments.is_empty());
+            }
+        }
+
+        let stick = Vec2::new(
+            axes.get(GamepadAxis::new(*gamepad, GamepadAxisType::LeftStickX))
+                .unwrap_or_default(),
+            axes.get(GamepadAxis::new(*gamepad, GamepadAxisType::LeftStickY))
+                .unwrap_or_default(),
+        );
+        match controls.stick_direction(stick) {
+            Some(turn) if stick_directions.get(gamepad) != Some(&turn) => {
+                stick_directions.insert(*gamepad, turn);
+                queue.push_turn(turn, **next_direction, !segments.is_empty());
+            }
+            Some(_) => {}
+            None => {
+                stick_directions.remove(gamepad);
+            }
+        }
+    }
+}
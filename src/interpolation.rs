@@ -0,0 +1,108 @@
+use bevy::math::Vec3A;
+use bevy::{prelude::*, transform::TransformSystem};
+
+use crate::config::GameConfig;
+use crate::game::{gameplay_running, Countdown, Paused, TickFraction};
+use crate::snake::{
+    point_along_trail, step_per_tick, Boost, GameSpeed, MovementMode, SegmentOrder, Snake,
+    SnakeTrail, SpeedModifier, StepOrigin, StepProgress,
+};
+use crate::state::GameState;
+
+// Slides the snake from cell to cell on the grid instead of having it jump a
+// whole cell every step. Only what is drawn moves, after the transforms have
+// been propagated. The `Transform`s the collisions are worked out from stay
+// on the cells.
+pub struct InterpolationPlugin;
+
+impl Plugin for InterpolationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_to_stage(
+            CoreStage::PostUpdate,
+            interpolate_grid_steps.after(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+// Draws each head part of the way from its `StepOrigin` to its cell, as far
+// as it has come towards the next step, and the body the same distance back
+// along the trail. A head that went further than a cell, wrapping around or
+// coming back after a lost life, is drawn right where it is.
+pub fn interpolate_grid_steps(
+    head_query: Query<
+        (
+            Entity,
+            &Transform,
+            &StepOrigin,
+            &StepProgress,
+            &SegmentOrder,
+            &SnakeTrail,
+            &Boost,
+        ),
+        With<Snake>,
+    >,
+    mut global_query: Query<&mut GlobalTransform>,
+    children_query: Query<&Children>,
+    config: Res<GameConfig>,
+    movement_mode: Res<MovementMode>,
+    (speed, modifier): (Res<GameSpeed>, Res<SpeedModifier>),
+    (state, paused, countdown, fraction): (
+        Res<State<GameState>>,
+        Res<Paused>,
+        Res<Countdown>,
+        Res<TickFraction>,
+    ),
+) {
+    if *movement_mode != MovementMode::Grid {
+        return;
+    }
+    // The time towards the next tick only counts while the ticks move the
+    // snake on
+    let fraction = if gameplay_running(&state, &paused, &countdown) {
+        **fraction
+    } else {
+        0.0
+    };
+    let cell = config.snake_size.x;
+
+    for (head, transform, origin, progress, segments, trail, boost) in &head_query {
+        let per_tick = step_per_tick(&config, *speed, &modifier, boost);
+        let step = origin.distance(transform.translation);
+        if step == 0.0 || step > cell * 1.01 {
+            continue;
+        }
+        // How much of the last step is still to be drawn
+        let behind = cell * (1.0 - (**progress + fraction * per_tick).min(1.0));
+        let head_offset = (**origin - transform.translation) * behind / cell;
+        shift_drawn(head, head_offset, &mut global_query, &children_query);
+
+        for (index, segment) in segments.iter().enumerate() {
+            let distance = (index + 1) as f32 * cell;
+            let offset =
+                point_along_trail(trail, distance + behind) - point_along_trail(trail, distance);
+            shift_drawn(*segment, offset, &mut global_query, &children_query);
+        }
+    }
+}
+
+// Moves where `entity` and its children are drawn by `offset`, keeping how
+// they are stacked
+fn shift_drawn(
+    entity: Entity,
+    offset: Vec3,
+    global_query: &mut Query<&mut GlobalTransform>,
+    children_query: &Query<&Children>,
+) {
+    let offset = Vec3A::from(offset.truncate().extend(0.0));
+    if let Ok(mut global) = global_query.get_mut(entity) {
+        *global.translation_mut() += offset;
+    }
+    let Ok(children) = children_query.get(entity) else {
+        return;
+    };
+    for child in children {
+        if let Ok(mut global) = global_query.get_mut(*child) {
+            *global.translation_mut() += offset;
+        }
+    }
+}
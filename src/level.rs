@@ -0,0 +1,447 @@
+use std::{fmt, fs, io, path::Path};
+
+use bevy::{prelude::*, utils::HashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::collision::{check_for_collisions, FoodEaten};
+use crate::config::GameConfig;
+use crate::food::FoodKind;
+use crate::game::{handle_game_over, Difficulty, FixedUpdate, HighScores, Scoreboard};
+use crate::snake::cell_center;
+use crate::spawning::{spawn_crate, spawn_ice_patch, spawn_wall_segment};
+use crate::state::GameState;
+use crate::walls::{block_rect, grid_bounds, reachable_cells, ArenaBounds, ArenaConfig};
+
+// Where the maps picked with `--level` are looked up by name
+pub const LEVELS_DIR: &str = "assets/levels";
+// How long "Level Complete" shows before the next level starts
+const LEVEL_COMPLETE_SECONDS: f32 = 2.0;
+
+// The levels of the campaign, each one harder than the last
+pub const CAMPAIGN: [CampaignLevel; 4] = [
+    CampaignLevel {
+        map: None,
+        obstacles: 2,
+        speed: 1.0,
+        target: 5,
+    },
+    CampaignLevel {
+        map: None,
+        obstacles: 5,
+        speed: 1.1,
+        target: 8,
+    },
+    CampaignLevel {
+        map: None,
+        obstacles: 9,
+        speed: 1.2,
+        target: 10,
+    },
+    CampaignLevel {
+        map: Some("maze"),
+        obstacles: 0,
+        speed: 1.3,
+        target: 12,
+    },
+];
+
+// Hand-made arena layouts, picked with `--level`, and the campaign leading
+// through a series of them
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CurrentLevel>()
+            .init_resource::<Campaign>()
+            .insert_resource(LevelCompleteTimer(Timer::from_seconds(
+                LEVEL_COMPLETE_SECONDS,
+                false,
+            )))
+            .add_system_set(
+                SystemSet::new().with_run_criteria(FixedUpdate).with_system(
+                    count_level_food
+                        .after(check_for_collisions)
+                        .after(handle_game_over),
+                ),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::LevelComplete).with_system(start_level_complete),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::LevelComplete).with_system(advance_level),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::CampaignComplete).with_system(record_campaign),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(rewind_campaign))
+            .add_system_set(
+                SystemSet::on_exit(GameState::CampaignComplete).with_system(rewind_campaign),
+            );
+    }
+}
+
+// The layout every run is played on, the open arena when `None`. The
+// campaign brings its own layouts.
+#[derive(Default, Deref, DerefMut)]
+pub struct CurrentLevel(pub Option<Level>);
+
+// One step of the campaign
+pub struct CampaignLevel {
+    // Name of the map in `assets/levels`, the open arena when `None`
+    pub map: Option<&'static str>,
+    // How many random obstacles the open arena gets
+    pub obstacles: usize,
+    // Multiplies the difficulty's starting speed
+    pub speed: f32,
+    // Foods to eat to clear the level
+    pub target: usize,
+}
+
+// How far into the campaign the player is. The score carries over from one
+// level to the next, the snake starts short again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LevelState {
+    // Into `CAMPAIGN`
+    pub index: usize,
+    pub foods_eaten: usize,
+    pub target: usize,
+    // The map of the level, loaded when the level is reached
+    pub layout: Option<Level>,
+}
+
+impl LevelState {
+    // A map that fails to load is left out, the level is then played in the
+    // open arena
+    pub fn new(index: usize, config: &GameConfig, arena: &ArenaConfig) -> LevelState {
+        let stage = &CAMPAIGN[index];
+        let layout = stage.map.and_then(|name| {
+            Level::load(name, config, arena)
+                .map_err(|err| warn!("playing level {} without its map: {name}: {err}", index + 1))
+                .ok()
+        });
+        LevelState {
+            index,
+            foods_eaten: 0,
+            target: stage.target,
+            layout,
+        }
+    }
+
+    pub fn stage(&self) -> &'static CampaignLevel {
+        &CAMPAIGN[self.index]
+    }
+
+    pub fn is_last(&self) -> bool {
+        self.index + 1 == CAMPAIGN.len()
+    }
+}
+
+// The campaign being played, `None` for a single run
+#[derive(Default, Deref, DerefMut)]
+pub struct Campaign(pub Option<LevelState>);
+
+impl Campaign {
+    // The layout the coming run is played on
+    pub fn layout<'a>(&'a self, current: &'a CurrentLevel) -> Option<&'a Level> {
+        match &self.0 {
+            Some(level) => level.layout.as_ref(),
+            None => current.as_ref(),
+        }
+    }
+
+    // Goes back to the first level, for a restart
+    pub fn rewind(&mut self, config: &GameConfig, arena: &ArenaConfig) {
+        if self.is_some() {
+            **self = Some(LevelState::new(0, config, arena));
+        }
+    }
+}
+
+#[derive(Deref, DerefMut)]
+pub struct LevelCompleteTimer(pub Timer);
+
+// A map read from a text file with one character per grid cell, the top
+// line being the top row of the arena:
+//
+//   `#` a wall   `.` empty   `S` where the snake starts   `F` the first food
+//   `C` a crate  `~` ice
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Level {
+    pub walls: Vec<IVec2>,
+    #[serde(default)]
+    pub crates: Vec<IVec2>,
+    #[serde(default)]
+    pub ice: Vec<IVec2>,
+    pub start: IVec2,
+    pub food: IVec2,
+}
+
+impl Level {
+    // Reads the map at `path`, or `assets/levels/<path>.txt` when `path` is
+    // just the name of a level
+    pub fn load(path: &str, config: &GameConfig, arena: &ArenaConfig) -> Result<Level, LevelError> {
+        let named = Path::new(LEVELS_DIR).join(format!("{path}.txt"));
+        let path = if Path::new(path).exists() {
+            Path::new(path)
+        } else {
+            named.as_path()
+        };
+        let contents = fs::read_to_string(path).map_err(LevelError::Read)?;
+        Level::parse(&contents, config, arena)
+    }
+
+    // Checks that the map covers exactly the cells inside the walls, and that
+    // the snake can get to the food without breaking any crates
+    pub fn parse(map: &str, config: &GameConfig, arena: &ArenaConfig) -> Result<Level, LevelError> {
+        // Levels are drawn for the arena as configured, whatever the window
+        let (min, max) = grid_bounds(config, &ArenaBounds::from_config(arena));
+        let size = max - min + 1;
+        let rows: Vec<&str> = map.lines().filter(|line| !line.trim().is_empty()).collect();
+        if rows.len() != size.y as usize {
+            return Err(LevelError::WrongHeight {
+                expected: size.y as usize,
+                found: rows.len(),
+            });
+        }
+
+        let mut walls = Vec::new();
+        let mut crates = Vec::new();
+        let mut ice = Vec::new();
+        let mut start = None;
+        let mut food = None;
+        for (row, line) in rows.iter().enumerate() {
+            let line = line.trim_end();
+            let width = line.chars().count();
+            if width != size.x as usize {
+                return Err(LevelError::WrongWidth {
+                    line: row + 1,
+                    expected: size.x as usize,
+                    found: width,
+                });
+            }
+            for (column, character) in line.chars().enumerate() {
+                let cell = IVec2::new(min.x + column as i32, max.y - row as i32);
+                let slot = match character {
+                    '#' => {
+                        walls.push(cell);
+                        continue;
+                    }
+                    'C' => {
+                        crates.push(cell);
+                        continue;
+                    }
+                    '~' => {
+                        ice.push(cell);
+                        continue;
+                    }
+                    '.' => continue,
+                    'S' => &mut start,
+                    'F' => &mut food,
+                    _ => {
+                        return Err(LevelError::UnknownCharacter {
+                            line: row + 1,
+                            column: column + 1,
+                            character,
+                        })
+                    }
+                };
+                if slot.replace(cell).is_some() {
+                    return Err(LevelError::Duplicate(character));
+                }
+            }
+        }
+
+        let start = start.ok_or(LevelError::Missing('S'))?;
+        let food = food.ok_or(LevelError::Missing('F'))?;
+        let level = Level {
+            walls,
+            crates,
+            ice,
+            start,
+            food,
+        };
+        if !level.reachable(min, max) {
+            return Err(LevelError::UnreachableFood);
+        }
+        Ok(level)
+    }
+
+    // Walks the free cells from the start, moving the way the snake does.
+    // Crates count as walls, so they can't be what boxes the food in.
+    fn reachable(&self, min: IVec2, max: IVec2) -> bool {
+        let blocked: HashSet<IVec2> = self.walls.iter().chain(&self.crates).copied().collect();
+        reachable_cells(min, max, &blocked, self.start).contains(&self.food)
+    }
+
+    // The walls as rectangles (center and size), neighbouring cells in a row
+    // merged into one so a maze doesn't need an entity per cell
+    pub fn wall_blocks(&self, config: &GameConfig) -> Vec<(Vec3, Vec2)> {
+        let mut cells = self.walls.clone();
+        cells.sort_by_key(|cell| (cell.y, cell.x));
+
+        let mut blocks = Vec::new();
+        let mut cells = cells.into_iter().peekable();
+        while let Some(first) = cells.next() {
+            let mut last = first;
+            while cells.peek() == Some(&(last + IVec2::X)) {
+                last = cells.next().unwrap();
+            }
+            let center = (cell_center(config, first) + cell_center(config, last)) / 2.0;
+            let length = (last.x - first.x + 1) as f32;
+            blocks.push((center, Vec2::new(length, 1.0) * config.snake_size));
+        }
+        blocks
+    }
+
+    pub fn spawn_walls(&self, commands: &mut Commands, config: &GameConfig) {
+        for (position, size) in self.wall_blocks(config) {
+            spawn_wall_segment(commands, block_rect(position, size));
+        }
+    }
+
+    pub fn spawn_crates(&self, commands: &mut Commands, config: &GameConfig) {
+        for cell in &self.crates {
+            spawn_crate(commands, config, cell_center(config, *cell));
+        }
+    }
+
+    pub fn spawn_ice(&self, commands: &mut Commands, config: &GameConfig) {
+        for cell in &self.ice {
+            spawn_ice_patch(commands, cell_center(config, *cell), config.snake_size);
+        }
+    }
+}
+
+// Clears the level once enough food is eaten. Dying in the same tick comes
+// first.
+pub fn count_level_food(
+    mut food_events: EventReader<FoodEaten>,
+    mut campaign: ResMut<Campaign>,
+    mut state: ResMut<State<GameState>>,
+) {
+    // Bonus and poison food are extra, they don't count towards the target
+    let eaten = food_events
+        .iter()
+        .filter(|event| event.kind == FoodKind::Normal)
+        .count();
+    let Some(level) = campaign.0.as_mut() else {
+        return;
+    };
+    if eaten == 0 || level.foods_eaten >= level.target {
+        return;
+    }
+
+    level.foods_eaten += eaten;
+    if level.foods_eaten >= level.target {
+        let next = if level.is_last() {
+            GameState::CampaignComplete
+        } else {
+            GameState::LevelComplete
+        };
+        let _ = state.set(next);
+    }
+}
+
+pub fn start_level_complete(mut timer: ResMut<LevelCompleteTimer>) {
+    timer.reset();
+}
+
+pub fn advance_level(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    arena: Res<ArenaConfig>,
+    mut timer: ResMut<LevelCompleteTimer>,
+    mut campaign: ResMut<Campaign>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if !timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    if let Some(level) = campaign.0.as_mut() {
+        *level = LevelState::new(level.index + 1, &config, &arena);
+    }
+    let _ = state.set(GameState::Playing);
+}
+
+// Finishing the campaign counts towards the best score like dying does
+pub fn record_campaign(
+    scoreboard: Res<Scoreboard>,
+    difficulty: Res<Difficulty>,
+    mut high_scores: ResMut<HighScores>,
+) {
+    high_scores.record(*difficulty, scoreboard.score);
+}
+
+pub fn rewind_campaign(
+    mut campaign: ResMut<Campaign>,
+    config: Res<GameConfig>,
+    arena: Res<ArenaConfig>,
+) {
+    campaign.rewind(&config, &arena);
+}
+
+#[derive(Debug)]
+pub enum LevelError {
+    // The file doesn't exist or couldn't be read
+    Read(io::Error),
+    // The map has a different number of rows than the arena
+    WrongHeight {
+        expected: usize,
+        found: usize,
+    },
+    // A row of the map is longer or shorter than the arena is wide
+    WrongWidth {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    UnknownCharacter {
+        line: usize,
+        column: usize,
+        character: char,
+    },
+    // There is no `S` or no `F`
+    Missing(char),
+    // There is more than one `S` or `F`
+    Duplicate(char),
+    // The walls or crates cut the food off from the snake
+    UnreachableFood,
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LevelError::Read(err) => write!(f, "could not read the level: {err}"),
+            LevelError::WrongHeight { expected, found } => {
+                write!(f, "the level has {found} rows, the arena needs {expected}")
+            }
+            LevelError::WrongWidth {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {line} is {found} cells wide, the arena needs {expected}"
+            ),
+            LevelError::UnknownCharacter {
+                line,
+                column,
+                character,
+            } => write!(
+                f,
+                "unknown character `{character}` at line {line}, column {column}"
+            ),
+            LevelError::Missing(character) => {
+                write!(f, "the level has no `{character}`")
+            }
+            LevelError::Duplicate(character) => {
+                write!(f, "the level has more than one `{character}`")
+            }
+            LevelError::UnreachableFood => {
+                write!(f, "the snake can't reach the food from where it starts")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
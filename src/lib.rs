@@ -0,0 +1,87 @@
+// Bevy systems routinely take many parameters and long query types
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+use bevy::prelude::*;
+
+pub mod accessibility;
+pub mod achievements;
+pub mod audio;
+pub mod background;
+pub mod camera;
+pub mod cli;
+pub mod collision;
+pub mod config;
+pub mod crates;
+pub mod daily;
+pub mod debug;
+pub mod display;
+pub mod food;
+pub mod game;
+pub mod hunger;
+pub mod hunter;
+pub mod ice;
+pub mod input;
+pub mod interpolation;
+pub mod level;
+pub mod locale;
+pub mod particles;
+pub mod pause;
+pub mod popups;
+pub mod portal;
+pub mod pulse;
+pub mod replay;
+pub mod run_stats;
+pub mod savegame;
+pub mod screenshot;
+pub mod snake;
+pub mod spawning;
+pub mod state;
+pub mod statistics;
+pub mod storage;
+pub mod theme;
+pub mod tutorial;
+pub mod ui;
+pub mod walls;
+
+pub use achievements::AchievementsPlugin;
+pub use audio::SnakeAudioPlugin;
+pub use camera::CameraShakePlugin;
+pub use collision::CollisionPlugin;
+pub use debug::DebugOverlayPlugin;
+pub use display::DisplayPlugin;
+pub use food::FoodPlugin;
+pub use game::SnakeGamePlugin;
+pub use input::SnakeInputPlugin;
+pub use particles::ParticlePlugin;
+pub use replay::ReplayPlugin;
+pub use savegame::SaveGamePlugin;
+pub use snake::SnakePlugin;
+pub use statistics::StatisticsPlugin;
+pub use storage::PersistencePlugin;
+pub use ui::SnakeRenderPlugin;
+pub use walls::ArenaPlugin;
+
+use config::GameConfig;
+use food::GameRng;
+use walls::ArenaConfig;
+
+// Builds the game rules on top of `plugins`: `DefaultPlugins` to play in a
+// window, or `MinimalPlugins` to run them headless and drive them with
+// `App::update`. The input and render plugins need a window, so adding them
+// is left to the caller.
+pub fn build_app(config: GameConfig, plugins: impl PluginGroup) -> App {
+    let arena = ArenaConfig::from_config(&config);
+    let window = arena.window_size();
+    let mut app = App::new();
+    app.insert_resource(WindowDescriptor {
+        width: window.x,
+        height: window.y,
+        ..default()
+    })
+    .insert_resource(arena)
+    .insert_resource(GameRng::new(config.seed))
+    .insert_resource(config)
+    .add_plugins(plugins)
+    .add_plugin(SnakeGamePlugin);
+    app
+}
@@ -0,0 +1,145 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::display::DisplaySettings;
+
+pub const LANG_DIR: &str = "assets/lang";
+// Built in, so a key missing from every file on disk still has its text
+const ENGLISH: &str = include_str!("../assets/lang/en.ron");
+
+// Gives the UI its text in the language picked on the menu, and switches it
+// over when another one is picked
+pub struct LocalePlugin;
+
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Locale>().add_system(switch_language);
+    }
+}
+
+// The languages there are text for in `LANG_DIR`, kept in the
+// `DisplaySettings`
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::German];
+
+    // In the language itself, so it can be found without reading the other
+    pub fn label(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+        }
+    }
+
+    // The name of its file in `LANG_DIR`
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::German => "de",
+        }
+    }
+
+    // The language `steps` places further along `ALL`, wrapping around
+    pub fn cycle(self, steps: isize) -> Language {
+        let count = Language::ALL.len() as isize;
+        let index = Language::ALL.iter().position(|l| *l == self).unwrap() as isize;
+        Language::ALL[(index + steps).rem_euclid(count) as usize]
+    }
+
+    pub fn path(self) -> PathBuf {
+        PathBuf::from(LANG_DIR).join(format!("{}.ron", self.code()))
+    }
+}
+
+// Every text of a language by its key, like "menu.play"
+pub type Strings = HashMap<String, String>;
+
+pub fn parse_strings(contents: &str) -> Result<Strings, String> {
+    ron::from_str(contents).map_err(|err| err.to_string())
+}
+
+// Reads the file of `language` from `LANG_DIR`
+pub fn load_strings(language: Language) -> Result<Strings, String> {
+    let path = language.path();
+    let contents = fs::read_to_string(&path).map_err(|err| err.to_string())?;
+    parse_strings(&contents).map_err(|err| format!("{}: {err}", path.display()))
+}
+
+// The text of the language being shown. A key it has no text for is shown
+// in English, and warned about the first time.
+pub struct Locale {
+    pub language: Language,
+    strings: Strings,
+    english: Strings,
+    warned: Mutex<HashSet<String>>,
+}
+
+impl Locale {
+    pub fn new(language: Language, strings: Strings) -> Locale {
+        Locale {
+            language,
+            strings,
+            english: parse_strings(ENGLISH).expect("the built in English text is valid"),
+            warned: default(),
+        }
+    }
+
+    // Without its file the language is English all the way through
+    pub fn load(language: Language) -> Locale {
+        let strings = load_strings(language).unwrap_or_else(|err| {
+            warn!("could not load the {} text: {err}", language.label());
+            Strings::new()
+        });
+        Locale::new(language, strings)
+    }
+
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        if let Some(text) = self.strings.get(key) {
+            return text;
+        }
+        let mut warned = self.warned.lock().unwrap();
+        if !warned.contains(key) {
+            warn!("no {} text for \"{key}\"", self.language.label());
+            warned.insert(key.to_string());
+        }
+        self.english.get(key).map_or(key, String::as_str)
+    }
+
+    // The text of `key` with every `{name}` in it replaced by its value
+    pub fn text(&self, key: &str, values: &[(&str, &dyn Display)]) -> String {
+        let mut text = self.get(key).to_string();
+        for (name, value) in values {
+            text = text.replace(&format!("{{{name}}}"), &value.to_string());
+        }
+        text
+    }
+}
+
+impl FromWorld for Locale {
+    fn from_world(world: &mut World) -> Self {
+        let language = world
+            .get_resource::<DisplaySettings>()
+            .map(|settings| settings.language)
+            .unwrap_or_default();
+        Locale::load(language)
+    }
+}
+
+// Follows the language picked in the `DisplaySettings`
+pub fn switch_language(settings: Res<DisplaySettings>, mut locale: ResMut<Locale>) {
+    if settings.language != locale.language {
+        *locale = Locale::load(settings.language);
+    }
+}
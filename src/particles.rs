@@ -0,0 +1,115 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::collision::FoodEaten;
+use crate::config::GameConfig;
+use crate::crates::CrateBroken;
+use crate::food::GameRng;
+use crate::game::Paused;
+use crate::state::GameState;
+use crate::theme::Theme;
+
+const PARTICLE_SIZE: Vec2 = Vec2::new(4.0, 4.0);
+const PARTICLE_LIFETIME: f32 = 0.4;
+const MIN_PARTICLES: usize = 10;
+const MAX_PARTICLES: usize = 20;
+// Units per second a particle starts flying away with
+const MIN_PARTICLE_SPEED: f32 = 80.0;
+const MAX_PARTICLE_SPEED: f32 = 240.0;
+// Above the snake and the food
+const PARTICLE_Z: f32 = 1.0;
+
+// The bursts of particles left where food is eaten or a crate breaks
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(spawn_food_particles)
+            .add_system(spawn_crate_particles)
+            .add_system(update_particles)
+            .add_system_set(SystemSet::on_exit(GameState::Playing).with_system(despawn_particles));
+    }
+}
+
+#[derive(Component)]
+pub struct Particle {
+    pub velocity: Vec2,
+    pub lifetime: Timer,
+}
+
+// The particles are part of the run, so they are thrown with the `GameRng`
+// and come out the same for the same seed
+pub fn spawn_food_particles(
+    mut commands: Commands,
+    mut food_events: EventReader<FoodEaten>,
+    mut rng: ResMut<GameRng>,
+    config: Res<GameConfig>,
+    theme: Res<Theme>,
+) {
+    for event in food_events.iter() {
+        let color = theme.sprite_color(&config, Some(&event.kind), None, false, false);
+        spawn_burst(&mut commands, &mut rng, color, event.position);
+    }
+}
+
+// Splinters, in the color of the crate, wherever one breaks
+pub fn spawn_crate_particles(
+    mut commands: Commands,
+    mut crate_events: EventReader<CrateBroken>,
+    mut rng: ResMut<GameRng>,
+    config: Res<GameConfig>,
+) {
+    for event in crate_events.iter() {
+        spawn_burst(&mut commands, &mut rng, config.crate_color, event.position);
+    }
+}
+
+fn spawn_burst(commands: &mut Commands, rng: &mut GameRng, color: Color, position: Vec3) {
+    let count = rng.rng.gen_range(MIN_PARTICLES..=MAX_PARTICLES);
+    for _ in 0..count {
+        let angle = rng.rng.gen_range(0.0..std::f32::consts::TAU);
+        let speed = rng.rng.gen_range(MIN_PARTICLE_SPEED..MAX_PARTICLE_SPEED);
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(PARTICLE_SIZE),
+                    ..default()
+                },
+                transform: Transform::from_translation(position.truncate().extend(PARTICLE_Z)),
+                ..default()
+            })
+            .insert(Particle {
+                velocity: Vec2::from_angle(angle) * speed,
+                lifetime: Timer::from_seconds(PARTICLE_LIFETIME, false),
+            });
+    }
+}
+
+// Moves the particles outwards and fades them until they run out of time
+pub fn update_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    paused: Res<Paused>,
+    mut query: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+) {
+    if **paused {
+        return;
+    }
+
+    for (entity, mut particle, mut transform, mut sprite) in &mut query {
+        particle.lifetime.tick(time.delta());
+        if particle.lifetime.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation += (particle.velocity * time.delta_seconds()).extend(0.0);
+        sprite.color.set_a(1.0 - particle.lifetime.percent());
+    }
+}
+
+pub fn despawn_particles(mut commands: Commands, query: Query<Entity, With<Particle>>) {
+    for entity in &query {
+        commands.entity(entity).despawn();
+    }
+}
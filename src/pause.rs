@@ -0,0 +1,319 @@
+use bevy::prelude::*;
+
+use crate::config::GameConfig;
+use crate::display::DisplaySettings;
+use crate::game::Paused;
+use crate::input::{restart_run, toggle_pause, ControlScheme};
+use crate::level::Campaign;
+use crate::locale::Locale;
+use crate::snake::Direction;
+use crate::state::GameState;
+use crate::theme::TEXT_COLOR;
+use crate::ui::{on_off_label, swatch_label, theme_label, SELECTED_MENU_ITEM_COLOR};
+use crate::walls::ArenaConfig;
+
+const PAUSE_TITLE_SIZE: f32 = 64.0;
+const PAUSE_FONT_SIZE: f32 = 32.0;
+const PAUSE_HINT_SIZE: f32 = 20.0;
+const PAUSE_ITEM_SPACING: Val = Val::Px(10.0);
+const PAUSE_BACKGROUND: Color = Color::rgba(0.0, 0.0, 0.0, 0.5);
+
+// The menu over the arena while the game is paused, worked with Up, Down and
+// Enter or the mouse. The game stays paused on every page of it.
+pub struct PauseMenuPlugin;
+
+impl Plugin for PauseMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PauseMenu>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(navigate_pause_menu.after(toggle_pause)),
+            )
+            .add_system(show_pause_menu.after(navigate_pause_menu))
+            .add_system(highlight_pause_selection.after(show_pause_menu));
+    }
+}
+
+// The entries of the pause menu, over both of its pages
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseItem {
+    Resume,
+    // Starts over the way R does
+    Restart,
+    // Opens the `PausePage::Settings`
+    Settings,
+    // Leaves the run for the main menu, without saving it
+    Quit,
+    Theme,
+    SnakeColor,
+    Colorblind,
+    Language,
+    // Back from the settings to the first page
+    Back,
+}
+
+impl PauseItem {
+    pub fn label(self, locale: &Locale, settings: &DisplaySettings) -> String {
+        match self {
+            PauseItem::Resume => locale.get("pause.resume").to_string(),
+            PauseItem::Restart => locale.get("pause.restart").to_string(),
+            PauseItem::Settings => locale.get("pause.settings").to_string(),
+            PauseItem::Quit => locale.get("pause.quit").to_string(),
+            PauseItem::Theme => locale.text(
+                "menu.theme",
+                &[("theme", &theme_label(locale, settings.theme))],
+            ),
+            PauseItem::SnakeColor => locale.text(
+                "menu.snake_color",
+                &[("color", &swatch_label(locale, settings.snake_swatch))],
+            ),
+            PauseItem::Colorblind => locale.text(
+                "menu.colorblind",
+                &[("state", &on_off_label(locale, settings.colorblind))],
+            ),
+            PauseItem::Language => {
+                locale.text("menu.language", &[("language", &settings.language.label())])
+            }
+            PauseItem::Back => locale.get("pause.back").to_string(),
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PausePage {
+    #[default]
+    Main,
+    // The display settings that can be changed in the middle of a run, the
+    // difficulty and the lives can't
+    Settings,
+}
+
+impl PausePage {
+    pub fn items(self) -> &'static [PauseItem] {
+        match self {
+            PausePage::Main => &[
+                PauseItem::Resume,
+                PauseItem::Restart,
+                PauseItem::Settings,
+                PauseItem::Quit,
+            ],
+            PausePage::Settings => &[
+                PauseItem::Theme,
+                PauseItem::SnakeColor,
+                PauseItem::Colorblind,
+                PauseItem::Language,
+                PauseItem::Back,
+            ],
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            PausePage::Main => "pause.title",
+            PausePage::Settings => "pause.settings_title",
+        }
+    }
+}
+
+// The page showing and the index of the highlighted entry in its `items`.
+// Every pause opens on Resume.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PauseMenu {
+    pub page: PausePage,
+    pub selection: usize,
+}
+
+impl PauseMenu {
+    pub fn selected(&self) -> PauseItem {
+        self.page.items()[self.selection]
+    }
+
+    fn open(&mut self, page: PausePage, item: PauseItem) {
+        self.page = page;
+        self.selection = page
+            .items()
+            .iter()
+            .position(|each| *each == item)
+            .unwrap_or(0);
+    }
+}
+
+// The overlay the pause menu is drawn on, for the page it shows
+#[derive(Component)]
+pub struct PauseMenuScreen(pub PausePage);
+
+// Up and Down, or hovering with the mouse, move the highlight. Enter or a
+// click picks the entry, and Left and Right change a setting as well. Enter
+// and Esc are used up, so nothing else starts from the same press.
+pub fn navigate_pause_menu(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    controls: Res<ControlScheme>,
+    interaction_query: Query<(&Interaction, &PauseItem), Changed<Interaction>>,
+    mut menu: ResMut<PauseMenu>,
+    mut paused: ResMut<Paused>,
+    mut settings: ResMut<DisplaySettings>,
+    mut state: ResMut<State<GameState>>,
+    mut campaign: ResMut<Campaign>,
+    config: Res<GameConfig>,
+    arena: Res<ArenaConfig>,
+) {
+    if paused.is_changed() {
+        *menu = PauseMenu::default();
+    }
+    if !**paused {
+        return;
+    }
+
+    let count = menu.page.items().len();
+    let mut picked = None;
+    for direction in controls.just_pressed(&keyboard_input) {
+        let step = match direction {
+            Direction::Up => {
+                menu.selection = (menu.selection + count - 1) % count;
+                continue;
+            }
+            Direction::Down => {
+                menu.selection = (menu.selection + 1) % count;
+                continue;
+            }
+            Direction::Left => -1,
+            Direction::Right => 1,
+        };
+        match menu.selected() {
+            PauseItem::Theme => settings.theme = settings.theme.cycle(step),
+            PauseItem::SnakeColor => settings.snake_swatch = settings.snake_swatch.cycle(step),
+            PauseItem::Colorblind => settings.colorblind = !settings.colorblind,
+            PauseItem::Language => settings.language = settings.language.cycle(step),
+            _ => {}
+        }
+    }
+    for (interaction, item) in &interaction_query {
+        let Some(index) = menu.page.items().iter().position(|each| each == item) else {
+            continue;
+        };
+        match interaction {
+            Interaction::Clicked => picked = Some(*item),
+            Interaction::Hovered if menu.selection != index => menu.selection = index,
+            Interaction::Hovered | Interaction::None => {}
+        }
+    }
+    if keyboard_input.clear_just_pressed(KeyCode::Return) {
+        picked = Some(menu.selected());
+    }
+    // Esc goes back a page, and from the first one back to the game
+    if keyboard_input.clear_just_pressed(KeyCode::Escape) {
+        picked = Some(match menu.page {
+            PausePage::Main => PauseItem::Resume,
+            PausePage::Settings => PauseItem::Back,
+        });
+    }
+
+    match picked {
+        Some(PauseItem::Resume) => **paused = false,
+        Some(PauseItem::Restart) => {
+            let _ = restart_run(&mut state, &mut campaign, &config, &arena);
+        }
+        Some(PauseItem::Settings) => menu.open(PausePage::Settings, PauseItem::Theme),
+        Some(PauseItem::Quit) => {
+            **paused = false;
+            let _ = state.set(GameState::Menu);
+        }
+        Some(PauseItem::Theme) => settings.theme = settings.theme.cycle(1),
+        Some(PauseItem::SnakeColor) => settings.snake_swatch = settings.snake_swatch.cycle(1),
+        Some(PauseItem::Colorblind) => settings.colorblind = !settings.colorblind,
+        Some(PauseItem::Language) => settings.language = settings.language.cycle(1),
+        Some(PauseItem::Back) => menu.open(PausePage::Main, PauseItem::Settings),
+        None => {}
+    }
+}
+
+// Builds the page of the menu that should be showing, and takes it down
+// once the game is running again or left. Moving the highlight leaves the
+// page as it is, see `highlight_pause_selection`.
+pub fn show_pause_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    locale: Res<Locale>,
+    settings: Res<DisplaySettings>,
+    paused: Res<Paused>,
+    menu: Res<PauseMenu>,
+    state: Res<State<GameState>>,
+    screen_query: Query<(Entity, &PauseMenuScreen)>,
+) {
+    let wanted = (**paused && *state.current() == GameState::Playing).then_some(menu.page);
+    let showing = screen_query.iter().next().map(|(_, screen)| screen.0);
+    if wanted == showing && !locale.is_changed() {
+        return;
+    }
+
+    for (screen, _) in &screen_query {
+        commands.entity(screen).despawn_recursive();
+    }
+    let Some(page) = wanted else {
+        return;
+    };
+    let font = asset_server.load("fonts/DejaVuSans-Bold.ttf");
+    let style = |font_size| TextStyle {
+        font: font.clone(),
+        font_size,
+        color: TEXT_COLOR,
+    };
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: PAUSE_BACKGROUND.into(),
+            ..default()
+        })
+        .insert(PauseMenuScreen(page))
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_section(
+                locale.get(page.title()),
+                style(PAUSE_TITLE_SIZE),
+            ));
+            for item in page.items() {
+                parent
+                    .spawn_bundle(
+                        TextBundle::from_section(
+                            item.label(&locale, &settings),
+                            style(PAUSE_FONT_SIZE),
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(PAUSE_ITEM_SPACING),
+                            ..default()
+                        }),
+                    )
+                    .insert(Interaction::default())
+                    .insert(*item);
+            }
+            parent.spawn_bundle(TextBundle::from_section(
+                locale.get("pause.quit_hint"),
+                style(PAUSE_HINT_SIZE),
+            ));
+        });
+}
+
+pub fn highlight_pause_selection(
+    menu: Res<PauseMenu>,
+    settings: Res<DisplaySettings>,
+    locale: Res<Locale>,
+    mut query: Query<(&PauseItem, &mut Text, ChangeTrackers<Text>)>,
+) {
+    for (item, mut text, tracker) in &mut query {
+        if !menu.is_changed() && !settings.is_changed() && !tracker.is_added() {
+            continue;
+        }
+        text.sections[0].value = item.label(&locale, &settings);
+        text.sections[0].style.color = if *item == menu.selected() {
+            SELECTED_MENU_ITEM_COLOR
+        } else {
+            TEXT_COLOR
+        };
+    }
+}
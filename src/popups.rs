@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+
+use crate::collision::{award_points, check_for_collisions, score_food, FoodEaten};
+use crate::game::{Combo, FixedUpdate, Paused, ScoreMultiplier};
+use crate::state::GameState;
+use crate::theme::Theme;
+
+const POPUP_FONT_SIZE: f32 = 20.0;
+const POPUP_LIFETIME: f32 = 0.8;
+// Units per second a popup floats up with
+const POPUP_SPEED: f32 = 60.0;
+// Above the snake, the food and the particles. The HUD is UI, drawn over
+// the whole arena.
+const POPUP_Z: f32 = 2.0;
+
+// The "+1" floating up from where a food worth points was eaten, showing
+// what it scored
+pub struct PopupPlugin;
+
+impl Plugin for PopupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::new().with_run_criteria(FixedUpdate).with_system(
+                spawn_score_popups
+                    .after(check_for_collisions)
+                    .before(score_food),
+            ),
+        )
+        .add_system(update_popups)
+        .add_system_set(SystemSet::on_exit(GameState::Playing).with_system(despawn_popups));
+    }
+}
+
+#[derive(Component)]
+pub struct Popup {
+    pub velocity: Vec2,
+    pub lifetime: Timer,
+}
+
+// Runs right before `score_food` and works the points out the same way, on
+// a copy of the multiplier and the combo, so they are exactly what is scored
+pub fn spawn_score_popups(
+    mut commands: Commands,
+    mut food_events: EventReader<FoodEaten>,
+    multiplier: Res<ScoreMultiplier>,
+    combo: Res<Combo>,
+    theme: Res<Theme>,
+    asset_server: Res<AssetServer>,
+) {
+    let mut multiplier = multiplier.clone();
+    let mut combo = combo.clone();
+    for event in food_events.iter() {
+        let Some(points) = award_points(event.kind, &mut multiplier, &mut combo) else {
+            continue;
+        };
+        commands
+            .spawn_bundle(Text2dBundle {
+                text: Text::from_section(
+                    format!("+{points}"),
+                    TextStyle {
+                        font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                        font_size: POPUP_FONT_SIZE,
+                        color: theme.text,
+                    },
+                )
+                .with_alignment(TextAlignment::CENTER),
+                transform: Transform::from_translation(event.position.truncate().extend(POPUP_Z)),
+                ..default()
+            })
+            .insert(Popup {
+                velocity: Vec2::new(0.0, POPUP_SPEED),
+                lifetime: Timer::from_seconds(POPUP_LIFETIME, false),
+            });
+    }
+}
+
+// Floats the popups up and fades them until they run out of time
+pub fn update_popups(
+    mut commands: Commands,
+    time: Res<Time>,
+    paused: Res<Paused>,
+    mut query: Query<(Entity, &mut Popup, &mut Transform, &mut Text)>,
+) {
+    if **paused {
+        return;
+    }
+
+    for (entity, mut popup, mut transform, mut text) in &mut query {
+        popup.lifetime.tick(time.delta());
+        if popup.lifetime.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation += (popup.velocity * time.delta_seconds()).extend(0.0);
+        for section in &mut text.sections {
+            section.style.color.set_a(1.0 - popup.lifetime.percent());
+        }
+    }
+}
+
+pub fn despawn_popups(mut commands: Commands, query: Query<Entity, With<Popup>>) {
+    for entity in &query {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+use bevy::sprite::collide_aabb::collide;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::collision::Hitbox;
+use crate::config::GameConfig;
+use crate::food::food_cells;
+use crate::game::FixedUpdate;
+use crate::snake::{decay_ghost_mode, move_segments, MovementMode, Snake, TRAIL_WRAP_DISTANCE};
+use crate::walls::ArenaBounds;
+
+// How many pairs of free cells are tried before the arena goes without
+// portals
+const PORTAL_SPAWN_ATTEMPTS: usize = 100;
+// Below the snake and the food, above the checkerboard
+pub const PORTAL_Z: f32 = -0.5;
+
+// Sends a head that enters one portal out of the other, when
+// `GameConfig::portals` is on
+pub struct PortalPlugin;
+
+impl Plugin for PortalPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Teleported>().add_system_set(
+            SystemSet::new()
+                .with_run_criteria(FixedUpdate)
+                .with_system(enter_portals.after(decay_ghost_mode).before(move_segments)),
+        );
+    }
+}
+
+// One mouth of a pair. A head entering it comes out of `exit`, the other
+// mouth, still heading the same way.
+#[derive(Component)]
+pub struct Portal {
+    pub exit: Entity,
+}
+
+// The portal a head just came out of, so it isn't sent straight back
+// through it. Taken off once the head has left it.
+#[derive(Component, Deref)]
+pub struct InPortal(pub Entity);
+
+// The heads that came out of a portal this tick. They didn't get to the
+// exit the way their velocity points, so `check_for_collisions` sweeps them
+// from the exit rather than from behind it.
+#[derive(Default, Deref, DerefMut)]
+pub struct Teleported(pub Vec<Entity>);
+
+// Two free cells for the portals, clear of everything `occupied` (given as
+// center and size). They are further apart than `TRAIL_WRAP_DISTANCE`, so
+// the body takes the jump between them the way it takes a ghost's wrap,
+// going in one mouth and out of the other. Returns `None` if there is no
+// such pair.
+pub fn random_portals(
+    rng: &mut impl Rng,
+    config: &GameConfig,
+    bounds: &ArenaBounds,
+    occupied: &[(Vec3, Vec2)],
+) -> Option<(Vec3, Vec3)> {
+    let free: Vec<Vec3> = food_cells(config, bounds, MovementMode::Grid)
+        .into_iter()
+        .filter(|cell| {
+            occupied
+                .iter()
+                .all(|(center, size)| collide(*cell, config.snake_size, *center, *size).is_none())
+        })
+        .collect();
+    for _ in 0..PORTAL_SPAWN_ATTEMPTS {
+        let (Some(first), Some(second)) = (free.choose(rng), free.choose(rng)) else {
+            return None;
+        };
+        if first.distance(*second) > TRAIL_WRAP_DISTANCE {
+            return Some((*first, *second));
+        }
+    }
+    None
+}
+
+// Moves a head that has come into a portal onto its exit. The head keeps its
+// direction and the body follows along the trail, the part still behind the
+// first mouth and the rest coming out of the second. Runs before
+// `check_for_collisions`, so a head brought out onto a wall or its own body
+// dies there as it would anywhere else.
+pub fn enter_portals(
+    mut commands: Commands,
+    mut snake_query: Query<(Entity, &mut Transform, &Hitbox, Option<&InPortal>), With<Snake>>,
+    portal_query: Query<(Entity, &Transform, &Hitbox, &Portal), Without<Snake>>,
+    mut teleported: ResMut<Teleported>,
+) {
+    teleported.clear();
+    for (snake, mut transform, hitbox, in_portal) in &mut snake_query {
+        let entered = portal_query.iter().find(|(_, portal, size, _)| {
+            collide(transform.translation, **hitbox, portal.translation, ***size).is_some()
+        });
+        let Some((portal, _, _, Portal { exit })) = entered else {
+            if in_portal.is_some() {
+                commands.entity(snake).remove::<InPortal>();
+            }
+            continue;
+        };
+        if in_portal.is_some_and(|in_portal| **in_portal == portal) {
+            continue;
+        }
+        let Ok((_, exit_transform, ..)) = portal_query.get(*exit) else {
+            continue;
+        };
+        let z = transform.translation.z;
+        transform.translation = exit_transform.translation.truncate().extend(z);
+        commands.entity(snake).insert(InPortal(*exit));
+        teleported.push(snake);
+    }
+}
@@ -0,0 +1,91 @@
+use bevy::prelude::*;
+
+use crate::collision::{FoodEaten, Hitbox};
+use crate::game::Paused;
+use crate::snake::SegmentOrder;
+
+pub const PULSE_SECONDS: f32 = 0.2;
+// How much bigger the sprite is drawn at the height of the pulse
+pub const PULSE_GROWTH: f32 = 0.15;
+// How long the pulse takes to ripple from the head down to the tail,
+// whatever the length
+const PULSE_RIPPLE_SECONDS: f32 = 0.2;
+
+// The snake swells up for a moment where it eats, and the swell runs down
+// its body
+pub struct EatPulsePlugin;
+
+impl Plugin for EatPulsePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(start_eat_pulses)
+            .add_system(update_eat_pulses.after(start_eat_pulses));
+    }
+}
+
+// Put on the head and the segments of a snake that just ate. Only the
+// sprite is drawn bigger, the `Hitbox` stays as it is. Eating again starts
+// it over, so pulses never add up.
+#[derive(Component)]
+pub struct EatPulse {
+    // Counts the delay and the pulse after it
+    pub timer: Timer,
+    // Seconds until this part of the body starts to swell
+    pub delay: f32,
+}
+
+impl EatPulse {
+    pub fn new(delay: f32) -> EatPulse {
+        EatPulse {
+            timer: Timer::from_seconds(delay + PULSE_SECONDS, false),
+            delay,
+        }
+    }
+
+    // Up and back down along half a sine
+    pub fn scale(&self) -> f32 {
+        let t = (self.timer.elapsed_secs() - self.delay) / PULSE_SECONDS;
+        if (0.0..=1.0).contains(&t) {
+            1.0 + PULSE_GROWTH * (t * std::f32::consts::PI).sin()
+        } else {
+            1.0
+        }
+    }
+}
+
+pub fn start_eat_pulses(
+    mut commands: Commands,
+    mut food_events: EventReader<FoodEaten>,
+    snake_query: Query<&SegmentOrder>,
+) {
+    for event in food_events.iter() {
+        let Ok(segments) = snake_query.get(event.snake) else {
+            continue;
+        };
+        commands.entity(event.snake).insert(EatPulse::new(0.0));
+        for (index, segment) in segments.iter().enumerate() {
+            let delay = PULSE_RIPPLE_SECONDS * (index + 1) as f32 / segments.len() as f32;
+            commands.entity(*segment).insert(EatPulse::new(delay));
+        }
+    }
+}
+
+// Sizes each sprite from its `Hitbox`, not from the size it had, and gives
+// it back its own size once done. Stands still while paused.
+pub fn update_eat_pulses(
+    mut commands: Commands,
+    time: Res<Time>,
+    paused: Res<Paused>,
+    mut query: Query<(Entity, &mut EatPulse, &Hitbox, &mut Sprite)>,
+) {
+    if **paused {
+        return;
+    }
+    for (entity, mut pulse, hitbox, mut sprite) in &mut query {
+        pulse.timer.tick(time.delta());
+        sprite.custom_size = Some(**hitbox * pulse.scale());
+        if pulse.timer.finished() {
+            sprite.custom_size = Some(**hitbox);
+            commands.entity(entity).remove::<EatPulse>();
+        }
+    }
+}
@@ -0,0 +1,283 @@
+use std::{fmt, fs, io, path::Path};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::collision::{grow_snake, GameOverEvent};
+use crate::food::GameRng;
+use crate::game::{
+    count_tick, handle_game_over, spawn_gameplay, Difficulty, FixedUpdate, GameMode, GameTick,
+    Scoreboard, StartingLives,
+};
+use crate::level::{CurrentLevel, Level};
+use crate::snake::{
+    drain_boost, move_snake, Boost, Direction, DirectionQueue, HeldDirections, MovementMode,
+    SegmentOrder, Snake,
+};
+use crate::state::GameState;
+use crate::walls::WallMode;
+
+// Bumped whenever a change to the game makes older replays play out differently
+pub const REPLAY_VERSION: u32 = 3;
+
+// Records every classic run, or plays one back when a `ReplayPlayer` is
+// inserted. Only classic runs are recorded: a replay steers one snake and
+// knows nothing of the time attack clock.
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReplayRecorder>()
+            .add_startup_system(start_replay)
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(FixedUpdate)
+                    .with_system(record_inputs.after(count_tick).before(move_snake))
+                    .with_system(
+                        play_inputs
+                            .after(count_tick)
+                            .before(move_snake)
+                            .before(drain_boost),
+                    )
+                    .with_system(remember_inputs.after(move_snake))
+                    .with_system(finish_replay.after(handle_game_over).after(grow_snake)),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::Playing)
+                    .with_system(start_recording.after(spawn_gameplay))
+                    .with_system(start_playback.after(spawn_gameplay)),
+            );
+    }
+}
+
+// Everything needed to play a run again: the settings it was started with
+// and the player's input, keyed by tick
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    // The lives the run started with
+    pub lives: usize,
+    pub movement_mode: MovementMode,
+    pub wall_mode: WallMode,
+    pub level: Option<Level>,
+    pub inputs: Vec<RecordedInput>,
+    // How the run ended, to check the playback against
+    pub final_score: usize,
+    pub final_length: usize,
+}
+
+impl Replay {
+    pub fn load(path: impl AsRef<Path>) -> Result<Replay, ReplayError> {
+        let contents = fs::read_to_string(path).map_err(ReplayError::Read)?;
+        let replay: Replay = ron::from_str(&contents).map_err(ReplayError::Parse)?;
+        if replay.version != REPLAY_VERSION {
+            return Err(ReplayError::Version(replay.version));
+        }
+        Ok(replay)
+    }
+}
+
+// The steering input as the snake saw it at the start of `tick`. Only ticks
+// where it differs from what the previous tick left behind are recorded.
+// Replays from before the boost never hold it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecordedInput {
+    pub tick: u64,
+    pub queue: Vec<Direction>,
+    pub held: Vec<Direction>,
+    #[serde(default)]
+    pub boost: bool,
+}
+
+impl RecordedInput {
+    fn new(
+        tick: GameTick,
+        queue: &DirectionQueue,
+        held: &HeldDirections,
+        boost: &Boost,
+    ) -> RecordedInput {
+        RecordedInput {
+            tick: *tick,
+            queue: queue.iter().copied().collect(),
+            held: held.to_vec(),
+            boost: boost.held,
+        }
+    }
+
+    fn same_input(&self, other: &RecordedInput) -> bool {
+        self.queue == other.queue && self.held == other.held && self.boost == other.boost
+    }
+}
+
+// The run being recorded, or the last one finished
+#[derive(Default)]
+pub struct ReplayRecorder {
+    pub replay: Replay,
+    // The input as the previous tick left it
+    last: RecordedInput,
+}
+
+// Feeds a recorded run back into the game instead of the player's input
+pub struct ReplayPlayer {
+    pub replay: Replay,
+    // Index into `replay.inputs` of the next input to play
+    next: usize,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> ReplayPlayer {
+        ReplayPlayer { replay, next: 0 }
+    }
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Read(io::Error),
+    Parse(ron::error::SpannedError),
+    // Written by a version of the game that plays differently
+    Version(u32),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Read(err) => write!(f, "could not read the replay: {err}"),
+            ReplayError::Parse(err) => write!(f, "could not parse the replay: {err}"),
+            ReplayError::Version(version) => write!(
+                f,
+                "the replay is from version {version} of the format, this game plays version {REPLAY_VERSION}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+// A replay skips the menu and starts playing right away. The menu is
+// still being entered at startup, so that has to be overwritten.
+pub fn start_replay(player: Option<Res<ReplayPlayer>>, mut state: ResMut<State<GameState>>) {
+    if player.is_some() {
+        let _ = state.overwrite_set(GameState::Playing);
+    }
+}
+
+pub fn start_recording(
+    mut recorder: ResMut<ReplayRecorder>,
+    rng: Res<GameRng>,
+    (difficulty, starting_lives): (Res<Difficulty>, Res<StartingLives>),
+    movement_mode: Res<MovementMode>,
+    wall_mode: Res<WallMode>,
+    level: Res<CurrentLevel>,
+) {
+    *recorder = ReplayRecorder {
+        replay: Replay {
+            version: REPLAY_VERSION,
+            seed: rng.seed,
+            difficulty: *difficulty,
+            lives: **starting_lives,
+            movement_mode: *movement_mode,
+            wall_mode: *wall_mode,
+            level: level.clone(),
+            ..default()
+        },
+        last: RecordedInput::default(),
+    };
+}
+
+// `spawn_gameplay` picks the walls from the difficulty, the replay knows
+// what they really were
+pub fn start_playback(player: Option<ResMut<ReplayPlayer>>, mut wall_mode: ResMut<WallMode>) {
+    if let Some(mut player) = player {
+        player.next = 0;
+        *wall_mode = player.replay.wall_mode;
+    }
+}
+
+pub fn record_inputs(
+    mut recorder: ResMut<ReplayRecorder>,
+    player: Option<Res<ReplayPlayer>>,
+    tick: Res<GameTick>,
+    mode: Res<GameMode>,
+    query: Query<(&DirectionQueue, &HeldDirections, &Boost), With<Snake>>,
+) {
+    if player.is_some() || *mode != GameMode::Classic {
+        return;
+    }
+    let Ok((queue, held, boost)) = query.get_single() else {
+        return;
+    };
+    let input = RecordedInput::new(*tick, queue, held, boost);
+    if !input.same_input(&recorder.last) {
+        recorder.replay.inputs.push(input);
+    }
+}
+
+// What the tick leaves behind, the next one only records what changes it
+pub fn remember_inputs(
+    mut recorder: ResMut<ReplayRecorder>,
+    tick: Res<GameTick>,
+    query: Query<(&DirectionQueue, &HeldDirections, &Boost), With<Snake>>,
+) {
+    if let Ok((queue, held, boost)) = query.get_single() {
+        recorder.last = RecordedInput::new(*tick, queue, held, boost);
+    }
+}
+
+pub fn play_inputs(
+    player: Option<ResMut<ReplayPlayer>>,
+    tick: Res<GameTick>,
+    mut query: Query<(&mut DirectionQueue, &mut HeldDirections, &mut Boost), With<Snake>>,
+) {
+    let Some(mut player) = player else {
+        return;
+    };
+    let Ok((mut queue, mut held, mut boost)) = query.get_single_mut() else {
+        return;
+    };
+    let Some(input) = player.replay.inputs.get(player.next) else {
+        return;
+    };
+    if input.tick != **tick {
+        return;
+    }
+
+    **queue = input.queue.iter().copied().collect();
+    **held = input.held.clone();
+    boost.held = input.boost;
+    player.next += 1;
+}
+
+// Runs in the tick the snake dies, while it is still around to be measured
+pub fn finish_replay(
+    mut game_over_events: EventReader<GameOverEvent>,
+    mut recorder: ResMut<ReplayRecorder>,
+    player: Option<Res<ReplayPlayer>>,
+    scoreboard: Res<Scoreboard>,
+    mode: Res<GameMode>,
+    query: Query<&SegmentOrder, With<Snake>>,
+) {
+    if game_over_events.iter().count() == 0 || *mode != GameMode::Classic {
+        return;
+    }
+    let Ok(segments) = query.get_single() else {
+        return;
+    };
+    let length = segments.len() + 1;
+    if let Some(player) = player {
+        let replay = &player.replay;
+        if (replay.final_score, replay.final_length) == (scoreboard.score, length) {
+            info!("the replay ended as recorded");
+        } else {
+            warn!(
+                "the replay ended with score {} and length {}, it was recorded with score {} and length {}",
+                scoreboard.score, length, replay.final_score, replay.final_length
+            );
+        }
+        return;
+    }
+
+    recorder.replay.final_score = scoreboard.score;
+    recorder.replay.final_length = length;
+}
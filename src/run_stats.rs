@@ -0,0 +1,165 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+
+use crate::collision::{grow_snake, DeathCause, FoodEaten, GameOverEvent};
+use crate::config::GameConfig;
+use crate::food::{FoodKind, GameRng};
+use crate::game::{
+    count_down_round, handle_game_over, spawn_gameplay, Combo, Difficulty, FixedUpdate, GameMode,
+    RoundTimer, Scoreboard,
+};
+use crate::level::Campaign;
+use crate::snake::{
+    move_segments, Boost, GameSpeed, MovementMode, Respawning, SegmentOrder, Snake, SpeedModifier,
+};
+use crate::state::GameState;
+
+// The columns of a row of `RunStats::csv_row`
+pub const RUN_CSV_HEADER: &str =
+    "timestamp,seed,duration_secs,score,max_length,foods_eaten,cause,difficulty";
+
+// Keeps the `RunStats` of the run being played, for the results on the game
+// over screen. The `PersistencePlugin` adds every finished run to the runs
+// file.
+pub struct RunStatsPlugin;
+
+impl Plugin for RunStatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunStats>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::Playing)
+                    .with_system(reset_run_stats.after(spawn_gameplay)),
+            )
+            .add_system_set(
+                SystemSet::new().with_run_criteria(FixedUpdate).with_system(
+                    record_run_stats
+                        .after(move_segments)
+                        .after(grow_snake)
+                        .after(handle_game_over)
+                        .after(count_down_round),
+                ),
+            );
+    }
+}
+
+// How the run being played has gone so far. A campaign counts as one run
+// over all its levels.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct RunStats {
+    // Seconds since the epoch when the run started
+    pub started_at: u64,
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    // Only the time the game was running, the pauses left out
+    pub duration_secs: f32,
+    pub score: usize,
+    // Segments including the head
+    pub max_length: usize,
+    // How long the snake was at the end, or the longest one in versus
+    pub length: usize,
+    // Every kind of food and pickup
+    pub foods_eaten: usize,
+    pub bonus_foods_eaten: usize,
+    pub best_combo: usize,
+    // How far the snake went, in cells, while it was moving
+    pub cells_travelled: f32,
+    // What ended the run, `None` while it goes on
+    pub cause: Option<DeathCause>,
+}
+
+impl RunStats {
+    // The run as a line of the runs file, in the order of `RUN_CSV_HEADER`
+    pub fn csv_row(&self) -> String {
+        format!(
+            "{},{},{:.2},{},{},{},{},{}",
+            self.started_at,
+            self.seed,
+            self.duration_secs,
+            self.score,
+            self.max_length,
+            self.foods_eaten,
+            self.cause.map_or("", DeathCause::label),
+            self.difficulty.label(),
+        )
+    }
+
+    // In cells per second, over the whole run
+    pub fn average_speed(&self) -> f32 {
+        if self.duration_secs > 0.0 {
+            self.cells_travelled / self.duration_secs
+        } else {
+            0.0
+        }
+    }
+}
+
+// Starts over for a new run, but not for the next level of a campaign
+pub fn reset_run_stats(
+    mut run: ResMut<RunStats>,
+    rng: Res<GameRng>,
+    (difficulty, mode): (Res<Difficulty>, Res<GameMode>),
+    campaign: Res<Campaign>,
+) {
+    if campaign.0.as_ref().is_some_and(|level| level.index > 0) {
+        return;
+    }
+    *run = RunStats {
+        started_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default(),
+        seed: rng.seed,
+        difficulty: difficulty.played_in(*mode),
+        ..default()
+    };
+}
+
+// Counted from the events and resources as the run goes, the snakes are
+// already being despawned by the time the results are shown. Replays count
+// too, `append_run` is what leaves them out of the runs file.
+pub fn record_run_stats(
+    mut food_events: EventReader<FoodEaten>,
+    mut game_over_events: EventReader<GameOverEvent>,
+    mut run: ResMut<RunStats>,
+    snake_query: Query<(&SegmentOrder, &Boost, Option<&Respawning>), With<Snake>>,
+    (scoreboard, combo): (Res<Scoreboard>, Res<Combo>),
+    (mode, round_timer): (Res<GameMode>, Res<RoundTimer>),
+    (speed, modifier, movement_mode): (Res<GameSpeed>, Res<SpeedModifier>, Res<MovementMode>),
+    config: Res<GameConfig>,
+) {
+    run.duration_secs += config.time_step;
+    run.score = scoreboard.score;
+    // A campaign starts every level with a fresh combo
+    run.best_combo = run.best_combo.max(combo.best);
+    for event in food_events.iter() {
+        run.foods_eaten += 1;
+        if event.kind == FoodKind::Bonus {
+            run.bonus_foods_eaten += 1;
+        }
+    }
+    run.length = 0;
+    // The fastest of the snakes still moving
+    let mut moving: Option<f32> = None;
+    for (segments, boost, respawning) in &snake_query {
+        run.max_length = run.max_length.max(segments.len() + 1);
+        run.length = run.length.max(segments.len() + 1);
+        if respawning.is_none() {
+            moving = Some(moving.unwrap_or(0.0).max(boost.factor()));
+        }
+    }
+    if let Some(boost) = moving {
+        let cells_per_second = match *movement_mode {
+            MovementMode::Grid => config.grid_steps_per_second,
+            MovementMode::Continuous => config.snake_speed / config.snake_size.x,
+        };
+        run.cells_travelled +=
+            cells_per_second * **speed * modifier.factor * boost * config.time_step;
+    }
+    if run.cause.is_none() {
+        run.cause = game_over_events.iter().next().map(|event| event.cause);
+    }
+    if run.cause.is_none() && *mode == GameMode::TimeAttack && round_timer.finished() {
+        run.cause = Some(DeathCause::TimeUp);
+    }
+}
@@ -0,0 +1,587 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::collision::Hitbox;
+use crate::config::GameConfig;
+use crate::crates::{Crate, Hammer};
+use crate::food::{
+    BonusCountdown, BonusFood, Food, FoodKind, FoodLifetime, GameRng, Magnet, SpawnAnimation,
+    Wander, GOLDEN_FOOD_SECONDS,
+};
+use crate::game::{
+    spawn_gameplay, Difficulty, GameMode, GameTick, Lives, ScoreMultiplier, Scoreboard,
+};
+use crate::hunger::{reset_hunger, Hunger};
+use crate::hunter::{reset_hunter_countdown, Hunter, HunterCountdown, HUNTER_RETARGET_SECONDS};
+use crate::ice::IcePatch;
+use crate::level::{Campaign, CurrentLevel, Level, LevelState};
+use crate::portal::{InPortal, Portal};
+use crate::replay::{start_recording, Replay, ReplayRecorder};
+use crate::snake::{
+    Boost, Direction, DirectionQueue, GameSpeed, GhostMode, MovementMode, NextDirection, Player,
+    SegmentOrder, Shield, Snake, SnakeTrail, SpeedModifier, SpeedRamp, StepProgress, Velocity,
+};
+use crate::spawning::{
+    spawn_bonus_food, spawn_crate, spawn_food_of_kind, spawn_hunter, spawn_ice_patch,
+    spawn_obstacle, spawn_portals, spawn_segment, spawn_snake,
+};
+use crate::state::GameState;
+use crate::walls::{Obstacle, WallMode};
+
+// Bumped whenever `SavedGame` changes, older saves are thrown away
+pub const SAVE_VERSION: u32 = 20;
+
+// Saving a run in progress and picking it up again later
+pub struct SaveGamePlugin;
+
+impl Plugin for SaveGamePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SavedGameSlot>()
+            .add_event::<SaveAndQuit>()
+            .add_system(save_and_quit)
+            .add_system_set(
+                SystemSet::on_enter(GameState::Playing).with_system(
+                    restore_saved_game
+                        .after(spawn_gameplay)
+                        .after(start_recording)
+                        .after(reset_hunger)
+                        .after(reset_hunter_countdown),
+                ),
+            );
+    }
+}
+
+// Sent to save the run and go back to the menu
+pub struct SaveAndQuit;
+
+// Everything a run is made of, down to where the random numbers are at, so
+// the tick after loading plays out like the one that never came
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedGame {
+    pub version: u32,
+    pub difficulty: Difficulty,
+    pub movement_mode: MovementMode,
+    pub wall_mode: WallMode,
+    // The layout being played, whether picked with `--level` or by the
+    // campaign
+    pub level: Option<Level>,
+    pub campaign: Option<LevelState>,
+    pub head: SavedHead,
+    // From right behind the head to the tip of the tail
+    pub segments: Vec<Vec3>,
+    pub trail: Vec<Vec3>,
+    // Everything but the bonus food, which has a timer to keep
+    pub food: Vec<SavedFood>,
+    pub bonus: Option<SavedBonus>,
+    pub bonus_countdown: usize,
+    // Center and size of every obstacle but the crates
+    pub obstacles: Vec<(Vec3, Vec2)>,
+    // Where every crate is, and the hits it has left
+    pub crates: Vec<(Vec3, u8)>,
+    // Center and size of every patch of ice
+    pub ice: Vec<(Vec3, Vec2)>,
+    // Both mouths of every pair of portals, each the exit of the other
+    pub portals: Vec<(Vec3, Vec3)>,
+    pub hunter: Option<SavedHunter>,
+    // Normal foods left until the next hunter
+    pub hunter_countdown: usize,
+    pub queue: Vec<Direction>,
+    pub score: usize,
+    pub lives: usize,
+    pub multiplier: Option<SavedMultiplier>,
+    pub speed: f32,
+    pub speed_modifier: Option<SavedModifier>,
+    // Seconds of ghost mode left
+    pub ghost: Option<f32>,
+    pub magnet: Option<SavedMagnet>,
+    // Seconds left on the hunger meter
+    pub hunger: f32,
+    pub tick: u64,
+    pub fixed_seed: Option<u64>,
+    pub seed: u64,
+    // How far `GameRng::rng` has got through the sequence of `seed`
+    pub rng_word_pos: u128,
+    pub replay: Replay,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedHead {
+    pub position: Vec3,
+    pub direction: Direction,
+    pub next_direction: Direction,
+    pub step_progress: f32,
+    pub velocity: Option<Vec2>,
+    pub shield: usize,
+    pub hammer: usize,
+    pub boost: Boost,
+    // Where the portal the head just came out of is
+    pub in_portal: Option<Vec3>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedFood {
+    pub position: Vec3,
+    pub kind: FoodKind,
+    // Seconds left on the `FoodLifetime` of food that expires
+    pub lifetime: Option<f32>,
+    // Seconds it has been growing for, while it is still popping up
+    pub growing: Option<f32>,
+    pub wander: Option<SavedWander>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedBonus {
+    pub position: Vec3,
+    // Seconds until it goes away
+    pub remaining: f32,
+    pub growing: Option<f32>,
+    pub wander: Option<SavedWander>,
+}
+
+// Where a wandering food is heading, and for how long
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedWander {
+    pub velocity: Vec2,
+    // Seconds the heading is kept for, and how many of them have gone by
+    pub turn_seconds: f32,
+    pub turn_elapsed: f32,
+}
+
+impl SavedWander {
+    fn new(wander: &Wander, velocity: &Velocity) -> SavedWander {
+        SavedWander {
+            velocity: **velocity,
+            turn_seconds: wander.next_turn.duration().as_secs_f32(),
+            turn_elapsed: wander.next_turn.elapsed_secs(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedHunter {
+    pub position: Vec3,
+    pub heading: Vec2,
+    // Seconds since it last looked for a head
+    pub retarget_elapsed: f32,
+    // The player whose head it is after
+    pub chasing: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedMultiplier {
+    pub factor: usize,
+    // Seconds until it runs out
+    pub remaining: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedModifier {
+    pub factor: f32,
+    pub remaining: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedMagnet {
+    pub radius: f32,
+    pub remaining: f32,
+}
+
+// The saved run, if there is one. `resuming` makes the next run pick it up
+// instead of starting over.
+#[derive(Default)]
+pub struct SavedGameSlot {
+    pub saved: Option<SavedGame>,
+    pub resuming: bool,
+}
+
+// Only a classic run is saved, versus rounds and time attack runs are too
+// short to be worth it
+pub fn save_and_quit(
+    mut save_events: EventReader<SaveAndQuit>,
+    mut slot: ResMut<SavedGameSlot>,
+    mut state: ResMut<State<GameState>>,
+    head_query: Query<
+        (
+            &Transform,
+            &Direction,
+            &NextDirection,
+            &StepProgress,
+            Option<&Velocity>,
+            &Shield,
+            &Hammer,
+            &Boost,
+            &SegmentOrder,
+            &SnakeTrail,
+            &DirectionQueue,
+            Option<&InPortal>,
+        ),
+        With<Snake>,
+    >,
+    transform_query: Query<&Transform>,
+    (food_query, obstacle_query, crate_query, ice_query, portal_query, countdown): (
+        Query<
+            (
+                &Transform,
+                &FoodKind,
+                Option<&BonusFood>,
+                Option<&FoodLifetime>,
+                Option<&SpawnAnimation>,
+                Option<(&Wander, &Velocity)>,
+            ),
+            With<Food>,
+        >,
+        Query<(&Transform, &Hitbox), (With<Obstacle>, Without<Crate>)>,
+        Query<(&Transform, &Crate)>,
+        Query<(&Transform, &Hitbox), With<IcePatch>>,
+        Query<(Entity, &Transform, &Portal)>,
+        Res<BonusCountdown>,
+    ),
+    (hunter_query, hunter_countdown): (Query<(&Transform, &Hunter)>, Res<HunterCountdown>),
+    mode: Res<GameMode>,
+    (difficulty, movement_mode, wall_mode, level, campaign): (
+        Res<Difficulty>,
+        Res<MovementMode>,
+        Res<WallMode>,
+        Res<CurrentLevel>,
+        Res<Campaign>,
+    ),
+    (scoreboard, lives, multiplier): (Res<Scoreboard>, Res<Lives>, Res<ScoreMultiplier>),
+    (speed, modifier, ghost, magnet, hunger): (
+        Res<GameSpeed>,
+        Res<SpeedModifier>,
+        Res<GhostMode>,
+        Res<Magnet>,
+        Res<Hunger>,
+    ),
+    tick: Res<GameTick>,
+    rng: Res<GameRng>,
+    recorder: Res<ReplayRecorder>,
+) {
+    if save_events.iter().count() == 0
+        || *state.current() != GameState::Playing
+        || *mode != GameMode::Classic
+    {
+        return;
+    }
+    let Ok((
+        transform,
+        direction,
+        next_direction,
+        step_progress,
+        velocity,
+        shield,
+        hammer,
+        boost,
+        segments,
+        trail,
+        queue,
+        in_portal,
+    )) = head_query.get_single()
+    else {
+        return;
+    };
+    let portal_position = |portal: Entity| {
+        portal_query
+            .get(portal)
+            .ok()
+            .map(|(_, transform, _)| transform.translation)
+    };
+
+    slot.saved = Some(SavedGame {
+        version: SAVE_VERSION,
+        difficulty: *difficulty,
+        movement_mode: *movement_mode,
+        wall_mode: *wall_mode,
+        level: campaign.layout(&level).cloned(),
+        campaign: campaign.0.clone(),
+        head: SavedHead {
+            position: transform.translation,
+            direction: *direction,
+            next_direction: **next_direction,
+            step_progress: **step_progress,
+            velocity: velocity.map(|velocity| **velocity),
+            shield: **shield,
+            hammer: **hammer,
+            boost: *boost,
+            in_portal: in_portal.and_then(|portal| portal_position(**portal)),
+        },
+        segments: segments
+            .iter()
+            .filter_map(|segment| transform_query.get(*segment).ok())
+            .map(|transform| transform.translation)
+            .collect(),
+        trail: trail.iter().copied().collect(),
+        food: food_query
+            .iter()
+            .filter(|(_, kind, ..)| **kind != FoodKind::Bonus)
+            .map(
+                |(transform, kind, _, lifetime, growing, wander)| SavedFood {
+                    position: transform.translation,
+                    kind: *kind,
+                    lifetime: lifetime.map(|lifetime| lifetime.remaining_secs()),
+                    growing: growing.map(|animation| animation.elapsed_secs()),
+                    wander: wander.map(|(wander, velocity)| SavedWander::new(wander, velocity)),
+                },
+            )
+            .collect(),
+        bonus: food_query
+            .iter()
+            .find_map(|(transform, _, bonus, _, growing, wander)| {
+                bonus.map(|bonus| SavedBonus {
+                    position: transform.translation,
+                    remaining: bonus.remaining_secs(),
+                    growing: growing.map(|animation| animation.elapsed_secs()),
+                    wander: wander.map(|(wander, velocity)| SavedWander::new(wander, velocity)),
+                })
+            }),
+        bonus_countdown: **countdown,
+        obstacles: obstacle_query
+            .iter()
+            .map(|(transform, hitbox)| (transform.translation, **hitbox))
+            .collect(),
+        crates: crate_query
+            .iter()
+            .map(|(transform, smashed)| (transform.translation, smashed.hits_remaining))
+            .collect(),
+        ice: ice_query
+            .iter()
+            .map(|(transform, hitbox)| (transform.translation, **hitbox))
+            .collect(),
+        // Each pair once, from the mouth that comes first
+        portals: portal_query
+            .iter()
+            .filter(|(portal, _, Portal { exit })| portal < exit)
+            .filter_map(|(_, transform, Portal { exit })| {
+                Some((transform.translation, portal_position(*exit)?))
+            })
+            .collect(),
+        hunter: hunter_query
+            .get_single()
+            .ok()
+            .map(|(transform, hunter)| SavedHunter {
+                position: transform.translation,
+                heading: hunter.heading,
+                retarget_elapsed: hunter.retarget.elapsed_secs(),
+                chasing: *hunter.chasing,
+            }),
+        hunter_countdown: **hunter_countdown,
+        queue: queue.iter().copied().collect(),
+        score: scoreboard.score,
+        lives: **lives,
+        multiplier: multiplier.is_active().then(|| SavedMultiplier {
+            factor: multiplier.factor,
+            remaining: multiplier.remaining_secs(),
+        }),
+        speed: **speed,
+        speed_modifier: modifier.is_active().then(|| SavedModifier {
+            factor: modifier.factor,
+            remaining: modifier.remaining_secs(),
+        }),
+        ghost: ghost.is_active().then(|| ghost.remaining_secs()),
+        magnet: magnet.is_active().then(|| SavedMagnet {
+            radius: magnet.radius,
+            remaining: magnet.remaining_secs(),
+        }),
+        hunger: hunger.value,
+        tick: **tick,
+        fixed_seed: rng.fixed_seed,
+        seed: rng.seed,
+        rng_word_pos: rng.rng.get_word_pos(),
+        replay: recorder.replay.clone(),
+    });
+    let _ = state.set(GameState::Menu);
+}
+
+// Replaces the fresh run `spawn_gameplay` set up with the saved one. The
+// save is used up, a run can only be continued once.
+pub fn restore_saved_game(
+    mut commands: Commands,
+    mut slot: ResMut<SavedGameSlot>,
+    config: Res<GameConfig>,
+    mut difficulty: ResMut<Difficulty>,
+    mut movement_mode: ResMut<MovementMode>,
+    mut wall_mode: ResMut<WallMode>,
+    (mut level, mut campaign, mut countdown, mut hunter_countdown): (
+        ResMut<CurrentLevel>,
+        ResMut<Campaign>,
+        ResMut<BonusCountdown>,
+        ResMut<HunterCountdown>,
+    ),
+    mut ramp: ResMut<SpeedRamp>,
+    (mut scoreboard, mut lives, mut multiplier): (
+        ResMut<Scoreboard>,
+        ResMut<Lives>,
+        ResMut<ScoreMultiplier>,
+    ),
+    (mut speed, mut modifier, mut ghost, mut magnet, mut hunger): (
+        ResMut<GameSpeed>,
+        ResMut<SpeedModifier>,
+        ResMut<GhostMode>,
+        ResMut<Magnet>,
+        ResMut<Hunger>,
+    ),
+    mut tick: ResMut<GameTick>,
+    mut rng: ResMut<GameRng>,
+    mut recorder: ResMut<ReplayRecorder>,
+) {
+    if !slot.resuming {
+        return;
+    }
+    slot.resuming = false;
+    let Some(saved) = slot.saved.take() else {
+        return;
+    };
+
+    *difficulty = saved.difficulty;
+    *movement_mode = saved.movement_mode;
+    *wall_mode = saved.wall_mode;
+    // The campaign knows its own layout, `--level` is only replaced by one
+    // saved outside of it
+    if saved.campaign.is_none() {
+        **level = saved.level.clone();
+    }
+    **campaign = saved.campaign;
+    *ramp = saved.difficulty.speed_ramp();
+    scoreboard.score = saved.score;
+    **lives = saved.lives;
+    if let Some(saved_multiplier) = saved.multiplier {
+        // Back into the window of a golden food, so the bar picks up where
+        // it was
+        multiplier.start(saved_multiplier.factor, GOLDEN_FOOD_SECONDS);
+        multiplier.remaining.set_elapsed(Duration::from_secs_f32(
+            GOLDEN_FOOD_SECONDS - saved_multiplier.remaining,
+        ));
+    }
+    *speed = GameSpeed(saved.speed);
+    if let Some(saved_modifier) = saved.speed_modifier {
+        modifier.slow_down(saved_modifier.factor, saved_modifier.remaining);
+    }
+    if let Some(seconds) = saved.ghost {
+        ghost.start(seconds);
+    }
+    if let Some(saved_magnet) = saved.magnet {
+        magnet.start(saved_magnet.radius, saved_magnet.remaining);
+    }
+    hunger.value = saved.hunger;
+    *tick = GameTick(saved.tick);
+    rng.fixed_seed = saved.fixed_seed;
+    rng.seed = saved.seed;
+    rng.rng = ChaCha12Rng::seed_from_u64(saved.seed);
+    rng.rng.set_word_pos(saved.rng_word_pos);
+    recorder.replay = saved.replay;
+
+    // Put down first, so the head can be told which one it is coming out of
+    let mut in_portal = None;
+    for positions in saved.portals {
+        let (first, second) = spawn_portals(&mut commands, &config, positions);
+        if saved.head.in_portal == Some(positions.0) {
+            in_portal = Some(first);
+        } else if saved.head.in_portal == Some(positions.1) {
+            in_portal = Some(second);
+        }
+    }
+
+    let segments: Vec<Entity> = saved
+        .segments
+        .into_iter()
+        .map(|position| spawn_segment(&mut commands, &config, Player::ONE, position))
+        .collect();
+    let head = spawn_snake(
+        &mut commands,
+        &config,
+        saved.movement_mode,
+        *speed,
+        Player::ONE,
+        saved.head.position,
+        saved.head.direction,
+    );
+    let mut head = commands.entity(head);
+    head.insert(NextDirection(saved.head.next_direction))
+        .insert(StepProgress(saved.head.step_progress))
+        .insert(Shield(saved.head.shield))
+        .insert(Hammer(saved.head.hammer))
+        .insert(saved.head.boost)
+        .insert(SegmentOrder(segments))
+        .insert(SnakeTrail(saved.trail.into_iter().collect()))
+        .insert(DirectionQueue(saved.queue.into_iter().collect()));
+    if let Some(velocity) = saved.head.velocity {
+        head.insert(Velocity(velocity));
+    }
+    if let Some(portal) = in_portal {
+        head.insert(InPortal(portal));
+    }
+
+    if let Some(level) = &saved.level {
+        level.spawn_walls(&mut commands, &config);
+    }
+    for (position, size) in saved.obstacles {
+        spawn_obstacle(&mut commands, position, size);
+    }
+    // The crates and ice of a level too, only the crates that weren't broken
+    // yet
+    for (position, hits_remaining) in saved.crates {
+        let entity = spawn_crate(&mut commands, &config, position);
+        commands.entity(entity).insert(Crate { hits_remaining });
+    }
+    for (position, size) in saved.ice {
+        spawn_ice_patch(&mut commands, position, size);
+    }
+    for saved_food in saved.food {
+        let food = spawn_food_of_kind(&mut commands, &config, saved_food.position, saved_food.kind);
+        if let Some(remaining) = saved_food.lifetime {
+            let mut lifetime = FoodLifetime::default();
+            let elapsed = lifetime.duration().as_secs_f32() - remaining;
+            lifetime.set_elapsed(Duration::from_secs_f32(elapsed));
+            commands.entity(food).insert(lifetime);
+        }
+        restore_food_motion(&mut commands, food, saved_food.growing, saved_food.wander);
+    }
+    if let Some(bonus) = saved.bonus {
+        let food = spawn_bonus_food(&mut commands, &config, bonus.position, bonus.remaining);
+        restore_food_motion(&mut commands, food, bonus.growing, bonus.wander);
+    }
+    **countdown = saved.bonus_countdown;
+    if let Some(saved_hunter) = saved.hunter {
+        let hunter = spawn_hunter(&mut commands, &config, saved_hunter.position);
+        let mut retarget = Timer::from_seconds(HUNTER_RETARGET_SECONDS, true);
+        retarget.set_elapsed(Duration::from_secs_f32(saved_hunter.retarget_elapsed));
+        commands.entity(hunter).insert(Hunter {
+            heading: saved_hunter.heading,
+            retarget,
+            chasing: Player(saved_hunter.chasing),
+            crashed: false,
+        });
+    }
+    **hunter_countdown = saved.hunter_countdown;
+}
+
+// Food that had finished popping up doesn't pop up again, and wandering food
+// keeps going the way it was, rather than rolling a new heading
+fn restore_food_motion(
+    commands: &mut Commands,
+    food: Entity,
+    growing: Option<f32>,
+    wander: Option<SavedWander>,
+) {
+    let mut food = commands.entity(food);
+    match growing {
+        Some(elapsed) => {
+            let mut animation = SpawnAnimation::default();
+            animation.set_elapsed(Duration::from_secs_f32(elapsed));
+            food.insert(animation);
+        }
+        None => {
+            food.remove::<SpawnAnimation>();
+        }
+    }
+    if let Some(wander) = wander {
+        let mut next_turn = Timer::from_seconds(wander.turn_seconds, false);
+        next_turn.set_elapsed(Duration::from_secs_f32(wander.turn_elapsed));
+        food.insert(Velocity(wander.velocity))
+            .insert(Wander { next_turn });
+    }
+}
@@ -0,0 +1,342 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{fs, num::NonZeroU32};
+
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::render::render_asset::RenderAssets;
+use bevy::render::render_resource::{
+    Buffer, BufferDescriptor, BufferUsages, CommandEncoderDescriptor, Extent3d, ImageCopyBuffer,
+    ImageDataLayout, MapMode, TextureFormat, TextureUsages,
+};
+use bevy::render::renderer::{RenderDevice, RenderQueue};
+use bevy::render::texture::BevyDefault;
+use bevy::render::{Extract, RenderApp, RenderStage};
+use bevy::tasks::IoTaskPool;
+use bevy::window::WindowId;
+use image::RgbaImage;
+
+use crate::locale::Locale;
+use crate::theme::TEXT_COLOR;
+
+// Relative to where the game was started from
+const SCREENSHOT_DIR: &str = "screenshots";
+// The camera draws into the capture image for this many frames before it is
+// copied, so the image is ready on the GPU and has a whole frame in it
+const SCREENSHOT_SETTLE_FRAMES: u32 = 2;
+const TOAST_SECONDS: f32 = 2.0;
+const TOAST_FONT_SIZE: f32 = 16.0;
+const TOAST_PADDING: Val = Val::Px(10.0);
+
+// F12 saves what is on screen, the HUD included, as a PNG in the
+// screenshots folder. Bevy can't read the window back itself, so the camera
+// draws into an image for a couple of frames instead, which is copied off the
+// GPU and written out on the IO task pool.
+pub struct ScreenshotPlugin;
+
+impl Plugin for ScreenshotPlugin {
+    fn build(&self, app: &mut App) {
+        let (sender, receiver) = mpsc::channel();
+        app.init_resource::<ScreenshotCapture>()
+            .insert_resource(ScreenshotReceiver(Mutex::new(receiver)))
+            .add_system(start_screenshot)
+            .add_system(finish_screenshot)
+            .add_system(hide_screenshot_toasts);
+
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .insert_resource(ScreenshotSender(sender))
+            .init_resource::<ScreenshotReadback>()
+            .add_system_to_stage(RenderStage::Extract, extract_screenshot)
+            .add_system_to_stage(RenderStage::Cleanup, read_back_screenshot);
+    }
+}
+
+// The image the camera draws into while a screenshot is being taken, and how
+// many frames it has drawn there
+#[derive(Default)]
+pub struct ScreenshotCapture {
+    pub image: Option<Handle<Image>>,
+    pub frames: u32,
+}
+
+// The pixels of a capture, `None` if it couldn't be read back
+pub struct ScreenshotReceiver(Mutex<Receiver<Option<RgbaImage>>>);
+
+struct ScreenshotSender(Sender<Option<RgbaImage>>);
+
+// Tells where the last screenshot went, until `timer` runs out
+#[derive(Component)]
+pub struct ScreenshotToast {
+    pub timer: Timer,
+}
+
+// The copy of the capture image on its way off the GPU
+struct PendingReadback {
+    buffer: Buffer,
+    // Whether the buffer could be mapped, once it has been
+    mapped: Arc<Mutex<Option<bool>>>,
+    size: Extent3d,
+    padded_row: usize,
+    format: TextureFormat,
+}
+
+#[derive(Default)]
+struct ScreenshotReadback {
+    requested: Option<Handle<Image>>,
+    pending: Option<PendingReadback>,
+}
+
+// Where a screenshot taken at `millis` since the epoch is saved
+pub fn screenshot_path(millis: u128) -> PathBuf {
+    Path::new(SCREENSHOT_DIR).join(format!("snake_{millis}.png"))
+}
+
+// The pixels of a copied texture as tightly packed RGBA rows. Copies off the
+// GPU pad every row out to `padded_row` bytes, and the window's format has
+// blue and red swapped on most platforms.
+pub fn unpad_rows(data: &[u8], width: u32, height: u32, padded_row: usize, bgra: bool) -> Vec<u8> {
+    let row = width as usize * 4;
+    let mut pixels = Vec::with_capacity(row * height as usize);
+    for padded in data.chunks(padded_row).take(height as usize) {
+        pixels.extend_from_slice(&padded[..row]);
+    }
+    if bgra {
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+    pixels
+}
+
+// Points the camera at a new image the size of the window, the first frame
+// F12 is pressed and no screenshot is being taken already
+pub fn start_screenshot(
+    keyboard_input: Res<Input<KeyCode>>,
+    windows: Res<Windows>,
+    mut capture: ResMut<ScreenshotCapture>,
+    mut images: ResMut<Assets<Image>>,
+    mut camera_query: Query<&mut Camera, With<Camera2d>>,
+) {
+    if capture.image.is_some() {
+        if capture.frames <= SCREENSHOT_SETTLE_FRAMES {
+            capture.frames += 1;
+        }
+        return;
+    }
+    if !keyboard_input.just_pressed(KeyCode::F12) {
+        return;
+    }
+    let Some(window) = windows.get_primary() else {
+        return;
+    };
+
+    let size = Extent3d {
+        width: window.physical_width().max(1),
+        height: window.physical_height().max(1),
+        depth_or_array_layers: 1,
+    };
+    let mut image = Image::default();
+    image.texture_descriptor.size = size;
+    image.texture_descriptor.format = TextureFormat::bevy_default();
+    image.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
+        | TextureUsages::COPY_DST
+        | TextureUsages::COPY_SRC
+        | TextureUsages::RENDER_ATTACHMENT;
+    image.resize(size);
+    let handle = images.add(image);
+
+    for mut camera in &mut camera_query {
+        camera.target = RenderTarget::Image(handle.clone());
+    }
+    *capture = ScreenshotCapture {
+        image: Some(handle),
+        frames: 0,
+    };
+}
+
+// Puts the camera back on the window once the capture has been read back,
+// and saves it without holding up the frame
+pub fn finish_screenshot(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    locale: Res<Locale>,
+    receiver: Res<ScreenshotReceiver>,
+    mut capture: ResMut<ScreenshotCapture>,
+    mut images: ResMut<Assets<Image>>,
+    mut camera_query: Query<&mut Camera, With<Camera2d>>,
+    toast_query: Query<Entity, With<ScreenshotToast>>,
+) {
+    let Ok(pixels) = receiver.0.lock().unwrap().try_recv() else {
+        return;
+    };
+    for mut camera in &mut camera_query {
+        camera.target = RenderTarget::Window(WindowId::primary());
+    }
+    if let Some(image) = capture.image.take() {
+        images.remove(image);
+    }
+    let Some(pixels) = pixels else {
+        error!("could not read the screenshot back");
+        return;
+    };
+
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or_default();
+    let path = screenshot_path(millis);
+    let task_path = path.clone();
+    IoTaskPool::get()
+        .spawn(async move {
+            let result = task_path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .map_err(|err| err.to_string())
+                .and_then(|_| pixels.save(&task_path).map_err(|err| err.to_string()));
+            if let Err(err) = result {
+                error!("could not save {}: {err}", task_path.display());
+            }
+        })
+        .detach();
+
+    // Only the latest screenshot's path is shown
+    for toast in &toast_query {
+        commands.entity(toast).despawn_recursive();
+    }
+    commands
+        .spawn_bundle(
+            TextBundle::from_section(
+                locale.text("screenshot.saved", &[("path", &path.display())]),
+                TextStyle {
+                    font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                    font_size: TOAST_FONT_SIZE,
+                    color: TEXT_COLOR,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: TOAST_PADDING,
+                    bottom: TOAST_PADDING,
+                    ..default()
+                },
+                ..default()
+            }),
+        )
+        .insert(ScreenshotToast {
+            timer: Timer::from_seconds(TOAST_SECONDS, false),
+        });
+}
+
+// Runs in real time, so the toast goes away over the pause too
+pub fn hide_screenshot_toasts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut ScreenshotToast)>,
+) {
+    for (entity, mut toast) in &mut query {
+        if toast.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+// Asks for the capture image to be copied once it has settled, exactly once
+fn extract_screenshot(
+    capture: Extract<Res<ScreenshotCapture>>,
+    mut readback: ResMut<ScreenshotReadback>,
+) {
+    if capture.frames == SCREENSHOT_SETTLE_FRAMES {
+        readback.requested = capture.image.clone();
+    }
+}
+
+// Copies the capture image into a buffer after the frame has been drawn, and
+// sends its pixels over on a later frame, once the buffer can be read
+fn read_back_screenshot(
+    mut readback: ResMut<ScreenshotReadback>,
+    gpu_images: Res<RenderAssets<Image>>,
+    device: Res<RenderDevice>,
+    queue: Res<RenderQueue>,
+    sender: Res<ScreenshotSender>,
+) {
+    if let Some(handle) = readback.requested.take() {
+        let Some(gpu_image) = gpu_images.get(&handle) else {
+            let _ = sender.0.send(None);
+            return;
+        };
+        let size = Extent3d {
+            width: gpu_image.size.x as u32,
+            height: gpu_image.size.y as u32,
+            depth_or_array_layers: 1,
+        };
+        let padded_row = RenderDevice::align_copy_bytes_per_row(size.width as usize * 4);
+        let buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("screenshot_buffer"),
+            size: (padded_row * size.height as usize) as u64,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("screenshot_encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            gpu_image.texture.as_image_copy(),
+            ImageCopyBuffer {
+                buffer: &buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(padded_row as u32),
+                    rows_per_image: None,
+                },
+            },
+            size,
+        );
+        queue.submit([encoder.finish()]);
+
+        let mapped = Arc::new(Mutex::new(None));
+        let callback_mapped = mapped.clone();
+        device.map_buffer(&buffer.slice(..), MapMode::Read, move |result| {
+            *callback_mapped.lock().unwrap() = Some(result.is_ok());
+        });
+        readback.pending = Some(PendingReadback {
+            buffer,
+            mapped,
+            size,
+            padded_row,
+            format: gpu_image.texture_format,
+        });
+        return;
+    }
+
+    let Some(pending) = &readback.pending else {
+        return;
+    };
+    let Some(mapped) = pending.mapped.lock().unwrap().take() else {
+        return;
+    };
+    let pending = readback.pending.take().unwrap();
+    let pixels = mapped
+        .then(|| {
+            let bgra = matches!(
+                pending.format,
+                TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb
+            );
+            let data = unpad_rows(
+                &pending.buffer.slice(..).get_mapped_range(),
+                pending.size.width,
+                pending.size.height,
+                pending.padded_row,
+                bgra,
+            );
+            pending.buffer.unmap();
+            RgbaImage::from_raw(pending.size.width, pending.size.height, data)
+        })
+        .flatten();
+    let _ = sender.0.send(pixels);
+}
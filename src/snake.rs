@@ -0,0 +1,790 @@
+use std::{collections::VecDeque, time::Duration};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::collision::{check_for_collisions, score_food};
+use crate::config::GameConfig;
+use crate::game::{count_tick, FixedUpdate};
+use crate::ice::Sliding;
+use crate::walls::{grid_bounds, ArenaBounds, WallMode};
+
+pub const SNAKE_STARTING_POSITION: Vec3 = Vec3::new(0.0, 0.0, 0.0);
+pub const INITIAL_SNAKE_DIRECTION: Vec2 = Vec2::new(-0.5, 0.0);
+// Longer than the head ever moves in a tick, shorter than the arena
+pub const TRAIL_WRAP_DISTANCE: f32 = 200.0;
+// How many turns can be typed ahead of the snake
+const INPUT_QUEUE_CAPACITY: usize = 3;
+// How long a snake that lost a life blinks in place before it moves on
+pub const RESPAWN_SECONDS: f32 = 1.5;
+// How long the head of a snake out of lives flashes, and how long its body
+// then takes to go, before the game over screen
+pub const DEATH_FLASH_SECONDS: f32 = 0.3;
+pub const DEATH_SEGMENTS_SECONDS: f32 = 0.7;
+// How much faster a boosting snake goes
+pub const BOOST_FACTOR: f32 = 1.8;
+// How long a full meter boosts for, and how long an empty one takes to fill
+// back up without boosting
+pub const BOOST_SECONDS: f32 = 2.0;
+pub const BOOST_REFILL_SECONDS: f32 = 8.0;
+// How full the meter has to be for a boost to start
+pub const BOOST_MIN_STAMINA: f32 = 0.25;
+
+// The snake's head and body, and how they move
+pub struct SnakePlugin;
+
+impl Plugin for SnakePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpeedRamp>()
+            .init_resource::<GameSpeed>()
+            .init_resource::<SpeedModifier>()
+            .init_resource::<GhostMode>()
+            .init_resource::<MovementMode>()
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(FixedUpdate)
+                    .with_system(count_down_respawn.after(count_tick).before(move_snake))
+                    .with_system(drain_boost.after(count_tick).before(move_snake))
+                    .with_system(move_snake.after(count_tick).before(check_for_collisions))
+                    .with_system(step_snake.after(move_snake))
+                    .with_system(decay_speed_modifier.after(score_food))
+                    .with_system(
+                        apply_velocity
+                            .after(move_snake)
+                            .before(check_for_collisions),
+                    )
+                    .with_system(
+                        wrap_through_walls
+                            .after(move_snake)
+                            .after(step_snake)
+                            .after(apply_velocity),
+                    )
+                    .with_system(decay_ghost_mode.after(wrap_through_walls))
+                    .with_system(
+                        move_segments
+                            .after(step_snake)
+                            .after(apply_velocity)
+                            .after(decay_ghost_mode)
+                            .before(check_for_collisions),
+                    ),
+            );
+    }
+}
+
+#[derive(Component)]
+pub struct Snake;
+
+// Which player steers a snake, put on its head and its body segments.
+// Player one is the only one outside of versus.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deref)]
+pub struct Player(pub u8);
+
+impl Player {
+    pub const ONE: Player = Player(0);
+    pub const TWO: Player = Player(1);
+}
+
+// How many fatal collisions the snake still gets away with. Each one turns
+// the snake around instead, see `use_shield`.
+#[derive(Component, Default, Deref, DerefMut)]
+pub struct Shield(pub usize);
+
+// How far the head has really moved since `record_statistics` last read
+// it, added up by the systems moving it
+#[derive(Component, Default, Deref, DerefMut)]
+pub struct Odometer(pub f32);
+
+// The turbo the player holds Shift for. `stamina` runs from 0 for an empty
+// meter to 1 for a full one. `held` is the key, `active` whether the snake
+// really is going faster.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Boost {
+    pub stamina: f32,
+    pub active: bool,
+    pub held: bool,
+}
+
+impl Boost {
+    // By how much the boost speeds the snake up, on top of the `GameSpeed` and
+    // the `SpeedModifier`
+    pub fn factor(&self) -> f32 {
+        if self.active {
+            BOOST_FACTOR
+        } else {
+            1.0
+        }
+    }
+}
+
+impl Default for Boost {
+    fn default() -> Self {
+        Boost {
+            stamina: 1.0,
+            active: false,
+            held: false,
+        }
+    }
+}
+
+// Put on a head that just lost a life. It holds still and blinks until the
+// timer runs out, and can't lose another life meanwhile.
+#[derive(Component, Deref, DerefMut)]
+pub struct Respawning(pub Timer);
+
+impl Default for Respawning {
+    fn default() -> Self {
+        Respawning(Timer::from_seconds(RESPAWN_SECONDS, false))
+    }
+}
+
+// Put on the head of a snake out of lives. The head flashes, then the body
+// goes one segment at a time from the tail, and once every dying snake is
+// done the run is over. Nothing moves or collides meanwhile.
+#[derive(Component)]
+pub struct Dying {
+    pub timer: Timer,
+    // The segments it died with
+    pub segments: usize,
+    pub segments_remaining: usize,
+}
+
+impl Dying {
+    pub fn new(segments: usize) -> Dying {
+        Dying {
+            timer: Timer::from_seconds(DEATH_FLASH_SECONDS + DEATH_SEGMENTS_SECONDS, false),
+            segments,
+            segments_remaining: segments,
+        }
+    }
+
+    pub fn flashing(&self) -> bool {
+        self.timer.elapsed_secs() < DEATH_FLASH_SECONDS
+    }
+
+    // How many segments should be left by now
+    pub fn segments_due(&self) -> usize {
+        let gone = ((self.timer.elapsed_secs() - DEATH_FLASH_SECONDS) / DEATH_SEGMENTS_SECONDS)
+            .clamp(0.0, 1.0);
+        (self.segments as f32 * (1.0 - gone)).ceil() as usize
+    }
+
+    // Jumps to the end, for a player who doesn't want to watch it
+    pub fn skip(&mut self) {
+        let duration = self.timer.duration();
+        self.timer.set_elapsed(duration);
+    }
+}
+
+// A piece of the snake's body, trailing behind the head
+#[derive(Component)]
+pub struct SnakeSegment;
+
+// Body segments ordered from the one right behind the head to the tail,
+// kept on the head
+#[derive(Component, Default, Deref, DerefMut)]
+pub struct SegmentOrder(pub Vec<Entity>);
+
+// Positions the head went through, most recent first
+#[derive(Component, Default, Deref, DerefMut)]
+pub struct SnakeTrail(pub VecDeque<Vec3>);
+
+// How the snake speeds up as it eats
+#[derive(Clone, Copy)]
+pub struct SpeedRamp {
+    // Added to the speed multiplier every `foods_per_step` foods
+    pub increment: f32,
+    pub foods_per_step: usize,
+    // The fastest the snake can get, as a multiple of its base speed
+    pub max_multiplier: f32,
+}
+
+impl Default for SpeedRamp {
+    fn default() -> Self {
+        SpeedRamp {
+            increment: 0.1,
+            foods_per_step: 1,
+            max_multiplier: 2.5,
+        }
+    }
+}
+
+// How fast the snake moves in the current game, as a multiple of its base
+// speed (`GameConfig::snake_speed` or `GameConfig::grid_steps_per_second`)
+#[derive(Clone, Copy, Deref, DerefMut)]
+pub struct GameSpeed(pub f32);
+
+impl Default for GameSpeed {
+    fn default() -> Self {
+        GameSpeed(1.0)
+    }
+}
+
+impl GameSpeed {
+    // Called with the new score whenever a food is eaten
+    pub fn ramp_up(&mut self, score: usize, ramp: &SpeedRamp) {
+        if ramp.foods_per_step > 0 && score.is_multiple_of(ramp.foods_per_step) {
+            **self = (**self + ramp.increment).min(ramp.max_multiplier);
+        }
+    }
+}
+
+// Scales the `GameSpeed` by `factor` until `remaining` runs out, without
+// touching the ramp underneath. Set by the slow motion pickup.
+pub struct SpeedModifier {
+    pub factor: f32,
+    pub remaining: Timer,
+}
+
+impl SpeedModifier {
+    // Another pickup while one is still going adds its time to what is left
+    pub fn slow_down(&mut self, factor: f32, seconds: f32) {
+        let left = if self.is_active() {
+            self.remaining_secs()
+        } else {
+            0.0
+        };
+        self.factor = factor;
+        self.remaining = Timer::from_seconds(left + seconds, false);
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.factor != 1.0
+    }
+
+    pub fn remaining_secs(&self) -> f32 {
+        self.remaining.duration().as_secs_f32() - self.remaining.elapsed_secs()
+    }
+}
+
+impl Default for SpeedModifier {
+    fn default() -> Self {
+        SpeedModifier {
+            factor: 1.0,
+            remaining: Timer::default(),
+        }
+    }
+}
+
+// While `remaining` runs, the snake passes through the outer walls and comes
+// out on the other side. Set by the ghost pickup; a second one starts the
+// time over.
+#[derive(Default)]
+pub struct GhostMode {
+    pub remaining: Option<Timer>,
+}
+
+impl GhostMode {
+    pub fn start(&mut self, seconds: f32) {
+        self.remaining = Some(Timer::from_seconds(seconds, false));
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.remaining.is_some()
+    }
+
+    pub fn remaining_secs(&self) -> f32 {
+        self.remaining.as_ref().map_or(0.0, |timer| {
+            timer.duration().as_secs_f32() - timer.elapsed_secs()
+        })
+    }
+}
+
+// Ends the slow motion once its time is up
+pub fn decay_speed_modifier(mut modifier: ResMut<SpeedModifier>, config: Res<GameConfig>) {
+    if !modifier.is_active() {
+        return;
+    }
+    modifier
+        .remaining
+        .tick(Duration::from_secs_f32(config.time_step));
+    if modifier.remaining.finished() {
+        *modifier = SpeedModifier::default();
+    }
+}
+
+#[derive(Component, Deref, DerefMut)]
+pub struct Velocity(pub Vec2);
+
+// Which way the snake's head is heading
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    // The cardinal direction closest to `vector`
+    pub fn from_vec2(vector: Vec2) -> Direction {
+        if vector.x.abs() >= vector.y.abs() {
+            if vector.x < 0.0 {
+                Direction::Left
+            } else {
+                Direction::Right
+            }
+        } else if vector.y < 0.0 {
+            Direction::Down
+        } else {
+            Direction::Up
+        }
+    }
+
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+
+    // How many cells one step in this direction moves along each axis
+    pub fn cell_offset(self) -> IVec2 {
+        match self {
+            Direction::Up => IVec2::new(0, 1),
+            Direction::Down => IVec2::new(0, -1),
+            Direction::Left => IVec2::new(-1, 0),
+            Direction::Right => IVec2::new(1, 0),
+        }
+    }
+}
+
+// How far the head has come towards its next grid cell, in cells
+#[derive(Component, Default, Deref, DerefMut)]
+pub struct StepProgress(pub f32);
+
+// Where the head was before its last grid step, for the render side to slide
+// it over from. Where it is when it didn't move, or was put somewhere else.
+#[derive(Component, Default, Deref, DerefMut)]
+pub struct StepOrigin(pub Vec3);
+
+// The direction the head will take on its next grid step. It is kept apart
+// from `Direction` so several turns within one step can't add up to a
+// reversal.
+#[derive(Component, Deref, DerefMut)]
+pub struct NextDirection(pub Direction);
+
+// Turns typed by the player that the snake hasn't taken yet, oldest first
+#[derive(Component, Default, Deref, DerefMut)]
+pub struct DirectionQueue(pub VecDeque<Direction>);
+
+impl DirectionQueue {
+    // Queues `turn` unless it wouldn't change anything, would head straight
+    // back into the body or the queue is full. Each turn is checked against
+    // the one that will be taken right before it, which is `next_direction`
+    // when nothing is queued yet.
+    pub fn push_turn(&mut self, turn: Direction, next_direction: Direction, has_body: bool) {
+        if self.len() >= INPUT_QUEUE_CAPACITY {
+            return;
+        }
+
+        let previous = self.back().copied().unwrap_or(next_direction);
+        let reverses = has_body && turn == previous.opposite();
+        if turn != previous && !reverses {
+            self.push_back(turn);
+        }
+    }
+}
+
+// The directions whose keys are held down right now, in the order they were
+// pressed. Free movement heads the snake towards the last of them.
+#[derive(Component, Default, Deref, DerefMut)]
+pub struct HeldDirections(pub Vec<Direction>);
+
+// How the snake moves around the arena
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MovementMode {
+    // The head steps from cell to cell of a `GameConfig::snake_size` grid
+    #[default]
+    Grid,
+    // The head glides freely with its `Velocity`
+    Continuous,
+}
+
+impl MovementMode {
+    // Passing `--continuous` on the command line brings back free movement
+    pub fn from_args() -> MovementMode {
+        if std::env::args().any(|arg| arg == "--continuous") {
+            MovementMode::Continuous
+        } else {
+            MovementMode::Grid
+        }
+    }
+}
+
+// The grid cell a position falls into
+pub fn grid_cell(config: &GameConfig, position: Vec3) -> IVec2 {
+    (position.truncate() / config.snake_size).round().as_ivec2()
+}
+
+// The center of a grid cell
+pub fn cell_center(config: &GameConfig, cell: IVec2) -> Vec3 {
+    (cell.as_vec2() * config.snake_size).extend(0.0)
+}
+
+// Steers every snake with its own queued turns and held directions. Free
+// movement only points the `Velocity`, `apply_velocity` does the moving. A
+// snake `Sliding` on ice goes straight on, its turns wait in the queue.
+pub fn move_snake(
+    mut query: Query<
+        (
+            Option<&mut Velocity>,
+            &mut Direction,
+            &mut NextDirection,
+            &SegmentOrder,
+            &mut DirectionQueue,
+            &HeldDirections,
+            &Boost,
+            &Sliding,
+        ),
+        (With<Snake>, Without<Respawning>),
+    >,
+    config: Res<GameConfig>,
+    movement_mode: Res<MovementMode>,
+    speed: Res<GameSpeed>,
+    modifier: Res<SpeedModifier>,
+) {
+    for (
+        snake_velocity,
+        mut direction,
+        mut next_direction,
+        segments,
+        mut queue,
+        held,
+        boost,
+        sliding,
+    ) in &mut query
+    {
+        // Once the snake has a body, turning straight back would mean biting it.
+        // A lone head may reverse freely.
+        let heading = *direction;
+        let can_turn = |turn: Direction| segments.is_empty() || turn != heading.opposite();
+
+        // On the grid the keys only steer, `step_snake` does the moving.
+        // A queued turn is only taken once the previous one has been stepped.
+        if *movement_mode == MovementMode::Grid {
+            if **next_direction == heading && !**sliding {
+                if let Some(turn) = queue.pop_front() {
+                    if can_turn(turn) {
+                        **next_direction = turn;
+                    }
+                }
+            }
+            continue;
+        }
+        let Some(mut snake_velocity) = snake_velocity else {
+            continue;
+        };
+        // A key just pressed turns the snake its way, then the held key
+        // pressed last keeps it heading there
+        let steered = if **sliding {
+            None
+        } else {
+            let turn = queue.pop_front().filter(|turn| can_turn(*turn));
+            turn.or_else(|| held.iter().rev().copied().find(|held| can_turn(*held)))
+        };
+        if let Some(steered) = steered {
+            **snake_velocity = steered.cell_offset().as_vec2();
+        }
+        let snake_speed = config.snake_speed * **speed * modifier.factor * boost.factor();
+        **snake_velocity = snake_velocity.normalize_or_zero() * snake_speed;
+
+        if **snake_velocity != Vec2::ZERO {
+            *direction = Direction::from_vec2(**snake_velocity);
+            **next_direction = *direction;
+        }
+    }
+}
+
+// Moves the head exactly one cell in its current direction, as often as the
+// current speed allows
+pub fn step_snake(
+    mut query: Query<
+        (
+            &mut Direction,
+            &NextDirection,
+            &mut StepProgress,
+            &mut Transform,
+            &mut Odometer,
+            &mut StepOrigin,
+            &Boost,
+        ),
+        (With<Snake>, Without<Respawning>),
+    >,
+    config: Res<GameConfig>,
+    bounds: Res<ArenaBounds>,
+    wall_mode: Res<WallMode>,
+    movement_mode: Res<MovementMode>,
+    speed: Res<GameSpeed>,
+    modifier: Res<SpeedModifier>,
+    ghost: Res<GhostMode>,
+) {
+    if *movement_mode != MovementMode::Grid {
+        return;
+    }
+
+    for (
+        mut direction,
+        next_direction,
+        mut progress,
+        mut transform,
+        mut odometer,
+        mut origin,
+        boost,
+    ) in &mut query
+    {
+        // At most one cell per tick, so the trail the body follows has no gaps
+        **progress += step_per_tick(&config, *speed, &modifier, boost);
+        if **progress < 1.0 {
+            continue;
+        }
+        **progress = (**progress - 1.0).min(1.0);
+
+        *direction = **next_direction;
+        let mut cell = grid_cell(&config, transform.translation) + direction.cell_offset();
+        if *wall_mode != WallMode::Lethal && !ghost.is_active() {
+            let (min, max) = grid_bounds(&config, &bounds);
+            cell = cell.clamp(min, max);
+        }
+        let before = transform.translation;
+        transform.translation = cell_center(&config, cell);
+        **odometer += before.distance(transform.translation);
+        **origin = before;
+    }
+}
+
+// How far the head comes towards its next cell every tick, in cells
+pub fn step_per_tick(
+    config: &GameConfig,
+    speed: GameSpeed,
+    modifier: &SpeedModifier,
+    boost: &Boost,
+) -> f32 {
+    config.grid_steps_per_second * *speed * modifier.factor * boost.factor() * config.time_step
+}
+
+// Boosts while the key is held and the meter lasts, and fills the meter
+// back up while not boosting. Once the meter runs dry the boost stops until
+// it has filled up to `BOOST_MIN_STAMINA` again.
+pub fn drain_boost(mut query: Query<&mut Boost, With<Snake>>, config: Res<GameConfig>) {
+    for mut boost in &mut query {
+        let needed = if boost.active { 0.0 } else { BOOST_MIN_STAMINA };
+        let active = boost.held && boost.stamina > needed;
+        let stamina = if active {
+            (boost.stamina - config.time_step / BOOST_SECONDS).max(0.0)
+        } else {
+            (boost.stamina + config.time_step / BOOST_REFILL_SECONDS).min(1.0)
+        };
+        let drained = Boost {
+            stamina,
+            active: active && stamina > 0.0,
+            held: boost.held,
+        };
+        if *boost != drained {
+            *boost = drained;
+        }
+    }
+}
+
+// Takes a ghost that left the arena back in on the opposite side. On the grid
+// it comes out on the first cell of the other end. Moving freely it wraps
+// once its center is past the middle of a wall, so one wall is left behind
+// as the other is entered.
+pub fn wrap_through_walls(
+    mut query: Query<&mut Transform, With<Snake>>,
+    config: Res<GameConfig>,
+    bounds: Res<ArenaBounds>,
+    movement_mode: Res<MovementMode>,
+    ghost: Res<GhostMode>,
+) {
+    if !ghost.is_active() {
+        return;
+    }
+
+    for mut transform in &mut query {
+        if *movement_mode == MovementMode::Grid {
+            let (min, max) = grid_bounds(&config, &bounds);
+            let size = max - min + IVec2::ONE;
+            let cell = grid_cell(&config, transform.translation);
+            let offset = cell - min;
+            let wrapped =
+                min + IVec2::new(offset.x.rem_euclid(size.x), offset.y.rem_euclid(size.y));
+            if wrapped != cell {
+                transform.translation = cell_center(&config, wrapped);
+            }
+            continue;
+        }
+
+        let position = &mut transform.translation;
+        if position.x < bounds.left {
+            position.x += bounds.width();
+        } else if position.x > bounds.right {
+            position.x -= bounds.width();
+        }
+        if position.y < bounds.bottom {
+            position.y += bounds.height();
+        } else if position.y > bounds.top {
+            position.y -= bounds.height();
+        }
+    }
+}
+
+// Lets the snake go again once it has blinked for `RESPAWN_SECONDS`
+pub fn count_down_respawn(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Respawning)>,
+    config: Res<GameConfig>,
+) {
+    for (snake, mut respawning) in &mut query {
+        respawning.tick(Duration::from_secs_f32(config.time_step));
+        if respawning.finished() {
+            commands.entity(snake).remove::<Respawning>();
+        }
+    }
+}
+
+// Counts the ghost down. A ghost running out halfway through a wall
+// is pushed back inside rather than left to die on it.
+pub fn decay_ghost_mode(
+    mut ghost: ResMut<GhostMode>,
+    mut query: Query<&mut Transform, With<Snake>>,
+    config: Res<GameConfig>,
+    bounds: Res<ArenaBounds>,
+) {
+    let Some(timer) = ghost.remaining.as_mut() else {
+        return;
+    };
+    timer.tick(Duration::from_secs_f32(config.time_step));
+    if !timer.finished() {
+        return;
+    }
+    ghost.remaining = None;
+
+    let (min, max) = bounds.inside(config.snake_size);
+    for mut transform in &mut query {
+        let inside = transform.translation.truncate().clamp(min, max);
+        transform.translation = inside.extend(transform.translation.z);
+    }
+}
+
+// Lays the body segments out along the path the head has travelled,
+// one snake width apart
+pub fn move_segments(
+    mut head_query: Query<
+        (&Transform, &SegmentOrder, &mut SnakeTrail),
+        (With<Snake>, Without<SnakeSegment>),
+    >,
+    mut segment_query: Query<&mut Transform, With<SnakeSegment>>,
+    config: Res<GameConfig>,
+) {
+    for (head_transform, segments, mut trail) in &mut head_query {
+        let head_position = head_transform.translation;
+        if trail.front() != Some(&head_position) {
+            trail.push_front(head_position);
+        }
+
+        for (index, segment) in segments.iter().enumerate() {
+            if let Ok(mut transform) = segment_query.get_mut(*segment) {
+                transform.translation =
+                    point_along_trail(&trail, (index + 1) as f32 * config.snake_size.x);
+            }
+        }
+
+        // Forget the part of the trail that is further back than the tail
+        let needed = (segments.len() + 1) as f32 * config.snake_size.x;
+        let mut travelled = 0.0;
+        for index in 1..trail.len() {
+            travelled += trail_step(trail[index - 1], trail[index]);
+            if travelled > needed {
+                trail.truncate(index + 1);
+                break;
+            }
+        }
+    }
+}
+
+// Walks `distance` back along the trail from the head, stopping at the
+// oldest known position if the snake hasn't travelled that far yet
+pub fn point_along_trail(trail: &VecDeque<Vec3>, distance: f32) -> Vec3 {
+    let mut remaining = distance;
+    for (newer, older) in trail.iter().zip(trail.iter().skip(1)) {
+        let step = trail_step(*newer, *older);
+        if step >= remaining && step > 0.0 {
+            return newer.lerp(*older, remaining / step);
+        }
+        remaining -= step;
+    }
+    trail.back().copied().unwrap_or_default()
+}
+
+// Turns the trail around `distance` behind the head, cutting off whatever is
+// further back, so the end of the tail becomes the front. Returns the new
+// front, or `None` if there is no trail yet.
+pub fn reverse_trail(trail: &mut VecDeque<Vec3>, distance: f32) -> Option<Vec3> {
+    let mut kept = Vec::new();
+    let mut remaining = distance;
+    for (index, newer) in trail.iter().enumerate() {
+        kept.push(*newer);
+        let Some(older) = trail.get(index + 1) else {
+            break;
+        };
+        let step = trail_step(*newer, *older);
+        if step >= remaining && step > 0.0 {
+            kept.push(newer.lerp(*older, remaining / step));
+            break;
+        }
+        remaining -= step;
+    }
+    *trail = kept.into_iter().rev().collect();
+    trail.front().copied()
+}
+
+// How far the head went from `older` to `newer`. A step longer than
+// `TRAIL_WRAP_DISTANCE` is a ghost wrapping around the arena, which covers no
+// distance at all, so the body follows through the wall instead of across
+// the arena.
+fn trail_step(newer: Vec3, older: Vec3) -> f32 {
+    let step = newer.distance(older);
+    if step > TRAIL_WRAP_DISTANCE {
+        0.0
+    } else {
+        step
+    }
+}
+
+// Moves everything with a `Velocity` by it. On casual walls a head slides
+// along them instead of going in, unless it is a ghost.
+pub fn apply_velocity(
+    mut query: Query<
+        (
+            &mut Transform,
+            &Velocity,
+            Option<&mut Odometer>,
+            Option<&Snake>,
+        ),
+        Without<Respawning>,
+    >,
+    config: Res<GameConfig>,
+    bounds: Res<ArenaBounds>,
+    wall_mode: Res<WallMode>,
+    ghost: Res<GhostMode>,
+) {
+    for (mut transform, velocity, odometer, head) in &mut query {
+        let before = transform.translation;
+        transform.translation.x += velocity.x * config.time_step;
+        transform.translation.y += velocity.y * config.time_step;
+
+        // Running into any other wall is handled by `check_for_collisions`
+        // and `bounce_off_walls`, a ghost is wrapped around by
+        // `wrap_through_walls`
+        if head.is_some() && *wall_mode == WallMode::Casual && !ghost.is_active() {
+            let (min, max) = bounds.inside(config.snake_size);
+            let clamped = transform.translation.truncate().clamp(min, max);
+            transform.translation = clamped.extend(transform.translation.z);
+        }
+        if let Some(mut odometer) = odometer {
+            **odometer += before.distance(transform.translation);
+        }
+    }
+}
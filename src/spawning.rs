@@ -0,0 +1,213 @@
+use bevy::{prelude::*, sprite::Rect};
+
+use crate::collision::{Collider, Hitbox};
+use crate::config::GameConfig;
+use crate::crates::{Crate, Hammer, CRATE_HITS};
+use crate::food::{
+    BonusFood, Food, FoodKind, FoodLifetime, SpawnAnimation, Wander, BONUS_FOOD_SCALE,
+};
+use crate::hunter::{Hunter, HUNTER_RETARGET_SECONDS};
+use crate::ice::{IcePatch, Sliding, ICE_Z};
+use crate::portal::{Portal, PORTAL_Z};
+use crate::snake::{
+    Boost, Direction, DirectionQueue, GameSpeed, HeldDirections, MovementMode, NextDirection,
+    Odometer, Player, SegmentOrder, Shield, Snake, SnakeSegment, SnakeTrail, StepOrigin,
+    StepProgress, Velocity,
+};
+use crate::walls::{InteriorWall, Obstacle, WallBundle};
+
+// The one place the pieces of a run are built: the snakes, the food, the
+// hunter and the obstacles, crates, ice, portals and walls put down in the
+// arena, for a fresh run, a respawn, a level or a restored save alike. Only
+// the outer walls come with the arena itself, see `spawn_walls`. Sizes come
+// from the `GameConfig`. Nothing here is drawn: the render side gives every
+// entity its sprite, colored after the `Theme`, once it is spawned.
+
+// Spawns `player`'s head at `position`, heading towards `heading`. A lone
+// snake starts in the middle of the arena heading along
+// `INITIAL_SNAKE_DIRECTION`, unless a level says otherwise.
+pub fn spawn_snake(
+    commands: &mut Commands,
+    config: &GameConfig,
+    movement_mode: MovementMode,
+    speed: GameSpeed,
+    player: Player,
+    position: Vec3,
+    heading: Direction,
+) -> Entity {
+    let mut snake = commands.spawn();
+    snake
+        .insert(Snake)
+        .insert(player)
+        .insert(heading)
+        .insert(NextDirection(heading))
+        .insert(StepProgress::default())
+        .insert(StepOrigin(position))
+        .insert(Shield::default())
+        .insert(SegmentOrder::default())
+        .insert(SnakeTrail::default())
+        .insert(DirectionQueue::default())
+        .insert(HeldDirections::default())
+        .insert(Boost::default())
+        .insert(Hammer::default())
+        .insert(Sliding::default())
+        .insert(Odometer::default())
+        .insert(Hitbox(config.snake_size))
+        .insert_bundle(TransformBundle::from_transform(
+            Transform::from_translation(position),
+        ));
+    // Grid movement doesn't need a velocity, the head is stepped directly
+    if movement_mode == MovementMode::Continuous {
+        snake.insert(Velocity(
+            heading.cell_offset().as_vec2() * config.snake_speed * *speed,
+        ));
+    }
+    snake.id()
+}
+
+// Spawns a body segment of `player`'s snake at `position`, the caller adds it
+// to the head's `SegmentOrder`
+pub fn spawn_segment(
+    commands: &mut Commands,
+    config: &GameConfig,
+    player: Player,
+    position: Vec3,
+) -> Entity {
+    commands
+        .spawn()
+        .insert(SnakeSegment)
+        .insert(player)
+        .insert(Hitbox(config.snake_size))
+        .insert_bundle(TransformBundle::from_transform(
+            Transform::from_translation(position),
+        ))
+        .id()
+}
+
+// Normal food at `position`
+pub fn spawn_food(commands: &mut Commands, config: &GameConfig, position: Vec3) -> Entity {
+    spawn_food_of_kind(commands, config, position, FoodKind::Normal)
+}
+
+pub fn spawn_food_of_kind(
+    commands: &mut Commands,
+    config: &GameConfig,
+    position: Vec3,
+    kind: FoodKind,
+) -> Entity {
+    let mut food = commands.spawn();
+    food.insert(Food)
+        .insert(kind)
+        .insert_bundle(TransformBundle::from_transform(
+            Transform::from_translation(position),
+        ))
+        .insert(Collider)
+        .insert(Hitbox(config.food_size))
+        .insert(SpawnAnimation::default());
+    if config.wandering_food {
+        food.insert(Velocity(Vec2::ZERO)).insert(Wander::default());
+    }
+    if config.expiring_food && kind == FoodKind::Normal {
+        food.insert(FoodLifetime::default());
+    }
+    food.id()
+}
+
+// Spawns a bonus food that is eaten for `seconds` more
+pub fn spawn_bonus_food(
+    commands: &mut Commands,
+    config: &GameConfig,
+    position: Vec3,
+    seconds: f32,
+) -> Entity {
+    let bonus = spawn_food_of_kind(commands, config, position, FoodKind::Bonus);
+    commands
+        .entity(bonus)
+        .insert(Hitbox(config.food_size * BONUS_FOOD_SCALE))
+        .insert(BonusFood {
+            timer: Timer::from_seconds(seconds, false),
+        });
+    bonus
+}
+
+pub fn spawn_obstacle(commands: &mut Commands, position: Vec3, size: Vec2) -> Entity {
+    commands
+        .spawn()
+        .insert(Obstacle)
+        .insert_bundle(TransformBundle::from_transform(
+            Transform::from_translation(position),
+        ))
+        .insert(Collider)
+        .insert(Hitbox(size))
+        .id()
+}
+
+// A crate a cell across at `position`, with all of its hits left
+pub fn spawn_crate(commands: &mut Commands, config: &GameConfig, position: Vec3) -> Entity {
+    let entity = spawn_obstacle(commands, position, config.snake_size);
+    commands.entity(entity).insert(Crate {
+        hits_remaining: CRATE_HITS,
+    });
+    entity
+}
+
+// A `size` patch of ice, drawn under everything that goes over it
+pub fn spawn_ice_patch(commands: &mut Commands, position: Vec3, size: Vec2) -> Entity {
+    commands
+        .spawn()
+        .insert(IcePatch)
+        .insert_bundle(TransformBundle::from_transform(
+            Transform::from_translation(position.truncate().extend(ICE_Z)),
+        ))
+        .insert(Hitbox(size))
+        .id()
+}
+
+// Puts down a pair of portals at `positions`, each the exit of the other
+pub fn spawn_portals(
+    commands: &mut Commands,
+    config: &GameConfig,
+    positions: (Vec3, Vec3),
+) -> (Entity, Entity) {
+    let first = commands.spawn().id();
+    let second = commands.spawn().id();
+    for (portal, position, exit) in [(first, positions.0, second), (second, positions.1, first)] {
+        commands
+            .entity(portal)
+            .insert(Portal { exit })
+            .insert_bundle(TransformBundle::from_transform(
+                Transform::from_translation(position.truncate().extend(PORTAL_Z)),
+            ))
+            .insert(Hitbox(config.snake_size));
+    }
+    (first, second)
+}
+
+// A hunter at `position`, lunging nowhere until it first looks for a head
+pub fn spawn_hunter(commands: &mut Commands, config: &GameConfig, position: Vec3) -> Entity {
+    commands
+        .spawn()
+        .insert(Hunter {
+            heading: Vec2::ZERO,
+            retarget: Timer::from_seconds(HUNTER_RETARGET_SECONDS, true),
+            chasing: Player::ONE,
+            crashed: false,
+        })
+        .insert_bundle(TransformBundle::from_transform(
+            Transform::from_translation(position),
+        ))
+        .insert(Velocity(Vec2::ZERO))
+        .insert(Collider)
+        .insert(Hitbox(config.snake_size))
+        .id()
+}
+
+// Puts up a wall inside the arena covering `rect`, for levels and the rings
+// of survival
+pub fn spawn_wall_segment(commands: &mut Commands, rect: Rect) -> Entity {
+    let center = (rect.min + rect.max) / 2.0;
+    commands
+        .spawn_bundle(WallBundle::at(center.extend(0.0), rect.size()))
+        .insert(InteriorWall)
+        .id()
+}
@@ -0,0 +1,19 @@
+// The screens the game moves through. Gameplay systems only run while
+// `Playing`, and everything spawned for a run is cleaned up when leaving it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameState {
+    // Waiting for the player to start a game
+    Menu,
+    // A game is in progress
+    Playing,
+    // The snake has died
+    GameOver,
+    // A level of the campaign was cleared, the next one loads shortly
+    LevelComplete,
+    // The last level of the campaign was cleared
+    CampaignComplete,
+    // The list of achievements, opened from the menu
+    Achievements,
+    // The lifetime statistics, opened from the menu
+    Statistics,
+}
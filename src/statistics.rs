@@ -0,0 +1,68 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::collision::{grow_snake, FoodEaten, GameOverEvent};
+use crate::config::GameConfig;
+use crate::game::{handle_game_over, FixedUpdate, GameTick};
+use crate::replay::ReplayPlayer;
+use crate::snake::{move_segments, Odometer, SegmentOrder, Snake};
+
+// Adds every game played to the lifetime `Statistics`. Replays are left out,
+// they were counted when they were played.
+pub struct StatisticsPlugin;
+
+impl Plugin for StatisticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Statistics>().add_system_set(
+            SystemSet::new().with_run_criteria(FixedUpdate).with_system(
+                record_statistics
+                    .after(move_segments)
+                    .after(grow_snake)
+                    .after(handle_game_over),
+            ),
+        );
+    }
+}
+
+// Totals over every game ever played, kept on disk by the `PersistencePlugin`
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Statistics {
+    // Every kind of food and pickup
+    pub foods_eaten: usize,
+    // Units the heads moved, a cell is `GameConfig::snake_size` across
+    pub distance: f32,
+    // Games that ended in a game over
+    pub games_played: usize,
+    // Segments including the head
+    pub longest_snake: usize,
+    pub longest_game_secs: f32,
+}
+
+// Reads the distance off the heads' `Odometer`s rather than working it out
+// from their speed, so it only counts what they really moved
+pub fn record_statistics(
+    mut food_events: EventReader<FoodEaten>,
+    mut game_over_events: EventReader<GameOverEvent>,
+    mut statistics: ResMut<Statistics>,
+    mut snake_query: Query<(&mut Odometer, &SegmentOrder), With<Snake>>,
+    player: Option<Res<ReplayPlayer>>,
+    tick: Res<GameTick>,
+    config: Res<GameConfig>,
+) {
+    if player.is_some() {
+        return;
+    }
+    statistics.foods_eaten += food_events.iter().count();
+    for (mut odometer, segments) in &mut snake_query {
+        statistics.distance += **odometer;
+        **odometer = 0.0;
+        statistics.longest_snake = statistics.longest_snake.max(segments.len() + 1);
+    }
+    let seconds = **tick as f32 * config.time_step;
+    statistics.longest_game_secs = statistics.longest_game_secs.max(seconds);
+    // Both snakes of a versus round dying at once is still one game
+    if game_over_events.iter().count() > 0 {
+        statistics.games_played += 1;
+    }
+}
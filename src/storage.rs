@@ -0,0 +1,236 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use bevy::{app::AppExit, prelude::*};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::achievements::Achievements;
+use crate::config::GameConfig;
+use crate::display::DisplaySettings;
+use crate::game::{GameMode, HighScores, Leaderboard};
+use crate::replay::{finish_replay, ReplayPlayer, ReplayRecorder};
+use crate::run_stats::{RunStats, RUN_CSV_HEADER};
+use crate::savegame::{SavedGame, SavedGameSlot, SAVE_VERSION};
+use crate::state::GameState;
+use crate::statistics::Statistics;
+
+// Folder inside the platform data directory the game keeps its files in
+const DATA_DIR_NAME: &str = "snake_rust";
+const HIGH_SCORES_FILE: &str = "high_scores.ron";
+const LEADERBOARD_FILE: &str = "leaderboard.ron";
+const ACHIEVEMENTS_FILE: &str = "achievements.ron";
+const STATISTICS_FILE: &str = "statistics.ron";
+const DISPLAY_SETTINGS_FILE: &str = "settings.ron";
+// The last run played, overwritten by the next one
+pub const REPLAY_FILE: &str = "last_replay.ron";
+const SAVED_GAME_FILE: &str = "saved_game.ron";
+// Every finished run, one row each, unless `GameConfig::runs_file` says
+// otherwise
+const RUNS_FILE: &str = "runs.csv";
+
+// Keeps the high scores, the leaderboard, the achievements, the statistics,
+// the display settings, a replay of the last run and the saved game between
+// runs of the game, and adds every finished run to the runs file.
+// Headless apps leave it out so they never touch the player's files.
+pub struct PersistencePlugin;
+
+impl Plugin for PersistencePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(load::<HighScores>(HIGH_SCORES_FILE))
+            .insert_resource(load::<Leaderboard>(LEADERBOARD_FILE))
+            .insert_resource(load::<Achievements>(ACHIEVEMENTS_FILE))
+            .insert_resource(load::<Statistics>(STATISTICS_FILE))
+            .insert_resource(load::<DisplaySettings>(DISPLAY_SETTINGS_FILE))
+            .insert_resource(SavedGameSlot {
+                saved: load_saved_game(),
+                resuming: false,
+            })
+            .add_system(save_leaderboard)
+            .add_system(save_achievements)
+            .add_system(save_display_settings)
+            .add_system(save_saved_game)
+            .add_system_set(
+                SystemSet::on_enter(GameState::GameOver)
+                    .with_system(save_high_scores)
+                    .with_system(save_replay.after(finish_replay))
+                    .with_system(append_run),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Playing).with_system(save_statistics))
+            .add_system_to_stage(CoreStage::Last, save_high_scores_on_exit)
+            .add_system_to_stage(CoreStage::Last, save_statistics_on_exit);
+    }
+}
+
+// Where `file_name` is kept, `None` on platforms without a data directory
+pub fn data_path(file_name: &str) -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(DATA_DIR_NAME).join(file_name))
+}
+
+// Reads `file_name` from the data directory. A missing or unreadable file
+// gives the default, so a corrupt file costs the saved data but not the game.
+pub fn load<T: DeserializeOwned + Default>(file_name: &str) -> T {
+    let Some(path) = data_path(file_name) else {
+        return T::default();
+    };
+    let Ok(contents) = fs::read_to_string(&path) else {
+        return T::default();
+    };
+    ron::from_str(&contents).unwrap_or_else(|err| {
+        warn!("ignoring {}: {err}", path.display());
+        T::default()
+    })
+}
+
+// Deletes `file_name` from the data directory if it is there
+pub fn remove(file_name: &str) {
+    let Some(path) = data_path(file_name) else {
+        return;
+    };
+    if let Err(err) = fs::remove_file(&path) {
+        if err.kind() != io::ErrorKind::NotFound {
+            warn!("could not remove {}: {err}", path.display());
+        }
+    }
+}
+
+// Writes `value` to `file_name` in the data directory, logging failures
+pub fn save<T: Serialize>(file_name: &str, value: &T) {
+    let Some(path) = data_path(file_name) else {
+        return;
+    };
+    let result = ron::to_string(value)
+        .map_err(|err| err.to_string())
+        .and_then(|contents| {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+            }
+            fs::write(&path, contents).map_err(|err| err.to_string())
+        });
+    if let Err(err) = result {
+        warn!("could not save {}: {err}", path.display());
+    }
+}
+
+pub fn save_high_scores(high_scores: Res<HighScores>) {
+    save(HIGH_SCORES_FILE, &*high_scores);
+}
+
+pub fn save_high_scores_on_exit(
+    mut app_exit_events: EventReader<AppExit>,
+    high_scores: Res<HighScores>,
+) {
+    if app_exit_events.iter().count() > 0 {
+        save(HIGH_SCORES_FILE, &*high_scores);
+    }
+}
+
+// The statistics change every tick, so they are only saved once a game is
+// left, whether it ended or not
+pub fn save_statistics(statistics: Res<Statistics>) {
+    save(STATISTICS_FILE, &*statistics);
+}
+
+pub fn save_statistics_on_exit(
+    mut app_exit_events: EventReader<AppExit>,
+    statistics: Res<Statistics>,
+) {
+    if app_exit_events.iter().count() > 0 {
+        save(STATISTICS_FILE, &*statistics);
+    }
+}
+
+// The leaderboard only changes when a new entry is added, saving it right
+// away means no entry is lost to a crash
+pub fn save_leaderboard(leaderboard: Res<Leaderboard>) {
+    if leaderboard.is_changed() && !leaderboard.is_added() {
+        save(LEADERBOARD_FILE, &*leaderboard);
+    }
+}
+
+// Saved as soon as one is unlocked, like the leaderboard
+pub fn save_achievements(achievements: Res<Achievements>) {
+    if achievements.is_changed() && !achievements.is_added() {
+        save(ACHIEVEMENTS_FILE, &*achievements);
+    }
+}
+
+// Saved every time F11 switches the window mode
+pub fn save_display_settings(settings: Res<DisplaySettings>) {
+    if settings.is_changed() && !settings.is_added() {
+        save(DISPLAY_SETTINGS_FILE, &*settings);
+    }
+}
+
+pub fn save_replay(
+    recorder: Res<ReplayRecorder>,
+    player: Option<Res<ReplayPlayer>>,
+    mode: Res<GameMode>,
+) {
+    // Watching a replay doesn't replace it, and only classic runs are recorded
+    if player.is_some() || *mode != GameMode::Classic {
+        return;
+    }
+    save(REPLAY_FILE, &recorder.replay);
+    if let Some(path) = data_path(REPLAY_FILE) {
+        info!("saved a replay of the run to {}", path.display());
+    }
+}
+
+// Adds the run that just ended to the runs file. A file that can't be
+// written to, say because another program holds it open, costs the row but
+// not the game.
+pub fn append_run(run: Res<RunStats>, config: Res<GameConfig>, player: Option<Res<ReplayPlayer>>) {
+    if !config.record_runs || player.is_some() {
+        return;
+    }
+    let Some(path) = config
+        .runs_file
+        .as_ref()
+        .map(PathBuf::from)
+        .or_else(|| data_path(RUNS_FILE))
+    else {
+        return;
+    };
+    if let Err(err) = append_csv_row(&path, RUN_CSV_HEADER, &run.csv_row()) {
+        warn!("could not add the run to {}: {err}", path.display());
+    }
+}
+
+// Adds `row` to the end of the file at `path`, starting a new file with
+// `header`
+pub fn append_csv_row(path: &Path, header: &str, row: &str) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    if file.metadata()?.len() == 0 {
+        writeln!(file, "{header}")?;
+    }
+    writeln!(file, "{row}")
+}
+
+// A save from another version of the game is dropped, the menu then simply
+// has nothing to continue
+fn load_saved_game() -> Option<SavedGame> {
+    let saved = load::<Option<SavedGame>>(SAVED_GAME_FILE)?;
+    if saved.version != SAVE_VERSION {
+        warn!(
+            "ignoring a saved game from version {} of the format",
+            saved.version
+        );
+        return None;
+    }
+    Some(saved)
+}
+
+// Continuing a saved game uses it up, so the file goes away with it
+pub fn save_saved_game(slot: Res<SavedGameSlot>) {
+    if !slot.is_changed() || slot.is_added() {
+        return;
+    }
+    match &slot.saved {
+        Some(saved) => save(SAVED_GAME_FILE, &Some(saved)),
+        None => remove(SAVED_GAME_FILE),
+    }
+}
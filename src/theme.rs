@@ -0,0 +1,450 @@
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::collision::Collider;
+use crate::config::GameConfig;
+use crate::crates::Crate;
+use crate::display::DisplaySettings;
+use crate::food::FoodKind;
+use crate::hunter::Hunter;
+use crate::snake::{Player, SegmentOrder, Snake, SnakeSegment};
+use crate::walls::{ArenaConfig, Obstacle};
+
+// The color the UI draws its text in, which the theme swaps for its own
+pub const TEXT_COLOR: Color = Color::rgb(0.8, 0.8, 0.8);
+// Bevy's own clear color, which the classic theme keeps
+const CLASSIC_BACKGROUND: Color = Color::rgb(0.4, 0.4, 0.4);
+// How far apart in RGB the food's color has to be from either end of the
+// snake's body, see `Theme::with_swatch`
+pub const MIN_FOOD_CONTRAST: f32 = 0.35;
+// How far the food's hue is turned at a time until it is far enough
+const FOOD_HUE_STEP: f32 = 30.0;
+// How much darker the tail of a swatch is than its head
+const SWATCH_TAIL_SHADE: f32 = 0.4;
+
+// Colors the arena and the UI after the theme picked on the menu, and
+// recolors what is already on screen when another one is picked
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Theme>()
+            .add_system(switch_theme)
+            .add_system(recolor_sprites.after(switch_theme))
+            // Once the sprites of new segments are in, before they are drawn
+            .add_system_to_stage(CoreStage::Last, shade_snake_bodies)
+            .add_system_to_stage(CoreStage::PostUpdate, tint_text);
+    }
+}
+
+// The presets there are to choose from, kept in the `DisplaySettings`
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThemeName {
+    // The colors of the `GameConfig`, with the walls of the `ArenaConfig`
+    #[default]
+    Classic,
+    Dark,
+    HighContrast,
+    RetroAmber,
+}
+
+impl ThemeName {
+    pub const ALL: [ThemeName; 4] = [
+        ThemeName::Classic,
+        ThemeName::Dark,
+        ThemeName::HighContrast,
+        ThemeName::RetroAmber,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ThemeName::Classic => "Classic",
+            ThemeName::Dark => "Dark",
+            ThemeName::HighContrast => "High Contrast",
+            ThemeName::RetroAmber => "Retro Amber",
+        }
+    }
+
+    // The theme `steps` places further along `ALL`, wrapping around
+    pub fn cycle(self, steps: isize) -> ThemeName {
+        let count = ThemeName::ALL.len() as isize;
+        let index = ThemeName::ALL.iter().position(|t| *t == self).unwrap() as isize;
+        ThemeName::ALL[(index + steps).rem_euclid(count) as usize]
+    }
+}
+
+// The colors of the first player's snake, picked in the settings over the
+// theme's own
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SnakeSwatch {
+    // Whatever the theme has
+    #[default]
+    Theme,
+    Green,
+    Blue,
+    Red,
+    Orange,
+    Purple,
+    White,
+}
+
+impl SnakeSwatch {
+    pub const ALL: [SnakeSwatch; 7] = [
+        SnakeSwatch::Theme,
+        SnakeSwatch::Green,
+        SnakeSwatch::Blue,
+        SnakeSwatch::Red,
+        SnakeSwatch::Orange,
+        SnakeSwatch::Purple,
+        SnakeSwatch::White,
+    ];
+
+    // The swatch `steps` places further along `ALL`, wrapping around
+    pub fn cycle(self, steps: isize) -> SnakeSwatch {
+        let count = SnakeSwatch::ALL.len() as isize;
+        let index = SnakeSwatch::ALL.iter().position(|s| *s == self).unwrap() as isize;
+        SnakeSwatch::ALL[(index + steps).rem_euclid(count) as usize]
+    }
+
+    // The head's color, `None` for the theme's
+    pub fn color(self) -> Option<Color> {
+        match self {
+            SnakeSwatch::Theme => None,
+            SnakeSwatch::Green => Some(Color::rgb(0.2, 0.85, 0.25)),
+            SnakeSwatch::Blue => Some(Color::rgb(0.2, 0.5, 1.0)),
+            SnakeSwatch::Red => Some(Color::rgb(0.9, 0.2, 0.2)),
+            SnakeSwatch::Orange => Some(Color::rgb(1.0, 0.55, 0.1)),
+            SnakeSwatch::Purple => Some(Color::rgb(0.6, 0.3, 0.9)),
+            SnakeSwatch::White => Some(Color::rgb(0.95, 0.95, 0.95)),
+        }
+    }
+
+    // The head's color and the darker one the body fades to
+    pub fn colors(self) -> Option<(Color, Color)> {
+        let head = self.color()?;
+        let [r, g, b, _] = head.as_rgba_f32();
+        let shade = SWATCH_TAIL_SHADE;
+        Some((head, Color::rgb(r * shade, g * shade, b * shade)))
+    }
+}
+
+// How far apart two colors are in RGB, their alpha left out
+pub fn color_distance(a: Color, b: Color) -> f32 {
+    Vec4::from(a).truncate().distance(Vec4::from(b).truncate())
+}
+
+// The colors of the theme in use. The food other than the normal kind keeps
+// the colors of the `GameConfig` in every theme, they tell the kinds apart.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub name: ThemeName,
+    // The first player's colors are the swatch's, if not `SnakeSwatch::Theme`
+    pub swatch: SnakeSwatch,
+    pub snake: Color,
+    pub player_two: Color,
+    // The ends of the body's gradient, the head being the other end
+    pub snake_tail: Color,
+    pub player_two_tail: Color,
+    pub food: Color,
+    pub obstacle: Color,
+    pub wall: Color,
+    // Around the arena
+    pub background: Color,
+    // The checkerboard inside the walls
+    pub grid: Color,
+    pub grid_alt: Color,
+    pub text: Color,
+}
+
+impl Theme {
+    pub fn new(name: ThemeName, config: &GameConfig, arena: &ArenaConfig) -> Theme {
+        match name {
+            ThemeName::Classic => Theme {
+                name,
+                swatch: SnakeSwatch::Theme,
+                snake: config.snake_color,
+                player_two: config.player_two_color,
+                snake_tail: config.snake_tail_color,
+                player_two_tail: config.player_two_tail_color,
+                food: config.food_color,
+                obstacle: config.obstacle_color,
+                wall: arena.color,
+                background: CLASSIC_BACKGROUND,
+                grid: config.grid_color,
+                grid_alt: config.grid_alt_color,
+                text: TEXT_COLOR,
+            },
+            ThemeName::Dark => Theme {
+                name,
+                swatch: SnakeSwatch::Theme,
+                snake: Color::rgb(0.3, 0.85, 0.4),
+                player_two: Color::rgb(0.35, 0.55, 1.0),
+                snake_tail: Color::rgb(0.1, 0.35, 0.15),
+                player_two_tail: Color::rgb(0.12, 0.2, 0.45),
+                food: Color::rgb(0.95, 0.35, 0.3),
+                obstacle: Color::rgb(0.45, 0.3, 0.2),
+                wall: Color::rgb(0.35, 0.35, 0.4),
+                background: Color::rgb(0.05, 0.05, 0.07),
+                grid: Color::rgb(0.1, 0.1, 0.12),
+                grid_alt: Color::rgb(0.13, 0.13, 0.15),
+                text: Color::rgb(0.85, 0.85, 0.85),
+            },
+            ThemeName::HighContrast => Theme {
+                name,
+                swatch: SnakeSwatch::Theme,
+                snake: Color::rgb(1.0, 1.0, 0.0),
+                player_two: Color::rgb(0.0, 1.0, 1.0),
+                snake_tail: Color::rgb(0.6, 0.6, 0.0),
+                player_two_tail: Color::rgb(0.0, 0.6, 0.6),
+                food: Color::rgb(1.0, 0.0, 0.0),
+                obstacle: Color::rgb(0.6, 0.6, 0.6),
+                wall: Color::WHITE,
+                background: Color::BLACK,
+                grid: Color::BLACK,
+                grid_alt: Color::rgb(0.08, 0.08, 0.08),
+                text: Color::WHITE,
+            },
+            ThemeName::RetroAmber => Theme {
+                name,
+                swatch: SnakeSwatch::Theme,
+                snake: Color::rgb(1.0, 0.75, 0.0),
+                player_two: Color::rgb(1.0, 0.55, 0.1),
+                snake_tail: Color::rgb(0.5, 0.35, 0.0),
+                player_two_tail: Color::rgb(0.5, 0.25, 0.05),
+                food: Color::rgb(1.0, 0.9, 0.5),
+                obstacle: Color::rgb(0.6, 0.4, 0.0),
+                wall: Color::rgb(0.8, 0.55, 0.0),
+                background: Color::rgb(0.1, 0.06, 0.0),
+                grid: Color::rgb(0.12, 0.08, 0.0),
+                grid_alt: Color::rgb(0.15, 0.1, 0.02),
+                text: Color::rgb(1.0, 0.7, 0.0),
+            },
+        }
+    }
+
+    // The theme with the first player's snake in the `swatch`'s colors. The
+    // food stays at least `MIN_FOOD_CONTRAST` away from both ends of the
+    // snake, its hue turned until it is. Should no hue be far enough, the
+    // farthest one does.
+    pub fn with_swatch(mut self, swatch: SnakeSwatch) -> Theme {
+        self.swatch = swatch;
+        if let Some((head, tail)) = swatch.colors() {
+            self.snake = head;
+            self.snake_tail = tail;
+        }
+
+        let contrast = |food: Color| {
+            color_distance(food, self.snake).min(color_distance(food, self.snake_tail))
+        };
+        if contrast(self.food) >= MIN_FOOD_CONTRAST {
+            return self;
+        }
+        let [hue, saturation, lightness, alpha] = self.food.as_hsla_f32();
+        let turns = (360.0 / FOOD_HUE_STEP) as usize;
+        let candidates = (1..turns).map(|turn| {
+            let hue = (hue + turn as f32 * FOOD_HUE_STEP).rem_euclid(360.0);
+            Color::hsla(hue, saturation, lightness, alpha).as_rgba()
+        });
+        let mut best = self.food;
+        for food in candidates {
+            if contrast(food) >= MIN_FOOD_CONTRAST {
+                best = food;
+                break;
+            }
+            if contrast(food) > contrast(best) {
+                best = food;
+            }
+        }
+        self.food = best;
+        self
+    }
+
+    // The theme and the swatch picked in the `settings`
+    pub fn from_settings(
+        settings: &DisplaySettings,
+        config: &GameConfig,
+        arena: &ArenaConfig,
+    ) -> Theme {
+        Theme::new(settings.theme, config, arena).with_swatch(settings.snake_swatch)
+    }
+
+    pub fn snake_color(&self, player: Option<&Player>) -> Color {
+        match player {
+            Some(&Player::TWO) => self.player_two,
+            _ => self.snake,
+        }
+    }
+
+    // The color of the body segment `index` places behind the head, out of
+    // `length`, on the way from the head's color to the tail's. The last
+    // segment is the tail's color.
+    pub fn segment_color(&self, player: Option<&Player>, index: usize, length: usize) -> Color {
+        let tail = match player {
+            Some(&Player::TWO) => self.player_two_tail,
+            _ => self.snake_tail,
+        };
+        let head = Vec4::from(self.snake_color(player));
+        let along = (index + 1) as f32 / length.max(1) as f32;
+        Color::from(head.lerp(Vec4::from(tail), along.min(1.0)))
+    }
+
+    // The color of whatever the game logic spawned, by what it is: food, a
+    // snake's head or body, an obstacle or else a wall
+    pub fn sprite_color(
+        &self,
+        config: &GameConfig,
+        food: Option<&FoodKind>,
+        player: Option<&Player>,
+        snake: bool,
+        obstacle: bool,
+    ) -> Color {
+        match food {
+            Some(FoodKind::Normal) => self.food,
+            Some(FoodKind::Bonus) => config.bonus_food_color,
+            Some(FoodKind::Poison) => config.poison_food_color,
+            Some(FoodKind::Golden) => config.golden_food_color,
+            Some(FoodKind::SlowMotion) => config.slow_motion_color,
+            Some(FoodKind::Ghost) => config.ghost_color,
+            Some(FoodKind::Shield) => config.shield_color,
+            Some(FoodKind::Magnet) => config.magnet_color,
+            Some(FoodKind::Hammer) => config.hammer_color,
+            None if snake => self.snake_color(player),
+            None if obstacle => self.obstacle,
+            None => self.wall,
+        }
+    }
+}
+
+impl FromWorld for Theme {
+    fn from_world(world: &mut World) -> Self {
+        let settings = world
+            .get_resource::<DisplaySettings>()
+            .cloned()
+            .unwrap_or_default();
+        Theme::from_settings(
+            &settings,
+            world.resource::<GameConfig>(),
+            world.resource::<ArenaConfig>(),
+        )
+    }
+}
+
+// Follows the theme and the snake's swatch picked in the `DisplaySettings`,
+// and the wall color of the `ArenaConfig`, and keeps the clear color with it
+pub fn switch_theme(
+    settings: Res<DisplaySettings>,
+    config: Res<GameConfig>,
+    arena: Res<ArenaConfig>,
+    mut theme: ResMut<Theme>,
+    mut clear_color: ResMut<ClearColor>,
+) {
+    if settings.theme != theme.name || settings.snake_swatch != theme.swatch || arena.is_changed() {
+        *theme = Theme::from_settings(&settings, &config, &arena);
+    }
+    if theme.is_changed() {
+        clear_color.0 = theme.background;
+    }
+}
+
+// Gives everything in the arena its color in the new theme. The alpha is
+// left as it was, a ghost stays see-through.
+pub fn recolor_sprites(
+    theme: Res<Theme>,
+    config: Res<GameConfig>,
+    mut sprite_query: Query<
+        (
+            AnyOf<(&mut Sprite, &mut TextureAtlasSprite)>,
+            Option<&FoodKind>,
+            Option<&Player>,
+            Option<&Obstacle>,
+            Option<&Crate>,
+            Or<(With<Snake>, With<SnakeSegment>)>,
+        ),
+        (
+            Or<(
+                With<Snake>,
+                With<SnakeSegment>,
+                With<FoodKind>,
+                With<Collider>,
+            )>,
+            Without<Hunter>,
+        ),
+    >,
+) {
+    if !theme.is_changed() || theme.is_added() {
+        return;
+    }
+
+    for ((sprite, atlas_sprite), food, player, obstacle, crate_box, snake) in &mut sprite_query {
+        let mut color = match crate_box {
+            Some(_) => config.crate_color,
+            None => theme.sprite_color(&config, food, player, snake, obstacle.is_some()),
+        };
+        if let Some(mut sprite) = sprite {
+            color.set_a(sprite.color.a());
+            sprite.color = color;
+        } else if let Some(mut sprite) = atlas_sprite {
+            color.set_a(sprite.color.a());
+            sprite.color = color;
+        }
+    }
+}
+
+// Colors every body along the gradient of `Theme::segment_color`, again
+// whenever its length changes, a new segment gets its sprite or the theme
+// changes. The alpha is left as it was.
+pub fn shade_snake_bodies(
+    theme: Res<Theme>,
+    head_query: Query<(Entity, Option<&Player>, &SegmentOrder), With<Snake>>,
+    mut segment_queries: ParamSet<(
+        Query<(), (With<SnakeSegment>, Added<Sprite>)>,
+        Query<&mut Sprite, With<SnakeSegment>>,
+    )>,
+    mut shaded: Local<HashMap<Entity, usize>>,
+) {
+    let new_sprites = !segment_queries.p0().is_empty();
+    shaded.retain(|head, _| head_query.contains(*head));
+    for (head, player, segments) in &head_query {
+        let length = segments.len();
+        if shaded.get(&head) == Some(&length) && !new_sprites && !theme.is_changed() {
+            continue;
+        }
+        shaded.insert(head, length);
+        for (index, segment) in segments.iter().enumerate() {
+            let mut segment_query = segment_queries.p1();
+            let Ok(mut sprite) = segment_query.get_mut(*segment) else {
+                continue;
+            };
+            let mut color = theme.segment_color(player, index, length);
+            color.set_a(sprite.color.a());
+            if sprite.color != color {
+                sprite.color = color;
+            }
+        }
+    }
+}
+
+// The UI spawns its text in `TEXT_COLOR`, this shows it in the theme's text
+// color instead. Text still in the previous theme's color moves over too.
+pub fn tint_text(
+    theme: Res<Theme>,
+    mut previous: Local<Option<Color>>,
+    mut query: Query<&mut Text>,
+) {
+    let old = previous.replace(theme.text).unwrap_or(TEXT_COLOR);
+    let untinted = |color: Color| color != theme.text && (color == TEXT_COLOR || color == old);
+    for mut text in &mut query {
+        if !text
+            .sections
+            .iter()
+            .any(|section| untinted(section.style.color))
+        {
+            continue;
+        }
+        for section in &mut text.sections {
+            if untinted(section.style.color) {
+                section.style.color = theme.text;
+            }
+        }
+    }
+}
@@ -0,0 +1,280 @@
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+
+use crate::collision::Hitbox;
+use crate::display::DisplaySettings;
+use crate::food::Food;
+use crate::game::{Countdown, GameMode};
+use crate::locale::Locale;
+use crate::replay::ReplayPlayer;
+use crate::state::GameState;
+use crate::theme::TEXT_COLOR;
+use crate::walls::{Obstacle, Wall};
+
+const TUTORIAL_BACKGROUND: Color = Color::rgba(0.0, 0.0, 0.0, 0.35);
+const TUTORIAL_PANEL_COLOR: Color = Color::rgba(0.1, 0.1, 0.1, 0.9);
+const TUTORIAL_PANEL_WIDTH: Val = Val::Px(520.0);
+const TUTORIAL_PANEL_PADDING: Val = Val::Px(20.0);
+const TUTORIAL_TITLE_SIZE: f32 = 36.0;
+const TUTORIAL_FONT_SIZE: f32 = 22.0;
+const TUTORIAL_HINT_SIZE: f32 = 16.0;
+const TUTORIAL_TITLE_COLOR: Color = Color::rgb(1.0, 0.8, 0.2);
+// A ring around what the step is about, just behind its sprite
+const HIGHLIGHT_WIDTH: f32 = 4.0;
+const HIGHLIGHT_COLOR: Color = Color::rgb(1.0, 0.85, 0.1);
+const HIGHLIGHT_Z: f32 = -0.1;
+
+// What a step of the tutorial rings in the arena
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TutorialTarget {
+    Nothing,
+    Food,
+    // The walls around the arena and the obstacles in it
+    Walls,
+}
+
+// The `Locale` key and the target of every step, in order. The key is the
+// step's title, and with ".body" after it its text.
+pub const TUTORIAL_STEPS: [(&str, TutorialTarget); 4] = [
+    ("tutorial.welcome", TutorialTarget::Nothing),
+    ("tutorial.food", TutorialTarget::Food),
+    ("tutorial.walls", TutorialTarget::Walls),
+    ("tutorial.ready", TutorialTarget::Nothing),
+];
+
+// Walks a new player through the controls, the food and the walls, one step
+// at a time, the first time a game is played. The game doesn't start
+// counting down until it is done or skipped, and it can be played again from
+// the menu.
+pub struct TutorialPlugin;
+
+impl Plugin for TutorialPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Tutorial>()
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(start_tutorial))
+            .add_system_set(SystemSet::on_exit(GameState::Playing).with_system(end_tutorial))
+            .add_system_to_stage(CoreStage::PreUpdate, advance_tutorial.after(InputSystem))
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                hold_countdown_for_tutorial.after(advance_tutorial),
+            )
+            .add_system(show_tutorial)
+            .add_system(highlight_tutorial_targets);
+    }
+}
+
+// The index into `TUTORIAL_STEPS` of the step showing, `None` when the
+// tutorial isn't
+#[derive(Default, Debug, Deref, DerefMut)]
+pub struct Tutorial(pub Option<usize>);
+
+impl Tutorial {
+    pub fn target(&self) -> TutorialTarget {
+        self.map_or(TutorialTarget::Nothing, |step| TUTORIAL_STEPS[step].1)
+    }
+}
+
+// The overlay the steps are shown on
+#[derive(Component)]
+pub struct TutorialOverlay;
+
+// A ring around something the tutorial points out
+#[derive(Component)]
+pub struct TutorialHighlight;
+
+// Put on what is ringed once it has its `TutorialHighlight`
+#[derive(Component)]
+pub struct TutorialHighlighted;
+
+// A game of one's own is how the tutorial is seen, versus and replays
+// leave it for later
+pub fn start_tutorial(
+    mut tutorial: ResMut<Tutorial>,
+    settings: Res<DisplaySettings>,
+    mode: Res<GameMode>,
+    player: Option<Res<ReplayPlayer>>,
+) {
+    if !settings.tutorial_seen && *mode != GameMode::Versus && player.is_none() {
+        **tutorial = Some(0);
+    }
+}
+
+// Leaving the game halfway through doesn't count as having seen it
+pub fn end_tutorial(mut tutorial: ResMut<Tutorial>) {
+    if tutorial.is_some() {
+        **tutorial = None;
+    }
+}
+
+// Enter or Space moves on to the next step, Esc skips the rest. Either way
+// the keys are used up, so nothing else reacts to them.
+pub fn advance_tutorial(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut tutorial: ResMut<Tutorial>,
+    mut settings: ResMut<DisplaySettings>,
+) {
+    let Some(step) = **tutorial else {
+        return;
+    };
+    let next = if keyboard_input.clear_just_pressed(KeyCode::Escape) {
+        TUTORIAL_STEPS.len()
+    } else if keyboard_input.clear_just_pressed(KeyCode::Return)
+        || keyboard_input.clear_just_pressed(KeyCode::Space)
+    {
+        step + 1
+    } else {
+        return;
+    };
+    if next < TUTORIAL_STEPS.len() {
+        **tutorial = Some(next);
+    } else {
+        **tutorial = None;
+        settings.tutorial_seen = true;
+    }
+}
+
+// Keeps the 3-2-1 from starting, and with it the game, until the tutorial
+// is over
+pub fn hold_countdown_for_tutorial(tutorial: Res<Tutorial>, mut countdown: ResMut<Countdown>) {
+    if tutorial.is_some() {
+        *countdown = Countdown::default();
+    }
+}
+
+// Builds the overlay for the step showing, over the whole window so it
+// keeps up with its size
+pub fn show_tutorial(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    locale: Res<Locale>,
+    tutorial: Res<Tutorial>,
+    overlay_query: Query<Entity, With<TutorialOverlay>>,
+) {
+    if !tutorial.is_changed() && !locale.is_changed() {
+        return;
+    }
+    for overlay in &overlay_query {
+        commands.entity(overlay).despawn_recursive();
+    }
+    let Some(step) = **tutorial else {
+        return;
+    };
+
+    let (key, _) = TUTORIAL_STEPS[step];
+    let font = asset_server.load("fonts/DejaVuSans-Bold.ttf");
+    let style = |font_size, color| TextStyle {
+        font: font.clone(),
+        font_size,
+        color,
+    };
+    let margin = Style {
+        margin: UiRect::all(Val::Px(6.0)),
+        max_size: Size::new(TUTORIAL_PANEL_WIDTH, Val::Undefined),
+        ..default()
+    };
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: TUTORIAL_BACKGROUND.into(),
+            ..default()
+        })
+        .insert(TutorialOverlay)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::ColumnReverse,
+                        align_items: AlignItems::Center,
+                        padding: UiRect::all(TUTORIAL_PANEL_PADDING),
+                        max_size: Size::new(TUTORIAL_PANEL_WIDTH, Val::Undefined),
+                        ..default()
+                    },
+                    color: TUTORIAL_PANEL_COLOR.into(),
+                    ..default()
+                })
+                .with_children(|panel| {
+                    panel.spawn_bundle(
+                        TextBundle::from_section(
+                            locale.get(key),
+                            style(TUTORIAL_TITLE_SIZE, TUTORIAL_TITLE_COLOR),
+                        )
+                        .with_style(margin.clone()),
+                    );
+                    panel.spawn_bundle(
+                        TextBundle::from_section(
+                            locale.get(&format!("{key}.body")),
+                            style(TUTORIAL_FONT_SIZE, TEXT_COLOR),
+                        )
+                        .with_style(margin.clone()),
+                    );
+                    panel.spawn_bundle(
+                        TextBundle::from_section(
+                            locale.text(
+                                "tutorial.hint",
+                                &[("step", &(step + 1)), ("steps", &TUTORIAL_STEPS.len())],
+                            ),
+                            style(TUTORIAL_HINT_SIZE, TEXT_COLOR),
+                        )
+                        .with_style(margin.clone()),
+                    );
+                });
+        });
+}
+
+// Rings what the step showing is about, and takes the rings off what it
+// isn't about
+pub fn highlight_tutorial_targets(
+    mut commands: Commands,
+    tutorial: Res<Tutorial>,
+    target_query: Query<
+        (Entity, &Hitbox, Option<&Food>, Option<&TutorialHighlighted>),
+        Or<(With<Food>, With<Wall>, With<Obstacle>)>,
+    >,
+    highlight_query: Query<(Entity, &Parent), With<TutorialHighlight>>,
+) {
+    let target = tutorial.target();
+    let ringed = |food: Option<&Food>| match target {
+        TutorialTarget::Nothing => false,
+        TutorialTarget::Food => food.is_some(),
+        TutorialTarget::Walls => food.is_none(),
+    };
+
+    for (highlight, parent) in &highlight_query {
+        let keep = target_query
+            .get(parent.get())
+            .is_ok_and(|(_, _, food, _)| ringed(food));
+        if !keep {
+            commands.entity(highlight).despawn_recursive();
+            commands
+                .entity(parent.get())
+                .remove::<TutorialHighlighted>();
+        }
+    }
+    for (entity, hitbox, food, highlighted) in &target_query {
+        if highlighted.is_some() || !ringed(food) {
+            continue;
+        }
+        commands
+            .entity(entity)
+            .insert(TutorialHighlighted)
+            .with_children(|parent| {
+                parent
+                    .spawn_bundle(SpriteBundle {
+                        sprite: Sprite {
+                            color: HIGHLIGHT_COLOR,
+                            custom_size: Some(**hitbox + 2.0 * HIGHLIGHT_WIDTH),
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(0.0, 0.0, HIGHLIGHT_Z),
+                        ..default()
+                    })
+                    .insert(TutorialHighlight);
+            });
+    }
+}
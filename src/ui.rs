@@ -0,0 +1,2527 @@
+use bevy::{asset::LoadState, prelude::*, transform::TransformSystem};
+
+use crate::accessibility::{food_circle_image, insert_food_sprite, AccessibilityPlugin};
+use crate::achievements::{Achievement, AchievementUnlocked, Achievements};
+use crate::background::{CheckerboardPlugin, BACKGROUND_DEPTH};
+use crate::camera::{ArenaCameraPlugin, CameraShakePlugin};
+use crate::collision::{Collider, DeathCause, Hitbox};
+use crate::config::GameConfig;
+use crate::crates::Crate;
+use crate::daily::{DailyChallenge, DailyDate};
+use crate::debug::{ColliderDebugPlugin, DebugOverlayPlugin};
+use crate::display::{DisplayPlugin, DisplaySettings};
+use crate::food::{
+    BonusFood, Food, FoodKind, FoodLifetime, GameRng, SpawnAnimation, FOOD_BLINK_SECONDS,
+};
+use crate::game::{
+    Combo, Countdown, Difficulty, GameMode, HighScores, LastDeath, Leaderboard, Lives, Paused,
+    RoundTimer, ScoreMultiplier, Scoreboard, StartingLives, VersusTally, COMBO_SECONDS,
+    LEADERBOARD_SIZE, MAX_COMBO, TIME_ATTACK_SECONDS,
+};
+use crate::hunger::Hunger;
+use crate::hunter::Hunter;
+use crate::ice::IcePatch;
+use crate::input::{navigate_menu, InitialsEntry, QuitPrompt, INITIALS_LENGTH};
+use crate::interpolation::InterpolationPlugin;
+use crate::level::{Campaign, CAMPAIGN};
+use crate::locale::{Locale, LocalePlugin};
+use crate::particles::ParticlePlugin;
+use crate::pause::PauseMenuPlugin;
+use crate::popups::PopupPlugin;
+use crate::portal::Portal;
+use crate::pulse::EatPulsePlugin;
+use crate::run_stats::RunStats;
+use crate::savegame::SavedGameSlot;
+use crate::screenshot::ScreenshotPlugin;
+use crate::snake::{
+    Boost, Dying, GameSpeed, GhostMode, NextDirection, Player, Respawning, Shield, Snake,
+    SnakeSegment, SpeedModifier, Velocity,
+};
+use crate::state::GameState;
+use crate::statistics::Statistics;
+use crate::theme::{SnakeSwatch, Theme, ThemeName, ThemePlugin, TEXT_COLOR};
+use crate::tutorial::TutorialPlugin;
+use crate::walls::{ArenaBounds, Obstacle, ShrinkingArena, WallLocation};
+
+const SCOREBOARD_FONT_SIZE: f32 = 32.0;
+// Keeps the score in the corner above the top wall
+const SCOREBOARD_TEXT_PADDING: Val = Val::Px(5.0);
+const SCORE_COLOR: Color = Color::rgb(0.1, 0.8, 0.1);
+const PAUSED_FONT_SIZE: f32 = 64.0;
+const TITLE_FONT_SIZE: f32 = 96.0;
+const MENU_FONT_SIZE: f32 = 32.0;
+const MENU_ITEM_SPACING: Val = Val::Px(10.0);
+const LEADERBOARD_FONT_SIZE: f32 = 24.0;
+// Keeps the leaderboard apart from what is above it
+const LEADERBOARD_MARGIN: Val = Val::Px(20.0);
+pub const SELECTED_MENU_ITEM_COLOR: Color = Color::rgb(0.1, 0.8, 0.1);
+const DISABLED_MENU_ITEM_COLOR: Color = Color::rgb(0.4, 0.4, 0.4);
+const GAME_OVER_FONT_SIZE: f32 = 80.0;
+// Makes a new record stand out on the game over screen
+const NEW_BEST_COLOR: Color = Color::rgb(1.0, 0.8, 0.2);
+// Dims the arena behind the game over screen
+const GAME_OVER_BACKGROUND: Color = Color::rgba(0.0, 0.0, 0.0, 0.75);
+const RESULTS_FONT_SIZE: f32 = 24.0;
+const RESULTS_PADDING: Val = Val::Px(12.0);
+const RESULTS_BACKGROUND: Color = Color::rgba(0.15, 0.15, 0.15, 0.9);
+// The bar showing how long the score multiplier has left, in the corner
+// opposite the score
+const MULTIPLIER_BAR_WIDTH: f32 = 150.0;
+const MULTIPLIER_BAR_HEIGHT: f32 = 12.0;
+const MULTIPLIER_BAR_PADDING: Val = Val::Px(15.0);
+const MULTIPLIER_BAR_BACKGROUND: Color = Color::rgb(0.25, 0.25, 0.25);
+// The combo sits below the score, with a bar of its own for the window
+const COMBO_TOP: Val = Val::Px(40.0);
+const COMBO_FONT_SIZE: f32 = 18.0;
+const COMBO_BAR_WIDTH: f32 = 100.0;
+const COMBO_BAR_HEIGHT: f32 = 8.0;
+const COMBO_BAR_MARGIN: Val = Val::Px(8.0);
+const COMBO_COLOR: Color = Color::rgb(1.0, 0.5, 0.1);
+// The boost meter, in the corner opposite the lives
+const BOOST_BAR_WIDTH: f32 = 100.0;
+const BOOST_BAR_HEIGHT: f32 = 8.0;
+const BOOST_BAR_PADDING: Val = Val::Px(10.0);
+const BOOST_COLOR: Color = Color::rgb(0.3, 0.8, 1.0);
+// The hunger meter, right above the boost meter, red once it runs low
+const HUNGER_BAR_BOTTOM: Val = Val::Px(26.0);
+const HUNGER_COLOR: Color = Color::rgb(0.9, 0.6, 0.2);
+const HUNGER_LOW_COLOR: Color = Color::rgb(0.9, 0.2, 0.2);
+const PORTAL_COLOR: Color = Color::rgb(0.6, 0.2, 0.9);
+// See-through, so the checkerboard shows under the ice
+const ICE_COLOR: Color = Color::rgba(0.55, 0.8, 1.0, 0.45);
+const HUNTER_COLOR: Color = Color::rgb(0.9, 0.1, 0.1);
+// The slow motion icon sits below the bar
+const SLOW_MOTION_ICON_SIZE: f32 = 24.0;
+const SLOW_MOTION_ICON_TOP: Val = Val::Px(35.0);
+// And the ghost countdown below that
+const GHOST_TIMER_TOP: Val = Val::Px(65.0);
+// The time attack clock, large and in the middle above the arena
+const ROUND_TIMER_FONT_SIZE: f32 = 40.0;
+const ROUND_TIMER_TOP: Val = Val::Px(5.0);
+// Through its last seconds the clock flashes between the text color and this
+const ROUND_TIMER_WARNING_SECONDS: f32 = 10.0;
+const ROUND_TIMER_WARNING_COLOR: Color = Color::rgb(0.9, 0.2, 0.2);
+// The lives left, as a row of small heads in the corner below the arena
+const LIFE_ICON_SIZE: f32 = 20.0;
+const LIFE_ICON_SPACING: Val = Val::Px(4.0);
+const LIVES_PADDING: Val = Val::Px(5.0);
+// How many times a second a respawning snake blinks
+const RESPAWN_BLINKS_PER_SECOND: f32 = 8.0;
+// The head of a dying snake, flashing until its body starts to go
+const DEATH_FLASH_COLOR: Color = Color::rgb(0.9, 0.1, 0.1);
+const DEATH_FLASHES_PER_SECOND: f32 = 10.0;
+// Achievement toasts, stacked upwards from the bottom right corner
+const TOAST_SECONDS: f32 = 3.0;
+const TOAST_SLIDE_SECONDS: f32 = 0.3;
+const TOAST_WIDTH: f32 = 280.0;
+const TOAST_HEIGHT: f32 = 56.0;
+const TOAST_PADDING: f32 = 10.0;
+const TOAST_FONT_SIZE: f32 = 18.0;
+const TOAST_BACKGROUND: Color = Color::rgba(0.1, 0.1, 0.1, 0.9);
+const DIALOG_BACKGROUND: Color = Color::rgba(0.0, 0.0, 0.0, 0.6);
+const DIALOG_PANEL_COLOR: Color = Color::rgba(0.1, 0.1, 0.1, 0.95);
+const DIALOG_PADDING: Val = Val::Px(20.0);
+const DIALOG_HINT_SIZE: f32 = 20.0;
+const LOCKED_ACHIEVEMENT_COLOR: Color = Color::rgb(0.4, 0.4, 0.4);
+// How see-through the snake is while it is a ghost
+const GHOST_ALPHA: f32 = 0.4;
+// The outline drawn around the head while it holds a shield, just behind it
+const SHIELD_OUTLINE_WIDTH: f32 = 3.0;
+const SHIELD_OUTLINE_ALPHA: f32 = 0.6;
+const SHIELD_OUTLINE_Z: f32 = -0.1;
+// The 3-2-1 before the snake moves, large in the middle of the arena, and the
+// "GO!" it ends on
+const COUNTDOWN_FONT_SIZE: f32 = 120.0;
+const GO_SECONDS: f32 = 0.5;
+// The eyes a head gets when its texture is missing. They sit `EYE_FORWARD`
+// of the head's size ahead of its middle and `EYE_SPREAD` to either side.
+const EYE_SIZE: f32 = 4.0;
+const EYE_COLOR: Color = Color::BLACK;
+const EYE_FORWARD: f32 = 0.2;
+const EYE_SPREAD: f32 = 0.22;
+const EYE_Z: f32 = 0.1;
+// Room left around the arena when sizing the window, enough for the score
+pub const WINDOW_MARGIN: f32 = 60.0;
+
+// The text showing the score
+#[derive(Component)]
+pub struct ScoreText;
+
+// The bar of the score multiplier, hidden while there is none, and the part
+// of it that empties as the time runs out
+#[derive(Component)]
+pub struct MultiplierBar;
+
+#[derive(Component)]
+pub struct MultiplierBarFill;
+
+// Holds one `LIFE_ICON_SIZE` head for every life left, empty with only one
+#[derive(Component)]
+pub struct LivesDisplay;
+
+// The current combo, hidden until there are two foods in a row, and the
+// part of its bar that empties as the window runs out
+#[derive(Component)]
+pub struct ComboDisplay;
+
+#[derive(Component)]
+pub struct ComboText;
+
+#[derive(Component)]
+pub struct ComboBarFill;
+
+// The boost meter of the first player's snake, hidden while there is none,
+// and the part of it that empties while it boosts
+#[derive(Component)]
+pub struct BoostBar;
+
+#[derive(Component)]
+pub struct BoostBarFill;
+
+// The hunger meter, only shown when `GameConfig::hunger` is on, and the
+// part of it that empties as the snake goes without food
+#[derive(Component)]
+pub struct HungerBar;
+
+#[derive(Component)]
+pub struct HungerBarFill;
+
+// Shown for as long as the slow motion pickup is slowing the snake down
+#[derive(Component)]
+pub struct SlowMotionIcon;
+
+// Drawn around the head while it holds a shield, as a child of it
+#[derive(Component)]
+pub struct ShieldOutline;
+
+// The seconds of ghost mode left, shown while it lasts
+#[derive(Component)]
+pub struct GhostTimerText;
+
+// The time left in a time attack run
+#[derive(Component)]
+pub struct RoundTimerText;
+
+// The seconds until the next ring of walls in survival
+#[derive(Component)]
+pub struct ShrinkTimerText;
+
+// The seconds left on the `Countdown`
+#[derive(Component)]
+pub struct CountdownText;
+
+// The title screen shown in the menu
+#[derive(Component)]
+pub struct MenuScreen;
+
+// A box over everything else asking to confirm something, see
+// `spawn_confirm_dialog`
+#[derive(Component)]
+pub struct ConfirmDialog;
+
+// The entries of the main menu, from top to bottom
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+pub enum MenuItem {
+    // Picks up the saved game, greyed out when there is none
+    Continue,
+    Play,
+    // Today's `DailyChallenge`, showing whether it was played yet
+    Daily,
+    // Scores as much as possible before the `RoundTimer` runs out
+    TimeAttack,
+    // Plays through the levels of `CAMPAIGN` one after the other
+    Campaign,
+    // Two snakes on one keyboard
+    Versus,
+    // One snake while the walls close in, see `ShrinkingArena`
+    Survival,
+    Difficulty,
+    // One life or `THREE_LIVES`, for every mode but versus
+    Lives,
+    // Lists the achievements, see `AchievementsScreen`
+    Achievements,
+    // Shows the lifetime `Statistics`
+    Statistics,
+    // Switches between the `ThemeName`s
+    Theme,
+    // Switches between the `SnakeSwatch`es
+    SnakeColor,
+    // Turns the colorblind mode on and off, see `AccessibilityPlugin`
+    Colorblind,
+    // Switches between the `Language`s of the `Locale`
+    Language,
+    // Starts a game with the `TutorialPlugin`'s steps shown again
+    Tutorial,
+    Quit,
+}
+
+impl MenuItem {
+    pub const ALL: [MenuItem; 17] = [
+        MenuItem::Continue,
+        MenuItem::Play,
+        MenuItem::Daily,
+        MenuItem::TimeAttack,
+        MenuItem::Campaign,
+        MenuItem::Versus,
+        MenuItem::Survival,
+        MenuItem::Difficulty,
+        MenuItem::Lives,
+        MenuItem::Achievements,
+        MenuItem::Statistics,
+        MenuItem::Theme,
+        MenuItem::SnakeColor,
+        MenuItem::Colorblind,
+        MenuItem::Language,
+        MenuItem::Tutorial,
+        MenuItem::Quit,
+    ];
+
+    pub fn label(
+        self,
+        locale: &Locale,
+        difficulty: Difficulty,
+        lives: StartingLives,
+        settings: &DisplaySettings,
+        high_scores: &HighScores,
+    ) -> String {
+        match self {
+            MenuItem::Continue => locale.get("menu.continue").to_string(),
+            MenuItem::Play => locale.get("menu.play").to_string(),
+            MenuItem::Daily => match high_scores.daily_best(&DailyDate::today().key()) {
+                Some(best) => locale.text("menu.daily_best", &[("best", &best)]),
+                None => locale.get("menu.daily_unplayed").to_string(),
+            },
+            MenuItem::TimeAttack => locale.get("menu.time_attack").to_string(),
+            MenuItem::Campaign => locale.get("menu.campaign").to_string(),
+            MenuItem::Versus => locale.get("menu.versus").to_string(),
+            MenuItem::Survival => locale.get("menu.survival").to_string(),
+            MenuItem::Difficulty => locale.text(
+                "menu.difficulty",
+                &[
+                    ("difficulty", &difficulty_label(locale, difficulty)),
+                    ("best", &high_scores.best(difficulty)),
+                ],
+            ),
+            MenuItem::Lives => locale.text("menu.lives", &[("lives", &*lives)]),
+            MenuItem::Achievements => locale.get("menu.achievements").to_string(),
+            MenuItem::Statistics => locale.get("menu.statistics").to_string(),
+            MenuItem::Theme => locale.text(
+                "menu.theme",
+                &[("theme", &theme_label(locale, settings.theme))],
+            ),
+            MenuItem::SnakeColor => locale.text(
+                "menu.snake_color",
+                &[("color", &swatch_label(locale, settings.snake_swatch))],
+            ),
+            MenuItem::Colorblind => locale.text(
+                "menu.colorblind",
+                &[("state", &on_off_label(locale, settings.colorblind))],
+            ),
+            MenuItem::Language => {
+                locale.text("menu.language", &[("language", &settings.language.label())])
+            }
+            MenuItem::Tutorial => locale.get("menu.tutorial").to_string(),
+            MenuItem::Quit => locale.get("menu.quit").to_string(),
+        }
+    }
+}
+
+// The difficulty's name in the language shown. Its `label` stays English for
+// the files it is written to.
+pub fn difficulty_label(locale: &Locale, difficulty: Difficulty) -> &str {
+    locale.get(match difficulty {
+        Difficulty::Easy => "difficulty.easy",
+        Difficulty::Normal => "difficulty.normal",
+        Difficulty::Hard => "difficulty.hard",
+    })
+}
+
+pub fn theme_label(locale: &Locale, theme: ThemeName) -> &str {
+    locale.get(match theme {
+        ThemeName::Classic => "theme.classic",
+        ThemeName::Dark => "theme.dark",
+        ThemeName::HighContrast => "theme.high_contrast",
+        ThemeName::RetroAmber => "theme.retro_amber",
+    })
+}
+
+pub fn swatch_label(locale: &Locale, swatch: SnakeSwatch) -> &str {
+    locale.get(match swatch {
+        SnakeSwatch::Theme => "swatch.theme",
+        SnakeSwatch::Green => "swatch.green",
+        SnakeSwatch::Blue => "swatch.blue",
+        SnakeSwatch::Red => "swatch.red",
+        SnakeSwatch::Orange => "swatch.orange",
+        SnakeSwatch::Purple => "swatch.purple",
+        SnakeSwatch::White => "swatch.white",
+    })
+}
+
+// What ended the run, in a sentence for the game over screen
+pub fn death_message(locale: &Locale, last_death: &LastDeath) -> Option<String> {
+    let key = match last_death.cause? {
+        DeathCause::Wall(WallLocation::Left) => "death.wall.left",
+        DeathCause::Wall(WallLocation::Right) => "death.wall.right",
+        DeathCause::Wall(WallLocation::Bottom) => "death.wall.bottom",
+        DeathCause::Wall(WallLocation::Top) => "death.wall.top",
+        DeathCause::Obstacle => "death.obstacle",
+        DeathCause::SelfCollision => "death.self_collision",
+        DeathCause::Poison => "death.poison",
+        DeathCause::Crushed => "death.crushed",
+        DeathCause::Hunter => "death.hunter",
+        DeathCause::Starved => "death.starved",
+        DeathCause::TimeUp => "death.time_up",
+    };
+    Some(locale.text(key, &[("length", &last_death.length)]))
+}
+
+// The results of the run on the game over screen, labels and values. The
+// time survived in minutes and seconds.
+pub fn results_rows(locale: &Locale, run: &RunStats) -> [(&'static str, String); 7] {
+    let survived = run.duration_secs as u32;
+    let speed = format!("{:.1}", run.average_speed());
+    [
+        ("results.score", run.score.to_string()),
+        ("results.length", run.length.to_string()),
+        ("results.foods_eaten", run.foods_eaten.to_string()),
+        ("results.bonus_foods", run.bonus_foods_eaten.to_string()),
+        (
+            "results.best_combo",
+            locale.text(
+                "results.best_combo_value",
+                &[("combo", &run.best_combo.min(MAX_COMBO))],
+            ),
+        ),
+        (
+            "results.time",
+            format!("{}:{:02}", survived / 60, survived % 60),
+        ),
+        (
+            "results.speed",
+            locale.text("results.speed_value", &[("speed", &speed)]),
+        ),
+    ]
+}
+
+pub fn on_off_label(locale: &Locale, on: bool) -> &str {
+    locale.get(if on { "common.on" } else { "common.off" })
+}
+
+// Index into `MenuItem::ALL` of the highlighted entry
+#[derive(Default, Deref, DerefMut)]
+pub struct MenuSelection(pub usize);
+
+// The screen summing up a finished run
+#[derive(Component)]
+pub struct GameOverScreen;
+
+// The box of `results_rows` on the `GameOverScreen`
+#[derive(Component)]
+pub struct ResultsPanel;
+
+// "Level Complete", shown between two levels of the campaign
+#[derive(Component)]
+pub struct LevelCompleteScreen;
+
+// The screen summing up a finished campaign
+#[derive(Component)]
+pub struct CampaignCompleteScreen;
+
+// Every achievement and whether it is unlocked yet
+#[derive(Component)]
+pub struct AchievementsScreen;
+
+// The lifetime statistics
+#[derive(Component)]
+pub struct StatisticsScreen;
+
+// Slides into the corner when an achievement is unlocked, and out again
+// once `timer` runs out
+#[derive(Component)]
+pub struct AchievementToast {
+    pub timer: Timer,
+}
+
+// The top runs, shown on the menu and the game over screen
+#[derive(Component)]
+pub struct LeaderboardTable;
+
+// Asks for initials on the game over screen when the run made the leaderboard
+#[derive(Component)]
+pub struct InitialsPrompt;
+
+// Size in pixels of one frame of the food sprite sheet
+const FOOD_FRAME_SIZE: f32 = 32.0;
+const FOOD_FRAMES: usize = 4;
+pub const FOOD_FRAME_SECONDS: f32 = 0.15;
+// Bonus food starts blinking when this much of its time is left
+const BONUS_BLINK_SECONDS: f32 = 1.0;
+// Times per second it turns on or off, expiring food blinks as fast
+const BONUS_BLINK_RATE: f32 = 8.0;
+
+// The images the snake and the food are drawn with. They are grayscale and
+// tinted with the colors from the config.
+pub struct SpriteTextures {
+    pub head: Handle<Image>,
+    pub body: Handle<Image>,
+    // The frames of the food's pulsing animation
+    pub food: Handle<TextureAtlas>,
+    // What the food looks like in colorblind mode instead
+    pub food_circle: Handle<Image>,
+}
+
+// Moves a sprite sheet on to its next frame every time it finishes
+#[derive(Component, Deref, DerefMut)]
+pub struct AnimationTimer(pub Timer);
+
+// A sprite whose texture hasn't finished loading yet. It is drawn as a flat
+// rectangle instead if the texture turns out to be missing.
+#[derive(Component)]
+pub struct PendingTexture;
+
+// Everything that is only there to be looked at: the camera, the sprites
+// and the text on screen
+pub struct SnakeRenderPlugin;
+
+impl Plugin for SnakeRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(ParticlePlugin)
+            .add_plugin(PopupPlugin)
+            .add_plugin(EatPulsePlugin)
+            .add_plugin(PauseMenuPlugin)
+            .add_plugin(ScreenshotPlugin)
+            .add_plugin(TutorialPlugin)
+            .add_plugin(AccessibilityPlugin)
+            .add_plugin(InterpolationPlugin)
+            .add_plugin(ThemePlugin)
+            .add_plugin(LocalePlugin)
+            .add_plugin(CheckerboardPlugin)
+            .add_plugin(CameraShakePlugin)
+            .add_plugin(ArenaCameraPlugin)
+            .add_plugin(DebugOverlayPlugin)
+            .add_plugin(ColliderDebugPlugin)
+            .add_plugin(DisplayPlugin)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_enter(GameState::Menu).with_system(spawn_menu))
+            .add_system_set(
+                SystemSet::on_update(GameState::Menu)
+                    .with_system(highlight_menu_selection.after(navigate_menu)),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Menu).with_system(despawn_menu))
+            .add_system_set(
+                SystemSet::on_enter(GameState::GameOver).with_system(spawn_game_over_screen),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::GameOver).with_system(despawn_game_over_screen),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::LevelComplete)
+                    .with_system(spawn_level_complete_screen),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::LevelComplete)
+                    .with_system(despawn_screen::<LevelCompleteScreen>),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::Achievements).with_system(spawn_achievements_screen),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Achievements)
+                    .with_system(despawn_screen::<AchievementsScreen>),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::Statistics).with_system(spawn_statistics_screen),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Statistics)
+                    .with_system(despawn_screen::<StatisticsScreen>),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::CampaignComplete)
+                    .with_system(spawn_campaign_complete_screen),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::CampaignComplete)
+                    .with_system(despawn_screen::<CampaignCompleteScreen>),
+            )
+            .add_system(show_quit_prompt)
+            .add_system(update_leaderboard_table)
+            .add_system(update_initials_prompt)
+            .add_system(update_scoreboard)
+            .add_system(update_multiplier_bar)
+            .add_system(update_boost_bar)
+            .add_system(update_hunger_bar)
+            .add_system(update_combo)
+            .add_system(update_lives_display)
+            .add_system(blink_respawning_snake)
+            .add_system(flash_dying_snake)
+            .add_system(show_achievement_toasts)
+            .add_system(slide_achievement_toasts)
+            .add_system(show_slow_motion_icon)
+            .add_system(update_ghost_timer)
+            .add_system(update_round_timer)
+            .add_system(update_countdown_text)
+            .add_system(update_shrink_timer)
+            .add_system(fade_ghost_snake)
+            .add_system(pop_spawning_food)
+            .add_system(show_shield_outline)
+            .add_system(fall_back_to_flat_colors)
+            .add_system(animate_sprite_sheets)
+            .add_system(blink_expiring_bonus_food)
+            .add_system(blink_expiring_food)
+            .add_system_to_stage(CoreStage::PostUpdate, add_sprites)
+            .add_system_to_stage(CoreStage::PostUpdate, add_portal_sprites)
+            .add_system_to_stage(CoreStage::PostUpdate, add_ice_sprites)
+            .add_system_to_stage(CoreStage::PostUpdate, add_hunter_sprite)
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                face_travel_direction.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+pub fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    locale: Res<Locale>,
+    config: Res<GameConfig>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    // Camera, seeing far enough back for the checkerboard
+    let mut camera = Camera2dBundle::default();
+    camera.projection.far += BACKGROUND_DEPTH;
+    commands.spawn_bundle(camera);
+
+    let food_sheet = TextureAtlas::from_grid(
+        asset_server.load("textures/food_sheet.png"),
+        Vec2::splat(FOOD_FRAME_SIZE),
+        FOOD_FRAMES,
+        1,
+    );
+    commands.insert_resource(SpriteTextures {
+        head: asset_server.load("textures/snake_head.png"),
+        body: asset_server.load("textures/snake_body.png"),
+        food: texture_atlases.add(food_sheet),
+        food_circle: images.add(food_circle_image()),
+    });
+
+    // Scoreboard
+    commands
+        .spawn_bundle(
+            TextBundle::from_sections([
+                TextSection::new(
+                    locale.get("hud.score"),
+                    TextStyle {
+                        font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                        font_size: SCOREBOARD_FONT_SIZE,
+                        color: TEXT_COLOR,
+                    },
+                ),
+                TextSection::from_style(TextStyle {
+                    font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                    font_size: SCOREBOARD_FONT_SIZE,
+                    color: SCORE_COLOR,
+                }),
+                TextSection::new(
+                    locale.get("hud.best"),
+                    TextStyle {
+                        font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                        font_size: SCOREBOARD_FONT_SIZE,
+                        color: TEXT_COLOR,
+                    },
+                ),
+                TextSection::from_style(TextStyle {
+                    font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                    font_size: SCOREBOARD_FONT_SIZE,
+                    color: SCORE_COLOR,
+                }),
+                TextSection::new(
+                    locale.get("hud.speed"),
+                    TextStyle {
+                        font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                        font_size: SCOREBOARD_FONT_SIZE,
+                        color: TEXT_COLOR,
+                    },
+                ),
+                TextSection::from_style(TextStyle {
+                    font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                    font_size: SCOREBOARD_FONT_SIZE,
+                    color: SCORE_COLOR,
+                }),
+            ])
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: SCOREBOARD_TEXT_PADDING,
+                    left: SCOREBOARD_TEXT_PADDING,
+                    ..default()
+                },
+                ..default()
+            }),
+        )
+        .insert(ScoreText);
+
+    // Score multiplier
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(
+                    Val::Px(MULTIPLIER_BAR_WIDTH),
+                    Val::Px(MULTIPLIER_BAR_HEIGHT),
+                ),
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: MULTIPLIER_BAR_PADDING,
+                    right: MULTIPLIER_BAR_PADDING,
+                    ..default()
+                },
+                ..default()
+            },
+            color: MULTIPLIER_BAR_BACKGROUND.into(),
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .insert(MultiplierBar)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                        ..default()
+                    },
+                    color: config.golden_food_color.into(),
+                    ..default()
+                })
+                .insert(MultiplierBarFill);
+        });
+
+    // Boost
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Px(BOOST_BAR_WIDTH), Val::Px(BOOST_BAR_HEIGHT)),
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: BOOST_BAR_PADDING,
+                    right: BOOST_BAR_PADDING,
+                    ..default()
+                },
+                ..default()
+            },
+            color: MULTIPLIER_BAR_BACKGROUND.into(),
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .insert(BoostBar)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                        ..default()
+                    },
+                    color: BOOST_COLOR.into(),
+                    ..default()
+                })
+                .insert(BoostBarFill);
+        });
+
+    // Hunger
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Px(BOOST_BAR_WIDTH), Val::Px(BOOST_BAR_HEIGHT)),
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: HUNGER_BAR_BOTTOM,
+                    right: BOOST_BAR_PADDING,
+                    ..default()
+                },
+                ..default()
+            },
+            color: MULTIPLIER_BAR_BACKGROUND.into(),
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .insert(HungerBar)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                        ..default()
+                    },
+                    color: HUNGER_COLOR.into(),
+                    ..default()
+                })
+                .insert(HungerBarFill);
+        });
+
+    // Lives
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: LIVES_PADDING,
+                    left: LIVES_PADDING,
+                    ..default()
+                },
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(LivesDisplay);
+
+    // Combo
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: COMBO_TOP,
+                    left: SCOREBOARD_TEXT_PADDING,
+                    ..default()
+                },
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .insert(ComboDisplay)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                        font_size: COMBO_FONT_SIZE,
+                        color: COMBO_COLOR,
+                    },
+                ))
+                .insert(ComboText);
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(COMBO_BAR_WIDTH), Val::Px(COMBO_BAR_HEIGHT)),
+                        margin: UiRect {
+                            left: COMBO_BAR_MARGIN,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    color: MULTIPLIER_BAR_BACKGROUND.into(),
+                    ..default()
+                })
+                .with_children(|bar| {
+                    bar.spawn_bundle(NodeBundle {
+                        style: Style {
+                            size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                            ..default()
+                        },
+                        color: COMBO_COLOR.into(),
+                        ..default()
+                    })
+                    .insert(ComboBarFill);
+                });
+        });
+
+    // Slow motion
+    commands
+        .spawn_bundle(
+            TextBundle::from_section(
+                locale.get("hud.slow"),
+                TextStyle {
+                    font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                    font_size: SLOW_MOTION_ICON_SIZE,
+                    color: config.slow_motion_color,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: SLOW_MOTION_ICON_TOP,
+                    right: MULTIPLIER_BAR_PADDING,
+                    ..default()
+                },
+                ..default()
+            }),
+        )
+        .insert(Visibility { is_visible: false })
+        .insert(SlowMotionIcon);
+
+    // Ghost mode
+    commands
+        .spawn_bundle(
+            TextBundle::from_sections([
+                TextSection::new(
+                    locale.get("hud.ghost"),
+                    TextStyle {
+                        font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                        font_size: SLOW_MOTION_ICON_SIZE,
+                        color: config.ghost_color,
+                    },
+                ),
+                TextSection::from_style(TextStyle {
+                    font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                    font_size: SLOW_MOTION_ICON_SIZE,
+                    color: config.ghost_color,
+                }),
+            ])
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: GHOST_TIMER_TOP,
+                    right: MULTIPLIER_BAR_PADDING,
+                    ..default()
+                },
+                ..default()
+            }),
+        )
+        .insert(Visibility { is_visible: false })
+        .insert(GhostTimerText);
+
+    // Time attack clock and survival's shrink countdown, centered across the
+    // top of the window. Never both shown at once.
+    spawn_top_center_text(&mut commands, &asset_server, RoundTimerText);
+    spawn_top_center_text(&mut commands, &asset_server, ShrinkTimerText);
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                        font_size: COUNTDOWN_FONT_SIZE,
+                        color: TEXT_COLOR,
+                    },
+                ))
+                .insert(Visibility { is_visible: false })
+                .insert(CountdownText);
+        });
+}
+
+fn spawn_top_center_text(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    marker: impl Component,
+) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: ROUND_TIMER_TOP,
+                    ..default()
+                },
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                        font_size: ROUND_TIMER_FONT_SIZE,
+                        color: TEXT_COLOR,
+                    },
+                ))
+                .insert(Visibility { is_visible: false })
+                .insert(marker);
+        });
+}
+
+pub fn spawn_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut selection: ResMut<MenuSelection>,
+    difficulty: Res<Difficulty>,
+    starting_lives: Res<StartingLives>,
+    high_scores: Res<HighScores>,
+    slot: Res<SavedGameSlot>,
+    theme: Res<Theme>,
+    settings: Res<DisplaySettings>,
+    locale: Res<Locale>,
+) {
+    // Continuing is the likely choice when there is something to continue
+    let first = if slot.saved.is_some() {
+        MenuItem::Continue
+    } else {
+        MenuItem::Play
+    };
+    **selection = MenuItem::ALL
+        .iter()
+        .position(|item| *item == first)
+        .unwrap();
+    let font = asset_server.load("fonts/DejaVuSans-Bold.ttf");
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(MenuScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_section(
+                locale.get("menu.title"),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: TITLE_FONT_SIZE,
+                    color: theme.snake,
+                },
+            ));
+            for item in MenuItem::ALL {
+                parent
+                    .spawn_bundle(
+                        TextBundle::from_section(
+                            item.label(
+                                &locale,
+                                *difficulty,
+                                *starting_lives,
+                                &settings,
+                                &high_scores,
+                            ),
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: MENU_FONT_SIZE,
+                                color: TEXT_COLOR,
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(MENU_ITEM_SPACING),
+                            ..default()
+                        }),
+                    )
+                    .insert(item);
+            }
+            parent
+                .spawn_bundle(leaderboard_table(font))
+                .insert(LeaderboardTable);
+        });
+}
+
+pub fn highlight_menu_selection(
+    selection: Res<MenuSelection>,
+    difficulty: Res<Difficulty>,
+    starting_lives: Res<StartingLives>,
+    high_scores: Res<HighScores>,
+    slot: Res<SavedGameSlot>,
+    settings: Res<DisplaySettings>,
+    locale: Res<Locale>,
+    mut query: Query<(&MenuItem, &mut Text)>,
+) {
+    if !selection.is_changed()
+        && !difficulty.is_changed()
+        && !starting_lives.is_changed()
+        && !settings.is_changed()
+        && !locale.is_changed()
+    {
+        return;
+    }
+
+    for (item, mut text) in &mut query {
+        text.sections[0].value = item.label(
+            &locale,
+            *difficulty,
+            *starting_lives,
+            &settings,
+            &high_scores,
+        );
+        text.sections[0].style.color = if *item == MenuItem::ALL[**selection] {
+            SELECTED_MENU_ITEM_COLOR
+        } else if *item == MenuItem::Continue && slot.saved.is_none() {
+            DISABLED_MENU_ITEM_COLOR
+        } else {
+            TEXT_COLOR
+        };
+    }
+}
+
+// The question and below it the keys that answer it
+pub fn spawn_confirm_dialog(
+    commands: &mut Commands,
+    font: Handle<Font>,
+    question: &str,
+    hint: &str,
+) -> Entity {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: DIALOG_BACKGROUND.into(),
+            ..default()
+        })
+        .insert(ConfirmDialog)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::ColumnReverse,
+                        align_items: AlignItems::Center,
+                        padding: UiRect::all(DIALOG_PADDING),
+                        ..default()
+                    },
+                    color: DIALOG_PANEL_COLOR.into(),
+                    ..default()
+                })
+                .with_children(|panel| {
+                    panel.spawn_bundle(TextBundle::from_section(
+                        question,
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: MENU_FONT_SIZE,
+                            color: TEXT_COLOR,
+                        },
+                    ));
+                    panel.spawn_bundle(TextBundle::from_section(
+                        hint,
+                        TextStyle {
+                            font,
+                            font_size: DIALOG_HINT_SIZE,
+                            color: TEXT_COLOR,
+                        },
+                    ));
+                });
+        })
+        .id()
+}
+
+// Asks before quitting for as long as the `QuitPrompt` is up
+pub fn show_quit_prompt(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    locale: Res<Locale>,
+    prompt: Res<QuitPrompt>,
+    dialog_query: Query<Entity, With<ConfirmDialog>>,
+) {
+    if !prompt.is_changed() {
+        return;
+    }
+    for dialog in &dialog_query {
+        commands.entity(dialog).despawn_recursive();
+    }
+    if **prompt {
+        spawn_confirm_dialog(
+            &mut commands,
+            asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+            locale.get("quit.title"),
+            locale.get("quit.hint"),
+        );
+    }
+}
+
+pub fn despawn_menu(mut commands: Commands, query: Query<Entity, With<MenuScreen>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// How the daily run went against the earlier attempts of the day, of which
+// only the best is kept
+fn daily_result(locale: &Locale, date: &DailyDate, high_scores: &HighScores) -> (String, Color) {
+    let date = date.key();
+    match (high_scores.last_was_best, high_scores.previous_daily) {
+        (true, Some(previous)) => (
+            locale.text(
+                "daily.new_best",
+                &[("date", &date), ("previous", &previous)],
+            ),
+            NEW_BEST_COLOR,
+        ),
+        (true, None) => (
+            locale.text("daily.first_attempt", &[("date", &date)]),
+            TEXT_COLOR,
+        ),
+        (false, Some(previous)) => (
+            locale.text("daily.best", &[("date", &date), ("best", &previous)]),
+            TEXT_COLOR,
+        ),
+        (false, None) => (locale.text("daily.title", &[("date", &date)]), TEXT_COLOR),
+    }
+}
+
+pub fn spawn_game_over_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    scoreboard: Res<Scoreboard>,
+    difficulty: Res<Difficulty>,
+    high_scores: Res<HighScores>,
+    rng: Res<GameRng>,
+    (mode, tally, theme): (Res<GameMode>, Res<VersusTally>, Res<Theme>),
+    (round_timer, arena, run): (Res<RoundTimer>, Res<ShrinkingArena>, Res<RunStats>),
+    (daily, last_death): (Res<DailyChallenge>, Res<LastDeath>),
+    locale: Res<Locale>,
+) {
+    if *mode == GameMode::Versus {
+        spawn_round_over_screen(
+            &mut commands,
+            &asset_server,
+            &locale,
+            &scoreboard,
+            &tally,
+            &theme,
+        );
+        return;
+    }
+    // Time attack keeps no best scores, it only says how long the run lasted
+    let title = locale.get(if *mode == GameMode::TimeAttack && round_timer.finished() {
+        "game_over.time_up"
+    } else {
+        "game_over.title"
+    });
+    let (best_text, best_color) = if *mode == GameMode::TimeAttack {
+        (
+            locale.text(
+                "game_over.time_attack",
+                &[
+                    ("survived", &round_timer.elapsed_secs().ceil()),
+                    ("total", &TIME_ATTACK_SECONDS),
+                ],
+            ),
+            TEXT_COLOR,
+        )
+    } else if *mode == GameMode::Survival {
+        (
+            locale.text("game_over.survival", &[("rings", &arena.rings)]),
+            TEXT_COLOR,
+        )
+    } else if *mode == GameMode::Daily {
+        daily_result(&locale, &daily.0, &high_scores)
+    } else if high_scores.last_was_best {
+        (
+            locale.text(
+                "game_over.new_best",
+                &[("difficulty", &difficulty_label(&locale, *difficulty))],
+            ),
+            NEW_BEST_COLOR,
+        )
+    } else {
+        (
+            locale.text(
+                "game_over.best",
+                &[
+                    ("difficulty", &difficulty_label(&locale, *difficulty)),
+                    ("best", &high_scores.best(*difficulty)),
+                ],
+            ),
+            TEXT_COLOR,
+        )
+    };
+    let death = death_message(&locale, &last_death).unwrap_or_default();
+    let font = asset_server.load("fonts/DejaVuSans-Bold.ttf");
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: GAME_OVER_BACKGROUND.into(),
+            ..default()
+        })
+        .insert(GameOverScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_section(
+                title,
+                TextStyle {
+                    font: font.clone(),
+                    font_size: GAME_OVER_FONT_SIZE,
+                    color: TEXT_COLOR,
+                },
+            ));
+            parent.spawn_bundle(TextBundle::from_section(
+                death,
+                TextStyle {
+                    font: font.clone(),
+                    font_size: MENU_FONT_SIZE,
+                    color: TEXT_COLOR,
+                },
+            ));
+            parent.spawn_bundle(TextBundle::from_section(
+                best_text,
+                TextStyle {
+                    font: font.clone(),
+                    font_size: MENU_FONT_SIZE,
+                    color: best_color,
+                },
+            ));
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::ColumnReverse,
+                        align_items: AlignItems::FlexStart,
+                        padding: UiRect::all(RESULTS_PADDING),
+                        margin: UiRect::all(RESULTS_PADDING),
+                        ..default()
+                    },
+                    color: RESULTS_BACKGROUND.into(),
+                    ..default()
+                })
+                .insert(ResultsPanel)
+                .with_children(|panel| {
+                    for (label, value) in results_rows(&locale, &run) {
+                        let style = |color| TextStyle {
+                            font: font.clone(),
+                            font_size: RESULTS_FONT_SIZE,
+                            color,
+                        };
+                        panel.spawn_bundle(TextBundle::from_sections([
+                            TextSection::new(format!("{}: ", locale.get(label)), style(TEXT_COLOR)),
+                            TextSection::new(value, style(SCORE_COLOR)),
+                        ]));
+                    }
+                });
+            // Passing this to `--seed` replays the same food placement
+            parent.spawn_bundle(TextBundle::from_section(
+                locale.text("game_over.seed", &[("seed", &rng.seed)]),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: MENU_FONT_SIZE,
+                    color: TEXT_COLOR,
+                },
+            ));
+            parent.spawn_bundle(TextBundle::from_section(
+                locale.get("game_over.hint"),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: MENU_FONT_SIZE,
+                    color: TEXT_COLOR,
+                },
+            ));
+            parent
+                .spawn_bundle(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: MENU_FONT_SIZE,
+                        color: NEW_BEST_COLOR,
+                    },
+                ))
+                .insert(InitialsPrompt);
+            parent
+                .spawn_bundle(leaderboard_table(font))
+                .insert(LeaderboardTable);
+        });
+}
+
+// Shown after a versus round instead of the game over screen: who won it,
+// in their snake's color, and the rounds each player won so far
+fn spawn_round_over_screen(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    locale: &Locale,
+    scoreboard: &Scoreboard,
+    tally: &VersusTally,
+    theme: &Theme,
+) {
+    let (result, result_color) = match tally.last_winner {
+        Some(Player::ONE) => ("round_over.player_one_wins", theme.snake),
+        Some(_) => ("round_over.player_two_wins", theme.player_two),
+        None => ("round_over.draw", TEXT_COLOR),
+    };
+    let font = asset_server.load("fonts/DejaVuSans-Bold.ttf");
+    let line = |text: String, color: Color| {
+        TextBundle::from_section(
+            text,
+            TextStyle {
+                font: font.clone(),
+                font_size: MENU_FONT_SIZE,
+                color,
+            },
+        )
+    };
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: GAME_OVER_BACKGROUND.into(),
+            ..default()
+        })
+        .insert(GameOverScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_section(
+                locale.get(result),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: GAME_OVER_FONT_SIZE,
+                    color: result_color,
+                },
+            ));
+            parent.spawn_bundle(line(
+                locale.text(
+                    "round_over.score",
+                    &[("one", &scoreboard.score), ("two", &scoreboard.player_two)],
+                ),
+                TEXT_COLOR,
+            ));
+            parent.spawn_bundle(line(
+                locale.text(
+                    "round_over.rounds_won",
+                    &[("one", &tally.wins[0]), ("two", &tally.wins[1])],
+                ),
+                SCORE_COLOR,
+            ));
+            parent.spawn_bundle(line(locale.get("round_over.hint").to_string(), TEXT_COLOR));
+        });
+}
+
+// Empty until `update_leaderboard_table` fills it in
+fn leaderboard_table(font: Handle<Font>) -> TextBundle {
+    TextBundle::from_section(
+        "",
+        TextStyle {
+            font,
+            font_size: LEADERBOARD_FONT_SIZE,
+            color: TEXT_COLOR,
+        },
+    )
+    .with_style(Style {
+        margin: UiRect {
+            top: LEADERBOARD_MARGIN,
+            ..default()
+        },
+        ..default()
+    })
+}
+
+pub fn update_leaderboard_table(
+    leaderboard: Res<Leaderboard>,
+    locale: Res<Locale>,
+    mut query: Query<(&mut Text, ChangeTrackers<Text>), With<LeaderboardTable>>,
+) {
+    for (mut text, tracker) in &mut query {
+        if !leaderboard.is_changed() && !tracker.is_added() {
+            continue;
+        }
+        text.sections[0].value = if leaderboard.entries.is_empty() {
+            locale.get("leaderboard.empty").to_string()
+        } else {
+            leaderboard
+                .entries
+                .iter()
+                .enumerate()
+                .map(|(index, entry)| {
+                    format!(
+                        "{:>2}. {}  {:>4}  {}",
+                        index + 1,
+                        entry.initials,
+                        entry.score,
+                        difficulty_label(&locale, entry.difficulty)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+    }
+}
+
+// Shows the initials typed so far, with a blank for each one still missing
+pub fn update_initials_prompt(
+    initials: Res<InitialsEntry>,
+    locale: Res<Locale>,
+    mut query: Query<&mut Text, With<InitialsPrompt>>,
+) {
+    for mut text in &mut query {
+        text.sections[0].value = match &**initials {
+            Some(entry) => locale.text(
+                "leaderboard.initials",
+                &[
+                    ("size", &LEADERBOARD_SIZE),
+                    (
+                        "initials",
+                        &format!("{entry}{}", "_".repeat(INITIALS_LENGTH - entry.len())),
+                    ),
+                ],
+            ),
+            None => String::new(),
+        };
+    }
+}
+
+pub fn despawn_game_over_screen(
+    mut commands: Commands,
+    query: Query<Entity, With<GameOverScreen>>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+pub fn spawn_level_complete_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    campaign: Res<Campaign>,
+    scoreboard: Res<Scoreboard>,
+    locale: Res<Locale>,
+) {
+    let Some(level) = &**campaign else {
+        return;
+    };
+    let font = asset_server.load("fonts/DejaVuSans-Bold.ttf");
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(LevelCompleteScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_section(
+                locale.text("level.complete", &[("level", &(level.index + 1))]),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: PAUSED_FONT_SIZE,
+                    color: TEXT_COLOR,
+                },
+            ));
+            parent.spawn_bundle(TextBundle::from_section(
+                locale.text("level.score", &[("score", &scoreboard.score)]),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: MENU_FONT_SIZE,
+                    color: SCORE_COLOR,
+                },
+            ));
+        });
+}
+
+pub fn spawn_campaign_complete_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    scoreboard: Res<Scoreboard>,
+    difficulty: Res<Difficulty>,
+    high_scores: Res<HighScores>,
+    locale: Res<Locale>,
+) {
+    let difficulty_name = difficulty_label(&locale, *difficulty);
+    let best_text = if high_scores.last_was_best {
+        locale.text("game_over.new_best", &[("difficulty", &difficulty_name)])
+    } else {
+        locale.text(
+            "game_over.best",
+            &[
+                ("difficulty", &difficulty_name),
+                ("best", &high_scores.best(*difficulty)),
+            ],
+        )
+    };
+    let font = asset_server.load("fonts/DejaVuSans-Bold.ttf");
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: GAME_OVER_BACKGROUND.into(),
+            ..default()
+        })
+        .insert(CampaignCompleteScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_section(
+                locale.get("campaign.title"),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: GAME_OVER_FONT_SIZE,
+                    color: NEW_BEST_COLOR,
+                },
+            ));
+            parent.spawn_bundle(TextBundle::from_section(
+                locale.text("campaign.cleared", &[("count", &CAMPAIGN.len())]),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: MENU_FONT_SIZE,
+                    color: TEXT_COLOR,
+                },
+            ));
+            parent.spawn_bundle(TextBundle::from_sections([
+                TextSection::new(
+                    locale.get("game_over.final_score"),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: MENU_FONT_SIZE,
+                        color: TEXT_COLOR,
+                    },
+                ),
+                TextSection::new(
+                    scoreboard.score.to_string(),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: MENU_FONT_SIZE,
+                        color: SCORE_COLOR,
+                    },
+                ),
+            ]));
+            parent.spawn_bundle(TextBundle::from_section(
+                best_text,
+                TextStyle {
+                    font: font.clone(),
+                    font_size: MENU_FONT_SIZE,
+                    color: TEXT_COLOR,
+                },
+            ));
+            parent.spawn_bundle(TextBundle::from_section(
+                locale.get("campaign.hint"),
+                TextStyle {
+                    font,
+                    font_size: MENU_FONT_SIZE,
+                    color: TEXT_COLOR,
+                },
+            ));
+        });
+}
+
+pub fn spawn_achievements_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    achievements: Res<Achievements>,
+    locale: Res<Locale>,
+) {
+    let font = asset_server.load("fonts/DejaVuSans-Bold.ttf");
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: GAME_OVER_BACKGROUND.into(),
+            ..default()
+        })
+        .insert(AchievementsScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_section(
+                locale.get("achievements.title"),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: GAME_OVER_FONT_SIZE,
+                    color: TEXT_COLOR,
+                },
+            ));
+            parent.spawn_bundle(TextBundle::from_section(
+                locale.text(
+                    "achievements.count",
+                    &[
+                        ("unlocked", &achievements.unlocked.len()),
+                        ("total", &Achievement::ALL.len()),
+                    ],
+                ),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: MENU_FONT_SIZE,
+                    color: TEXT_COLOR,
+                },
+            ));
+            for achievement in Achievement::ALL {
+                let (status, color) = if achievements.is_unlocked(achievement) {
+                    ("achievements.unlocked", SCORE_COLOR)
+                } else {
+                    ("achievements.locked", LOCKED_ACHIEVEMENT_COLOR)
+                };
+                parent.spawn_bundle(
+                    TextBundle::from_section(
+                        locale.text(
+                            "achievements.entry",
+                            &[
+                                ("title", &achievement.title(&locale)),
+                                ("description", &achievement.description(&locale)),
+                                ("status", &locale.get(status)),
+                            ],
+                        ),
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: LEADERBOARD_FONT_SIZE,
+                            color,
+                        },
+                    )
+                    .with_style(Style {
+                        margin: UiRect::all(MENU_ITEM_SPACING),
+                        ..default()
+                    }),
+                );
+            }
+            parent.spawn_bundle(
+                TextBundle::from_section(
+                    locale.get("common.back_hint"),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: LEADERBOARD_FONT_SIZE,
+                        color: TEXT_COLOR,
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect {
+                        top: LEADERBOARD_MARGIN,
+                        ..default()
+                    },
+                    ..default()
+                }),
+            );
+        });
+}
+
+// The distance in cells and the longest game in minutes and seconds
+pub fn spawn_statistics_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    statistics: Res<Statistics>,
+    config: Res<GameConfig>,
+    locale: Res<Locale>,
+) {
+    let longest_game = statistics.longest_game_secs as u32;
+    let cells = format!("{:.0}", statistics.distance / config.snake_size.x);
+    let rows = [
+        (
+            "statistics.games_played",
+            statistics.games_played.to_string(),
+        ),
+        ("statistics.foods_eaten", statistics.foods_eaten.to_string()),
+        (
+            "statistics.distance",
+            locale.text("statistics.distance_cells", &[("cells", &cells)]),
+        ),
+        (
+            "statistics.longest_snake",
+            statistics.longest_snake.to_string(),
+        ),
+        (
+            "statistics.longest_game",
+            format!("{}:{:02}", longest_game / 60, longest_game % 60),
+        ),
+    ];
+    let font = asset_server.load("fonts/DejaVuSans-Bold.ttf");
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: GAME_OVER_BACKGROUND.into(),
+            ..default()
+        })
+        .insert(StatisticsScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_section(
+                locale.get("statistics.title"),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: GAME_OVER_FONT_SIZE,
+                    color: TEXT_COLOR,
+                },
+            ));
+            for (label, value) in rows {
+                let style = |color| TextStyle {
+                    font: font.clone(),
+                    font_size: MENU_FONT_SIZE,
+                    color,
+                };
+                parent.spawn_bundle(
+                    TextBundle::from_sections([
+                        TextSection::new(format!("{}: ", locale.get(label)), style(TEXT_COLOR)),
+                        TextSection::new(value, style(SCORE_COLOR)),
+                    ])
+                    .with_style(Style {
+                        margin: UiRect::all(MENU_ITEM_SPACING),
+                        ..default()
+                    }),
+                );
+            }
+            parent.spawn_bundle(
+                TextBundle::from_section(
+                    locale.get("common.back_hint"),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: LEADERBOARD_FONT_SIZE,
+                        color: TEXT_COLOR,
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect {
+                        top: LEADERBOARD_MARGIN,
+                        ..default()
+                    },
+                    ..default()
+                }),
+            );
+        });
+}
+
+// Every new achievement gets a toast of its own, above the ones still showing
+pub fn show_achievement_toasts(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    locale: Res<Locale>,
+    mut unlocked_events: EventReader<AchievementUnlocked>,
+    toast_query: Query<(), With<AchievementToast>>,
+) {
+    let showing = toast_query.iter().count();
+    for (index, event) in (showing..).zip(unlocked_events.iter()) {
+        let font = asset_server.load("fonts/DejaVuSans-Bold.ttf");
+        let style = |color| TextStyle {
+            font: font.clone(),
+            font_size: TOAST_FONT_SIZE,
+            color,
+        };
+        commands
+            .spawn_bundle(NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Px(TOAST_WIDTH), Val::Px(TOAST_HEIGHT)),
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        bottom: Val::Px(
+                            TOAST_PADDING + index as f32 * (TOAST_HEIGHT + TOAST_PADDING),
+                        ),
+                        right: Val::Px(-TOAST_WIDTH),
+                        ..default()
+                    },
+                    flex_direction: FlexDirection::ColumnReverse,
+                    justify_content: JustifyContent::Center,
+                    padding: UiRect::all(Val::Px(TOAST_PADDING)),
+                    ..default()
+                },
+                color: TOAST_BACKGROUND.into(),
+                ..default()
+            })
+            .insert(AchievementToast {
+                timer: Timer::from_seconds(TOAST_SECONDS, false),
+            })
+            .with_children(|parent| {
+                parent.spawn_bundle(TextBundle::from_section(
+                    locale.get("achievements.toast"),
+                    style(TEXT_COLOR),
+                ));
+                parent.spawn_bundle(TextBundle::from_section(
+                    event.achievement.title(&locale),
+                    style(NEW_BEST_COLOR),
+                ));
+            });
+    }
+}
+
+// In from the right edge, and back out over the last moment of the timer.
+// Runs in real time, so it carries on over the pause and the game over
+// screen.
+pub fn slide_achievement_toasts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut AchievementToast, &mut Style)>,
+) {
+    for (entity, mut toast, mut style) in &mut query {
+        toast.timer.tick(time.delta());
+        if toast.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let remaining = TOAST_SECONDS - toast.timer.elapsed_secs();
+        let shown = (toast.timer.elapsed_secs().min(remaining) / TOAST_SLIDE_SECONDS).min(1.0);
+        style.position.right = Val::Px(-TOAST_WIDTH + shown * (TOAST_WIDTH + TOAST_PADDING));
+    }
+}
+
+pub fn despawn_screen<T: Component>(mut commands: Commands, query: Query<Entity, With<T>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// The game logic only places things in the arena. Everything it spawns is
+// given a sprite here, colored by what it is.
+pub fn add_sprites(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    theme: Res<Theme>,
+    settings: Res<DisplaySettings>,
+    textures: Res<SpriteTextures>,
+    query: Query<
+        (
+            Entity,
+            &Hitbox,
+            Option<&Snake>,
+            Option<&Player>,
+            Option<&FoodKind>,
+            Option<&Obstacle>,
+            Option<&Crate>,
+            Option<&Collider>,
+            Option<&SpawnAnimation>,
+        ),
+        (
+            Or<(
+                Added<Snake>,
+                Added<SnakeSegment>,
+                Added<Food>,
+                Added<Collider>,
+            )>,
+            Without<Hunter>,
+        ),
+    >,
+) {
+    for (entity, hitbox, snake, player, food, obstacle, crate_box, collider, spawning) in &query {
+        let mut entity = commands.entity(entity);
+        entity.insert_bundle(VisibilityBundle::default());
+        // Only the walls are colliders without being food or an obstacle
+        let is_snake = snake.is_some() || collider.is_none();
+        let color = match crate_box {
+            Some(_) => config.crate_color,
+            None => theme.sprite_color(&config, food, player, is_snake, obstacle.is_some()),
+        };
+        if food.is_some() {
+            let scale = spawning.map_or(1.0, SpawnAnimation::scale);
+            insert_food_sprite(
+                &mut entity,
+                color,
+                **hitbox * scale,
+                &textures,
+                settings.colorblind,
+            );
+            continue;
+        }
+
+        let texture = match (snake, collider) {
+            (Some(_), _) => Some(&textures.head),
+            (None, Some(_)) => None,
+            (None, None) => Some(&textures.body),
+        };
+        entity.insert(Sprite {
+            color,
+            custom_size: Some(**hitbox),
+            ..default()
+        });
+        match texture {
+            Some(texture) => entity.insert(texture.clone()).insert(PendingTexture),
+            None => entity.insert(Handle::<Image>::default()),
+        };
+    }
+}
+
+// Bonus food blinks through its last second
+pub fn blink_expiring_bonus_food(mut query: Query<(&BonusFood, &mut Visibility)>) {
+    for (bonus, mut visibility) in &mut query {
+        let remaining = bonus.remaining_secs();
+        let visible = remaining > BONUS_BLINK_SECONDS
+            || ((remaining * BONUS_BLINK_RATE) as u32).is_multiple_of(2);
+        if visibility.is_visible != visible {
+            visibility.is_visible = visible;
+        }
+    }
+}
+
+// Expiring food blinks through its last `FOOD_BLINK_SECONDS` before it moves
+pub fn blink_expiring_food(mut query: Query<(&FoodLifetime, &mut Visibility)>) {
+    for (lifetime, mut visibility) in &mut query {
+        let remaining = lifetime.remaining_secs();
+        let visible = remaining > FOOD_BLINK_SECONDS
+            || ((remaining * BONUS_BLINK_RATE) as u32).is_multiple_of(2);
+        if visibility.is_visible != visible {
+            visibility.is_visible = visible;
+        }
+    }
+}
+
+// The portals are no collider, so `add_sprites` leaves them alone
+pub fn add_portal_sprites(mut commands: Commands, query: Query<(Entity, &Hitbox), Added<Portal>>) {
+    for (portal, hitbox) in &query {
+        commands
+            .entity(portal)
+            .insert(Sprite {
+                color: PORTAL_COLOR,
+                custom_size: Some(**hitbox),
+                ..default()
+            })
+            .insert(Handle::<Image>::default())
+            .insert_bundle(VisibilityBundle::default());
+    }
+}
+
+// The ice is no collider either
+pub fn add_ice_sprites(mut commands: Commands, query: Query<(Entity, &Hitbox), Added<IcePatch>>) {
+    for (ice, hitbox) in &query {
+        commands
+            .entity(ice)
+            .insert(Sprite {
+                color: ICE_COLOR,
+                custom_size: Some(**hitbox),
+                ..default()
+            })
+            .insert(Handle::<Image>::default())
+            .insert_bundle(VisibilityBundle::default());
+    }
+}
+
+// The hunter is drawn in the same red in every theme, `add_sprites` leaves
+// it alone
+pub fn add_hunter_sprite(mut commands: Commands, query: Query<(Entity, &Hitbox), Added<Hunter>>) {
+    for (hunter, hitbox) in &query {
+        commands
+            .entity(hunter)
+            .insert(Sprite {
+                color: HUNTER_COLOR,
+                custom_size: Some(**hitbox),
+                ..default()
+            })
+            .insert(Handle::<Image>::default())
+            .insert_bundle(VisibilityBundle::default());
+    }
+}
+
+// Swaps textures that failed to load for the default white image, leaving
+// only the tint. Sprite sheets become a plain sprite of the same color. A
+// head without its texture is given eyes, so it still shows which way it is
+// going.
+pub fn fall_back_to_flat_colors(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    texture_atlases: Res<Assets<TextureAtlas>>,
+    image_query: Query<
+        (Entity, &Handle<Image>, Option<&Hitbox>, Option<&Snake>),
+        With<PendingTexture>,
+    >,
+    sheet_query: Query<(Entity, &Handle<TextureAtlas>, &TextureAtlasSprite), With<PendingTexture>>,
+) {
+    for (entity, texture, hitbox, head) in &image_query {
+        match asset_server.get_load_state(texture) {
+            LoadState::Loaded => {
+                commands.entity(entity).remove::<PendingTexture>();
+            }
+            LoadState::Failed => {
+                let mut entity = commands.entity(entity);
+                entity
+                    .insert(Handle::<Image>::default())
+                    .remove::<PendingTexture>();
+                if let (Some(hitbox), Some(_)) = (hitbox, head) {
+                    entity.with_children(|parent| spawn_eyes(parent, **hitbox));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for (entity, atlas, sprite) in &sheet_query {
+        let Some(atlas) = texture_atlases.get(atlas) else {
+            continue;
+        };
+        match asset_server.get_load_state(&atlas.texture) {
+            LoadState::Loaded => {
+                commands.entity(entity).remove::<PendingTexture>();
+            }
+            LoadState::Failed => {
+                commands
+                    .entity(entity)
+                    .remove::<TextureAtlasSprite>()
+                    .remove::<Handle<TextureAtlas>>()
+                    .remove::<AnimationTimer>()
+                    .remove::<PendingTexture>()
+                    .insert(Sprite {
+                        color: sprite.color,
+                        custom_size: sprite.custom_size,
+                        ..default()
+                    })
+                    .insert(Handle::<Image>::default());
+            }
+            _ => {}
+        }
+    }
+}
+
+// Steps through the frames of every sprite sheet, holding still while the
+// game is paused
+pub fn animate_sprite_sheets(
+    time: Res<Time>,
+    paused: Res<Paused>,
+    texture_atlases: Res<Assets<TextureAtlas>>,
+    mut query: Query<(
+        &mut AnimationTimer,
+        &mut TextureAtlasSprite,
+        &Handle<TextureAtlas>,
+    )>,
+) {
+    if **paused {
+        return;
+    }
+
+    for (mut timer, mut sprite, atlas) in &mut query {
+        timer.tick(time.delta());
+        if timer.just_finished() {
+            if let Some(atlas) = texture_atlases.get(atlas) {
+                sprite.index = (sprite.index + 1) % atlas.textures.len();
+            }
+        }
+    }
+}
+
+// Two dark squares on the front half of a head facing right
+fn spawn_eyes(parent: &mut ChildBuilder, head_size: Vec2) {
+    for side in [-1.0, 1.0] {
+        parent.spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: EYE_COLOR,
+                custom_size: Some(Vec2::splat(EYE_SIZE)),
+                ..default()
+            },
+            transform: Transform::from_xyz(
+                head_size.x * EYE_FORWARD,
+                side * head_size.y * EYE_SPREAD,
+                EYE_Z,
+            ),
+            ..default()
+        });
+    }
+}
+
+// Turns the head, whose texture and eyes look to the right, the way it is
+// going. On the grid that is the way the next step goes, so a turn shows as
+// soon as the snake has taken it up. Runs after the gameplay systems and
+// before the transforms are propagated, so it is never a frame behind.
+pub fn face_travel_direction(
+    mut query: Query<(&mut Transform, &NextDirection, Option<&Velocity>), With<Snake>>,
+) {
+    for (mut transform, next_direction, velocity) in &mut query {
+        let heading = match velocity {
+            Some(velocity) if **velocity != Vec2::ZERO => **velocity,
+            _ => next_direction.cell_offset().as_vec2(),
+        };
+        let rotation = Quat::from_rotation_z(heading.y.atan2(heading.x));
+        if transform.rotation != rotation {
+            transform.rotation = rotation;
+        }
+    }
+}
+
+// In versus the best score makes way for the second player's
+pub fn update_scoreboard(
+    scoreboard: Res<Scoreboard>,
+    speed: Res<GameSpeed>,
+    (difficulty, mode, daily): (Res<Difficulty>, Res<GameMode>, Res<DailyChallenge>),
+    high_scores: Res<HighScores>,
+    locale: Res<Locale>,
+    mut query: Query<&mut Text, With<ScoreText>>,
+) {
+    let Ok(mut text) = query.get_single_mut() else {
+        return;
+    };
+    text.sections[1].value = scoreboard.score.to_string();
+    if *mode == GameMode::Versus {
+        text.sections[0].value = locale.get("hud.player_one").to_string();
+        text.sections[2].value = locale.get("hud.player_two").to_string();
+        text.sections[3].value = scoreboard.player_two.to_string();
+    } else {
+        text.sections[0].value = locale.get("hud.score").to_string();
+        text.sections[2].value = locale.get("hud.best").to_string();
+        // A run beating the record shows as the best while it is still going
+        let best = if *mode == GameMode::Daily {
+            high_scores.daily_best(&daily.0.key()).unwrap_or(0)
+        } else {
+            high_scores.best(*difficulty)
+        };
+        let best = best.max(scoreboard.score);
+        text.sections[3].value = best.to_string();
+    }
+    if locale.is_changed() {
+        text.sections[4].value = locale.get("hud.speed").to_string();
+    }
+    text.sections[5].value = format!("{:.1}x", **speed);
+}
+
+pub fn update_multiplier_bar(
+    multiplier: Res<ScoreMultiplier>,
+    mut bar_query: Query<&mut Visibility, With<MultiplierBar>>,
+    mut fill_query: Query<&mut Style, With<MultiplierBarFill>>,
+) {
+    if !multiplier.is_changed() {
+        return;
+    }
+    for mut visibility in &mut bar_query {
+        visibility.is_visible = multiplier.is_active();
+    }
+    let duration = multiplier.remaining.duration().as_secs_f32();
+    if duration == 0.0 {
+        return;
+    }
+    for mut style in &mut fill_query {
+        style.size.width = Val::Percent(100.0 * multiplier.remaining_secs() / duration);
+    }
+}
+
+pub fn update_boost_bar(
+    snake_query: Query<(&Player, &Boost), With<Snake>>,
+    changed_query: Query<(), (With<Snake>, Changed<Boost>)>,
+    removed: RemovedComponents<Boost>,
+    mut bar_query: Query<&mut Visibility, With<BoostBar>>,
+    mut fill_query: Query<&mut Style, With<BoostBarFill>>,
+) {
+    if changed_query.is_empty() && removed.iter().next().is_none() {
+        return;
+    }
+    let boost = snake_query
+        .iter()
+        .find(|(player, _)| **player == Player::ONE)
+        .map(|(_, boost)| *boost);
+    for mut visibility in &mut bar_query {
+        visibility.is_visible = boost.is_some();
+    }
+    let Some(boost) = boost else {
+        return;
+    };
+    for mut style in &mut fill_query {
+        style.size.width = Val::Percent(100.0 * boost.stamina);
+    }
+}
+
+pub fn update_hunger_bar(
+    hunger: Res<Hunger>,
+    config: Res<GameConfig>,
+    mode: Res<GameMode>,
+    state: Res<State<GameState>>,
+    mut bar_query: Query<&mut Visibility, With<HungerBar>>,
+    mut fill_query: Query<(&mut Style, &mut UiColor), With<HungerBarFill>>,
+) {
+    if !hunger.is_changed() && !state.is_changed() {
+        return;
+    }
+    let shown = config.hunger && *mode != GameMode::Versus && *state.current() != GameState::Menu;
+    for mut visibility in &mut bar_query {
+        visibility.is_visible = shown;
+    }
+    let color = if hunger.is_low() {
+        HUNGER_LOW_COLOR
+    } else {
+        HUNGER_COLOR
+    };
+    for (mut style, mut fill) in &mut fill_query {
+        style.size.width = Val::Percent(100.0 * hunger.fraction());
+        *fill = color.into();
+    }
+}
+
+pub fn update_combo(
+    combo: Res<Combo>,
+    locale: Res<Locale>,
+    mut display_query: Query<&mut Visibility, With<ComboDisplay>>,
+    mut text_query: Query<&mut Text, With<ComboText>>,
+    mut fill_query: Query<&mut Style, With<ComboBarFill>>,
+) {
+    if !combo.is_changed() && !locale.is_changed() {
+        return;
+    }
+    for mut visibility in &mut display_query {
+        visibility.is_visible = combo.count > 1;
+    }
+    for mut text in &mut text_query {
+        text.sections[0].value = locale.text("hud.combo", &[("factor", &combo.factor())]);
+    }
+    for mut style in &mut fill_query {
+        style.size.width = Val::Percent(100.0 * combo.remaining_secs() / COMBO_SECONDS);
+    }
+}
+
+// Rebuilt whenever a life is lost or won back, and hidden on the menu and in
+// a game played with only the one
+pub fn update_lives_display(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<Theme>,
+    lives: Res<Lives>,
+    mode: Res<GameMode>,
+    state: Res<State<GameState>>,
+    query: Query<Entity, With<LivesDisplay>>,
+) {
+    if !lives.is_changed() && !state.is_changed() {
+        return;
+    }
+    let shown = if *mode == GameMode::Versus || *state.current() == GameState::Menu {
+        0
+    } else {
+        **lives
+    };
+    for display in &query {
+        commands.entity(display).despawn_descendants();
+        if shown <= 1 {
+            continue;
+        }
+        commands.entity(display).with_children(|parent| {
+            for _ in 0..shown {
+                parent.spawn_bundle(ImageBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(LIFE_ICON_SIZE), Val::Px(LIFE_ICON_SIZE)),
+                        margin: UiRect {
+                            right: LIFE_ICON_SPACING,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    image: asset_server.load("textures/snake_head.png").into(),
+                    color: theme.snake.into(),
+                    ..default()
+                });
+            }
+        });
+    }
+}
+
+// Flickers the head that just lost a life, for as long as it holds still
+pub fn blink_respawning_snake(
+    mut query: Query<(&mut Visibility, Option<&Respawning>), With<Snake>>,
+) {
+    for (mut visibility, respawning) in &mut query {
+        let visible = respawning.is_none_or(|respawning| {
+            ((respawning.elapsed_secs() * RESPAWN_BLINKS_PER_SECOND) as u32).is_multiple_of(2)
+        });
+        if visibility.is_visible != visible {
+            visibility.is_visible = visible;
+        }
+    }
+}
+
+// Flashes the head of a snake out of lives red, and leaves it red while its
+// body goes
+pub fn flash_dying_snake(
+    theme: Res<Theme>,
+    config: Res<GameConfig>,
+    mut query: Query<(&mut Sprite, &Player, &Dying), With<Snake>>,
+) {
+    for (mut sprite, player, dying) in &mut query {
+        let red = !dying.flashing()
+            || ((dying.timer.elapsed_secs() * DEATH_FLASHES_PER_SECOND) as u32).is_multiple_of(2);
+        let mut color = if red {
+            DEATH_FLASH_COLOR
+        } else {
+            theme.sprite_color(&config, None, Some(player), true, false)
+        };
+        color.set_a(sprite.color.a());
+        sprite.color = color;
+    }
+}
+
+pub fn show_slow_motion_icon(
+    modifier: Res<SpeedModifier>,
+    locale: Res<Locale>,
+    mut query: Query<(&mut Text, &mut Visibility), With<SlowMotionIcon>>,
+) {
+    if !modifier.is_changed() && !locale.is_changed() {
+        return;
+    }
+    for (mut text, mut visibility) in &mut query {
+        visibility.is_visible = modifier.is_active();
+        text.sections[0].value = locale.get("hud.slow").to_string();
+    }
+}
+
+pub fn update_ghost_timer(
+    ghost: Res<GhostMode>,
+    locale: Res<Locale>,
+    mut query: Query<(&mut Text, &mut Visibility), With<GhostTimerText>>,
+) {
+    if !ghost.is_changed() && !locale.is_changed() {
+        return;
+    }
+    for (mut text, mut visibility) in &mut query {
+        visibility.is_visible = ghost.is_active();
+        text.sections[0].value = locale.get("hud.ghost").to_string();
+        text.sections[1].value = format!("{:.1}", ghost.remaining_secs());
+    }
+}
+
+// Minutes and seconds, rounded up so the clock only shows 0:00 once the
+// time is up. In the last seconds it flashes twice a second.
+pub fn update_round_timer(
+    round_timer: Res<RoundTimer>,
+    mode: Res<GameMode>,
+    state: Res<State<GameState>>,
+    mut query: Query<(&mut Text, &mut Visibility), With<RoundTimerText>>,
+) {
+    for (mut text, mut visibility) in &mut query {
+        visibility.is_visible =
+            *mode == GameMode::TimeAttack && *state.current() != GameState::Menu;
+        let remaining = round_timer.remaining_secs().max(0.0);
+        let seconds = remaining.ceil() as u32;
+        text.sections[0].value = format!("{}:{:02}", seconds / 60, seconds % 60);
+        let flashing = remaining <= ROUND_TIMER_WARNING_SECONDS
+            && ((remaining * 2.0) as u32).is_multiple_of(2);
+        text.sections[0].style.color = if flashing {
+            ROUND_TIMER_WARNING_COLOR
+        } else {
+            TEXT_COLOR
+        };
+    }
+}
+
+// Shows the whole seconds left on the `Countdown`, then "GO!" for a moment
+// as the snake sets off. Out of the way of "PAUSED" while paused.
+pub fn update_countdown_text(
+    time: Res<Time>,
+    countdown: Res<Countdown>,
+    paused: Res<Paused>,
+    state: Res<State<GameState>>,
+    locale: Res<Locale>,
+    mut go_left: Local<f32>,
+    mut query: Query<(&mut Text, &mut Visibility), With<CountdownText>>,
+) {
+    let label = if !countdown.finished() {
+        *go_left = GO_SECONDS;
+        let remaining = countdown.duration().as_secs_f32() - countdown.elapsed_secs();
+        Some(remaining.ceil().to_string())
+    } else if *go_left > 0.0 {
+        *go_left -= time.delta_seconds();
+        Some(locale.get("hud.go").to_string())
+    } else {
+        None
+    };
+
+    for (mut text, mut visibility) in &mut query {
+        visibility.is_visible =
+            label.is_some() && *state.current() == GameState::Playing && !**paused;
+        if let Some(label) = &label {
+            text.sections[0].value = label.clone();
+        }
+    }
+}
+
+// Hidden again once the arena is as small as it gets
+pub fn update_shrink_timer(
+    arena: Res<ShrinkingArena>,
+    mode: Res<GameMode>,
+    state: Res<State<GameState>>,
+    config: Res<GameConfig>,
+    bounds: Res<ArenaBounds>,
+    locale: Res<Locale>,
+    mut query: Query<(&mut Text, &mut Visibility), With<ShrinkTimerText>>,
+) {
+    for (mut text, mut visibility) in &mut query {
+        visibility.is_visible = *mode == GameMode::Survival
+            && *state.current() != GameState::Menu
+            && arena.can_shrink(&config, &bounds);
+        text.sections[0].value =
+            locale.text("hud.shrink", &[("seconds", &arena.remaining_secs().ceil())]);
+    }
+}
+
+// Draws new food at the size it has grown to so far, then at its full size
+pub fn pop_spawning_food(
+    mut query: Query<
+        (
+            &Hitbox,
+            Option<&SpawnAnimation>,
+            AnyOf<(&mut Sprite, &mut TextureAtlasSprite)>,
+        ),
+        With<Food>,
+    >,
+) {
+    for (hitbox, spawning, (sprite, atlas_sprite)) in &mut query {
+        let size = Some(**hitbox * spawning.map_or(1.0, SpawnAnimation::scale));
+        if let Some(mut sprite) = sprite {
+            if sprite.custom_size != size {
+                sprite.custom_size = size;
+            }
+        } else if let Some(mut sprite) = atlas_sprite {
+            if sprite.custom_size != size {
+                sprite.custom_size = size;
+            }
+        }
+    }
+}
+
+// Segments grown while the snake is a ghost fade in with the rest of it
+pub fn fade_ghost_snake(
+    ghost: Res<GhostMode>,
+    mut query: Query<&mut Sprite, Or<(With<Snake>, With<SnakeSegment>)>>,
+) {
+    let alpha = if ghost.is_active() { GHOST_ALPHA } else { 1.0 };
+    for mut sprite in &mut query {
+        if sprite.color.a() != alpha {
+            sprite.color.set_a(alpha);
+        }
+    }
+}
+
+pub fn show_shield_outline(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    head_query: Query<(Entity, &Shield, &Hitbox, Option<&Children>), Changed<Shield>>,
+    outline_query: Query<Entity, With<ShieldOutline>>,
+) {
+    for (head, shield, hitbox, children) in &head_query {
+        let outline = children
+            .into_iter()
+            .flatten()
+            .find(|child| outline_query.contains(**child));
+        match (outline, **shield > 0) {
+            (None, true) => {
+                let mut color = config.shield_color;
+                color.set_a(SHIELD_OUTLINE_ALPHA);
+                let outline = commands
+                    .spawn_bundle(SpriteBundle {
+                        sprite: Sprite {
+                            color,
+                            custom_size: Some(**hitbox + Vec2::splat(SHIELD_OUTLINE_WIDTH * 2.0)),
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(0.0, 0.0, SHIELD_OUTLINE_Z),
+                        ..default()
+                    })
+                    .insert(ShieldOutline)
+                    .id();
+                commands.entity(head).add_child(outline);
+            }
+            (Some(outline), false) => commands.entity(*outline).despawn_recursive(),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,626 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use bevy::{
+    prelude::*,
+    sprite::{
+        collide_aabb::{collide, Collision},
+        Rect,
+    },
+    utils::HashSet,
+    window::WindowResized,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::collision::{check_for_collisions, Collider, DeathCause, Hitbox, SnakeDied};
+use crate::config::GameConfig;
+use crate::food::{free_food_position, BonusFood, FoodKind, GameRng};
+use crate::game::{count_tick, lose_life, FixedUpdate, GameMode};
+use crate::portal::Portal;
+use crate::snake::{
+    cell_center, grid_cell, move_segments, MovementMode, Snake, SnakeSegment, SnakeTrail,
+};
+use crate::spawning::{spawn_bonus_food, spawn_food_of_kind, spawn_wall_segment};
+use crate::state::GameState;
+use crate::ui::WINDOW_MARGIN;
+
+// How many random spots are tried for each obstacle before giving up on it
+const OBSTACLE_SPAWN_ATTEMPTS: usize = 32;
+// How often survival closes the arena in by another ring of walls
+pub const SHRINK_SECONDS: f32 = 15.0;
+// The fewest cells across either way survival leaves open
+pub const MIN_ARENA_CELLS: i32 = 9;
+// The arena follows the window down to this size, a smaller window cuts it off
+pub const MIN_ARENA_WIDTH: f32 = 300.0;
+pub const MIN_ARENA_HEIGHT: f32 = 200.0;
+
+// The walls around the arena, and the rings survival closes it in with. The
+// walls move with the window when it is resized.
+pub struct ArenaPlugin;
+
+impl Plugin for ArenaPlugin {
+    fn build(&self, app: &mut App) {
+        // Headless apps have no window plugin to register it, and never resize
+        app.add_event::<WindowResized>()
+            .init_resource::<WallMode>()
+            .init_resource::<ArenaConfig>()
+            .init_resource::<ArenaBounds>()
+            .init_resource::<ShrinkingArena>()
+            .add_startup_system(spawn_walls)
+            .add_system(resize_arena)
+            .add_system_set(
+                SystemSet::new().with_run_criteria(FixedUpdate).with_system(
+                    shrink_arena
+                        .after(count_tick)
+                        .after(move_segments)
+                        .before(check_for_collisions)
+                        .before(lose_life),
+                ),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::Playing).with_system(reset_shrinking_arena),
+            );
+    }
+}
+
+// How the outer walls treat the snake
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WallMode {
+    // Touching a wall ends the game
+    #[default]
+    Lethal,
+    // The snake is kept inside the arena and can slide along the walls
+    Casual,
+    // The snake bounces off the walls, its `Velocity` reflected. On the grid,
+    // where there is no velocity to reflect, the walls hold it in like
+    // `Casual`.
+    Bounce,
+}
+
+impl WallMode {
+    // Passing `--casual` on the command line keeps the snake inside the
+    // walls, `--bounce` bounces it off them
+    pub fn from_args() -> WallMode {
+        if std::env::args().any(|arg| arg == "--bounce") {
+            WallMode::Bounce
+        } else if std::env::args().any(|arg| arg == "--casual") {
+            WallMode::Casual
+        } else {
+            WallMode::Lethal
+        }
+    }
+}
+
+// The arena the game starts out with, centered on the origin. `build_app`
+// makes it from the walls of the `GameConfig`, everything else reads it or
+// the `ArenaBounds` it leads to. Changing it resizes the arena, and the
+// window with it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArenaConfig {
+    pub width: f32,
+    pub height: f32,
+    pub thickness: f32,
+    pub color: Color,
+}
+
+impl ArenaConfig {
+    pub fn from_config(config: &GameConfig) -> ArenaConfig {
+        ArenaConfig {
+            width: config.right_wall - config.left_wall,
+            height: config.top_wall - config.bottom_wall,
+            thickness: config.wall_thickness,
+            color: config.wall_color,
+        }
+    }
+
+    // The window that fits the arena with its walls and some room around them
+    pub fn window_size(&self) -> Vec2 {
+        Vec2::new(self.width, self.height) + self.thickness + 2.0 * WINDOW_MARGIN
+    }
+}
+
+impl FromWorld for ArenaConfig {
+    fn from_world(world: &mut World) -> Self {
+        ArenaConfig::from_config(world.resource::<GameConfig>())
+    }
+}
+
+// Where the walls around the arena are centered right now, and how thick
+// they are. It starts out as the `ArenaConfig` and follows the window from
+// there.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArenaBounds {
+    pub left: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub top: f32,
+    pub thickness: f32,
+}
+
+impl ArenaBounds {
+    pub fn from_config(arena: &ArenaConfig) -> ArenaBounds {
+        ArenaBounds {
+            left: -arena.width / 2.0,
+            right: arena.width / 2.0,
+            bottom: -arena.height / 2.0,
+            top: arena.height / 2.0,
+            thickness: arena.thickness,
+        }
+    }
+
+    pub fn width(&self) -> f32 {
+        self.right - self.left
+    }
+
+    pub fn height(&self) -> f32 {
+        self.top - self.bottom
+    }
+
+    // The arena filling a window of `width` by `height` around the same
+    // center, the other way around from `ArenaConfig::window_size`
+    pub fn fit_window(&self, width: f32, height: f32) -> ArenaBounds {
+        let center = Vec2::new(self.left + self.right, self.bottom + self.top) / 2.0;
+        let room = self.thickness + 2.0 * WINDOW_MARGIN;
+        let half = Vec2::new(
+            (width - room).max(MIN_ARENA_WIDTH),
+            (height - room).max(MIN_ARENA_HEIGHT),
+        ) / 2.0;
+        ArenaBounds {
+            left: center.x - half.x,
+            right: center.x + half.x,
+            bottom: center.y - half.y,
+            top: center.y + half.y,
+            thickness: self.thickness,
+        }
+    }
+
+    // The lowest and highest spots the center of a `size` box can be on
+    // without reaching into the walls. The walls are centered on the bounds,
+    // so only half of their thickness reaches into the arena.
+    pub fn inside(&self, size: Vec2) -> (Vec2, Vec2) {
+        let margin = self.thickness / 2.0 + size / 2.0;
+        (
+            Vec2::new(self.left, self.bottom) + margin,
+            Vec2::new(self.right, self.top) - margin,
+        )
+    }
+}
+
+impl FromWorld for ArenaBounds {
+    fn from_world(world: &mut World) -> Self {
+        ArenaBounds::from_config(world.resource::<ArenaConfig>())
+    }
+}
+
+#[derive(Bundle)]
+pub struct WallBundle {
+    // You can nest bundles inside of other bundles like this
+    // Allowing you to compose their functionality
+    #[bundle]
+    pub transform_bundle: TransformBundle,
+    pub wall: Wall,
+    pub collider: Collider,
+    pub hitbox: Hitbox,
+}
+
+// Anything built as a wall, around the arena or inside it. Obstacles are
+// blocks, not walls.
+#[derive(Component)]
+pub struct Wall;
+
+// Which side of the arena is this wall located on?
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WallLocation {
+    Left,
+    Right,
+    Bottom,
+    Top,
+}
+
+impl WallLocation {
+    // The wall whose `side` the head reached into, a head coming at the
+    // left wall from the right reaches into its right side. Nothing when the
+    // head was already inside.
+    pub fn hit_on(side: &Collision) -> Option<WallLocation> {
+        match side {
+            Collision::Right => Some(WallLocation::Left),
+            Collision::Left => Some(WallLocation::Right),
+            Collision::Top => Some(WallLocation::Bottom),
+            Collision::Bottom => Some(WallLocation::Top),
+            Collision::Inside => None,
+        }
+    }
+
+    pub fn position(&self, bounds: &ArenaBounds) -> Vec2 {
+        let center = Vec2::new(bounds.left + bounds.right, bounds.bottom + bounds.top) / 2.0;
+        match self {
+            WallLocation::Left => Vec2::new(bounds.left, center.y),
+            WallLocation::Right => Vec2::new(bounds.right, center.y),
+            WallLocation::Bottom => Vec2::new(center.x, bounds.bottom),
+            WallLocation::Top => Vec2::new(center.x, bounds.top),
+        }
+    }
+
+    // `GameConfig::validate` makes sure the arena isn't empty
+    pub fn size(&self, bounds: &ArenaBounds) -> Vec2 {
+        let arena_height = bounds.height();
+        let arena_width = bounds.width();
+
+        match self {
+            WallLocation::Left | WallLocation::Right => {
+                Vec2::new(bounds.thickness, arena_height + bounds.thickness)
+            }
+
+            WallLocation::Bottom | WallLocation::Top => {
+                Vec2::new(arena_width + bounds.thickness, bounds.thickness)
+            }
+        }
+    }
+}
+
+pub fn spawn_walls(mut commands: Commands, bounds: Res<ArenaBounds>) {
+    for location in [
+        WallLocation::Left,
+        WallLocation::Right,
+        WallLocation::Bottom,
+        WallLocation::Top,
+    ] {
+        commands
+            .spawn_bundle(WallBundle::new(location, &bounds))
+            .insert(location);
+    }
+}
+
+// The lowest and highest grid cells that lie completely inside the walls
+pub fn grid_bounds(config: &GameConfig, bounds: &ArenaBounds) -> (IVec2, IVec2) {
+    let (min, max) = bounds.inside(config.snake_size);
+    (
+        (min / config.snake_size).ceil().as_ivec2(),
+        (max / config.snake_size).floor().as_ivec2(),
+    )
+}
+
+// Every cell the snake can get to from `start` without going through any of
+// the `blocked` ones, moving the way it does, `start` included
+pub fn reachable_cells(
+    min: IVec2,
+    max: IVec2,
+    blocked: &HashSet<IVec2>,
+    start: IVec2,
+) -> HashSet<IVec2> {
+    let mut seen = HashSet::from_iter([start]);
+    let mut frontier = VecDeque::from([start]);
+    while let Some(cell) = frontier.pop_front() {
+        for step in [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y] {
+            let next = cell + step;
+            let inside = next.cmpge(min).all() && next.cmple(max).all();
+            if inside && !blocked.contains(&next) && seen.insert(next) {
+                frontier.push_back(next);
+            }
+        }
+    }
+    seen
+}
+
+// Moves the walls out to the edges of a resized window, or to those of a
+// changed `ArenaConfig` along with the window, and pushes whatever the arena
+// shrank away from back inside: onto the nearest cell in grid mode. The
+// walls are moved rather than spawned again, so nothing keeping track of
+// them notices.
+pub fn resize_arena(
+    mut resized_events: EventReader<WindowResized>,
+    mut bounds: ResMut<ArenaBounds>,
+    arena: Res<ArenaConfig>,
+    mut windows: Option<ResMut<Windows>>,
+    config: Res<GameConfig>,
+    movement_mode: Res<MovementMode>,
+    mut wall_query: Query<(
+        &WallLocation,
+        &mut Transform,
+        &mut Hitbox,
+        Option<&mut Sprite>,
+    )>,
+    mut inside_query: Query<
+        (&mut Transform, &Hitbox, Option<&mut SnakeTrail>),
+        (
+            Or<(With<Snake>, With<SnakeSegment>, With<FoodKind>)>,
+            Without<WallLocation>,
+        ),
+    >,
+) {
+    let resized = if arena.is_changed() {
+        // The window about to follow needs no fitting of its own
+        resized_events.clear();
+        if let Some(window) = windows
+            .as_mut()
+            .and_then(|windows| windows.get_primary_mut())
+        {
+            let size = arena.window_size();
+            window.set_resolution(size.x, size.y);
+        }
+        ArenaBounds::from_config(&arena)
+    } else {
+        let Some(window) = resized_events.iter().last() else {
+            return;
+        };
+        bounds.fit_window(window.width, window.height)
+    };
+    if resized == *bounds {
+        return;
+    }
+    *bounds = resized;
+
+    for (location, mut transform, mut hitbox, sprite) in &mut wall_query {
+        transform.translation = location.position(&bounds).extend(transform.translation.z);
+        **hitbox = location.size(&bounds);
+        if let Some(mut sprite) = sprite {
+            sprite.custom_size = Some(**hitbox);
+        }
+    }
+
+    let (min_cell, max_cell) = grid_bounds(&config, &bounds);
+    let push_inside = |position: Vec3, size: Vec2| {
+        if *movement_mode == MovementMode::Grid {
+            let cell = grid_cell(&config, position).clamp(min_cell, max_cell);
+            return cell_center(&config, cell).truncate().extend(position.z);
+        }
+        let (min, max) = bounds.inside(size);
+        position.truncate().clamp(min, max).extend(position.z)
+    };
+    for (mut transform, hitbox, trail) in &mut inside_query {
+        let inside = push_inside(transform.translation, **hitbox);
+        if inside != transform.translation {
+            transform.translation = inside;
+        }
+        // The body follows the head's trail, so that is pushed inside too
+        if let Some(mut trail) = trail {
+            for point in trail.iter_mut() {
+                *point = push_inside(*point, **hitbox);
+            }
+        }
+    }
+}
+
+// A wall inside the arena, drawn by a level. Like an obstacle it is lethal
+// on every difficulty.
+#[derive(Component)]
+pub struct InteriorWall;
+
+// The rect of a `size` block centered on `position`
+pub fn block_rect(position: Vec3, size: Vec2) -> Rect {
+    let center = position.truncate();
+    Rect {
+        min: center - size / 2.0,
+        max: center + size / 2.0,
+    }
+}
+
+// A block inside the arena. Unlike the walls it is lethal on every
+// difficulty, there is no side to keep the snake on.
+#[derive(Component)]
+pub struct Obstacle;
+
+// Rolls `count` blocks (given as center and size) on the grid, none of them
+// overlapping or crossing the row and column of any of `starts`, where the
+// snakes start. A block that finds no room is left out.
+pub fn random_obstacles(
+    rng: &mut impl Rng,
+    config: &GameConfig,
+    bounds: &ArenaBounds,
+    count: usize,
+    starts: &[Vec3],
+) -> Vec<(Vec3, Vec2)> {
+    let (min, max) = grid_bounds(config, bounds);
+    let starts: Vec<IVec2> = starts
+        .iter()
+        .map(|start| grid_cell(config, *start))
+        .collect();
+    let max_size = config.obstacle_max_size as i32;
+    let mut obstacles: Vec<(Vec3, Vec2)> = Vec::new();
+
+    for _ in 0..count {
+        for _ in 0..OBSTACLE_SPAWN_ATTEMPTS {
+            let cells = IVec2::new(rng.gen_range(1..=max_size), rng.gen_range(1..=max_size));
+            let last_corner = max - cells + 1;
+            if last_corner.x < min.x || last_corner.y < min.y {
+                continue;
+            }
+            let corner = IVec2::new(
+                rng.gen_range(min.x..=last_corner.x),
+                rng.gen_range(min.y..=last_corner.y),
+            );
+            let far_corner = corner + cells - 1;
+            let crosses_start = starts.iter().any(|start| {
+                (corner.x..=far_corner.x).contains(&start.x)
+                    || (corner.y..=far_corner.y).contains(&start.y)
+            });
+            let center = (cell_center(config, corner) + cell_center(config, far_corner)) / 2.0;
+            let size = cells.as_vec2() * config.snake_size;
+            let overlaps = obstacles
+                .iter()
+                .any(|(other, other_size)| collide(center, size, *other, *other_size).is_some());
+            if !crosses_start && !overlaps {
+                obstacles.push((center, size));
+                break;
+            }
+        }
+    }
+    obstacles
+}
+
+impl WallBundle {
+    // This "builder method" allows us to reuse logic across out wall entities,
+    // making our code easier to read and less prone to bugs when we change the logic
+
+    pub fn new(location: WallLocation, bounds: &ArenaBounds) -> WallBundle {
+        // We need to convert our Vec2 into Vec3, by giving it a z-coordinate
+        // This is used to determine the order of our sprites
+        WallBundle::at(location.position(bounds).extend(0.0), location.size(bounds))
+    }
+
+    // A wall of any size anywhere, such as the ones a level draws
+    pub fn at(position: Vec3, size: Vec2) -> WallBundle {
+        WallBundle {
+            transform_bundle: TransformBundle::from_transform(Transform::from_translation(
+                position,
+            )),
+            wall: Wall,
+            collider: Collider,
+            hitbox: Hitbox(size),
+        }
+    }
+}
+
+// How far survival has closed the arena in, and how long until the next
+// ring of walls.
+pub struct ShrinkingArena {
+    pub rings: i32,
+    pub next: Timer,
+}
+
+impl ShrinkingArena {
+    pub fn remaining_secs(&self) -> f32 {
+        self.next.duration().as_secs_f32() - self.next.elapsed_secs()
+    }
+
+    // Whether another ring would still leave `MIN_ARENA_CELLS` open
+    pub fn can_shrink(&self, config: &GameConfig, bounds: &ArenaBounds) -> bool {
+        let (min, max) = ring_bounds(config, bounds, self.rings + 1);
+        (max - min + 1).min_element() >= MIN_ARENA_CELLS
+    }
+}
+
+impl Default for ShrinkingArena {
+    fn default() -> Self {
+        ShrinkingArena {
+            rings: 0,
+            next: Timer::from_seconds(SHRINK_SECONDS, true),
+        }
+    }
+}
+
+// The cells still open once `rings` rings of walls are up
+pub fn ring_bounds(config: &GameConfig, bounds: &ArenaBounds, rings: i32) -> (IVec2, IVec2) {
+    let (min, max) = grid_bounds(config, bounds);
+    (min + rings, max - rings)
+}
+
+// The ring of walls just inside the cells between `min` and `max`, as the
+// center and size of its four sides
+pub fn ring_blocks(config: &GameConfig, min: IVec2, max: IVec2) -> Vec<(Vec3, Vec2)> {
+    let block = |from: IVec2, to: IVec2| {
+        let center = (cell_center(config, from) + cell_center(config, to)) / 2.0;
+        let size = (to - from + 1).as_vec2() * config.snake_size;
+        (center, size)
+    };
+    vec![
+        block(min, IVec2::new(max.x, min.y)),
+        block(IVec2::new(min.x, max.y), max),
+        block(IVec2::new(min.x, min.y + 1), IVec2::new(min.x, max.y - 1)),
+        block(IVec2::new(max.x, min.y + 1), IVec2::new(max.x, max.y - 1)),
+    ]
+}
+
+pub fn reset_shrinking_arena(mut arena: ResMut<ShrinkingArena>) {
+    *arena = ShrinkingArena::default();
+}
+
+// Every `SHRINK_SECONDS` of survival another ring of walls goes up just
+// inside the last one, until the arena is down to `MIN_ARENA_CELLS`. A snake
+// caught under the new walls is crushed, food under them is put back down
+// somewhere still open.
+pub fn shrink_arena(
+    mut commands: Commands,
+    mut arena: ResMut<ShrinkingArena>,
+    mode: Res<GameMode>,
+    snake_query: Query<(Entity, &Transform, &Hitbox), With<Snake>>,
+    segment_query: Query<(&Transform, &Hitbox), With<SnakeSegment>>,
+    food_query: Query<(Entity, &Transform, &Hitbox, &FoodKind, Option<&BonusFood>)>,
+    wall_query: Query<
+        (&Transform, &Hitbox),
+        Or<(With<Obstacle>, With<InteriorWall>, With<Portal>)>,
+    >,
+    config: Res<GameConfig>,
+    bounds: Res<ArenaBounds>,
+    movement_mode: Res<MovementMode>,
+    mut rng: ResMut<GameRng>,
+    mut died_events: EventWriter<SnakeDied>,
+) {
+    if *mode != GameMode::Survival || !arena.can_shrink(&config, &bounds) {
+        return;
+    }
+    arena.next.tick(Duration::from_secs_f32(config.time_step));
+    if !arena.next.just_finished() {
+        return;
+    }
+
+    let (min, max) = ring_bounds(&config, &bounds, arena.rings);
+    arena.rings += 1;
+    let ring = ring_blocks(&config, min, max);
+    let under_ring = |position: Vec3, size: Vec2| {
+        ring.iter()
+            .any(|(center, block)| collide(position, size, *center, *block).is_some())
+    };
+    for (position, size) in &ring {
+        spawn_wall_segment(&mut commands, block_rect(*position, *size));
+    }
+
+    for (snake, transform, hitbox) in &snake_query {
+        let body_crushed = segment_query
+            .iter()
+            .any(|(segment, size)| under_ring(segment.translation, **size));
+        if under_ring(transform.translation, **hitbox) || body_crushed {
+            died_events.send(SnakeDied {
+                snake,
+                cause: DeathCause::Crushed,
+            });
+        }
+    }
+
+    // The food that is moved keeps clear of everything else, the ring included
+    let mut occupied = ring.clone();
+    occupied.extend(
+        snake_query
+            .iter()
+            .map(|(_, transform, hitbox)| (transform.translation, **hitbox))
+            .chain(
+                segment_query
+                    .iter()
+                    .map(|(transform, hitbox)| (transform.translation, **hitbox)),
+            )
+            .chain(
+                wall_query
+                    .iter()
+                    .map(|(transform, hitbox)| (transform.translation, **hitbox)),
+            ),
+    );
+    let (buried, open): (Vec<_>, Vec<_>) = food_query
+        .iter()
+        .partition(|(_, transform, hitbox, ..)| under_ring(transform.translation, ***hitbox));
+    occupied.extend(
+        open.iter()
+            .map(|(_, transform, hitbox, ..)| (transform.translation, ***hitbox)),
+    );
+    for (food, _, hitbox, kind, bonus) in buried {
+        commands.entity(food).despawn();
+        // Checked at the size of normal food, so anything bigger is padded
+        let margin = **hitbox - config.food_size;
+        let padded: Vec<(Vec3, Vec2)> = occupied
+            .iter()
+            .map(|(center, size)| (*center, *size + margin))
+            .collect();
+        let Some(position) =
+            free_food_position(&mut rng.rng, &config, &bounds, *movement_mode, &padded)
+        else {
+            continue;
+        };
+        match bonus {
+            Some(bonus) => {
+                spawn_bonus_food(&mut commands, &config, position, bonus.remaining_secs())
+            }
+            None => spawn_food_of_kind(&mut commands, &config, position, *kind),
+        };
+        occupied.push((position, **hitbox));
+    }
+}
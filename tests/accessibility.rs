@@ -0,0 +1,14 @@
+use snake::accessibility::food_circle_image;
+
+#[test]
+fn the_food_circle_is_a_rimmed_disc_on_a_clear_square() {
+    let image = food_circle_image();
+    let size = image.texture_descriptor.size.width as usize;
+    let pixel = |x: usize, y: usize| {
+        let start = (y * size + x) * 4;
+        image.data[start..start + 4].to_vec()
+    };
+    assert_eq!(pixel(0, 0), [0, 0, 0, 0]);
+    assert_eq!(pixel(size / 2, size / 2), [255, 255, 255, 255]);
+    assert_eq!(pixel(size / 2, 1), [0, 0, 0, 255]);
+}
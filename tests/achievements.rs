@@ -0,0 +1,69 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::Headless;
+use snake::achievements::{Achievement, AchievementUnlocked, Achievements, HUNGRY_FOODS};
+use snake::config::GameConfig;
+use snake::food::FoodKind;
+use snake::game::Difficulty;
+use snake::state::GameState;
+
+// The walls don't kill on Easy, so the snake lives long enough to eat
+fn started(achievements: Achievements) -> Headless {
+    let mut game = Headless::with_setup(
+        GameConfig {
+            seed: Some(13),
+            ..GameConfig::default()
+        },
+        |app| {
+            app.insert_resource(achievements)
+                .insert_resource(Difficulty::Easy);
+        },
+    );
+    game.start_game();
+    game
+}
+
+fn unlocked(game: &Headless) -> Vec<Achievement> {
+    game.app.world.resource::<Achievements>().unlocked.clone()
+}
+
+// The unlocks sent over the last two updates
+fn toasts(game: &Headless) -> usize {
+    let events = game.app.world.resource::<Events<AchievementUnlocked>>();
+    events.get_reader().iter(events).count()
+}
+
+#[test]
+fn eating_enough_in_one_game_unlocks_it_once() {
+    let mut game = started(Achievements::default());
+    game.eat(HUNGRY_FOODS - 1);
+    assert!(unlocked(&game).is_empty());
+
+    game.eat(1);
+    assert_eq!(unlocked(&game), [Achievement::Hungry]);
+    assert_eq!(toasts(&game), 1);
+    game.eat(2);
+    assert_eq!(unlocked(&game), [Achievement::Hungry]);
+    assert_eq!(toasts(&game), 0);
+}
+
+#[test]
+fn an_unlocked_achievement_is_not_shown_again() {
+    let mut game = started(Achievements {
+        unlocked: vec![Achievement::Hungry],
+    });
+    game.eat(HUNGRY_FOODS);
+    assert_eq!(unlocked(&game), [Achievement::Hungry]);
+    assert_eq!(toasts(&game), 0);
+}
+
+#[test]
+fn dying_right_away_is_an_achievement_too() {
+    let mut game = started(Achievements::default());
+    game.eat_kind(1, FoodKind::Poison);
+    game.play_out_death();
+    assert_eq!(game.state(), GameState::GameOver);
+    assert_eq!(unlocked(&game), [Achievement::Unlucky]);
+}
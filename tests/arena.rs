@@ -0,0 +1,359 @@
+mod common;
+
+use bevy::ecs::system::CommandQueue;
+use bevy::prelude::*;
+use bevy::sprite::Rect;
+use bevy::window::{WindowId, WindowResized};
+
+use common::Headless;
+use snake::background::checkerboard_tiles;
+use snake::collision::Hitbox;
+use snake::config::GameConfig;
+use snake::food::Food;
+use snake::game::Difficulty;
+use snake::snake::{
+    grid_cell, Direction, GameSpeed, HeldDirections, MovementMode, Snake, Velocity,
+};
+use snake::spawning::spawn_wall_segment;
+use snake::state::GameState;
+use snake::ui::WINDOW_MARGIN;
+use snake::walls::{
+    grid_bounds, ArenaBounds, ArenaConfig, Wall, WallLocation, WallMode, MIN_ARENA_HEIGHT,
+    MIN_ARENA_WIDTH,
+};
+
+// The walls don't kill on Easy, and the snake holds still
+fn started() -> Headless {
+    let mut game = Headless::with_setup(
+        GameConfig {
+            seed: Some(5),
+            ..GameConfig::default()
+        },
+        |app| {
+            app.insert_resource(Difficulty::Easy)
+                .insert_resource(GameSpeed(0.0));
+        },
+    );
+    game.start_game();
+    game
+}
+
+fn resize(game: &mut Headless, width: f32, height: f32) {
+    game.app.world.send_event(WindowResized {
+        id: WindowId::primary(),
+        width,
+        height,
+    });
+    game.tick(1);
+}
+
+fn bounds(game: &Headless) -> ArenaBounds {
+    *game.app.world.resource::<ArenaBounds>()
+}
+
+// The window a `width` by `height` arena needs, as `build_app` sizes it
+fn window_for(config: &GameConfig, width: f32, height: f32) -> Vec2 {
+    Vec2::new(width, height) + config.wall_thickness + 2.0 * WINDOW_MARGIN
+}
+
+#[test]
+fn the_walls_move_out_to_a_bigger_window() {
+    let mut game = started();
+    let config = game.app.world.resource::<GameConfig>().clone();
+    let walls_before = game
+        .app
+        .world
+        .query::<&WallLocation>()
+        .iter(&game.app.world)
+        .count();
+
+    let window = window_for(&config, 1200.0, 800.0);
+    resize(&mut game, window.x, window.y);
+    let bounds = bounds(&game);
+    assert_eq!((bounds.left, bounds.right), (-600.0, 600.0));
+    assert_eq!((bounds.bottom, bounds.top), (-400.0, 400.0));
+
+    let mut wall_query = game.app.world.query::<(&WallLocation, &Transform)>();
+    let walls: Vec<_> = wall_query.iter(&game.app.world).collect();
+    assert_eq!(walls.len(), walls_before);
+    for (location, transform) in walls {
+        let expected = location.position(&bounds);
+        assert_eq!(transform.translation.truncate(), expected);
+    }
+}
+
+#[test]
+fn the_walls_are_built_from_the_arena_config() {
+    let mut game = Headless::new(GameConfig {
+        left_wall: -200.0,
+        right_wall: 200.0,
+        bottom_wall: -150.0,
+        top_wall: 150.0,
+        wall_thickness: 6.0,
+        ..GameConfig::default()
+    });
+    let arena = *game.app.world.resource::<ArenaConfig>();
+    assert_eq!(
+        (arena.width, arena.height, arena.thickness),
+        (400.0, 300.0, 6.0)
+    );
+    let bounds = bounds(&game);
+    assert_eq!(bounds, ArenaBounds::from_config(&arena));
+    assert_eq!((bounds.left, bounds.top), (-200.0, 150.0));
+
+    let mut wall_query = game
+        .app
+        .world
+        .query::<(&WallLocation, &Transform, &Hitbox)>();
+    for (location, transform, hitbox) in wall_query.iter(&game.app.world) {
+        assert_eq!(transform.translation.truncate(), location.position(&bounds));
+        assert_eq!(hitbox.min_element(), 6.0);
+    }
+}
+
+#[test]
+fn a_new_arena_config_moves_the_walls_and_keeps_the_snake_inside() {
+    let mut game = started();
+    let config = game.app.world.resource::<GameConfig>().clone();
+    let head = game.head();
+    game.app
+        .world
+        .get_mut::<Transform>(head)
+        .unwrap()
+        .translation = Vec3::new(400.0, 260.0, 0.0);
+
+    let arena = ArenaConfig {
+        width: 400.0,
+        height: 300.0,
+        thickness: 4.0,
+        ..*game.app.world.resource::<ArenaConfig>()
+    };
+    game.app.insert_resource(arena);
+    game.tick(1);
+    let bounds = bounds(&game);
+    assert_eq!(bounds, ArenaBounds::from_config(&arena));
+
+    let mut wall_query = game
+        .app
+        .world
+        .query::<(&WallLocation, &Transform, &Hitbox)>();
+    for (location, transform, hitbox) in wall_query.iter(&game.app.world) {
+        assert_eq!(transform.translation.truncate(), location.position(&bounds));
+        assert_eq!(**hitbox, location.size(&bounds));
+    }
+    let (min, max) = grid_bounds(&config, &bounds);
+    let cell = grid_cell(&config, game.head_position());
+    assert!(cell.cmpge(min).all() && cell.cmple(max).all());
+}
+
+#[test]
+fn walls_off_the_center_are_turned_down() {
+    let uneven = GameConfig {
+        left_wall: -400.0,
+        right_wall: 500.0,
+        ..GameConfig::default()
+    };
+    assert!(uneven.validate().is_err());
+    let low = GameConfig {
+        bottom_wall: -350.0,
+        ..GameConfig::default()
+    };
+    assert!(low.validate().is_err());
+}
+
+#[test]
+fn shrinking_pushes_the_snake_and_the_food_back_inside() {
+    let mut game = started();
+    let config = game.app.world.resource::<GameConfig>().clone();
+    let head = game.head();
+    game.app
+        .world
+        .get_mut::<Transform>(head)
+        .unwrap()
+        .translation = Vec3::new(400.0, 260.0, 0.0);
+
+    let window = window_for(&config, 400.0, 300.0);
+    resize(&mut game, window.x, window.y);
+    let (min, max) = grid_bounds(&config, &bounds(&game));
+    let mut query = game
+        .app
+        .world
+        .query_filtered::<&Transform, Or<(With<Snake>, With<Food>)>>();
+    for transform in query.iter(&game.app.world) {
+        let cell = grid_cell(&config, transform.translation);
+        assert!(cell.cmpge(min).all() && cell.cmple(max).all());
+    }
+}
+
+#[test]
+fn the_arena_stops_shrinking_at_its_smallest() {
+    let mut game = started();
+    resize(&mut game, 50.0, 50.0);
+    let bounds = bounds(&game);
+    assert_eq!(bounds.width(), MIN_ARENA_WIDTH);
+    assert_eq!(bounds.height(), MIN_ARENA_HEIGHT);
+}
+
+#[test]
+fn the_checkerboard_covers_exactly_the_inside_of_the_walls() {
+    let config = GameConfig::default();
+    // Not a whole number of cells across, so the edge squares are cut off
+    let bounds = ArenaBounds {
+        left: -266.5,
+        right: 266.5,
+        bottom: -185.5,
+        top: 185.5,
+        thickness: config.wall_thickness,
+    };
+    let (min, max) = bounds.inside(Vec2::ZERO);
+    let tiles = checkerboard_tiles(&config, &bounds);
+
+    let area: f32 = tiles.iter().map(|(_, size, _)| size.x * size.y).sum();
+    let inside = (max - min).x * (max - min).y;
+    assert!((area - inside).abs() < 1.0);
+    for (center, size, _) in &tiles {
+        assert!((*center - *size / 2.0).cmpge(min - 0.001).all());
+        assert!((*center + *size / 2.0).cmple(max + 0.001).all());
+    }
+
+    // Next to each other, two full squares have different shades
+    let middle = tiles.iter().find(|(center, _, _)| *center == Vec2::ZERO);
+    let right = tiles
+        .iter()
+        .find(|(center, _, _)| *center == Vec2::new(config.snake_size.x, 0.0));
+    assert_ne!(middle.unwrap().2, right.unwrap().2);
+}
+
+#[test]
+fn a_head_pushed_against_the_walls_never_reaches_into_them() {
+    for (snake_size, wall_thickness) in [(20.0, 10.0), (10.0, 4.0), (30.0, 24.0), (16.0, 0.0)] {
+        let config = GameConfig {
+            seed: Some(5),
+            snake_size: Vec2::splat(snake_size),
+            wall_thickness,
+            obstacle_count: 0,
+            ..GameConfig::default()
+        };
+        let mut game = Headless::with_setup(config, |app| {
+            app.insert_resource(Difficulty::Easy)
+                .insert_resource(MovementMode::Continuous);
+        });
+        game.start_game();
+
+        // Into the top wall, then around the arena into the other three
+        for direction in [
+            Direction::Up,
+            Direction::Right,
+            Direction::Down,
+            Direction::Left,
+        ] {
+            let head = game.head();
+            **game.app.world.get_mut::<HeldDirections>(head).unwrap() = vec![direction];
+            game.tick(90);
+
+            let (head, head_box) = game
+                .app
+                .world
+                .query_filtered::<(&Transform, &Hitbox), With<Snake>>()
+                .single(&game.app.world);
+            let (head, head_box) = (head.translation.truncate(), **head_box);
+            let mut wall_query = game
+                .app
+                .world
+                .query_filtered::<(&Transform, &Hitbox), With<WallLocation>>();
+            let mut touching = false;
+            for (wall, wall_box) in wall_query.iter(&game.app.world) {
+                let gap =
+                    (head - wall.translation.truncate()).abs() - (head_box + **wall_box) / 2.0;
+                touching |= gap.max_element() < 1e-3;
+                assert!(
+                    gap.max_element() > -1e-3,
+                    "a {snake_size} head heading {direction:?} reached {gap} into a \
+                     {wall_thickness} wall"
+                );
+            }
+            assert!(
+                touching,
+                "a {snake_size} head heading {direction:?} never got to a wall"
+            );
+        }
+    }
+}
+
+#[test]
+fn bouncy_walls_turn_the_snake_back_into_the_arena() {
+    let mut game = Headless::with_setup(
+        GameConfig {
+            seed: Some(5),
+            obstacle_count: 0,
+            ..GameConfig::default()
+        },
+        |app| {
+            app.insert_resource(MovementMode::Continuous);
+        },
+    );
+    game.start_game();
+    *game.app.world.resource_mut::<WallMode>() = WallMode::Bounce;
+
+    // The snake starts in the middle heading left, a second takes it into
+    // the left wall and back
+    game.tick(60);
+    assert_eq!(game.state(), GameState::Playing);
+    let head = game.head();
+    assert!(game.app.world.get::<Velocity>(head).unwrap().x > 0.0);
+    assert_eq!(
+        *game.app.world.get::<Direction>(head).unwrap(),
+        Direction::Right
+    );
+    let bounds = *game.app.world.resource::<ArenaBounds>();
+    let config = game.app.world.resource::<GameConfig>().clone();
+    let (min, _) = bounds.inside(config.snake_size);
+    assert!(game.head_position().x >= min.x);
+}
+
+#[test]
+fn a_wall_segment_is_a_wall_the_snake_dies_on() {
+    let mut game = Headless::with_setup(
+        GameConfig {
+            obstacle_count: 0,
+            ..GameConfig::default()
+        },
+        |app| {
+            app.insert_resource(Difficulty::Easy);
+        },
+    );
+    game.start_game();
+    let outer_walls = game
+        .app
+        .world
+        .query_filtered::<(), With<Wall>>()
+        .iter(&game.app.world)
+        .count();
+    assert_eq!(outer_walls, 4);
+
+    // Across the way the snake is heading
+    let cell = game.app.world.resource::<GameConfig>().snake_size;
+    let center = game.head_position().truncate() - Vec2::new(3.0 * cell.x, 0.0);
+    let mut queue = CommandQueue::default();
+    let wall = spawn_wall_segment(
+        &mut Commands::new(&mut queue, &game.app.world),
+        Rect {
+            min: center - cell * Vec2::new(0.5, 2.0),
+            max: center + cell * Vec2::new(0.5, 2.0),
+        },
+    );
+    queue.apply(&mut game.app.world);
+    assert_eq!(
+        **game.app.world.get::<Hitbox>(wall).unwrap(),
+        cell * Vec2::new(1.0, 4.0)
+    );
+    assert!(game.app.world.get::<Wall>(wall).is_some());
+
+    for _ in 0..60 {
+        if game.dying() {
+            break;
+        }
+        game.tick(1);
+    }
+    assert!(game.dying());
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::Headless;
+use snake::config::GameConfig;
+use snake::food::FoodKind;
+use snake::snake::{Player, SnakeSegment};
+use snake::theme::{shade_snake_bodies, Theme};
+
+// Gives the segments a plain white sprite the way `add_sprites` would,
+// without the textures the headless app has no assets for
+fn add_segment_sprites(mut commands: Commands, query: Query<Entity, Added<SnakeSegment>>) {
+    for segment in &query {
+        commands.entity(segment).insert(Sprite::default());
+    }
+}
+
+fn started() -> Headless {
+    let mut game = Headless::with_setup(
+        GameConfig {
+            seed: Some(2),
+            obstacle_count: 0,
+            ..GameConfig::default()
+        },
+        |app| {
+            app.init_resource::<Theme>()
+                .add_system_to_stage(CoreStage::PostUpdate, add_segment_sprites)
+                .add_system_to_stage(CoreStage::Last, shade_snake_bodies);
+        },
+    );
+    game.start_game();
+    game.tick(1);
+    game
+}
+
+fn colors(game: &mut Headless) -> Vec<Color> {
+    game.segments()
+        .into_iter()
+        .map(|segment| game.app.world.get::<Sprite>(segment).unwrap().color)
+        .collect()
+}
+
+fn expected(game: &Headless, length: usize) -> Vec<Color> {
+    let theme = game.app.world.resource::<Theme>();
+    (0..length)
+        .map(|index| theme.segment_color(Some(&Player::ONE), index, length))
+        .collect()
+}
+
+#[test]
+fn a_new_segment_is_shaded_in_the_update_it_appears() {
+    let mut game = started();
+    game.eat(3);
+    assert_eq!(colors(&mut game), expected(&game, 3));
+
+    // Right in the update the segment got its sprite, before anything is drawn
+    let snake = game.head();
+    game.eat_now(snake, FoodKind::Normal);
+    assert_eq!(colors(&mut game), expected(&game, 4));
+}
+
+#[test]
+fn the_gradient_spreads_again_when_the_body_shrinks() {
+    let mut game = started();
+    game.eat(4);
+    let snake = game.head();
+    game.eat_now(snake, FoodKind::Poison);
+    game.tick(1);
+    let length = game.segments().len();
+    assert!(length < 4);
+    assert_eq!(colors(&mut game), expected(&game, length));
+}
+
+#[test]
+fn the_colors_follow_a_new_theme() {
+    let mut game = started();
+    game.eat(3);
+    let config = game.app.world.resource::<GameConfig>().clone();
+    let tail = Color::rgb(0.9, 0.0, 0.9);
+    let mut theme = game.app.world.resource_mut::<Theme>();
+    theme.snake_tail = tail;
+    game.tick(1);
+    let last = *colors(&mut game).last().unwrap();
+    assert!(Vec4::from(last).abs_diff_eq(Vec4::from(tail), 1e-6));
+    assert_ne!(tail, config.snake_tail_color);
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use common::Headless;
+use snake::config::GameConfig;
+use snake::food::{BonusFood, BONUS_FOOD_EVERY, BONUS_FOOD_SECONDS};
+use snake::game::{Difficulty, Paused};
+use snake::state::GameState;
+
+fn bonus_count(game: &mut Headless) -> usize {
+    game.app
+        .world
+        .query::<&BonusFood>()
+        .iter(&game.app.world)
+        .count()
+}
+
+fn ticks(config: &GameConfig, seconds: f32) -> usize {
+    (seconds / config.time_step).ceil() as usize
+}
+
+#[test]
+fn bonus_food_waits_out_a_pause_and_then_expires() {
+    let config = GameConfig {
+        seed: Some(5),
+        ..GameConfig::default()
+    };
+    // The walls don't kill on Easy, so the snake outlives the bonus
+    let mut game = Headless::with_setup(config.clone(), |app| {
+        app.insert_resource(Difficulty::Easy);
+    });
+    game.start_game();
+
+    game.eat(BONUS_FOOD_EVERY - 1);
+    assert_eq!(bonus_count(&mut game), 0);
+    game.eat(1);
+    assert_eq!(bonus_count(&mut game), 1);
+
+    **game.app.world.resource_mut::<Paused>() = true;
+    game.tick(ticks(&config, BONUS_FOOD_SECONDS * 2.0));
+    assert_eq!(bonus_count(&mut game), 1);
+
+    **game.app.world.resource_mut::<Paused>() = false;
+    game.tick(ticks(&config, BONUS_FOOD_SECONDS));
+    assert_eq!(game.state(), GameState::Playing);
+    assert_eq!(bonus_count(&mut game), 0);
+}
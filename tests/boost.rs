@@ -0,0 +1,139 @@
+mod common;
+
+use common::Headless;
+use snake::config::GameConfig;
+use snake::food::SLOW_MOTION_FACTOR;
+use snake::game::{Difficulty, Paused};
+use snake::snake::{
+    Boost, SpeedModifier, BOOST_FACTOR, BOOST_MIN_STAMINA, BOOST_REFILL_SECONDS, BOOST_SECONDS,
+};
+
+fn ticks(config: &GameConfig, seconds: f32) -> usize {
+    (seconds / config.time_step).ceil() as usize
+}
+
+// The walls don't kill on Easy, so the snake can go anywhere for a while
+fn started(config: &GameConfig) -> Headless {
+    let mut game = Headless::with_setup(config.clone(), |app| {
+        app.insert_resource(Difficulty::Easy);
+    });
+    game.start_game();
+    game.tick(1);
+    game
+}
+
+fn boost(game: &mut Headless) -> Boost {
+    let head = game.head();
+    *game.app.world.get::<Boost>(head).unwrap()
+}
+
+// The boost key as the input would hold it
+fn hold_boost(game: &mut Headless, held: bool) {
+    let head = game.head();
+    game.app.world.get_mut::<Boost>(head).unwrap().held = held;
+}
+
+// How far the head gets in `seconds`
+fn distance(game: &mut Headless, config: &GameConfig, seconds: f32) -> f32 {
+    let start = game.head_position();
+    game.tick(ticks(config, seconds));
+    start.distance(game.head_position())
+}
+
+fn config() -> GameConfig {
+    GameConfig {
+        seed: Some(5),
+        obstacle_count: 0,
+        ..GameConfig::default()
+    }
+}
+
+#[test]
+fn boosting_speeds_the_snake_up() {
+    let config = config();
+    let mut normal = started(&config);
+    let mut boosted = started(&config);
+    hold_boost(&mut boosted, true);
+
+    let full = distance(&mut normal, &config, 0.5);
+    let fast = distance(&mut boosted, &config, 0.5);
+    let expected = full * BOOST_FACTOR;
+    assert!(
+        (fast - expected).abs() <= config.snake_size.x,
+        "moved {fast} instead of about {expected}"
+    );
+}
+
+#[test]
+fn boost_and_slow_motion_multiply() {
+    let config = config();
+    let mut normal = started(&config);
+    let mut both = started(&config);
+    hold_boost(&mut both, true);
+    both.app
+        .world
+        .resource_mut::<SpeedModifier>()
+        .slow_down(SLOW_MOTION_FACTOR, 5.0);
+
+    let full = distance(&mut normal, &config, 1.0);
+    let moved = distance(&mut both, &config, 1.0);
+    let expected = full * BOOST_FACTOR * SLOW_MOTION_FACTOR;
+    assert!(
+        (moved - expected).abs() <= config.snake_size.x,
+        "moved {moved} instead of about {expected}"
+    );
+}
+
+#[test]
+fn an_empty_meter_stops_the_boost_until_it_fills_up_again() {
+    let config = config();
+    let mut game = started(&config);
+    hold_boost(&mut game, true);
+    game.tick(1);
+    assert!(boost(&mut game).active);
+
+    game.tick(ticks(&config, BOOST_SECONDS));
+    let drained = boost(&mut game);
+    assert!(!drained.active);
+    assert!(drained.stamina < BOOST_MIN_STAMINA);
+
+    // Still held, it fills back up without boosting, and starts again once
+    // there is enough
+    let refill = ticks(&config, BOOST_REFILL_SECONDS * BOOST_MIN_STAMINA);
+    game.tick(refill - 2);
+    assert!(!boost(&mut game).active);
+    game.tick(5);
+    assert!(boost(&mut game).active);
+
+    hold_boost(&mut game, false);
+    game.tick(ticks(&config, BOOST_REFILL_SECONDS));
+    assert_eq!(boost(&mut game).stamina, 1.0);
+}
+
+#[test]
+fn the_meter_holds_while_paused() {
+    let config = config();
+    let mut game = started(&config);
+    hold_boost(&mut game, true);
+    game.tick(10);
+    let before = boost(&mut game);
+    **game.app.world.resource_mut::<Paused>() = true;
+    game.tick(60);
+    assert_eq!(boost(&mut game), before);
+}
+
+#[test]
+fn a_resumed_run_keeps_what_is_left_of_the_meter() {
+    let config = config();
+    let mut game = started(&config);
+    hold_boost(&mut game, true);
+    game.tick(10);
+    let before = boost(&mut game);
+    assert!(before.stamina < 1.0);
+
+    let saved = game.save_and_quit();
+    let mut resumed = Headless::resumed(config, saved, |app| {
+        app.insert_resource(Difficulty::Easy);
+    });
+    assert_eq!(boost(&mut resumed), before);
+}
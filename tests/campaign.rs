@@ -0,0 +1,64 @@
+mod common;
+
+use common::Headless;
+use snake::config::GameConfig;
+use snake::game::Scoreboard;
+use snake::level::{Campaign, LevelState, CAMPAIGN};
+use snake::state::GameState;
+use snake::walls::ArenaConfig;
+
+// Long enough for "Level Complete" to go away
+const LEVEL_COMPLETE_TICKS: usize = 150;
+
+fn campaign_at(index: usize) -> Headless {
+    let config = GameConfig {
+        seed: Some(3),
+        ..GameConfig::default()
+    };
+    let level = LevelState::new(index, &config, &ArenaConfig::from_config(&config));
+    let mut game = Headless::with_setup(config, |app| {
+        app.insert_resource(Campaign(Some(level)));
+    });
+    game.start_game();
+    game
+}
+
+fn level_index(game: &Headless) -> usize {
+    game.app
+        .world
+        .resource::<Campaign>()
+        .0
+        .as_ref()
+        .unwrap()
+        .index
+}
+
+#[test]
+fn the_next_level_keeps_the_score_but_not_the_length() {
+    let mut game = campaign_at(0);
+    let target = CAMPAIGN[0].target;
+
+    game.eat(target - 1);
+    assert_eq!(game.state(), GameState::Playing);
+    game.eat(1);
+    assert_eq!(game.state(), GameState::LevelComplete);
+
+    game.tick(LEVEL_COMPLETE_TICKS);
+    assert_eq!(game.state(), GameState::Playing);
+    assert_eq!(level_index(&game), 1);
+    assert_eq!(game.app.world.resource::<Scoreboard>().score, target);
+    assert!(game.segments().is_empty());
+}
+
+#[test]
+fn clearing_the_last_level_ends_the_campaign() {
+    let last = CAMPAIGN.len() - 1;
+    let mut game = campaign_at(last);
+
+    // The earlier foods of the level don't matter
+    let mut campaign = game.app.world.resource_mut::<Campaign>();
+    let level = campaign.0.as_mut().unwrap();
+    level.foods_eaten = level.target - 1;
+    game.eat(1);
+    assert_eq!(game.state(), GameState::CampaignComplete);
+}
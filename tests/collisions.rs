@@ -0,0 +1,144 @@
+mod common;
+
+use bevy::ecs::event::Events;
+use bevy::prelude::*;
+use bevy::sprite::collide_aabb::Collision;
+
+use common::Headless;
+use snake::collision::{resolve_overlap, CollisionEvent};
+use snake::config::GameConfig;
+use snake::food::{Food, FOOD_SPAWN_SECONDS};
+use snake::game::Difficulty;
+use snake::snake::{GameSpeed, Snake};
+use snake::walls::{ArenaBounds, WallLocation};
+
+// The walls don't kill on Easy, and the snake holds still wherever it is put
+fn started() -> Headless {
+    let mut game = Headless::with_setup(
+        GameConfig {
+            seed: Some(5),
+            obstacle_count: 0,
+            ..GameConfig::default()
+        },
+        |app| {
+            app.insert_resource(Difficulty::Easy)
+                .insert_resource(GameSpeed(0.0));
+        },
+    );
+    game.start_game();
+    // The first tick after the countdown only catches the timestep up, and
+    // the food needs to be done popping up
+    let time_step = game.app.world.resource::<GameConfig>().time_step;
+    game.tick(1 + (FOOD_SPAWN_SECONDS / time_step).ceil() as usize);
+    game
+}
+
+fn move_head(game: &mut Headless, position: Vec3) -> Vec<CollisionEvent> {
+    let head = game.head();
+    game.app
+        .world
+        .get_mut::<Transform>(head)
+        .unwrap()
+        .translation = position;
+    game.tick(1);
+    let mut events = game.app.world.resource_mut::<Events<CollisionEvent>>();
+    events.drain().collect()
+}
+
+#[test]
+fn touching_a_wall_is_told_apart_from_food() {
+    let mut game = started();
+    let bounds = *game.app.world.resource::<ArenaBounds>();
+    let mut wall_query = game.app.world.query::<(Entity, &WallLocation)>();
+    let left_wall = wall_query
+        .iter(&game.app.world)
+        .find(|(_, location)| matches!(location, WallLocation::Left))
+        .map(|(wall, _)| wall)
+        .unwrap();
+    let head = game.head();
+
+    let events = move_head(&mut game, Vec3::new(bounds.left + 10.0, 0.0, 0.0));
+    assert!(events.iter().any(|event| matches!(
+        event,
+        CollisionEvent::Wall { snake, wall, side: Collision::Right }
+            if *snake == head && *wall == left_wall
+    )));
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, CollisionEvent::Food { .. })));
+}
+
+#[test]
+fn reaching_food_is_told_apart_from_a_wall() {
+    let mut game = started();
+    let mut food_query = game
+        .app
+        .world
+        .query_filtered::<(Entity, &Transform), (With<Food>, Without<Snake>)>();
+    let (food, position) = food_query
+        .iter(&game.app.world)
+        .map(|(food, transform)| (food, transform.translation))
+        .next()
+        .unwrap();
+
+    let events = move_head(&mut game, position);
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, CollisionEvent::Food { food: eaten, .. } if *eaten == food)));
+    assert!(!events.iter().any(|event| matches!(
+        event,
+        CollisionEvent::Wall { .. } | CollisionEvent::Obstacle { .. }
+    )));
+}
+
+// A 10 by 10 box against a 20 by 20 wall at the origin
+fn push_out(position: Vec2) -> Option<(Vec2, Collision)> {
+    resolve_overlap(
+        position.extend(0.0),
+        Vec2::splat(10.0),
+        Vec3::ZERO,
+        Vec2::splat(20.0),
+    )
+}
+
+fn side_of(result: Option<(Vec2, Collision)>) -> Option<&'static str> {
+    result.map(|(_, side)| match side {
+        Collision::Left => "left",
+        Collision::Right => "right",
+        Collision::Bottom => "bottom",
+        Collision::Top => "top",
+        Collision::Inside => "inside",
+    })
+}
+
+#[test]
+fn overlaps_are_pushed_out_the_shortest_way() {
+    let cases = [
+        (Vec2::new(-13.0, 2.0), Vec2::new(-2.0, 0.0), "left"),
+        (Vec2::new(12.0, -4.0), Vec2::new(3.0, 0.0), "right"),
+        (Vec2::new(3.0, -14.0), Vec2::new(0.0, -1.0), "bottom"),
+        (Vec2::new(-6.0, 11.0), Vec2::new(0.0, 4.0), "top"),
+    ];
+    for (position, push, side) in cases {
+        let result = push_out(position);
+        assert_eq!(result.as_ref().map(|(push, _)| *push), Some(push), "{side}");
+        assert_eq!(side_of(result), Some(side));
+    }
+}
+
+#[test]
+fn a_grazed_corner_reports_the_side_it_was_barely_into() {
+    // Well over the top edge, just over the right one
+    let grazed = push_out(Vec2::new(14.0, 8.0));
+    assert_eq!(grazed.as_ref().map(|(push, _)| *push), Some(Vec2::X));
+    assert_eq!(side_of(grazed), Some("right"));
+    assert_eq!(side_of(push_out(Vec2::new(8.0, 14.0))), Some("top"));
+}
+
+#[test]
+fn exact_corners_push_sideways_and_touching_is_no_overlap() {
+    assert_eq!(side_of(push_out(Vec2::new(-12.0, -12.0))), Some("left"));
+    assert_eq!(side_of(push_out(Vec2::new(12.0, 12.0))), Some("right"));
+    assert_eq!(side_of(push_out(Vec2::new(15.0, 0.0))), None);
+    assert_eq!(side_of(push_out(Vec2::new(15.0, 15.0))), None);
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use common::{Headless, TICKS_PER_FOOD};
+use snake::collision::award_points;
+use snake::config::GameConfig;
+use snake::food::{FoodKind, BONUS_FOOD_POINTS, GOLDEN_FOOD_FACTOR};
+use snake::game::{Combo, Difficulty, Paused, ScoreMultiplier, Scoreboard, COMBO_SECONDS};
+
+fn ticks(config: &GameConfig, seconds: f32) -> usize {
+    (seconds / config.time_step).ceil() as usize
+}
+
+fn started() -> Headless {
+    // The walls don't kill on Easy, so the snake outlives the window
+    let mut game = Headless::with_setup(
+        GameConfig {
+            seed: Some(8),
+            ..GameConfig::default()
+        },
+        |app| {
+            app.insert_resource(Difficulty::Easy);
+        },
+    );
+    game.start_game();
+    game.tick(TICKS_PER_FOOD);
+    game
+}
+
+fn score(game: &Headless) -> usize {
+    game.app.world.resource::<Scoreboard>().score
+}
+
+fn eat_quickly(game: &mut Headless, foods: usize) {
+    let snake = game.head();
+    for _ in 0..foods {
+        game.eat_now(snake, FoodKind::Normal);
+    }
+}
+
+#[test]
+fn foods_in_quick_succession_score_more_up_to_the_cap() {
+    let mut game = started();
+    eat_quickly(&mut game, 3);
+    assert_eq!(score(&game), 1 + 2 + 3);
+
+    eat_quickly(&mut game, 4);
+    assert_eq!(score(&game), 1 + 2 + 3 + 4 + 5 + 5 + 5);
+    assert_eq!(game.app.world.resource::<Combo>().best, 7);
+}
+
+#[test]
+fn the_combo_starts_over_once_the_window_lapses() {
+    let mut game = started();
+    let config = game.app.world.resource::<GameConfig>().clone();
+    eat_quickly(&mut game, 2);
+
+    game.tick(ticks(&config, COMBO_SECONDS) + 1);
+    assert_eq!(game.app.world.resource::<Combo>().count, 0);
+    eat_quickly(&mut game, 1);
+    assert_eq!(score(&game), 1 + 2 + 1);
+    assert_eq!(game.app.world.resource::<Combo>().best, 2);
+}
+
+#[test]
+fn the_window_stops_while_paused() {
+    let mut game = started();
+    eat_quickly(&mut game, 2);
+    let before = game.app.world.resource::<Combo>().remaining_secs();
+
+    **game.app.world.resource_mut::<Paused>() = true;
+    game.tick(600);
+    let combo = game.app.world.resource::<Combo>();
+    assert_eq!(combo.count, 2);
+    assert_eq!(combo.remaining_secs(), before);
+}
+
+#[test]
+fn golden_food_scores_before_its_multiplier_starts() {
+    let mut multiplier = ScoreMultiplier::default();
+    let mut combo = Combo::default();
+    let golden = award_points(FoodKind::Golden, &mut multiplier, &mut combo);
+    assert_eq!(golden, Some(1));
+    assert_eq!(multiplier.factor, GOLDEN_FOOD_FACTOR);
+    // The second food of the combo, under the multiplier
+    let bonus = award_points(FoodKind::Bonus, &mut multiplier, &mut combo);
+    assert_eq!(bonus, Some(BONUS_FOOD_POINTS * GOLDEN_FOOD_FACTOR * 2));
+    assert_eq!(
+        award_points(FoodKind::Shield, &mut multiplier, &mut combo),
+        None
+    );
+    assert_eq!(combo.count, 2);
+}
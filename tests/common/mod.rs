@@ -0,0 +1,210 @@
+// Each test file uses only some of the helpers
+#![allow(dead_code)]
+
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use bevy::time::{create_time_channels, TimeSender};
+
+use snake::build_app;
+use snake::collision::FoodEaten;
+use snake::config::GameConfig;
+use snake::food::FoodKind;
+use snake::game::{Combo, Countdown, Paused};
+use snake::savegame::{SaveAndQuit, SavedGame, SavedGameSlot};
+use snake::snake::{
+    DirectionQueue, Dying, SegmentOrder, Snake, DEATH_FLASH_SECONDS, DEATH_SEGMENTS_SECONDS,
+};
+use snake::state::GameState;
+
+// Ticks in between two foods `eat` counts, so the head has moved on before
+// the body grows where it was
+pub const TICKS_PER_FOOD: usize = 12;
+
+// A windowless app whose clock only moves when `tick` is called, one
+// gameplay step at a time
+pub struct Headless {
+    pub app: App,
+    clock: TimeSender,
+    now: Instant,
+    step: Duration,
+}
+
+impl Headless {
+    pub fn new(config: GameConfig) -> Headless {
+        Headless::with_setup(config, |_| {})
+    }
+
+    // Lets `setup` insert resources before the first update runs the
+    // startup systems
+    pub fn with_setup(config: GameConfig, setup: impl FnOnce(&mut App)) -> Headless {
+        // Rounded up so that every update runs exactly one fixed step
+        let step = Duration::from_nanos((config.time_step as f64 * 1e9).ceil() as u64);
+        let (clock, receiver) = create_time_channels();
+        let mut app = build_app(config, MinimalPlugins);
+        app.insert_resource(receiver);
+        setup(&mut app);
+
+        let now = Instant::now();
+        clock.0.send(now).unwrap();
+        app.update();
+        Headless {
+            app,
+            clock,
+            now,
+            step,
+        }
+    }
+
+    // A fresh app picking up `saved`, through its countdown
+    pub fn resumed(config: GameConfig, saved: SavedGame, setup: impl FnOnce(&mut App)) -> Headless {
+        let mut game = Headless::with_setup(config, |app| {
+            app.insert_resource(SavedGameSlot {
+                saved: Some(saved),
+                resuming: true,
+            });
+            setup(app);
+        });
+        game.start_game();
+        game
+    }
+
+    pub fn start_game(&mut self) {
+        self.app
+            .world
+            .resource_mut::<State<GameState>>()
+            .set(GameState::Playing)
+            .unwrap();
+        self.tick(1);
+        self.skip_countdown();
+    }
+
+    // Lets the snake go with the next tick, without waiting out the 3-2-1
+    pub fn skip_countdown(&mut self) {
+        let mut countdown = self.app.world.resource_mut::<Countdown>();
+        let left = countdown.duration() - countdown.elapsed();
+        countdown.tick(left);
+    }
+
+    // Pauses and saves the run the way the pause menu does, and hands the
+    // save over
+    pub fn save_and_quit(&mut self) -> SavedGame {
+        **self.app.world.resource_mut::<Paused>() = true;
+        self.app.world.send_event(SaveAndQuit);
+        self.tick(1);
+        self.app
+            .world
+            .resource_mut::<SavedGameSlot>()
+            .saved
+            .take()
+            .expect("the run was not saved")
+    }
+
+    // Whether a snake is out of lives and playing out its death
+    pub fn dying(&mut self) -> bool {
+        self.app
+            .world
+            .query_filtered::<(), With<Dying>>()
+            .iter(&self.app.world)
+            .next()
+            .is_some()
+    }
+
+    // Waits for a snake that died to play out `Dying`, the way the game
+    // over screen does
+    pub fn play_out_death(&mut self) {
+        let seconds = DEATH_FLASH_SECONDS + DEATH_SEGMENTS_SECONDS;
+        let ticks = (seconds / self.step.as_secs_f32()).ceil() as usize + 1;
+        for _ in 0..ticks {
+            if !self.dying() {
+                return;
+            }
+            self.tick(1);
+        }
+    }
+
+    pub fn tick(&mut self, ticks: usize) {
+        for _ in 0..ticks {
+            self.now += self.step;
+            self.clock.0.send(self.now).unwrap();
+            self.app.update();
+        }
+    }
+
+    // One frame `steps` gameplay steps long. Shorter than a step, no fixed
+    // update runs in it unless it adds up to one with the frames before.
+    // Longer, it runs that many in a burst.
+    pub fn frame(&mut self, steps: f64) {
+        self.now += self.step.mul_f64(steps);
+        self.clock.0.send(self.now).unwrap();
+        self.app.update();
+    }
+
+    // Counts `foods` pieces of food as eaten wherever the snake is, one after
+    // the other. Each is worth its points on its own, without a combo.
+    pub fn eat(&mut self, foods: usize) {
+        self.eat_kind(foods, FoodKind::Normal);
+    }
+
+    pub fn eat_kind(&mut self, foods: usize, kind: FoodKind) {
+        for _ in 0..foods {
+            self.tick(TICKS_PER_FOOD);
+            let snake = self.head();
+            self.eat_by(snake, kind);
+        }
+    }
+
+    // Counts a piece of food as eaten by `snake` right away, carrying on the
+    // combo if there is one
+    pub fn eat_now(&mut self, snake: Entity, kind: FoodKind) {
+        let food = self.app.world.spawn().id();
+        self.app.world.send_event(FoodEaten {
+            snake,
+            food,
+            position: Vec3::ZERO,
+            kind,
+        });
+        self.tick(1);
+    }
+
+    // Counts a piece of food as eaten by `snake` on its own, after any combo
+    // has run out
+    pub fn eat_by(&mut self, snake: Entity, kind: FoodKind) {
+        self.app.world.resource_mut::<Combo>().count = 0;
+        self.eat_now(snake, kind);
+    }
+
+    // The head of the only snake
+    pub fn head(&mut self) -> Entity {
+        self.app
+            .world
+            .query_filtered::<Entity, With<Snake>>()
+            .single(&self.app.world)
+    }
+
+    pub fn segments(&mut self) -> Vec<Entity> {
+        self.app
+            .world
+            .query_filtered::<&SegmentOrder, With<Snake>>()
+            .single(&self.app.world)
+            .0
+            .clone()
+    }
+
+    pub fn queue(&mut self) -> Mut<'_, DirectionQueue> {
+        let head = self.head();
+        self.app.world.get_mut::<DirectionQueue>(head).unwrap()
+    }
+
+    pub fn head_position(&mut self) -> Vec3 {
+        self.app
+            .world
+            .query_filtered::<&Transform, With<Snake>>()
+            .single(&self.app.world)
+            .translation
+    }
+
+    pub fn state(&self) -> GameState {
+        *self.app.world.resource::<State<GameState>>().current()
+    }
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::Headless;
+use snake::config::GameConfig;
+use snake::game::{Countdown, Difficulty, Paused, StartingLives, COUNTDOWN_SECONDS, THREE_LIVES};
+use snake::snake::Direction;
+use snake::state::GameState;
+
+fn counting_down(game: &Headless) -> bool {
+    !game.app.world.resource::<Countdown>().finished()
+}
+
+// The ticks the whole countdown takes
+fn countdown_ticks(game: &Headless) -> usize {
+    let time_step = game.app.world.resource::<GameConfig>().time_step;
+    (COUNTDOWN_SECONDS / time_step).ceil() as usize
+}
+
+#[test]
+fn the_snake_holds_still_until_the_countdown_is_over() {
+    let mut game = Headless::new(GameConfig {
+        seed: Some(3),
+        ..GameConfig::default()
+    });
+    game.app
+        .world
+        .resource_mut::<State<GameState>>()
+        .set(GameState::Playing)
+        .unwrap();
+    game.tick(1);
+    let start = game.head_position();
+
+    // Turning while it counts down is kept for when it is over
+    game.queue().push_back(Direction::Up);
+    game.tick(countdown_ticks(&game) / 2);
+    assert!(counting_down(&game));
+    assert_eq!(game.head_position(), start);
+    assert_eq!(game.queue().front(), Some(&Direction::Up));
+
+    game.tick(countdown_ticks(&game) / 2 + 10);
+    assert!(!counting_down(&game));
+    assert!(game.head_position().y > start.y);
+}
+
+#[test]
+fn a_lost_life_counts_down_again() {
+    let mut game = Headless::with_setup(
+        GameConfig {
+            seed: Some(12),
+            ..GameConfig::default()
+        },
+        |app| {
+            app.insert_resource(Difficulty::Hard)
+                .insert_resource(StartingLives(THREE_LIVES));
+        },
+    );
+    game.start_game();
+    game.tick(5);
+    assert!(!counting_down(&game));
+
+    let wall = game.app.world.resource::<GameConfig>().left_wall;
+    let head = game.head();
+    game.app
+        .world
+        .get_mut::<Transform>(head)
+        .unwrap()
+        .translation = Vec3::new(wall, 0.0, 0.0);
+    game.tick(1);
+    assert_eq!(game.state(), GameState::Playing);
+    assert!(counting_down(&game));
+}
+
+fn pause_and_resume(countdown_on_resume: bool) -> Headless {
+    let mut game = Headless::new(GameConfig {
+        seed: Some(3),
+        countdown_on_resume,
+        ..GameConfig::default()
+    });
+    game.start_game();
+    game.tick(5);
+    **game.app.world.resource_mut::<Paused>() = true;
+    game.tick(5);
+    **game.app.world.resource_mut::<Paused>() = false;
+    game.tick(1);
+    game
+}
+
+#[test]
+fn resuming_counts_down_only_when_configured_to() {
+    assert!(!counting_down(&pause_and_resume(false)));
+    assert!(counting_down(&pause_and_resume(true)));
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use bevy::ecs::system::CommandQueue;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+
+use common::Headless;
+use snake::collision::DeathCause;
+use snake::config::GameConfig;
+use snake::crates::{random_crates, Crate, Hammer, CRATE_POINTS, HAMMER_SMASHES};
+use snake::food::FoodKind;
+use snake::game::{Difficulty, LastDeath, Scoreboard};
+use snake::snake::{cell_center, grid_cell, Boost};
+use snake::spawning::spawn_crate;
+use snake::walls::{grid_bounds, reachable_cells, ArenaBounds, ArenaConfig};
+
+fn config() -> GameConfig {
+    GameConfig {
+        seed: Some(6),
+        obstacle_count: 0,
+        ..GameConfig::default()
+    }
+}
+
+fn started(config: GameConfig) -> Headless {
+    let mut game = Headless::with_setup(config, |app| {
+        app.insert_resource(Difficulty::Easy);
+    });
+    game.start_game();
+    game.tick(1);
+    game
+}
+
+// A crate a couple of cells ahead of the head, which starts out going left
+fn crate_ahead(game: &mut Headless) -> Entity {
+    let config = game.app.world.resource::<GameConfig>().clone();
+    let head = game.head_position();
+    let cell = grid_cell(&config, head) - IVec2::new(2, 0);
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, &game.app.world);
+    let entity = spawn_crate(&mut commands, &config, cell_center(&config, cell));
+    queue.apply(&mut game.app.world);
+    entity
+}
+
+fn hammer(game: &mut Headless) -> usize {
+    let head = game.head();
+    **game.app.world.get::<Hammer>(head).unwrap()
+}
+
+fn crates(game: &mut Headless) -> usize {
+    game.app
+        .world
+        .query::<&Crate>()
+        .iter(&game.app.world)
+        .count()
+}
+
+#[test]
+fn a_boosting_snake_breaks_a_crate_for_points() {
+    let mut game = started(config());
+    let smashed = crate_ahead(&mut game);
+    let head = game.head();
+    game.app.world.get_mut::<Boost>(head).unwrap().held = true;
+
+    game.tick(20);
+    assert!(game.app.world.get_entity(smashed).is_none());
+    assert!(!game.dying());
+    assert_eq!(game.app.world.resource::<Scoreboard>().score, CRATE_POINTS);
+}
+
+#[test]
+fn running_into_a_crate_otherwise_kills() {
+    let mut game = started(config());
+    let blocker = crate_ahead(&mut game);
+
+    for _ in 0..20 {
+        game.tick(1);
+        if game.dying() {
+            break;
+        }
+    }
+    assert!(game.dying(), "the snake went through the crate");
+    assert!(game.app.world.get_entity(blocker).is_some());
+    assert_eq!(
+        game.app.world.resource::<LastDeath>().cause,
+        Some(DeathCause::Obstacle)
+    );
+}
+
+#[test]
+fn a_hammer_breaks_crates_without_boosting() {
+    let mut game = started(config());
+    let snake = game.head();
+    game.eat_now(snake, FoodKind::Hammer);
+    assert_eq!(hammer(&mut game), HAMMER_SMASHES);
+
+    let smashed = crate_ahead(&mut game);
+    game.tick(20);
+    assert!(game.app.world.get_entity(smashed).is_none());
+    assert!(!game.dying());
+    assert_eq!(hammer(&mut game), HAMMER_SMASHES - 1);
+    assert_eq!(game.app.world.resource::<Scoreboard>().score, CRATE_POINTS);
+}
+
+#[test]
+fn random_crates_never_cut_any_of_the_arena_off() {
+    let config = config();
+    let bounds = ArenaBounds::from_config(&ArenaConfig::from_config(&config));
+    let (min, max) = grid_bounds(&config, &bounds);
+    let start = Vec3::ZERO;
+    for seed in 0..5 {
+        let mut rng = ChaCha12Rng::seed_from_u64(seed);
+        let placed = random_crates(&mut rng, &config, &bounds, 200, &[], &[start]);
+        assert!(!placed.is_empty());
+
+        let blocked: HashSet<IVec2> = placed
+            .iter()
+            .map(|position| grid_cell(&config, *position))
+            .collect();
+        assert_eq!(blocked.len(), placed.len());
+        let start = grid_cell(&config, start);
+        assert!(blocked
+            .iter()
+            .all(|cell| cell.x != start.x && cell.y != start.y));
+        let size = max - min + 1;
+        let open = reachable_cells(min, max, &blocked, start);
+        assert_eq!(open.len() + blocked.len(), (size.x * size.y) as usize);
+    }
+}
+
+#[test]
+fn the_open_arena_gets_the_configured_crates() {
+    let mut game = started(GameConfig {
+        crate_count: 6,
+        ..config()
+    });
+    assert_eq!(crates(&mut game), 6);
+
+    let mut none = started(config());
+    assert_eq!(crates(&mut none), 0);
+}
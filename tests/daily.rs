@@ -0,0 +1,105 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::Headless;
+use snake::config::GameConfig;
+use snake::daily::{DailyChallenge, DailyDate};
+use snake::food::{Food, GameRng};
+use snake::game::{Difficulty, GameMode, HighScores, Lives, StartingLives};
+use snake::state::GameState;
+use snake::walls::WallMode;
+
+const LEAP_DAY: DailyDate = DailyDate {
+    year: 2024,
+    month: 2,
+    day: 29,
+};
+
+// Whatever seed and difficulty the player has set, the day decides the run
+fn daily(date: DailyDate, seed: u64) -> Headless {
+    let mut game = Headless::with_setup(
+        GameConfig {
+            seed: Some(seed),
+            ..GameConfig::default()
+        },
+        |app| {
+            app.insert_resource(GameMode::Daily)
+                .insert_resource(DailyChallenge(date))
+                .insert_resource(Difficulty::Easy)
+                .insert_resource(StartingLives(3));
+        },
+    );
+    game.start_game();
+    game
+}
+
+fn food_positions(game: &mut Headless) -> Vec<Vec3> {
+    let mut query = game.app.world.query_filtered::<&Transform, With<Food>>();
+    query
+        .iter(&game.app.world)
+        .map(|transform| transform.translation)
+        .collect()
+}
+
+// Heads straight for the wall, which is always lethal in the daily
+fn play_until_game_over(game: &mut Headless, foods: usize) {
+    game.eat(foods);
+    for _ in 0..600 {
+        if game.state() == GameState::GameOver {
+            return;
+        }
+        game.tick(1);
+    }
+    panic!("the daily run didn't end");
+}
+
+#[test]
+fn days_are_counted_from_the_epoch_in_the_gregorian_calendar() {
+    let date = |days| DailyDate::from_days_since_epoch(days).key();
+    assert_eq!(date(0), "1970-01-01");
+    assert_eq!(date(-1), "1969-12-31");
+    assert_eq!(date(11_016), "2000-02-29");
+    assert_eq!(date(19_782), LEAP_DAY.key());
+}
+
+#[test]
+fn the_seed_of_a_day_never_changes() {
+    assert_eq!(LEAP_DAY.seed(), 0x293C_C02A_EA25_E48A);
+    let next_day = DailyDate::from_days_since_epoch(19_783);
+    assert_ne!(next_day.seed(), LEAP_DAY.seed());
+}
+
+#[test]
+fn everyone_gets_the_same_run_on_the_same_day() {
+    let mut first = daily(LEAP_DAY, 1);
+    let mut second = daily(LEAP_DAY, 2);
+    assert_eq!(first.app.world.resource::<GameRng>().seed, LEAP_DAY.seed());
+    assert_eq!(food_positions(&mut first), food_positions(&mut second));
+    first.eat(3);
+    second.eat(3);
+    assert_eq!(food_positions(&mut first), food_positions(&mut second));
+
+    // Without the options picked on the menu
+    assert_eq!(**first.app.world.resource::<Lives>(), 1);
+    assert_eq!(*first.app.world.resource::<WallMode>(), WallMode::Lethal);
+}
+
+#[test]
+fn only_the_best_attempt_of_the_day_is_kept() {
+    let mut game = daily(LEAP_DAY, 1);
+    play_until_game_over(&mut game, 3);
+    let high_scores = game.app.world.resource::<HighScores>();
+    assert_eq!(high_scores.daily_best(&LEAP_DAY.key()), Some(3));
+    assert_eq!(high_scores.previous_daily, None);
+    assert!(high_scores.last_was_best);
+    // The daily doesn't count for the difficulty's best
+    assert_eq!(high_scores.best(Difficulty::Normal), 0);
+
+    game.start_game();
+    play_until_game_over(&mut game, 1);
+    let high_scores = game.app.world.resource::<HighScores>();
+    assert_eq!(high_scores.daily_best(&LEAP_DAY.key()), Some(3));
+    assert_eq!(high_scores.previous_daily, Some(3));
+    assert!(!high_scores.last_was_best);
+}
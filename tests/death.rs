@@ -0,0 +1,99 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::Headless;
+use snake::config::GameConfig;
+use snake::food::FoodKind;
+use snake::game::{Difficulty, GameTick};
+use snake::input::skip_death_animation;
+use snake::snake::{Dying, DEATH_FLASH_SECONDS};
+use snake::state::GameState;
+
+// On Hard the walls are lethal, and there is only the one life
+fn dying_with_segments(segments: usize) -> Headless {
+    let mut game = Headless::with_setup(GameConfig::default(), |app| {
+        app.insert_resource(Difficulty::Hard)
+            .init_resource::<Input<KeyCode>>()
+            .init_resource::<Input<GamepadButton>>()
+            .add_system_to_stage(CoreStage::PreUpdate, skip_death_animation);
+    });
+    game.start_game();
+    game.eat(segments);
+    // Into the left wall
+    let wall = game.app.world.resource::<GameConfig>().left_wall;
+    let head = game.head();
+    game.app
+        .world
+        .get_mut::<Transform>(head)
+        .unwrap()
+        .translation = Vec3::new(wall, 0.0, 0.0);
+    game.tick(1);
+    assert!(game.dying(), "the snake didn't die");
+    game
+}
+
+fn dying(game: &mut Headless) -> &Dying {
+    let head = game.head();
+    game.app.world.get::<Dying>(head).unwrap()
+}
+
+#[test]
+fn the_body_goes_from_the_tail_before_the_game_over_screen() {
+    let mut game = dying_with_segments(4);
+    let segments = game.segments();
+    assert_eq!(segments.len(), 4);
+    assert_eq!(game.state(), GameState::Playing);
+
+    // The head flashes with the body still in one piece
+    game.tick(5);
+    assert_eq!(game.segments(), segments);
+    assert!(dying(&mut game).flashing());
+
+    // It is gone one segment at a time, each time the last one
+    let mut lengths = Vec::new();
+    while game.state() == GameState::Playing {
+        let now = game.segments();
+        assert_eq!(
+            now[..],
+            segments[..now.len()],
+            "a segment went out of order"
+        );
+        if lengths.last() != Some(&now.len()) {
+            lengths.push(now.len());
+        }
+        game.tick(1);
+    }
+    assert_eq!(lengths, [4, 3, 2, 1]);
+    assert_eq!(game.state(), GameState::GameOver);
+}
+
+#[test]
+fn nothing_moves_or_collides_while_the_snake_dies() {
+    let mut game = dying_with_segments(2);
+    let tick = **game.app.world.resource::<GameTick>();
+    let position = game.head_position();
+    game.tick(30);
+    let head = game.head();
+    game.eat_now(head, FoodKind::Poison);
+    assert_eq!(**game.app.world.resource::<GameTick>(), tick);
+    assert_eq!(game.head_position(), position);
+    assert!(dying(&mut game).timer.elapsed_secs() > DEATH_FLASH_SECONDS);
+}
+
+#[test]
+fn any_key_skips_to_the_game_over_screen() {
+    let mut game = dying_with_segments(3);
+    game.app
+        .world
+        .resource_mut::<Input<KeyCode>>()
+        .press(KeyCode::Space);
+    game.tick(1);
+    assert_eq!(game.state(), GameState::GameOver);
+    // The key that skipped it doesn't get to restart the game as well
+    assert!(!game
+        .app
+        .world
+        .resource::<Input<KeyCode>>()
+        .just_pressed(KeyCode::Space));
+}
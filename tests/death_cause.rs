@@ -0,0 +1,90 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::Headless;
+use snake::collision::DeathCause;
+use snake::config::GameConfig;
+use snake::game::{Difficulty, LastDeath};
+use snake::locale::{Language, Locale};
+use snake::snake::Direction;
+use snake::state::GameState;
+use snake::ui::death_message;
+use snake::walls::WallLocation;
+
+// On Hard the walls are lethal, and there is only the one life. Nothing
+// else in the arena to run into.
+fn started(segments: usize) -> Headless {
+    let config = GameConfig {
+        seed: Some(5),
+        obstacle_count: 0,
+        ..GameConfig::default()
+    };
+    let mut game = Headless::with_setup(config, |app| {
+        app.insert_resource(Difficulty::Hard);
+    });
+    game.start_game();
+    game.eat(segments);
+    game
+}
+
+fn move_head_to(game: &mut Headless, position: Vec3) {
+    let head = game.head();
+    game.app
+        .world
+        .get_mut::<Transform>(head)
+        .unwrap()
+        .translation = position;
+    game.tick(1);
+    game.play_out_death();
+    assert_eq!(game.state(), GameState::GameOver);
+}
+
+#[test]
+fn running_into_a_wall_names_the_wall() {
+    let mut game = started(2);
+    let config = game.app.world.resource::<GameConfig>().clone();
+    move_head_to(&mut game, Vec3::new(0.0, config.top_wall, 0.0));
+
+    let last_death = game.app.world.resource::<LastDeath>();
+    assert_eq!(last_death.cause, Some(DeathCause::Wall(WallLocation::Top)));
+    assert_eq!(
+        death_message(&Locale::load(Language::English), last_death).unwrap(),
+        "You ran into the top wall"
+    );
+}
+
+#[test]
+fn biting_the_tail_tells_how_long_the_snake_was() {
+    let mut game = started(4);
+    // Around a square, back into the body
+    game.queue()
+        .extend([Direction::Down, Direction::Right, Direction::Up]);
+    for _ in 0..120 {
+        if game.dying() {
+            break;
+        }
+        game.tick(1);
+    }
+    game.play_out_death();
+    assert_eq!(game.state(), GameState::GameOver);
+
+    let last_death = game.app.world.resource::<LastDeath>();
+    assert_eq!(last_death.cause, Some(DeathCause::SelfCollision));
+    assert_eq!(last_death.length, 5);
+    assert_eq!(
+        death_message(&Locale::load(Language::English), last_death).unwrap(),
+        "You bit your own tail at length 5"
+    );
+}
+
+#[test]
+fn a_new_run_forgets_the_last_death() {
+    let mut game = started(1);
+    let wall = game.app.world.resource::<GameConfig>().left_wall;
+    move_head_to(&mut game, Vec3::new(wall, 0.0, 0.0));
+    assert!(game.app.world.resource::<LastDeath>().cause.is_some());
+
+    game.start_game();
+    assert!(game.app.world.resource::<LastDeath>().cause.is_none());
+}
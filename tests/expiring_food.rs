@@ -0,0 +1,131 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::Headless;
+use snake::collision::FoodEaten;
+use snake::config::GameConfig;
+use snake::food::{Food, FoodKind, FoodLifetime, FOOD_LIFETIME_SECONDS};
+use snake::game::{Difficulty, Paused, Scoreboard};
+use snake::snake::GameSpeed;
+
+// The snake holds still, so the food is left alone until it expires
+fn started() -> Headless {
+    let mut game = Headless::with_setup(
+        GameConfig {
+            seed: Some(3),
+            expiring_food: true,
+            ..GameConfig::default()
+        },
+        |app| {
+            app.insert_resource(Difficulty::Easy)
+                .insert_resource(GameSpeed(0.0));
+        },
+    );
+    game.start_game();
+    // The first tick only catches the timestep up
+    game.tick(1);
+    game
+}
+
+fn ticks(config: &GameConfig, seconds: f32) -> usize {
+    (seconds / config.time_step).ceil() as usize
+}
+
+fn normal_food(game: &mut Headless) -> Vec<(Entity, Vec3)> {
+    game.app
+        .world
+        .query::<(Entity, &Transform, &FoodKind)>()
+        .iter(&game.app.world)
+        .filter(|(_, _, kind)| **kind == FoodKind::Normal)
+        .map(|(food, transform, _)| (food, transform.translation))
+        .collect()
+}
+
+// Runs the only food's lifetime down to its last tick
+fn nearly_expire(game: &mut Headless) -> Entity {
+    let config = game.app.world.resource::<GameConfig>().clone();
+    let (food, _) = normal_food(game)[0];
+    let mut lifetime = game.app.world.get_mut::<FoodLifetime>(food).unwrap();
+    let left = lifetime.duration() - std::time::Duration::from_secs_f32(config.time_step / 2.0);
+    lifetime.set_elapsed(left);
+    food
+}
+
+#[test]
+fn uneaten_food_moves_somewhere_else() {
+    let mut game = started();
+    let config = game.app.world.resource::<GameConfig>().clone();
+    let before = normal_food(&mut game);
+    assert_eq!(before.len(), 1);
+
+    game.tick(ticks(&config, FOOD_LIFETIME_SECONDS) + 1);
+    let after = normal_food(&mut game);
+    assert_eq!(after.len(), 1);
+    assert_ne!(after[0].0, before[0].0);
+    assert_ne!(after[0].1, before[0].1);
+    // The new one has all of its time ahead of it
+    let lifetime = game.app.world.get::<FoodLifetime>(after[0].0).unwrap();
+    assert!(lifetime.remaining_secs() > FOOD_LIFETIME_SECONDS - 1.0);
+}
+
+#[test]
+fn food_eaten_as_it_expires_counts_and_is_only_replaced_once() {
+    let mut game = started();
+    let food = nearly_expire(&mut game);
+    let snake = game.head();
+    game.app.world.send_event(FoodEaten {
+        snake,
+        food,
+        position: Vec3::ZERO,
+        kind: FoodKind::Normal,
+    });
+    game.tick(1);
+
+    assert_eq!(game.app.world.resource::<Scoreboard>().score, 1);
+    let left = normal_food(&mut game);
+    assert_eq!(left.len(), 1);
+    assert_ne!(left[0].0, food);
+}
+
+#[test]
+fn the_lifetime_stops_while_paused() {
+    let mut game = started();
+    let food = nearly_expire(&mut game);
+    **game.app.world.resource_mut::<Paused>() = true;
+    game.tick(60);
+    assert!(game.app.world.get::<Food>(food).is_some());
+
+    **game.app.world.resource_mut::<Paused>() = false;
+    game.tick(1);
+    assert!(game.app.world.get_entity(food).is_none());
+}
+
+#[test]
+fn a_resumed_run_keeps_the_time_left_on_the_food() {
+    let mut game = started();
+    let config = game.app.world.resource::<GameConfig>().clone();
+    game.tick(ticks(&config, FOOD_LIFETIME_SECONDS / 2.0));
+    let (food, position) = normal_food(&mut game)[0];
+    let remaining = game
+        .app
+        .world
+        .get::<FoodLifetime>(food)
+        .unwrap()
+        .remaining_secs();
+    assert!(remaining < FOOD_LIFETIME_SECONDS);
+
+    let saved = game.save_and_quit();
+    let mut resumed = Headless::resumed(config, saved, |app| {
+        app.insert_resource(Difficulty::Easy);
+    });
+    let (food, restored) = normal_food(&mut resumed)[0];
+    assert_eq!(restored, position);
+    let left = resumed
+        .app
+        .world
+        .get::<FoodLifetime>(food)
+        .unwrap()
+        .remaining_secs();
+    assert!((left - remaining).abs() < 1e-4);
+}
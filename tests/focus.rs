@@ -0,0 +1,55 @@
+mod common;
+
+use bevy::window::{WindowFocused, WindowId};
+
+use common::Headless;
+use snake::config::GameConfig;
+use snake::game::{GameTick, Paused};
+use snake::input::pause_on_focus_loss;
+
+fn started(pause_on_focus_loss_enabled: bool) -> Headless {
+    let config = GameConfig {
+        pause_on_focus_loss: pause_on_focus_loss_enabled,
+        ..GameConfig::default()
+    };
+    let mut game = Headless::with_setup(config, |app| {
+        app.add_event::<WindowFocused>()
+            .add_system(pause_on_focus_loss);
+    });
+    game.start_game();
+    game
+}
+
+fn focus(game: &mut Headless, focused: bool) {
+    game.app.world.send_event(WindowFocused {
+        id: WindowId::primary(),
+        focused,
+    });
+    game.tick(1);
+}
+
+#[test]
+fn losing_focus_pauses_until_the_player_resumes() {
+    let mut game = started(true);
+    focus(&mut game, false);
+    assert!(**game.app.world.resource::<Paused>());
+
+    // Coming back to the window isn't enough to carry on
+    let tick = **game.app.world.resource::<GameTick>();
+    focus(&mut game, true);
+    game.tick(30);
+    assert!(**game.app.world.resource::<Paused>());
+    assert_eq!(**game.app.world.resource::<GameTick>(), tick);
+
+    // Nor does resuming catch up on the ticks missed in the meantime
+    **game.app.world.resource_mut::<Paused>() = false;
+    game.tick(2);
+    assert!(**game.app.world.resource::<GameTick>() <= tick + 2);
+}
+
+#[test]
+fn background_play_keeps_going_without_focus() {
+    let mut game = started(false);
+    focus(&mut game, false);
+    assert!(!**game.app.world.resource::<Paused>());
+}
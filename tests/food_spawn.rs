@@ -0,0 +1,91 @@
+mod common;
+
+use bevy::ecs::event::Events;
+use bevy::prelude::*;
+
+use common::Headless;
+use snake::collision::FoodEaten;
+use snake::config::GameConfig;
+use snake::food::{spawn_pop_scale, Food, SpawnAnimation, FOOD_SPAWN_SECONDS};
+use snake::game::{Difficulty, Paused, Scoreboard};
+use snake::snake::{GameSpeed, Snake};
+
+// The snake holds still wherever it is put
+fn started() -> Headless {
+    let mut game = Headless::with_setup(
+        GameConfig {
+            seed: Some(5),
+            obstacle_count: 0,
+            ..GameConfig::default()
+        },
+        |app| {
+            app.insert_resource(Difficulty::Easy)
+                .insert_resource(GameSpeed(0.0));
+        },
+    );
+    game.start_game();
+    game
+}
+
+fn food(game: &mut Headless) -> (Entity, Vec3) {
+    game.app
+        .world
+        .query_filtered::<(Entity, &Transform), (With<Food>, Without<Snake>)>()
+        .iter(&game.app.world)
+        .map(|(food, transform)| (food, transform.translation))
+        .next()
+        .unwrap()
+}
+
+fn elapsed(game: &Headless, food: Entity) -> Option<f32> {
+    game.app
+        .world
+        .get::<SpawnAnimation>(food)
+        .map(|animation| animation.elapsed_secs())
+}
+
+fn move_head_onto(game: &mut Headless, position: Vec3) -> usize {
+    let head = game.head();
+    game.app
+        .world
+        .get_mut::<Transform>(head)
+        .unwrap()
+        .translation = position;
+    game.tick(1);
+    let mut events = game.app.world.resource_mut::<Events<FoodEaten>>();
+    events.drain().count()
+}
+
+#[test]
+fn food_grows_a_little_past_its_size_and_back() {
+    assert_eq!(spawn_pop_scale(0.0), 0.0);
+    assert!((spawn_pop_scale(1.0) - 1.0).abs() < 1e-6);
+    let peak = (0..=100)
+        .map(|step| spawn_pop_scale(step as f32 / 100.0))
+        .fold(0.0, f32::max);
+    assert!(peak > 1.0 && peak < 1.1, "overshoots to {peak}");
+}
+
+#[test]
+fn food_can_only_be_eaten_once_it_has_mostly_grown() {
+    let mut game = started();
+    let (food, position) = food(&mut game);
+    assert!(elapsed(&game, food).is_some());
+    assert_eq!(move_head_onto(&mut game, position), 0);
+
+    // Left on it, the head eats it as soon as it may
+    let time_step = game.app.world.resource::<GameConfig>().time_step;
+    game.tick((FOOD_SPAWN_SECONDS / time_step).ceil() as usize);
+    assert_eq!(game.app.world.resource::<Scoreboard>().score, 1);
+    assert!(game.app.world.get_entity(food).is_none());
+}
+
+#[test]
+fn food_stops_growing_while_the_game_is_paused() {
+    let mut game = started();
+    let (food, _) = food(&mut game);
+    let before = elapsed(&game, food);
+    **game.app.world.resource_mut::<Paused>() = true;
+    game.tick(10);
+    assert_eq!(elapsed(&game, food), before);
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::Headless;
+use snake::config::GameConfig;
+use snake::food::{FoodKind, GHOST_SECONDS};
+use snake::game::Difficulty;
+use snake::snake::{GhostMode, MovementMode, Snake};
+use snake::state::GameState;
+
+fn ticks(config: &GameConfig, seconds: f32) -> usize {
+    (seconds / config.time_step).ceil() as usize
+}
+
+// On Hard the walls are lethal, so only the ghost gets through them
+fn started(config: &GameConfig, movement_mode: MovementMode) -> Headless {
+    let mut game = Headless::with_setup(config.clone(), |app| {
+        app.insert_resource(Difficulty::Hard)
+            .insert_resource(movement_mode);
+    });
+    game.start_game();
+    game
+}
+
+#[test]
+fn a_ghost_comes_out_of_the_opposite_wall() {
+    let config = GameConfig {
+        seed: Some(5),
+        ..GameConfig::default()
+    };
+    let mut game = started(&config, MovementMode::Grid);
+    game.eat_kind(1, FoodKind::Ghost);
+
+    // The snake starts in the middle heading left
+    let mut wrapped = false;
+    for _ in 0..ticks(&config, GHOST_SECONDS) {
+        game.tick(1);
+        assert_eq!(game.state(), GameState::Playing);
+        if game.head_position().x > 0.0 {
+            wrapped = true;
+            break;
+        }
+    }
+    assert!(wrapped, "the snake never came out on the right");
+}
+
+#[test]
+fn running_out_inside_a_wall_pushes_the_snake_back_in() {
+    let config = GameConfig {
+        seed: Some(5),
+        ..GameConfig::default()
+    };
+    let mut game = started(&config, MovementMode::Continuous);
+    game.tick(5);
+
+    // Right after wrapping, halfway through the right wall on its way in
+    game.app
+        .world
+        .resource_mut::<GhostMode>()
+        .start(config.time_step);
+    let mut head = game
+        .app
+        .world
+        .query_filtered::<&mut Transform, With<Snake>>()
+        .single_mut(&mut game.app.world);
+    head.translation.x = config.right_wall;
+    game.tick(2);
+
+    assert!(!game.app.world.resource::<GhostMode>().is_active());
+    assert_eq!(game.state(), GameState::Playing);
+    let inside = config.right_wall - config.wall_thickness / 2.0 - config.snake_size.x / 2.0;
+    assert!(game.head_position().x <= inside);
+}
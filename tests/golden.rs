@@ -0,0 +1,79 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::Headless;
+use snake::config::GameConfig;
+use snake::food::{FoodKind, GOLDEN_FOOD_FACTOR, GOLDEN_FOOD_SECONDS};
+use snake::game::{Difficulty, Paused, ScoreMultiplier, Scoreboard};
+use snake::state::GameState;
+
+fn ticks(config: &GameConfig, seconds: f32) -> usize {
+    (seconds / config.time_step).ceil() as usize
+}
+
+fn score(game: &Headless) -> usize {
+    game.app.world.resource::<Scoreboard>().score
+}
+
+fn factor(game: &Headless) -> usize {
+    game.app.world.resource::<ScoreMultiplier>().factor
+}
+
+fn started(config: &GameConfig) -> Headless {
+    // The walls don't kill on Easy, so the snake outlives the window
+    let mut game = Headless::with_setup(config.clone(), |app| {
+        app.insert_resource(Difficulty::Easy);
+    });
+    game.start_game();
+    game
+}
+
+#[test]
+fn a_second_golden_food_refreshes_the_window() {
+    let config = GameConfig {
+        seed: Some(5),
+        ..GameConfig::default()
+    };
+    let mut game = started(&config);
+
+    game.eat_kind(1, FoodKind::Golden);
+    assert_eq!(score(&game), 1);
+    game.eat(1);
+    assert_eq!(score(&game), 1 + GOLDEN_FOOD_FACTOR);
+
+    game.tick(ticks(&config, GOLDEN_FOOD_SECONDS / 2.0));
+    game.eat_kind(1, FoodKind::Golden);
+    assert_eq!(factor(&game), GOLDEN_FOOD_FACTOR);
+    game.tick(ticks(&config, GOLDEN_FOOD_SECONDS * 0.75));
+    assert_eq!(factor(&game), GOLDEN_FOOD_FACTOR);
+
+    // Paused, the window doesn't run out
+    **game.app.world.resource_mut::<Paused>() = true;
+    game.tick(ticks(&config, GOLDEN_FOOD_SECONDS));
+    assert_eq!(factor(&game), GOLDEN_FOOD_FACTOR);
+
+    **game.app.world.resource_mut::<Paused>() = false;
+    game.tick(ticks(&config, GOLDEN_FOOD_SECONDS / 2.0));
+    assert_eq!(game.state(), GameState::Playing);
+    assert_eq!(factor(&game), 1);
+}
+
+#[test]
+fn restarting_clears_the_multiplier() {
+    let config = GameConfig {
+        seed: Some(5),
+        ..GameConfig::default()
+    };
+    let mut game = started(&config);
+    game.eat_kind(1, FoodKind::Golden);
+    assert_eq!(factor(&game), GOLDEN_FOOD_FACTOR);
+
+    game.app
+        .world
+        .resource_mut::<State<GameState>>()
+        .restart()
+        .unwrap();
+    game.tick(1);
+    assert_eq!(factor(&game), 1);
+}
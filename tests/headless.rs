@@ -0,0 +1,173 @@
+mod common;
+
+use bevy::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+
+use common::Headless;
+use snake::collision::FoodEaten;
+use snake::config::GameConfig;
+use snake::food::{free_food_position, Food, FoodKind};
+use snake::game::Scoreboard;
+use snake::snake::{
+    cell_center, grid_cell, MovementMode, Snake, SnakeSegment, INITIAL_SNAKE_DIRECTION,
+    SNAKE_STARTING_POSITION,
+};
+use snake::walls::{grid_bounds, random_obstacles, ArenaBounds, ArenaConfig};
+
+#[test]
+fn snake_moves_in_the_initial_direction() {
+    let mut game = Headless::new(GameConfig::default());
+    game.start_game();
+    let start = game.head_position();
+    assert_eq!(start, SNAKE_STARTING_POSITION);
+
+    // Half a second is a few grid steps at the default speed
+    game.tick(30);
+    let moved = game.head_position() - start;
+    assert!(moved.length() > 0.0, "the snake didn't move");
+    assert_eq!(
+        moved.truncate().normalize(),
+        INITIAL_SNAKE_DIRECTION.normalize()
+    );
+}
+
+#[test]
+fn food_is_never_placed_on_the_snake() {
+    let config = GameConfig::default();
+    let bounds = ArenaBounds::from_config(&ArenaConfig::from_config(&config));
+    let mut rng = StdRng::seed_from_u64(7);
+    let (min, max) = grid_bounds(&config, &bounds);
+
+    // Everything but the top row is taken, so some rolls are bound to miss
+    let occupied: Vec<(Vec3, Vec2)> = (min.x..=max.x)
+        .flat_map(|x| (min.y..max.y).map(move |y| IVec2::new(x, y)))
+        .map(|cell| (cell_center(&config, cell), config.snake_size))
+        .collect();
+
+    for _ in 0..100 {
+        let position =
+            free_food_position(&mut rng, &config, &bounds, MovementMode::Grid, &occupied)
+                .expect("the top row is free");
+        let cell = grid_cell(&config, position);
+        assert_eq!(cell.y, max.y);
+        assert!((min.x..=max.x).contains(&cell.x));
+    }
+}
+
+#[test]
+fn obstacles_leave_the_starting_lines_free() {
+    let config = GameConfig::default();
+    let bounds = ArenaBounds::from_config(&ArenaConfig::from_config(&config));
+    let start = SNAKE_STARTING_POSITION;
+
+    for seed in 0..50 {
+        let obstacles = random_obstacles(
+            &mut StdRng::seed_from_u64(seed),
+            &config,
+            &bounds,
+            20,
+            &[start],
+        );
+        assert!(!obstacles.is_empty());
+        for (center, size) in &obstacles {
+            let min = *center - size.extend(0.0) / 2.0;
+            let max = *center + size.extend(0.0) / 2.0;
+            assert!(
+                !(min.x..max.x).contains(&start.x),
+                "blocks the starting row"
+            );
+            assert!(
+                !(min.y..max.y).contains(&start.y),
+                "blocks the starting column"
+            );
+        }
+        // The same seed rolls the same blocks
+        assert_eq!(
+            random_obstacles(
+                &mut StdRng::seed_from_u64(seed),
+                &config,
+                &bounds,
+                20,
+                &[start]
+            ),
+            obstacles
+        );
+    }
+}
+
+// Where the normal food is, leaving out any poison that came along
+fn food_positions(game: &mut Headless) -> Vec<(Entity, Vec3)> {
+    game.app
+        .world
+        .query_filtered::<(Entity, &Transform, &FoodKind), With<Food>>()
+        .iter(&game.app.world)
+        .filter(|(_, _, kind)| **kind == FoodKind::Normal)
+        .map(|(food, transform, _)| (food, transform.translation))
+        .collect()
+}
+
+#[test]
+fn eaten_food_is_replaced_one_for_one() {
+    let config = GameConfig {
+        simultaneous_food: 3,
+        seed: Some(11),
+        ..GameConfig::default()
+    };
+    let mut game = Headless::new(config.clone());
+    game.start_game();
+    // The clock needs a few updates before the first tick runs
+    game.tick(5);
+    assert_eq!(food_positions(&mut game).len(), 3);
+
+    // Two pieces eaten in the same tick both count, the second as a combo
+    let foods = food_positions(&mut game);
+    let snake = game.head();
+    for (food, position) in &foods[..2] {
+        game.app.world.send_event(FoodEaten {
+            snake,
+            food: *food,
+            position: *position,
+            kind: FoodKind::Normal,
+        });
+    }
+    game.tick(1);
+
+    let replaced = food_positions(&mut game);
+    assert_eq!(replaced.len(), 3);
+    assert_eq!(game.app.world.resource::<Scoreboard>().score, 1 + 2);
+    assert!(replaced.iter().any(|(food, _)| *food == foods[2].0));
+    for (index, (_, position)) in replaced.iter().enumerate() {
+        for (_, other) in &replaced[index + 1..] {
+            assert_ne!(
+                grid_cell(&config, *position),
+                grid_cell(&config, *other),
+                "two pieces of food share a cell"
+            );
+        }
+    }
+}
+
+#[test]
+fn the_game_carries_on_without_a_snake_or_food() {
+    let mut game = Headless::new(GameConfig {
+        seed: Some(4),
+        ..GameConfig::default()
+    });
+    game.start_game();
+    game.tick(10);
+
+    let mut query = game
+        .app
+        .world
+        .query_filtered::<Entity, Or<(With<Snake>, With<SnakeSegment>, With<Food>)>>();
+    let doomed: Vec<Entity> = query.iter(&game.app.world).collect();
+    for entity in doomed {
+        game.app.world.despawn(entity);
+    }
+    game.tick(60);
+
+    // The food comes back by itself, and nothing is scored without a snake
+    let mut food_query = game.app.world.query_filtered::<(), With<Food>>();
+    assert_eq!(food_query.iter(&game.app.world).count(), 1);
+    assert_eq!(game.app.world.resource::<Scoreboard>().score, 0);
+}
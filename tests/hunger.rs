@@ -0,0 +1,122 @@
+mod common;
+
+use common::Headless;
+use snake::collision::DeathCause;
+use snake::config::GameConfig;
+use snake::food::FoodKind;
+use snake::game::{Difficulty, GameMode, LastDeath, Paused};
+use snake::hunger::{Hunger, HUNGER_BONUS_REFILL, HUNGER_FOOD_REFILL, HUNGER_SECONDS};
+use snake::snake::GameSpeed;
+
+fn ticks(config: &GameConfig, seconds: f32) -> usize {
+    (seconds / config.time_step).ceil() as usize
+}
+
+// The snake is held still, so only the hunger can kill it
+fn started(hunger: bool, difficulty: Difficulty) -> Headless {
+    started_in(GameMode::Classic, hunger, difficulty)
+}
+
+fn started_in(mode: GameMode, hunger: bool, difficulty: Difficulty) -> Headless {
+    let mut game = Headless::with_setup(
+        GameConfig {
+            seed: Some(3),
+            obstacle_count: 0,
+            hunger,
+            ..GameConfig::default()
+        },
+        |app| {
+            app.insert_resource(difficulty).insert_resource(mode);
+        },
+    );
+    game.start_game();
+    game.tick(1);
+    game.app.insert_resource(GameSpeed(0.0));
+    game
+}
+
+fn hunger(game: &Headless) -> Hunger {
+    *game.app.world.resource::<Hunger>()
+}
+
+#[test]
+fn an_empty_meter_starves_the_snake() {
+    let mut game = started(true, Difficulty::Normal);
+    let config = game.app.world.resource::<GameConfig>().clone();
+    game.tick(ticks(&config, HUNGER_SECONDS) - 5);
+    assert!(!game.dying());
+    assert!(hunger(&game).is_low());
+
+    game.tick(10);
+    assert!(game.dying(), "the snake never starved");
+    assert_eq!(
+        game.app.world.resource::<LastDeath>().cause,
+        Some(DeathCause::Starved)
+    );
+}
+
+#[test]
+fn classic_games_go_without_a_meter() {
+    let mut game = started(false, Difficulty::Normal);
+    let config = game.app.world.resource::<GameConfig>().clone();
+    game.tick(ticks(&config, HUNGER_SECONDS * 1.5));
+    assert!(!game.dying());
+    assert_eq!(hunger(&game), Hunger::default());
+}
+
+// What is on the meter a tick after eating `kind` with a second left. A
+// fresh game every time, as the held still snake bites what it grows.
+fn refilled(kind: FoodKind) -> f32 {
+    let mut game = started(true, Difficulty::Normal);
+    game.app.insert_resource(Hunger {
+        value: 1.0,
+        max: HUNGER_SECONDS,
+    });
+    let snake = game.head();
+    game.eat_now(snake, kind);
+    hunger(&game).value
+}
+
+#[test]
+fn food_fills_the_meter_and_the_bonus_more_so() {
+    let config = GameConfig::default();
+    let normal = refilled(FoodKind::Normal);
+    let expected = 1.0 + HUNGER_FOOD_REFILL * HUNGER_SECONDS - config.time_step;
+    assert!((normal - expected).abs() < 1e-3);
+
+    let bonus = refilled(FoodKind::Bonus);
+    let expected = (1.0 + HUNGER_BONUS_REFILL * HUNGER_SECONDS).min(HUNGER_SECONDS);
+    assert!((bonus - (expected - config.time_step)).abs() < 1e-3);
+    assert!(bonus > normal);
+
+    // The pickups are no meal
+    assert!(refilled(FoodKind::Shield) < 1.0);
+}
+
+#[test]
+fn harder_games_drain_it_faster() {
+    let mut easy = started(true, Difficulty::Easy);
+    let mut hard = started(true, Difficulty::Hard);
+    easy.tick(60);
+    hard.tick(60);
+    assert!(hunger(&hard).value < hunger(&easy).value);
+}
+
+#[test]
+fn the_daily_challenge_drains_it_at_its_own_pace() {
+    let mut daily = started_in(GameMode::Daily, true, Difficulty::Easy);
+    let mut normal = started(true, Difficulty::Normal);
+    daily.tick(60);
+    normal.tick(60);
+    assert_eq!(hunger(&daily), hunger(&normal));
+}
+
+#[test]
+fn the_meter_stands_still_while_paused() {
+    let mut game = started(true, Difficulty::Normal);
+    game.tick(10);
+    let before = hunger(&game);
+    game.app.insert_resource(Paused(true));
+    game.tick(60);
+    assert_eq!(hunger(&game), before);
+}
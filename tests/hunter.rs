@@ -0,0 +1,247 @@
+mod common;
+
+use bevy::ecs::system::CommandQueue;
+use bevy::prelude::*;
+
+use common::Headless;
+use snake::collision::DeathCause;
+use snake::config::GameConfig;
+use snake::food::FoodKind;
+use snake::game::{Difficulty, GameMode, LastDeath, Scoreboard};
+use snake::hunter::{
+    Hunter, HunterCountdown, HUNTER_AFTER_FOODS, HUNTER_BONUS_POINTS, HUNTER_RETARGET_SECONDS,
+    HUNTER_SAFE_DISTANCE, HUNTER_SPEED_FACTOR,
+};
+use snake::snake::{cell_center, GameSpeed, Velocity};
+use snake::spawning::spawn_hunter;
+use snake::walls::{grid_bounds, ArenaBounds};
+
+fn started(hunter: bool, difficulty: Difficulty) -> Headless {
+    started_in(GameMode::Classic, hunter, difficulty)
+}
+
+fn started_in(mode: GameMode, hunter: bool, difficulty: Difficulty) -> Headless {
+    let mut game = Headless::with_setup(
+        GameConfig {
+            seed: Some(4),
+            obstacle_count: 0,
+            hunter,
+            ..GameConfig::default()
+        },
+        |app| {
+            app.insert_resource(difficulty).insert_resource(mode);
+        },
+    );
+    game.start_game();
+    game.tick(1);
+    game
+}
+
+fn ticks(config: &GameConfig, seconds: f32) -> usize {
+    (seconds / config.time_step).ceil() as usize
+}
+
+// Quicker than `Headless::eat`, and from the right of the arena, so the
+// snake doesn't reach the left wall on Hard
+fn feed(game: &mut Headless, foods: usize) {
+    if game.segments().is_empty() {
+        let config = game.app.world.resource::<GameConfig>().clone();
+        let bounds = *game.app.world.resource::<ArenaBounds>();
+        let (_, max) = grid_bounds(&config, &bounds);
+        let head = game.head();
+        game.app
+            .world
+            .get_mut::<Transform>(head)
+            .unwrap()
+            .translation = cell_center(&config, IVec2::new(max.x - 1, 0));
+    }
+    for _ in 0..foods {
+        game.tick(8);
+        let snake = game.head();
+        game.eat_by(snake, FoodKind::Normal);
+    }
+}
+
+fn hunters(game: &mut Headless) -> Vec<(Entity, Vec3)> {
+    game.app
+        .world
+        .query_filtered::<(Entity, &Transform), With<Hunter>>()
+        .iter(&game.app.world)
+        .map(|(hunter, transform)| (hunter, transform.translation))
+        .collect()
+}
+
+fn release_at(game: &mut Headless, position: Vec3) -> Entity {
+    let config = game.app.world.resource::<GameConfig>().clone();
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, &game.app.world);
+    let hunter = spawn_hunter(&mut commands, &config, position);
+    queue.apply(&mut game.app.world);
+    hunter
+}
+
+fn heading(game: &Headless, hunter: Entity) -> Vec2 {
+    game.app.world.get::<Hunter>(hunter).unwrap().heading
+}
+
+#[test]
+fn a_hunter_comes_out_after_enough_food_away_from_the_head() {
+    let mut game = started(true, Difficulty::Hard);
+    feed(&mut game, HUNTER_AFTER_FOODS - 1);
+    assert!(hunters(&mut game).is_empty());
+
+    feed(&mut game, 1);
+    let out = hunters(&mut game);
+    assert_eq!(out.len(), 1);
+    let head = game.head_position();
+    assert!(out[0].1.distance(head) >= HUNTER_SAFE_DISTANCE);
+}
+
+#[test]
+fn no_hunter_on_easy_or_unless_asked_for() {
+    for mut game in [
+        started(true, Difficulty::Easy),
+        started(false, Difficulty::Hard),
+    ] {
+        feed(&mut game, HUNTER_AFTER_FOODS);
+        assert!(hunters(&mut game).is_empty());
+        assert!(!game.dying());
+    }
+}
+
+// The daily challenge is played on its own difficulty, not the one picked
+#[test]
+fn a_daily_run_gets_a_hunter_even_with_easy_picked() {
+    let mut game = started_in(GameMode::Daily, true, Difficulty::Easy);
+    feed(&mut game, HUNTER_AFTER_FOODS);
+    assert_eq!(hunters(&mut game).len(), 1);
+}
+
+#[test]
+fn the_hunter_goes_for_the_head_slower_than_the_snake() {
+    let mut game = started(false, Difficulty::Hard);
+    let config = game.app.world.resource::<GameConfig>().clone();
+    let hunter = release_at(&mut game, Vec3::new(200.0, 200.0, 0.0));
+    game.tick(1);
+
+    let speed = **game.app.world.resource::<GameSpeed>();
+    let snake_speed = config.grid_steps_per_second * config.snake_size.x * speed;
+    let velocity = **game.app.world.get::<Velocity>(hunter).unwrap();
+    assert!((velocity.length() - snake_speed * HUNTER_SPEED_FACTOR).abs() < 1e-3);
+    let position = game.app.world.get::<Transform>(hunter).unwrap().translation;
+    let towards = (game.head_position() - position).truncate().normalize();
+    assert!(velocity.normalize().dot(towards) > 0.99);
+}
+
+#[test]
+fn it_looks_for_the_head_again_every_so_often() {
+    let mut game = started(false, Difficulty::Hard);
+    let config = game.app.world.resource::<GameConfig>().clone();
+    game.app.insert_resource(GameSpeed(0.0));
+    let hunter = release_at(&mut game, Vec3::new(200.0, 200.0, 0.0));
+    game.tick(1);
+    let first = heading(&game, hunter);
+
+    let head = game.head();
+    game.app
+        .world
+        .get_mut::<Transform>(head)
+        .unwrap()
+        .translation = Vec3::new(200.0, -200.0, 0.0);
+    game.tick(ticks(&config, HUNTER_RETARGET_SECONDS) - 2);
+    assert_eq!(heading(&game, hunter), first);
+    game.tick(2);
+    assert!(heading(&game, hunter).dot(Vec2::NEG_Y) > 0.99);
+}
+
+#[test]
+fn catching_the_snake_kills_it() {
+    let mut game = started(false, Difficulty::Hard);
+    let head = game.head_position();
+    release_at(&mut game, head);
+    game.tick(1);
+
+    assert!(game.dying(), "the snake got away");
+    assert_eq!(
+        game.app.world.resource::<LastDeath>().cause,
+        Some(DeathCause::Hunter)
+    );
+    assert!(hunters(&mut game).is_empty());
+}
+
+#[test]
+fn a_hunter_lunging_into_a_wall_is_worth_points() {
+    let mut game = started(false, Difficulty::Hard);
+    let config = game.app.world.resource::<GameConfig>().clone();
+    let bounds = *game.app.world.resource::<ArenaBounds>();
+    let (min, _) = bounds.inside(config.snake_size);
+    let hunter = release_at(&mut game, Vec3::new(min.x + 1.0, 200.0, 0.0));
+    game.app.world.get_mut::<Hunter>(hunter).unwrap().heading = Vec2::NEG_X;
+
+    game.tick(10);
+    assert!(game.app.world.get_entity(hunter).is_none());
+    assert_eq!(
+        game.app.world.resource::<Scoreboard>().score,
+        HUNTER_BONUS_POINTS
+    );
+    assert!(!game.dying());
+}
+
+#[test]
+fn a_hunter_crashing_as_it_reaches_the_head_is_harmless() {
+    let mut game = started(false, Difficulty::Hard);
+    game.app.insert_resource(GameSpeed(0.0));
+    let config = game.app.world.resource::<GameConfig>().clone();
+    let bounds = *game.app.world.resource::<ArenaBounds>();
+    let (min, _) = bounds.inside(config.snake_size);
+    // Into the left wall by a unit, with the head just clear of it
+    let hunter = release_at(&mut game, Vec3::new(min.x - 1.0, 200.0, 0.0));
+    let head = game.head();
+    game.app
+        .world
+        .get_mut::<Transform>(head)
+        .unwrap()
+        .translation = Vec3::new(min.x + 14.0, 200.0, 0.0);
+
+    game.tick(1);
+    assert!(game.app.world.get_entity(hunter).is_none());
+    assert!(!game.dying(), "a crashed hunter caught the snake");
+    assert_eq!(
+        game.app.world.resource::<Scoreboard>().score,
+        HUNTER_BONUS_POINTS
+    );
+}
+
+// Where the hunter is and what it is up to, and the food left until the
+// next one
+fn hunter_state(game: &mut Headless) -> (Vec3, Vec2, f32, u8, usize) {
+    let (transform, hunter) = game
+        .app
+        .world
+        .query::<(&Transform, &Hunter)>()
+        .single(&game.app.world);
+    (
+        transform.translation,
+        hunter.heading,
+        hunter.retarget.elapsed_secs(),
+        *hunter.chasing,
+        **game.app.world.resource::<HunterCountdown>(),
+    )
+}
+
+#[test]
+fn a_resumed_run_keeps_its_hunter_and_countdown() {
+    let mut game = started(true, Difficulty::Hard);
+    let config = game.app.world.resource::<GameConfig>().clone();
+    feed(&mut game, 3);
+    release_at(&mut game, Vec3::new(200.0, 200.0, 0.0));
+    game.tick(30);
+    let expected = hunter_state(&mut game);
+    assert_eq!(expected.4, HUNTER_AFTER_FOODS - 3);
+
+    let saved = game.save_and_quit();
+    let mut resumed = Headless::resumed(config, saved, |app| {
+        app.insert_resource(Difficulty::Hard);
+    });
+    assert_eq!(hunter_state(&mut resumed), expected);
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use bevy::ecs::system::CommandQueue;
+use bevy::prelude::*;
+use bevy::sprite::collide_aabb::collide;
+
+use common::Headless;
+use snake::collision::Hitbox;
+use snake::config::GameConfig;
+use snake::game::Difficulty;
+use snake::ice::{IcePatch, Sliding};
+use snake::snake::{cell_center, grid_cell, Direction};
+use snake::spawning::spawn_ice_patch;
+
+fn config() -> GameConfig {
+    GameConfig {
+        seed: Some(8),
+        obstacle_count: 0,
+        ..GameConfig::default()
+    }
+}
+
+fn started(config: GameConfig) -> Headless {
+    let mut game = Headless::with_setup(config, |app| {
+        app.insert_resource(Difficulty::Hard);
+    });
+    game.start_game();
+    game.tick(1);
+    game
+}
+
+// A row of `length` icy cells starting right ahead of the head, which
+// starts out going left
+fn ice_ahead(game: &mut Headless, length: i32) -> IVec2 {
+    let config = game.app.world.resource::<GameConfig>().clone();
+    let start = head_cell(game);
+    let first = start - IVec2::X;
+    let last = start - IVec2::new(length, 0);
+    let center = (cell_center(&config, first) + cell_center(&config, last)) / 2.0;
+    let size = Vec2::new(length as f32, 1.0) * config.snake_size;
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, &game.app.world);
+    spawn_ice_patch(&mut commands, center, size);
+    queue.apply(&mut game.app.world);
+    start
+}
+
+fn head_cell(game: &mut Headless) -> IVec2 {
+    let config = game.app.world.resource::<GameConfig>().clone();
+    let position = game.head_position();
+    grid_cell(&config, position)
+}
+
+fn sliding(game: &mut Headless) -> bool {
+    let head = game.head();
+    **game.app.world.get::<Sliding>(head).unwrap()
+}
+
+// Ticks until the head has moved on to another cell
+fn step(game: &mut Headless) {
+    let cell = head_cell(game);
+    for _ in 0..20 {
+        game.tick(1);
+        if head_cell(game) != cell {
+            return;
+        }
+    }
+    panic!("the head stopped moving");
+}
+
+#[test]
+fn turns_wait_until_the_head_is_off_the_ice() {
+    let mut game = started(config());
+    let start = ice_ahead(&mut game, 3);
+    step(&mut game);
+
+    let head = game.head();
+    game.queue()
+        .push_turn(Direction::Up, Direction::Left, false);
+    for x in 2..=4 {
+        step(&mut game);
+        assert!(sliding(&mut game));
+        assert_eq!(head_cell(&mut game), start - IVec2::new(x, 0));
+    }
+
+    step(&mut game);
+    assert!(!sliding(&mut game));
+    assert_eq!(head_cell(&mut game), start - IVec2::new(4, -1));
+    assert_eq!(
+        *game.app.world.get::<Direction>(head).unwrap(),
+        Direction::Up
+    );
+}
+
+#[test]
+fn the_ice_is_nothing_to_crash_into() {
+    let mut game = started(config());
+    let start = ice_ahead(&mut game, 3);
+    for _ in 0..5 {
+        step(&mut game);
+    }
+    assert!(!game.dying());
+    assert_eq!(head_cell(&mut game), start - IVec2::new(5, 0));
+}
+
+#[test]
+fn random_ice_never_lies_where_the_snake_starts() {
+    let mut game = started(GameConfig {
+        ice_count: 8,
+        ..config()
+    });
+    let config = game.app.world.resource::<GameConfig>().clone();
+    let patches: Vec<(Vec3, Vec2)> = game
+        .app
+        .world
+        .query_filtered::<(&Transform, &Hitbox), With<IcePatch>>()
+        .iter(&game.app.world)
+        .map(|(transform, hitbox)| (transform.translation, **hitbox))
+        .collect();
+    assert!(!patches.is_empty());
+
+    let start = cell_center(&config, IVec2::ZERO);
+    for (center, size) in patches {
+        assert!(collide(start, config.snake_size, center, size).is_none());
+    }
+    assert!(!sliding(&mut game));
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::Headless;
+use snake::config::GameConfig;
+use snake::game::GameTick;
+use snake::input::{queue_direction_input, steering, track_held_directions, ControlScheme};
+use snake::snake::{move_snake, Direction, MovementMode, Snake};
+
+// The keys are read every frame, the way the `SnakeInputPlugin` does it
+fn started() -> Headless {
+    let mut game = Headless::with_setup(GameConfig::default(), |app| {
+        app.insert_resource(MovementMode::Continuous)
+            .init_resource::<Input<KeyCode>>()
+            .init_resource::<ControlScheme>()
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(steering)
+                    .with_system(queue_direction_input.before(move_snake))
+                    .with_system(track_held_directions.before(move_snake)),
+            );
+    });
+    game.start_game();
+    // The first tick after the countdown only catches the timestep up
+    game.tick(1);
+    game
+}
+
+fn direction(game: &mut Headless) -> Direction {
+    *game
+        .app
+        .world
+        .query_filtered::<&Direction, With<Snake>>()
+        .single(&game.app.world)
+}
+
+fn tick(game: &Headless) -> u64 {
+    **game.app.world.resource::<GameTick>()
+}
+
+#[test]
+fn a_tap_shorter_than_a_step_still_turns_the_snake() {
+    let mut game = started();
+    let before = tick(&game);
+    game.app
+        .world
+        .resource_mut::<Input<KeyCode>>()
+        .press(KeyCode::Up);
+    game.frame(0.25);
+    {
+        let mut keyboard_input = game.app.world.resource_mut::<Input<KeyCode>>();
+        keyboard_input.release(KeyCode::Up);
+        keyboard_input.clear();
+    }
+    game.frame(0.25);
+    // Up and gone again before the next step
+    assert_eq!(tick(&game), before);
+    assert_eq!(direction(&mut game), Direction::Left);
+
+    game.frame(0.5);
+    assert_eq!(tick(&game), before + 1);
+    assert_eq!(direction(&mut game), Direction::Up);
+}
+
+#[test]
+fn a_press_is_taken_once_however_many_steps_catch_up() {
+    let mut game = started();
+    let before = tick(&game);
+    game.app
+        .world
+        .resource_mut::<Input<KeyCode>>()
+        .press(KeyCode::Up);
+    game.frame(3.0);
+    assert_eq!(tick(&game), before + 3);
+    assert_eq!(direction(&mut game), Direction::Up);
+    assert!(game.queue().is_empty());
+
+    // Held down, the keys aren't pressed again in the next burst
+    game.app.world.resource_mut::<Input<KeyCode>>().clear();
+    game.frame(3.0);
+    assert!(game.queue().is_empty());
+    assert_eq!(direction(&mut game), Direction::Up);
+}
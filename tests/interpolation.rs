@@ -0,0 +1,38 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::Headless;
+use snake::config::GameConfig;
+use snake::game::TickFraction;
+use snake::snake::{MovementMode, Snake, StepOrigin};
+
+#[test]
+fn a_grid_step_remembers_the_cell_it_came_from() {
+    let config = GameConfig::default();
+    let mut game = Headless::with_setup(config.clone(), |app| {
+        app.insert_resource(MovementMode::Grid);
+    });
+    game.start_game();
+
+    let start = game.head_position();
+    let mut stepped = false;
+    for _ in 0..100 {
+        game.tick(1);
+        let fraction = **game.app.world.resource::<TickFraction>();
+        assert!((0.0..1.0).contains(&fraction));
+        if game.head_position() != start {
+            stepped = true;
+            break;
+        }
+    }
+    assert!(stepped, "the snake never moved a cell");
+
+    let origin = **game
+        .app
+        .world
+        .query_filtered::<&StepOrigin, With<Snake>>()
+        .single(&game.app.world);
+    assert_eq!(origin, start);
+    assert!((origin.distance(game.head_position()) - config.snake_size.x).abs() < 1e-3);
+}
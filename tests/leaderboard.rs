@@ -0,0 +1,42 @@
+use snake::game::{Difficulty, Leaderboard, LeaderboardEntry, LEADERBOARD_SIZE};
+
+fn entry(initials: &str, score: usize) -> LeaderboardEntry {
+    LeaderboardEntry {
+        initials: initials.to_string(),
+        score,
+        difficulty: Difficulty::Normal,
+    }
+}
+
+#[test]
+fn ties_keep_the_earlier_run_first() {
+    let mut leaderboard = Leaderboard::default();
+    leaderboard.insert(entry("AAA", 5));
+    leaderboard.insert(entry("BBB", 7));
+    assert_eq!(leaderboard.insert(entry("CCC", 5)), Some(2));
+
+    let order: Vec<&str> = leaderboard
+        .entries
+        .iter()
+        .map(|entry| entry.initials.as_str())
+        .collect();
+    assert_eq!(order, ["BBB", "AAA", "CCC"]);
+}
+
+#[test]
+fn a_full_table_drops_its_lowest_run() {
+    let mut leaderboard = Leaderboard::default();
+    for score in 1..=LEADERBOARD_SIZE {
+        leaderboard.insert(entry("AAA", score));
+    }
+    // Tying the last place isn't enough once the table is full
+    assert!(!leaderboard.qualifies(1));
+    assert_eq!(leaderboard.insert(entry("BBB", 1)), None);
+
+    assert_eq!(
+        leaderboard.insert(entry("CCC", 3)),
+        Some(LEADERBOARD_SIZE - 2)
+    );
+    assert_eq!(leaderboard.entries.len(), LEADERBOARD_SIZE);
+    assert_eq!(leaderboard.entries.last().unwrap().score, 2);
+}
@@ -0,0 +1,139 @@
+use bevy::prelude::*;
+
+use snake::config::GameConfig;
+use snake::level::{Level, LevelError};
+use snake::walls::{grid_bounds, ArenaBounds, ArenaConfig};
+
+fn default_grid_bounds() -> (IVec2, IVec2) {
+    let config = GameConfig::default();
+    grid_bounds(
+        &config,
+        &ArenaBounds::from_config(&ArenaConfig::from_config(&config)),
+    )
+}
+
+// An open map of the default arena with `S` and `F` at the given cells,
+// counted from the top left corner
+fn open_map(start: (usize, usize), food: (usize, usize)) -> Vec<Vec<char>> {
+    let (min, max) = default_grid_bounds();
+    let size = max - min + 1;
+    let mut map = vec![vec!['.'; size.x as usize]; size.y as usize];
+    map[start.1][start.0] = 'S';
+    map[food.1][food.0] = 'F';
+    map
+}
+
+fn parse(map: &[Vec<char>]) -> Result<Level, LevelError> {
+    let text: Vec<String> = map.iter().map(|row| row.iter().collect()).collect();
+    let config = GameConfig::default();
+    Level::parse(
+        &text.join("\n"),
+        &config,
+        &ArenaConfig::from_config(&config),
+    )
+}
+
+#[test]
+fn the_shipped_maze_fits_the_default_arena() {
+    let config = GameConfig::default();
+    let level = Level::load("maze", &config, &ArenaConfig::from_config(&config)).unwrap();
+    assert_eq!(level.start, IVec2::ZERO);
+    assert!(!level.walls.is_empty());
+
+    // The merged blocks cover exactly the wall cells
+    let covered: f32 = level
+        .wall_blocks(&config)
+        .iter()
+        .map(|(_, size)| size.x * size.y)
+        .sum();
+    let cell = config.snake_size.x * config.snake_size.y;
+    assert_eq!(covered, level.walls.len() as f32 * cell);
+}
+
+#[test]
+fn the_top_line_is_the_top_row() {
+    let level = parse(&open_map((0, 0), (1, 0))).unwrap();
+    let (min, max) = default_grid_bounds();
+    assert_eq!(level.start, IVec2::new(min.x, max.y));
+    assert_eq!(level.food, IVec2::new(min.x + 1, max.y));
+}
+
+#[test]
+fn malformed_maps_are_rejected() {
+    let mut unknown = open_map((0, 0), (1, 0));
+    unknown[3][5] = 'x';
+    assert!(matches!(
+        parse(&unknown),
+        Err(LevelError::UnknownCharacter {
+            line: 4,
+            column: 6,
+            character: 'x'
+        })
+    ));
+
+    let mut no_start = open_map((0, 0), (1, 0));
+    no_start[0][0] = '.';
+    assert!(matches!(parse(&no_start), Err(LevelError::Missing('S'))));
+
+    let mut short = open_map((0, 0), (1, 0));
+    short[2].pop();
+    assert!(matches!(
+        parse(&short),
+        Err(LevelError::WrongWidth { line: 3, .. })
+    ));
+
+    let mut too_few_rows = open_map((0, 0), (1, 0));
+    too_few_rows.pop();
+    assert!(matches!(
+        parse(&too_few_rows),
+        Err(LevelError::WrongHeight { .. })
+    ));
+}
+
+#[test]
+fn food_walled_off_from_the_snake_is_rejected() {
+    // The food sits in the bottom right corner, boxed in by two walls
+    let mut map = open_map((0, 0), (0, 0));
+    let (right, bottom) = (map[0].len() - 1, map.len() - 1);
+    map[0][0] = 'S';
+    map[bottom][right] = 'F';
+    map[bottom][right - 1] = '#';
+    map[bottom - 1][right] = '#';
+    assert!(matches!(parse(&map), Err(LevelError::UnreachableFood)));
+}
+
+#[test]
+fn crates_are_placed_but_may_not_box_the_food_in() {
+    let mut map = open_map((0, 0), (0, 0));
+    let (right, bottom) = (map[0].len() - 1, map.len() - 1);
+    map[0][0] = 'S';
+    map[bottom][right] = 'F';
+    map[bottom][right - 1] = 'C';
+    let level = parse(&map).unwrap();
+    let (_, max) = default_grid_bounds();
+    assert_eq!(
+        level.crates,
+        vec![IVec2::new(max.x - 1, max.y - bottom as i32)]
+    );
+    assert!(level.walls.is_empty());
+
+    // A crate can be broken, but only by a snake already boosting or with a
+    // hammer, so the food must be in reach without that
+    map[bottom - 1][right] = 'C';
+    assert!(matches!(parse(&map), Err(LevelError::UnreachableFood)));
+}
+
+#[test]
+fn ice_is_read_from_the_map() {
+    let mut map = open_map((0, 0), (3, 0));
+    map[0][1] = '~';
+    map[0][2] = '~';
+    let level = parse(&map).unwrap();
+    let (min, max) = default_grid_bounds();
+    assert_eq!(
+        level.ice,
+        vec![IVec2::new(min.x + 1, max.y), IVec2::new(min.x + 2, max.y)]
+    );
+    // The snake slides over it to the food
+    assert!(level.walls.is_empty());
+}
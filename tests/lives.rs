@@ -0,0 +1,125 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::Headless;
+use snake::config::GameConfig;
+use snake::food::FoodKind;
+use snake::game::{respawn_position, Difficulty, Lives, StartingLives, THREE_LIVES};
+use snake::snake::{cell_center, Direction, Respawning, RESPAWN_SECONDS};
+use snake::state::GameState;
+use snake::walls::{ArenaBounds, ArenaConfig};
+
+// On Hard the walls are lethal
+fn with_lives(lives: usize) -> Headless {
+    let mut game = Headless::with_setup(
+        GameConfig {
+            seed: Some(12),
+            ..GameConfig::default()
+        },
+        |app| {
+            app.insert_resource(Difficulty::Hard)
+                .insert_resource(StartingLives(lives));
+        },
+    );
+    game.start_game();
+    game.tick(5);
+    game
+}
+
+fn lives(game: &Headless) -> usize {
+    **game.app.world.resource::<Lives>()
+}
+
+fn respawning(game: &mut Headless) -> bool {
+    let head = game.head();
+    game.app.world.get::<Respawning>(head).is_some()
+}
+
+// Puts the head inside the left wall
+fn run_into_wall(game: &mut Headless) {
+    let wall = game.app.world.resource::<GameConfig>().left_wall;
+    let head = game.head();
+    game.app
+        .world
+        .get_mut::<Transform>(head)
+        .unwrap()
+        .translation = Vec3::new(wall, 0.0, 0.0);
+    game.tick(1);
+}
+
+// The countdown after the lost life first, then the blinking
+fn wait_out_respawn(game: &mut Headless) {
+    game.skip_countdown();
+    let time_step = game.app.world.resource::<GameConfig>().time_step;
+    game.tick((RESPAWN_SECONDS / time_step).ceil() as usize + 1);
+}
+
+#[test]
+fn a_lost_life_brings_back_a_lone_head_in_the_middle() {
+    let mut game = with_lives(THREE_LIVES);
+    game.eat(3);
+    run_into_wall(&mut game);
+
+    assert_eq!(game.state(), GameState::Playing);
+    assert_eq!(lives(&game), THREE_LIVES - 1);
+    assert!(game.segments().is_empty());
+    assert!(respawning(&mut game));
+    let head = game.head();
+    assert_eq!(
+        *game.app.world.get::<Direction>(head).unwrap(),
+        Direction::Left
+    );
+    // Right in the middle, or next to it if the food is there
+    let cell = game.app.world.resource::<GameConfig>().snake_size.x;
+    assert!(game.head_position().distance(Vec3::ZERO) <= cell);
+}
+
+#[test]
+fn a_respawning_head_holds_still_and_cannot_die() {
+    let mut game = with_lives(THREE_LIVES);
+    run_into_wall(&mut game);
+    let respawned = game.head_position();
+
+    game.eat_kind(1, FoodKind::Poison);
+    assert_eq!(lives(&game), THREE_LIVES - 1);
+    assert_eq!(game.head_position(), respawned);
+
+    wait_out_respawn(&mut game);
+    assert!(!respawning(&mut game));
+    game.tick(10);
+    assert_ne!(game.head_position(), respawned);
+}
+
+#[test]
+fn the_run_ends_with_the_last_life() {
+    let mut game = with_lives(THREE_LIVES);
+    for _ in 1..THREE_LIVES {
+        run_into_wall(&mut game);
+        wait_out_respawn(&mut game);
+    }
+    assert_eq!(game.state(), GameState::Playing);
+    run_into_wall(&mut game);
+    game.play_out_death();
+    assert_eq!(game.state(), GameState::GameOver);
+}
+
+#[test]
+fn a_single_life_ends_the_run_right_away() {
+    let mut game = with_lives(1);
+    run_into_wall(&mut game);
+    game.play_out_death();
+    assert_eq!(game.state(), GameState::GameOver);
+}
+
+#[test]
+fn the_respawn_keeps_clear_of_whatever_is_in_the_middle() {
+    let config = GameConfig::default();
+    let bounds = ArenaBounds::from_config(&ArenaConfig::from_config(&config));
+    let middle = cell_center(&config, IVec2::ZERO);
+    assert_eq!(respawn_position(&config, &bounds, &[]), middle);
+
+    let position = respawn_position(&config, &bounds, &[(middle, config.food_size)]);
+    assert_ne!(position, middle);
+    assert_eq!(position.distance(middle), config.snake_size.x);
+}
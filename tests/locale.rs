@@ -0,0 +1,61 @@
+use snake::display::DisplaySettings;
+use snake::locale::{load_strings, Language, Locale, Strings};
+
+#[test]
+fn every_language_has_text_for_every_english_key() {
+    let english = load_strings(Language::English).unwrap();
+    for language in Language::ALL {
+        let strings = load_strings(language).unwrap();
+        let mut missing: Vec<_> = english
+            .keys()
+            .filter(|key| !strings.contains_key(*key))
+            .collect();
+        missing.sort();
+        assert!(missing.is_empty(), "{language:?} is missing {missing:?}");
+    }
+}
+
+#[test]
+fn german_is_shown_with_its_umlauts() {
+    let locale = Locale::load(Language::German);
+    assert_eq!(locale.get("menu.survival"), "Überleben");
+    assert_eq!(locale.get("menu.title"), "SNAKE");
+}
+
+#[test]
+fn a_missing_key_falls_back_to_english() {
+    let strings = Strings::from([("menu.play".to_string(), "Spielen".to_string())]);
+    let locale = Locale::new(Language::German, strings);
+    assert_eq!(locale.get("menu.play"), "Spielen");
+    assert_eq!(locale.get("menu.quit"), "Quit");
+    // Asked for again, it is still there without a second warning
+    assert_eq!(locale.get("menu.quit"), "Quit");
+    // And a key no language has shows as itself
+    assert_eq!(locale.get("menu.nowhere"), "menu.nowhere");
+}
+
+#[test]
+fn values_are_filled_into_their_placeholders() {
+    let locale = Locale::load(Language::English);
+    assert_eq!(
+        locale.text("round_over.score", &[("one", &3), ("two", &12)]),
+        "Score: 3 - 12"
+    );
+    assert_eq!(
+        Locale::load(Language::German).text("hud.combo", &[("factor", &4)]),
+        "Kombo x4"
+    );
+}
+
+#[test]
+fn languages_cycle_both_ways() {
+    assert_eq!(Language::English.cycle(1), Language::German);
+    assert_eq!(Language::English.cycle(-1), Language::German);
+    assert_eq!(Language::German.cycle(2), Language::German);
+}
+
+#[test]
+fn settings_saved_before_there_were_languages_are_in_english() {
+    let settings: DisplaySettings = ron::from_str("(colorblind: true)").unwrap();
+    assert_eq!(settings.language, Language::English);
+}
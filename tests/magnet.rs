@@ -0,0 +1,79 @@
+mod common;
+
+use bevy::{ecs::system::CommandQueue, prelude::*};
+
+use common::Headless;
+use snake::config::GameConfig;
+use snake::food::{Magnet, MAGNET_SECONDS};
+use snake::snake::GameSpeed;
+use snake::spawning::{spawn_food, spawn_obstacle};
+
+// A run with the snake held still in the middle, so only the food moves
+fn standing_still() -> Headless {
+    let mut game = Headless::new(GameConfig {
+        seed: Some(5),
+        ..GameConfig::default()
+    });
+    game.start_game();
+    game.tick(5);
+    *game.app.world.resource_mut::<GameSpeed>() = GameSpeed(0.0);
+    game
+}
+
+fn spawn(game: &mut Headless, place: impl FnOnce(&mut Commands, &GameConfig) -> Entity) -> Entity {
+    let config = game.app.world.resource::<GameConfig>().clone();
+    let mut queue = CommandQueue::default();
+    let entity = place(&mut Commands::new(&mut queue, &game.app.world), &config);
+    queue.apply(&mut game.app.world);
+    entity
+}
+
+fn position(game: &Headless, entity: Entity) -> Vec3 {
+    game.app.world.get::<Transform>(entity).unwrap().translation
+}
+
+fn switch_on(game: &mut Headless) {
+    let radius = game.app.world.resource::<GameConfig>().magnet_radius;
+    game.app
+        .world
+        .resource_mut::<Magnet>()
+        .start(radius, MAGNET_SECONDS);
+}
+
+#[test]
+fn only_food_in_reach_is_pulled_in() {
+    let mut game = standing_still();
+    let head = game.head_position();
+    let near = spawn(&mut game, |commands, config| {
+        spawn_food(commands, config, head + Vec3::new(0.0, 100.0, 0.0))
+    });
+    let far = spawn(&mut game, |commands, config| {
+        spawn_food(commands, config, head + Vec3::new(300.0, 200.0, 0.0))
+    });
+    let far_start = position(&game, far);
+
+    switch_on(&mut game);
+    game.tick(10);
+    assert!(position(&game, near).distance(head) < 100.0);
+    assert_eq!(position(&game, far), far_start);
+}
+
+#[test]
+fn pulled_food_stops_at_an_obstacle() {
+    let mut game = standing_still();
+    let head = game.head_position();
+    let size = game.app.world.resource::<GameConfig>().snake_size;
+    let food = spawn(&mut game, |commands, config| {
+        spawn_food(commands, config, head + Vec3::new(0.0, 120.0, 0.0))
+    });
+    let obstacle = head + Vec3::new(0.0, 60.0, 0.0);
+    spawn(&mut game, |commands, _| {
+        spawn_obstacle(commands, obstacle, size)
+    });
+
+    switch_on(&mut game);
+    game.tick(120);
+    let stopped = position(&game, food);
+    assert!(stopped.y >= obstacle.y + size.y);
+    assert!(stopped.y < head.y + 120.0);
+}
@@ -0,0 +1,150 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::Headless;
+use snake::collision::{Collider, Hitbox};
+use snake::config::GameConfig;
+use snake::food::{Food, FoodKind};
+use snake::game::{Difficulty, Scoreboard};
+use snake::snake::{Direction, HeldDirections, MovementMode, Snake};
+use snake::state::GameState;
+
+// Slow enough for the snake to stay clear of the walls for a second
+fn config() -> GameConfig {
+    GameConfig {
+        seed: Some(3),
+        snake_speed: 200.0,
+        ..GameConfig::default()
+    }
+}
+
+fn started(config: &GameConfig) -> Headless {
+    let mut game = Headless::with_setup(config.clone(), |app| {
+        app.insert_resource(MovementMode::Continuous);
+    });
+    game.start_game();
+    // The first tick after the countdown only catches the timestep up
+    game.tick(1);
+    game
+}
+
+fn one_second(game: &mut Headless, config: &GameConfig) -> Vec3 {
+    let start = game.head_position();
+    game.tick((1.0 / config.time_step).round() as usize);
+    game.head_position() - start
+}
+
+fn hold(game: &mut Headless, directions: &[Direction]) {
+    let head = game.head();
+    let mut held = game.app.world.get_mut::<HeldDirections>(head).unwrap();
+    **held = directions.to_vec();
+}
+
+#[test]
+fn a_second_of_ticks_moves_the_head_its_speed() {
+    let config = config();
+    let mut game = started(&config);
+    let moved = one_second(&mut game, &config);
+    assert!(
+        (moved.length() - config.snake_speed).abs() < 1e-2,
+        "{moved}"
+    );
+}
+
+#[test]
+fn holding_a_key_turns_the_snake_without_speeding_it_up() {
+    let config = config();
+    let mut game = started(&config);
+    hold(&mut game, &[Direction::Up]);
+    let moved = one_second(&mut game, &config);
+    assert!(moved.x.abs() < 1e-2, "{moved}");
+    assert!((moved.y - config.snake_speed).abs() < 1e-2, "{moved}");
+    let mut query = game.app.world.query_filtered::<&Direction, With<Snake>>();
+    assert_eq!(*query.single(&game.app.world), Direction::Up);
+}
+
+fn heads(turn: Direction, expected: Vec3) {
+    let config = config();
+    let mut game = started(&config);
+    game.queue().push_back(turn);
+    game.tick(1);
+    let moved = one_second(&mut game, &config);
+    let expected = expected * config.snake_speed;
+    assert!(moved.abs_diff_eq(expected, 1e-2), "{turn:?}: {moved}");
+}
+
+#[test]
+fn each_key_heads_the_snake_its_way() {
+    heads(Direction::Up, Vec3::Y);
+    heads(Direction::Down, -Vec3::Y);
+    heads(Direction::Left, -Vec3::X);
+    // Only a lone head may turn straight back
+    heads(Direction::Right, Vec3::X);
+}
+
+#[test]
+fn the_key_pressed_last_wins() {
+    let config = config();
+    let mut game = started(&config);
+    hold(&mut game, &[Direction::Down, Direction::Up]);
+    let moved = one_second(&mut game, &config);
+    assert!(moved.x.abs() < 1e-2 && moved.y > 0.0, "{moved}");
+}
+
+#[test]
+fn a_snake_with_a_body_cannot_turn_straight_back() {
+    let config = config();
+    let mut game = started(&config);
+    game.eat(1);
+    game.queue().push_back(Direction::Right);
+    hold(&mut game, &[Direction::Right]);
+    let moved = one_second(&mut game, &config);
+    assert!(moved.y.abs() < 1e-2 && moved.x < 0.0, "{moved}");
+}
+
+// Far more than a food or a wall is across every tick
+fn too_fast() -> GameConfig {
+    GameConfig {
+        seed: Some(3),
+        snake_speed: 5000.0,
+        obstacle_count: 0,
+        ..GameConfig::default()
+    }
+}
+
+#[test]
+fn a_snake_too_fast_to_land_on_food_still_eats_it() {
+    let config = too_fast();
+    let mut game = started(&config);
+    let step = config.snake_speed * config.time_step;
+    assert!(step > config.snake_size.x + config.food_size.x);
+
+    // Halfway along the way the head goes next tick
+    let position = game.head_position() - Vec3::new(step / 2.0, 0.0, 0.0);
+    game.app
+        .world
+        .spawn()
+        .insert(Food)
+        .insert(FoodKind::Normal)
+        .insert(Collider)
+        .insert(Hitbox(config.food_size))
+        .insert_bundle(TransformBundle::from_transform(
+            Transform::from_translation(position),
+        ));
+    game.tick(1);
+    assert_eq!(game.app.world.resource::<Scoreboard>().score, 1);
+}
+
+#[test]
+fn a_snake_too_fast_to_land_in_a_wall_still_hits_it() {
+    let config = too_fast();
+    let mut game = Headless::with_setup(config.clone(), |app| {
+        app.insert_resource(MovementMode::Continuous)
+            .insert_resource(Difficulty::Hard);
+    });
+    game.start_game();
+    game.tick(30);
+    game.play_out_death();
+    assert_eq!(game.state(), GameState::GameOver);
+}
@@ -0,0 +1,163 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::Headless;
+use snake::config::GameConfig;
+use snake::display::DisplaySettings;
+use snake::game::{GameTick, Paused, Scoreboard};
+use snake::input::{toggle_pause, ControlScheme};
+use snake::pause::{navigate_pause_menu, PauseItem, PauseMenu, PausePage};
+use snake::snake::Snake;
+use snake::state::GameState;
+use snake::theme::ThemeName;
+
+// The menu's own system, without the overlay the headless app can't draw
+fn paused() -> Headless {
+    let mut game = Headless::with_setup(GameConfig::default(), |app| {
+        app.init_resource::<Input<KeyCode>>()
+            .init_resource::<Gamepads>()
+            .init_resource::<Input<GamepadButton>>()
+            .init_resource::<ControlScheme>()
+            .init_resource::<DisplaySettings>()
+            .init_resource::<PauseMenu>()
+            .add_system(toggle_pause)
+            .add_system(navigate_pause_menu.after(toggle_pause));
+    });
+    game.start_game();
+    game.eat(2);
+    press(&mut game, KeyCode::P);
+    assert!(**game.app.world.resource::<Paused>());
+    game
+}
+
+fn press(game: &mut Headless, key: KeyCode) {
+    game.app.world.resource_mut::<Input<KeyCode>>().press(key);
+    game.tick(1);
+    let mut keyboard_input = game.app.world.resource_mut::<Input<KeyCode>>();
+    keyboard_input.release(key);
+    keyboard_input.clear();
+}
+
+fn menu(game: &Headless) -> PauseMenu {
+    *game.app.world.resource::<PauseMenu>()
+}
+
+fn tick(game: &Headless) -> u64 {
+    **game.app.world.resource::<GameTick>()
+}
+
+#[test]
+fn the_menu_opens_on_resume_and_enter_resumes() {
+    let mut game = paused();
+    assert_eq!(menu(&game).selected(), PauseItem::Resume);
+    press(&mut game, KeyCode::Down);
+    press(&mut game, KeyCode::Up);
+    press(&mut game, KeyCode::Return);
+    assert!(!**game.app.world.resource::<Paused>());
+
+    // The next pause starts over from the top
+    press(&mut game, KeyCode::P);
+    press(&mut game, KeyCode::Down);
+    press(&mut game, KeyCode::P);
+    press(&mut game, KeyCode::P);
+    assert_eq!(menu(&game).selected(), PauseItem::Resume);
+}
+
+#[test]
+fn esc_pauses_and_resumes_instead_of_closing_the_game() {
+    let mut game = paused();
+    press(&mut game, KeyCode::P);
+    press(&mut game, KeyCode::Escape);
+    assert!(**game.app.world.resource::<Paused>());
+    assert_eq!(game.state(), GameState::Playing);
+    press(&mut game, KeyCode::Escape);
+    assert!(!**game.app.world.resource::<Paused>());
+}
+
+#[test]
+fn esc_goes_back_from_the_settings() {
+    let mut game = paused();
+    press(&mut game, KeyCode::Down);
+    press(&mut game, KeyCode::Down);
+    press(&mut game, KeyCode::Return);
+    press(&mut game, KeyCode::Escape);
+    assert_eq!(menu(&game).page, PausePage::Main);
+    assert!(**game.app.world.resource::<Paused>());
+}
+
+#[test]
+fn restart_starts_a_fresh_run() {
+    let mut game = paused();
+    press(&mut game, KeyCode::Down);
+    assert_eq!(menu(&game).selected(), PauseItem::Restart);
+    press(&mut game, KeyCode::Return);
+    game.tick(1);
+
+    assert_eq!(game.state(), GameState::Playing);
+    assert_eq!(game.app.world.resource::<Scoreboard>().score, 0);
+    assert!(game.segments().is_empty());
+    assert!(!**game.app.world.resource::<Paused>());
+}
+
+#[test]
+fn settings_change_with_the_game_frozen_and_go_back_to_the_menu() {
+    let mut game = paused();
+    let before = (tick(&game), game.head_position());
+    press(&mut game, KeyCode::Down);
+    press(&mut game, KeyCode::Down);
+    press(&mut game, KeyCode::Return);
+    assert_eq!(menu(&game).page, PausePage::Settings);
+
+    // Theme is first
+    press(&mut game, KeyCode::Right);
+    assert_ne!(
+        game.app.world.resource::<DisplaySettings>().theme,
+        ThemeName::default()
+    );
+    game.tick(60);
+    assert_eq!((tick(&game), game.head_position()), before);
+
+    press(&mut game, KeyCode::Up);
+    assert_eq!(menu(&game).selected(), PauseItem::Back);
+    press(&mut game, KeyCode::Return);
+    assert_eq!(menu(&game).page, PausePage::Main);
+    assert_eq!(menu(&game).selected(), PauseItem::Settings);
+    assert!(**game.app.world.resource::<Paused>());
+}
+
+#[test]
+fn quit_goes_to_the_menu_and_clears_the_arena() {
+    let mut game = paused();
+    press(&mut game, KeyCode::Up);
+    assert_eq!(menu(&game).selected(), PauseItem::Quit);
+    press(&mut game, KeyCode::Return);
+    game.tick(1);
+
+    assert_eq!(game.state(), GameState::Menu);
+    let snakes = game
+        .app
+        .world
+        .query_filtered::<(), With<Snake>>()
+        .iter(&game.app.world)
+        .count();
+    assert_eq!(snakes, 0);
+}
+
+#[test]
+fn the_mouse_highlights_and_picks_entries() {
+    let mut game = paused();
+    let quit = game
+        .app
+        .world
+        .spawn()
+        .insert(PauseItem::Quit)
+        .insert(Interaction::Hovered)
+        .id();
+    game.tick(1);
+    assert_eq!(menu(&game).selected(), PauseItem::Quit);
+
+    *game.app.world.get_mut::<Interaction>(quit).unwrap() = Interaction::Clicked;
+    game.tick(2);
+    assert_eq!(game.state(), GameState::Menu);
+}
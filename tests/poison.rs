@@ -0,0 +1,36 @@
+mod common;
+
+use common::Headless;
+use snake::config::GameConfig;
+use snake::food::FoodKind;
+use snake::game::Scoreboard;
+use snake::state::GameState;
+
+fn started() -> Headless {
+    let mut game = Headless::new(GameConfig {
+        seed: Some(5),
+        ..GameConfig::default()
+    });
+    game.start_game();
+    game
+}
+
+#[test]
+fn poison_takes_two_segments_and_a_point() {
+    let mut game = started();
+    game.eat(3);
+    assert_eq!(game.segments().len(), 3);
+
+    game.eat_kind(1, FoodKind::Poison);
+    assert_eq!(game.state(), GameState::Playing);
+    assert_eq!(game.segments().len(), 1);
+    assert_eq!(game.app.world.resource::<Scoreboard>().score, 2);
+}
+
+#[test]
+fn poison_kills_a_snake_without_a_tail() {
+    let mut game = started();
+    game.eat_kind(1, FoodKind::Poison);
+    game.play_out_death();
+    assert_eq!(game.state(), GameState::GameOver);
+}
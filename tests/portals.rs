@@ -0,0 +1,297 @@
+mod common;
+
+use bevy::ecs::system::CommandQueue;
+use bevy::prelude::*;
+use bevy::sprite::collide_aabb::collide;
+
+use common::Headless;
+use snake::collision::Hitbox;
+use snake::config::GameConfig;
+use snake::food::{Food, FoodKind};
+use snake::game::Difficulty;
+use snake::portal::{InPortal, Portal};
+use snake::snake::{grid_cell, Direction, GameSpeed, MovementMode, TRAIL_WRAP_DISTANCE};
+use snake::spawning::{spawn_obstacle, spawn_portals};
+use snake::walls::{grid_bounds, ArenaBounds};
+
+fn started(config: GameConfig, difficulty: Difficulty) -> Headless {
+    let mut game = Headless::with_setup(config, |app| {
+        app.insert_resource(difficulty);
+    });
+    game.start_game();
+    game.tick(1);
+    game
+}
+
+fn open_arena() -> GameConfig {
+    GameConfig {
+        seed: Some(9),
+        obstacle_count: 0,
+        ..GameConfig::default()
+    }
+}
+
+// Puts a pair down at two cells of the grid
+fn place_portals(game: &mut Headless, entry: IVec2, exit: IVec2) {
+    let config = game.app.world.resource::<GameConfig>().clone();
+    let cell = |cell: IVec2| (cell.as_vec2() * config.snake_size).extend(0.0);
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, &game.app.world);
+    spawn_portals(&mut commands, &config, (cell(entry), cell(exit)));
+    queue.apply(&mut game.app.world);
+}
+
+fn portals(game: &mut Headless) -> Vec<(Entity, Vec3, Vec2, Entity)> {
+    game.app
+        .world
+        .query::<(Entity, &Transform, &Hitbox, &Portal)>()
+        .iter(&game.app.world)
+        .map(|(entity, transform, hitbox, portal)| {
+            (entity, transform.translation, **hitbox, portal.exit)
+        })
+        .collect()
+}
+
+fn head_cell(game: &mut Headless) -> IVec2 {
+    let config = game.app.world.resource::<GameConfig>().clone();
+    let position = game.head_position();
+    grid_cell(&config, position)
+}
+
+#[test]
+fn the_head_comes_out_of_the_other_portal_heading_the_same_way() {
+    let mut game = started(open_arena(), Difficulty::Easy);
+    let start = head_cell(&mut game);
+    let entry = start - IVec2::new(2, 0);
+    let exit = IVec2::new(5, 8);
+    place_portals(&mut game, entry, exit);
+
+    let mut came_out = false;
+    for _ in 0..40 {
+        game.tick(1);
+        let cell = head_cell(&mut game);
+        assert_ne!(cell, entry, "the head was left in the portal");
+        if cell.y == exit.y {
+            came_out = true;
+            // Moving on from the exit, and not back in
+            assert!(cell.x <= exit.x);
+        }
+    }
+    assert!(came_out, "the head never came out");
+    let head = game.head();
+    assert_eq!(
+        *game.app.world.get::<Direction>(head).unwrap(),
+        Direction::Left
+    );
+    assert!(head_cell(&mut game).x < exit.x);
+}
+
+#[test]
+fn the_body_threads_through_both_mouths() {
+    let mut game = started(open_arena(), Difficulty::Easy);
+    game.eat(4);
+    let start = head_cell(&mut game);
+    let entry = start - IVec2::new(2, 0);
+    let exit = IVec2::new(5, 8);
+    place_portals(&mut game, entry, exit);
+    let config = game.app.world.resource::<GameConfig>().clone();
+
+    let mut split = false;
+    for _ in 0..80 {
+        game.tick(1);
+        let segments: Vec<Vec3> = game
+            .segments()
+            .into_iter()
+            .map(|segment| {
+                game.app
+                    .world
+                    .get::<Transform>(segment)
+                    .unwrap()
+                    .translation
+            })
+            .collect();
+        let (mut behind, mut through) = (0, 0);
+        for position in segments {
+            // Always on a cell of one of the two stretches, never somewhere
+            // in between
+            let cell = grid_cell(&config, position);
+            assert_eq!(position.truncate(), cell.as_vec2() * config.snake_size);
+            if cell.y == entry.y && cell.x > entry.x {
+                behind += 1;
+            } else if cell.y == exit.y && cell.x <= exit.x {
+                through += 1;
+            } else {
+                panic!("a segment snapped to {cell}");
+            }
+        }
+        split |= behind > 0 && through > 0;
+    }
+    assert!(split, "the body never went through");
+    // Long after, it has all come out
+    let segments = game.segments();
+    for segment in segments {
+        let position = game
+            .app
+            .world
+            .get::<Transform>(segment)
+            .unwrap()
+            .translation;
+        assert_eq!(grid_cell(&config, position).y, exit.y);
+    }
+}
+
+#[test]
+fn coming_out_in_front_of_a_wall_still_dies_on_it() {
+    let mut game = started(open_arena(), Difficulty::Hard);
+    let config = game.app.world.resource::<GameConfig>().clone();
+    let bounds = *game.app.world.resource::<ArenaBounds>();
+    let (min, _) = grid_bounds(&config, &bounds);
+    let start = head_cell(&mut game);
+    let entry = start - IVec2::new(2, 0);
+    let exit = IVec2::new(min.x, 8);
+    place_portals(&mut game, entry, exit);
+
+    for _ in 0..40 {
+        game.tick(1);
+        if game.dying() {
+            break;
+        }
+    }
+    assert!(game.dying(), "the snake went on through the wall");
+    assert_eq!(head_cell(&mut game).y, exit.y);
+}
+
+#[test]
+fn moving_freely_out_of_a_portal_leaves_what_is_behind_the_exit_alone() {
+    // More than two cells a tick, so the way back from the exit would reach
+    // past the cell behind it
+    let config = GameConfig {
+        snake_speed: 2700.0,
+        ..open_arena()
+    };
+    let step = config.snake_speed * config.time_step;
+    let mut game = Headless::with_setup(config.clone(), |app| {
+        app.insert_resource(Difficulty::Hard)
+            .insert_resource(MovementMode::Continuous);
+    });
+    game.start_game();
+    game.tick(1);
+    let entry = head_cell(&mut game) - IVec2::new(3, 0);
+    let exit = IVec2::new(5, 8);
+    place_portals(&mut game, entry, exit);
+    let cell = |cell: IVec2| (cell.as_vec2() * config.snake_size).extend(0.0);
+    let mut queue = CommandQueue::default();
+    // Heading left, so right behind the exit
+    spawn_obstacle(
+        &mut Commands::new(&mut queue, &game.app.world),
+        cell(exit + IVec2::X),
+        config.snake_size,
+    );
+    queue.apply(&mut game.app.world);
+    // One step short of the entry
+    let head = game.head();
+    game.app
+        .world
+        .get_mut::<Transform>(head)
+        .unwrap()
+        .translation = cell(entry) + Vec3::X * step;
+
+    game.tick(1);
+    assert_eq!(game.head_position().truncate(), cell(exit).truncate());
+    game.tick(5);
+    assert!(!game.dying(), "the snake hit what was behind the exit");
+}
+
+#[test]
+fn food_never_lands_on_a_portal() {
+    let mut game = started(
+        GameConfig {
+            portals: true,
+            simultaneous_food: 3,
+            ..open_arena()
+        },
+        Difficulty::Easy,
+    );
+    game.app.insert_resource(GameSpeed(0.0));
+    let placed = portals(&mut game);
+    assert_eq!(placed.len(), 2);
+    let (first, first_position, _, first_exit) = placed[0];
+    let (second, second_position, _, second_exit) = placed[1];
+    assert_eq!((first_exit, second_exit), (second, first));
+    assert!(first_position.distance(second_position) > TRAIL_WRAP_DISTANCE);
+
+    let snake = game.head();
+    for _ in 0..60 {
+        game.eat_now(snake, FoodKind::Normal);
+        let food: Vec<(Vec3, Vec2)> = game
+            .app
+            .world
+            .query_filtered::<(&Transform, &Hitbox), With<Food>>()
+            .iter(&game.app.world)
+            .map(|(transform, hitbox)| (transform.translation, **hitbox))
+            .collect();
+        for (position, size) in food {
+            for (_, portal, portal_size, _) in &placed {
+                assert!(collide(position, size, *portal, *portal_size).is_none());
+            }
+        }
+    }
+}
+
+#[test]
+fn no_portals_unless_asked_for() {
+    let mut game = started(open_arena(), Difficulty::Easy);
+    assert!(portals(&mut game).is_empty());
+}
+
+// Every mouth's cell, along with the cell of its exit
+fn pairs(game: &mut Headless) -> Vec<(IVec2, IVec2)> {
+    let config = game.app.world.resource::<GameConfig>().clone();
+    let placed = portals(game);
+    let cell = |entity: Entity| {
+        let (_, position, ..) = placed.iter().find(|(each, ..)| *each == entity).unwrap();
+        grid_cell(&config, *position)
+    };
+    let mut pairs: Vec<(IVec2, IVec2)> = placed
+        .iter()
+        .map(|(entity, _, _, exit)| (cell(*entity), cell(*exit)))
+        .collect();
+    pairs.sort_by_key(|(cell, _)| (cell.x, cell.y));
+    pairs
+}
+
+#[test]
+fn a_resumed_run_gets_its_portals_back() {
+    let mut game = started(open_arena(), Difficulty::Easy);
+    let start = head_cell(&mut game);
+    let entry = start - IVec2::new(2, 0);
+    let exit = IVec2::new(5, 8);
+    place_portals(&mut game, entry, exit);
+    for _ in 0..40 {
+        game.tick(1);
+        if head_cell(&mut game).y == exit.y {
+            break;
+        }
+    }
+    assert_eq!(head_cell(&mut game), exit, "the head never came out");
+    let expected = pairs(&mut game);
+
+    let saved = game.save_and_quit();
+    let mut resumed = Headless::resumed(open_arena(), saved, |app| {
+        app.insert_resource(Difficulty::Easy);
+    });
+    assert_eq!(pairs(&mut resumed), expected);
+    // Still on its way out of the exit, and not sent back through it
+    let head = resumed.head();
+    let in_portal = **resumed.app.world.get::<InPortal>(head).unwrap();
+    let (_, position, ..) = portals(&mut resumed)
+        .into_iter()
+        .find(|(portal, ..)| *portal == in_portal)
+        .unwrap();
+    let config = open_arena();
+    assert_eq!(grid_cell(&config, position), exit);
+    for _ in 0..10 {
+        resumed.tick(1);
+        assert_eq!(head_cell(&mut resumed).y, exit.y);
+    }
+}
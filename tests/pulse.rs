@@ -0,0 +1,113 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::Headless;
+use snake::collision::Hitbox;
+use snake::config::GameConfig;
+use snake::food::FoodKind;
+use snake::game::Paused;
+use snake::pulse::{start_eat_pulses, update_eat_pulses, EatPulse, PULSE_GROWTH};
+
+// The pulse's systems, on sprites given to the snake by hand since the
+// headless app draws none
+fn started() -> Headless {
+    let mut game = Headless::with_setup(GameConfig::default(), |app| {
+        app.add_system(start_eat_pulses)
+            .add_system(update_eat_pulses.after(start_eat_pulses));
+    });
+    game.start_game();
+    game.eat(2);
+    let head = game.head();
+    let mut parts = game.segments();
+    parts.push(head);
+    for part in parts {
+        let size = **game.app.world.get::<Hitbox>(part).unwrap();
+        game.app.world.entity_mut(part).insert(Sprite {
+            custom_size: Some(size),
+            ..default()
+        });
+    }
+    game
+}
+
+fn head_size(game: &mut Headless) -> (Vec2, Vec2) {
+    let head = game.head();
+    let sprite = game
+        .app
+        .world
+        .get::<Sprite>(head)
+        .unwrap()
+        .custom_size
+        .unwrap();
+    (sprite, **game.app.world.get::<Hitbox>(head).unwrap())
+}
+
+#[test]
+fn eating_swells_the_head_and_lets_it_go_back() {
+    let mut game = started();
+    let head = game.head();
+    game.eat_now(head, FoodKind::Normal);
+    game.tick(5);
+    let (sprite, hitbox) = head_size(&mut game);
+    assert!(sprite.x > hitbox.x, "the head didn't swell");
+    assert!(sprite.x <= hitbox.x * (1.0 + PULSE_GROWTH) + 1e-3);
+
+    game.tick(30);
+    let (sprite, hitbox) = head_size(&mut game);
+    assert_eq!(sprite, hitbox);
+    assert!(game.app.world.get::<EatPulse>(head).is_none());
+}
+
+#[test]
+fn eating_again_starts_the_pulse_over_instead_of_stacking_it() {
+    let mut game = started();
+    let head = game.head();
+    let mut largest: f32 = 0.0;
+    for _ in 0..10 {
+        game.eat_now(head, FoodKind::Normal);
+        game.tick(2);
+        largest = largest.max(head_size(&mut game).0.x);
+    }
+    let (_, hitbox) = head_size(&mut game);
+    assert!(largest <= hitbox.x * (1.0 + PULSE_GROWTH) + 1e-3);
+}
+
+#[test]
+fn the_pulse_runs_down_the_body() {
+    let mut game = started();
+    let head = game.head();
+    // The segment grown from the food comes after the pulse started
+    let segments = game.segments();
+    game.eat_now(head, FoodKind::Normal);
+    let delays: Vec<f32> = segments
+        .into_iter()
+        .map(|segment| game.app.world.get::<EatPulse>(segment).unwrap().delay)
+        .collect();
+    assert_eq!(delays.len(), 2);
+    assert!(delays.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn the_pulse_holds_while_paused() {
+    let mut game = started();
+    let head = game.head();
+    game.eat_now(head, FoodKind::Normal);
+    **game.app.world.resource_mut::<Paused>() = true;
+    let before = game
+        .app
+        .world
+        .get::<EatPulse>(head)
+        .unwrap()
+        .timer
+        .elapsed();
+    game.tick(30);
+    let after = game
+        .app
+        .world
+        .get::<EatPulse>(head)
+        .unwrap()
+        .timer
+        .elapsed();
+    assert_eq!(before, after);
+}
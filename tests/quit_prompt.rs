@@ -0,0 +1,57 @@
+mod common;
+
+use bevy::app::AppExit;
+use bevy::ecs::event::Events;
+use bevy::prelude::*;
+
+use common::Headless;
+use snake::config::GameConfig;
+use snake::input::{confirm_quit, QuitPrompt};
+use snake::state::GameState;
+
+fn on_the_menu() -> Headless {
+    let game = Headless::with_setup(GameConfig::default(), |app| {
+        app.init_resource::<Input<KeyCode>>()
+            .init_resource::<QuitPrompt>()
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(confirm_quit));
+    });
+    assert_eq!(game.state(), GameState::Menu);
+    game
+}
+
+fn press(game: &mut Headless, key: KeyCode) {
+    game.app.world.resource_mut::<Input<KeyCode>>().press(key);
+    game.tick(1);
+    let mut keyboard_input = game.app.world.resource_mut::<Input<KeyCode>>();
+    keyboard_input.release(key);
+    keyboard_input.clear();
+}
+
+fn quitting(game: &Headless) -> bool {
+    let events = game.app.world.resource::<Events<AppExit>>();
+    events.get_reader().iter(events).next().is_some()
+}
+
+fn prompt(game: &Headless) -> bool {
+    **game.app.world.resource::<QuitPrompt>()
+}
+
+#[test]
+fn esc_on_the_menu_asks_first() {
+    let mut game = on_the_menu();
+    press(&mut game, KeyCode::Escape);
+    assert!(prompt(&game));
+    assert!(!quitting(&game));
+
+    press(&mut game, KeyCode::Return);
+    assert!(quitting(&game));
+}
+
+#[test]
+fn esc_again_stays_in_the_game() {
+    let mut game = on_the_menu();
+    press(&mut game, KeyCode::Escape);
+    press(&mut game, KeyCode::Escape);
+    assert!(!prompt(&game));
+    assert!(!quitting(&game));
+}
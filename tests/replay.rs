@@ -0,0 +1,88 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::Headless;
+use snake::config::GameConfig;
+use snake::food::Food;
+use snake::game::Scoreboard;
+use snake::replay::{ReplayPlayer, ReplayRecorder};
+use snake::snake::Direction;
+use snake::state::GameState;
+
+// Long enough for any run to hit a wall
+const MAX_TICKS: usize = 10_000;
+// How long the test steers towards the food before letting the snake crash
+const STEERED_TICKS: usize = 1_500;
+
+// Runs until the snake dies and returns its score and length. The body
+// goes while the snake is dying, so its length is taken the tick before, or
+// is the `length` it had then if it already died.
+fn finish_run(game: &mut Headless, mut length: usize) -> (usize, usize) {
+    for _ in 0..MAX_TICKS {
+        if game.dying() {
+            game.play_out_death();
+            assert_eq!(game.state(), GameState::GameOver);
+            let score = game.app.world.resource::<Scoreboard>().score;
+            return (score, length);
+        }
+        length = game.segments().len() + 1;
+        game.tick(1);
+    }
+    panic!("the snake never died");
+}
+
+// Turns towards the food whenever nothing is queued
+fn steer_towards_food(game: &mut Headless) {
+    let head = game.head_position();
+    let Some(food) = game
+        .app
+        .world
+        .query_filtered::<&Transform, With<Food>>()
+        .iter(&game.app.world)
+        .next()
+        .map(|transform| transform.translation)
+    else {
+        return;
+    };
+    let mut queue = game.queue();
+    if queue.is_empty() {
+        let offset = (food - head).truncate();
+        let turn = if offset.x.abs() > 1.0 {
+            Direction::from_vec2(Vec2::new(offset.x, 0.0))
+        } else {
+            Direction::from_vec2(Vec2::new(0.0, offset.y))
+        };
+        queue.push_back(turn);
+    }
+}
+
+#[test]
+fn a_replay_ends_like_the_recorded_run() {
+    let config = GameConfig {
+        seed: Some(42),
+        ..GameConfig::default()
+    };
+
+    let mut game = Headless::new(config.clone());
+    game.start_game();
+    let mut length = 1;
+    for _ in 0..STEERED_TICKS {
+        if game.dying() {
+            break;
+        }
+        length = game.segments().len() + 1;
+        steer_towards_food(&mut game);
+        game.tick(1);
+    }
+    let recorded = finish_run(&mut game, length);
+    assert!(recorded.0 > 0, "the test never reached the food");
+    let replay = game.app.world.resource::<ReplayRecorder>().replay.clone();
+    assert_eq!((replay.final_score, replay.final_length), recorded);
+
+    // The replay starts playing by itself
+    let mut game = Headless::with_setup(config, |app| {
+        app.insert_resource(ReplayPlayer::new(replay));
+    });
+    assert_eq!(finish_run(&mut game, 1), recorded);
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use std::fs;
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use common::Headless;
+use snake::collision::DeathCause;
+use snake::config::GameConfig;
+use snake::food::FoodKind;
+use snake::game::{Difficulty, GameMode, RoundTimer, TIME_ATTACK_SECONDS};
+use snake::locale::{Language, Locale};
+use snake::run_stats::{RunStats, RUN_CSV_HEADER};
+use snake::state::GameState;
+use snake::storage::append_csv_row;
+use snake::ui::results_rows;
+use snake::walls::WallLocation;
+
+fn run_stats(game: &Headless) -> RunStats {
+    game.app.world.resource::<RunStats>().clone()
+}
+
+#[test]
+fn a_run_into_the_wall_is_recorded_as_it_went() {
+    let mut game = Headless::new(GameConfig {
+        seed: Some(8),
+        ..GameConfig::default()
+    });
+    game.start_game();
+    game.eat(2);
+    // Heading left until the wall kills it
+    for _ in 0..600 {
+        if game.state() == GameState::GameOver {
+            break;
+        }
+        game.tick(1);
+    }
+    assert_eq!(game.state(), GameState::GameOver);
+
+    let run = run_stats(&game);
+    assert_eq!(run.seed, 8);
+    assert_eq!(run.difficulty, Difficulty::Normal);
+    assert_eq!(run.score, 2);
+    assert_eq!(run.foods_eaten, 2);
+    assert_eq!(run.max_length, 3);
+    assert_eq!(run.cause, Some(DeathCause::Wall(WallLocation::Left)));
+    assert!(run.duration_secs > 0.0);
+    assert!(run.csv_row().ends_with(",2,3,2,wall,Normal"));
+
+    // The next run starts over
+    game.start_game();
+    let run = run_stats(&game);
+    assert_eq!(run.foods_eaten, 0);
+    assert_eq!(run.cause, None);
+}
+
+#[test]
+fn the_results_are_counted_while_the_run_goes() {
+    let mut game = Headless::with_setup(GameConfig::default(), |app| {
+        app.insert_resource(Difficulty::Hard);
+    });
+    game.start_game();
+    game.eat(1);
+    game.eat_kind(1, FoodKind::Bonus);
+    // Three more right after the bonus food make a combo of four
+    let head = game.head();
+    for _ in 0..3 {
+        game.eat_now(head, FoodKind::Normal);
+    }
+    let wall = game.app.world.resource::<GameConfig>().left_wall;
+    game.app
+        .world
+        .get_mut::<Transform>(head)
+        .unwrap()
+        .translation = Vec3::new(wall, 0.0, 0.0);
+    game.tick(1);
+    game.play_out_death();
+    assert_eq!(game.state(), GameState::GameOver);
+
+    // Still there with the snake gone
+    let run = run_stats(&game);
+    assert_eq!(run.foods_eaten, 5);
+    assert_eq!(run.bonus_foods_eaten, 1);
+    assert_eq!(run.best_combo, 4);
+    assert_eq!(run.length, 6);
+    assert!(run.average_speed() > 0.0);
+
+    let locale = Locale::load(Language::English);
+    let rows = results_rows(&locale, &run);
+    assert_eq!(rows[3], ("results.bonus_foods", "1".to_string()));
+    assert_eq!(rows[4], ("results.best_combo", "x4".to_string()));
+}
+
+#[test]
+fn a_time_attack_run_ends_with_the_time_up() {
+    let mut game = Headless::with_setup(GameConfig::default(), |app| {
+        app.insert_resource(GameMode::TimeAttack)
+            .insert_resource(Difficulty::Easy);
+    });
+    game.start_game();
+    let almost = Duration::from_secs_f32(TIME_ATTACK_SECONDS - 0.1);
+    game.app
+        .world
+        .resource_mut::<RoundTimer>()
+        .set_elapsed(almost);
+    game.tick(10);
+    assert_eq!(game.state(), GameState::GameOver);
+    assert_eq!(run_stats(&game).cause, Some(DeathCause::TimeUp));
+}
+
+#[test]
+fn the_runs_file_gets_its_header_once() {
+    let dir = std::env::temp_dir().join(format!("snake_runs_{}", std::process::id()));
+    let path = dir.join("nested").join("runs.csv");
+    append_csv_row(&path, RUN_CSV_HEADER, "1").unwrap();
+    append_csv_row(&path, RUN_CSV_HEADER, "2").unwrap();
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        format!("{RUN_CSV_HEADER}\n1\n2\n")
+    );
+
+    // A folder in the way fails without panicking
+    assert!(append_csv_row(&dir, RUN_CSV_HEADER, "3").is_err());
+    fs::remove_dir_all(&dir).unwrap();
+}
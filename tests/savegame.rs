@@ -0,0 +1,78 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::Headless;
+use snake::config::GameConfig;
+use snake::food::Food;
+use snake::game::{GameTick, Paused, Scoreboard};
+use snake::savegame::{SaveAndQuit, SavedGameSlot};
+use snake::snake::Direction;
+use snake::state::GameState;
+
+// How long the run goes on before it is saved, and after it is continued
+const TICKS_BEFORE_SAVE: u64 = 40;
+const TICKS_AFTER_LOAD: u64 = 30;
+
+// Plays the same turns in every run so they stay comparable
+fn play_until(game: &mut Headless, tick: u64) {
+    while **game.app.world.resource::<GameTick>() < tick {
+        let now = **game.app.world.resource::<GameTick>();
+        if now == 10 {
+            game.queue().push_back(Direction::Up);
+        }
+        game.tick(1);
+    }
+}
+
+fn snapshot(game: &mut Headless) -> (Vec3, Vec<Vec3>, usize, usize) {
+    let head = game.head_position();
+    let food = game
+        .app
+        .world
+        .query_filtered::<&Transform, With<Food>>()
+        .iter(&game.app.world)
+        .map(|transform| transform.translation)
+        .collect();
+    let score = game.app.world.resource::<Scoreboard>().score;
+    let length = game.segments().len();
+    (head, food, score, length)
+}
+
+#[test]
+fn a_continued_run_plays_out_like_one_never_saved() {
+    let config = GameConfig {
+        seed: Some(7),
+        ..GameConfig::default()
+    };
+
+    let mut uninterrupted = Headless::new(config.clone());
+    uninterrupted.start_game();
+    play_until(&mut uninterrupted, TICKS_BEFORE_SAVE + TICKS_AFTER_LOAD);
+    let expected = snapshot(&mut uninterrupted);
+
+    // Pause first, like a player would before saving
+    let mut saved = Headless::new(config.clone());
+    saved.start_game();
+    play_until(&mut saved, TICKS_BEFORE_SAVE);
+    **saved.app.world.resource_mut::<Paused>() = true;
+    saved.app.world.send_event(SaveAndQuit);
+    saved.tick(1);
+    assert_eq!(saved.state(), GameState::Menu);
+    let save = saved.app.world.resource_mut::<SavedGameSlot>().saved.take();
+    assert!(save.is_some(), "the run was not saved");
+
+    let mut continued = Headless::with_setup(config, |app| {
+        app.insert_resource(SavedGameSlot {
+            saved: save,
+            resuming: true,
+        });
+    });
+    continued.start_game();
+    assert_eq!(
+        **continued.app.world.resource::<GameTick>(),
+        TICKS_BEFORE_SAVE
+    );
+    play_until(&mut continued, TICKS_BEFORE_SAVE + TICKS_AFTER_LOAD);
+    assert_eq!(snapshot(&mut continued), expected);
+}
@@ -0,0 +1,28 @@
+use std::path::Path;
+
+use snake::screenshot::{screenshot_path, unpad_rows};
+
+#[test]
+fn readback_rows_lose_their_padding_and_come_out_as_rgba() {
+    // Two pixels a row, padded out to 12 bytes
+    let data = [
+        1, 2, 3, 4, 5, 6, 7, 8, 0, 0, 0, 0, //
+        9, 10, 11, 12, 13, 14, 15, 16, 0, 0, 0, 0,
+    ];
+    assert_eq!(
+        unpad_rows(&data, 2, 2, 12, false),
+        [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16]
+    );
+    assert_eq!(
+        unpad_rows(&data, 2, 2, 12, true),
+        [3, 2, 1, 4, 7, 6, 5, 8, 11, 10, 9, 12, 15, 14, 13, 16]
+    );
+}
+
+#[test]
+fn screenshots_are_named_after_when_they_were_taken() {
+    assert_eq!(
+        screenshot_path(1234),
+        Path::new("screenshots").join("snake_1234.png")
+    );
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::Headless;
+use snake::config::GameConfig;
+use snake::food::FoodKind;
+use snake::game::Difficulty;
+use snake::snake::{Direction, Shield, Snake};
+use snake::state::GameState;
+
+// Plenty for the snake to get from the middle to a wall
+const TICKS_TO_WALL: usize = 600;
+
+// On Hard the walls are lethal
+fn started() -> Headless {
+    let config = GameConfig {
+        seed: Some(5),
+        ..GameConfig::default()
+    };
+    let mut game = Headless::with_setup(config, |app| {
+        app.insert_resource(Difficulty::Hard);
+    });
+    game.start_game();
+    game
+}
+
+fn shields(game: &mut Headless) -> usize {
+    **game
+        .app
+        .world
+        .query_filtered::<&Shield, With<Snake>>()
+        .single(&game.app.world)
+}
+
+fn heading(game: &mut Headless) -> Direction {
+    *game
+        .app
+        .world
+        .query_filtered::<&Direction, With<Snake>>()
+        .single(&game.app.world)
+}
+
+// Ticks until the shield is used up, failing if the snake dies instead
+fn hit_wall(game: &mut Headless) {
+    for _ in 0..TICKS_TO_WALL {
+        game.tick(1);
+        assert_eq!(game.state(), GameState::Playing);
+        if shields(game) == 0 {
+            return;
+        }
+    }
+    panic!("the snake never reached the wall");
+}
+
+#[test]
+fn shields_do_not_stack_past_the_cap() {
+    let mut game = started();
+    game.eat_kind(2, FoodKind::Shield);
+    assert_eq!(shields(&mut game), GameConfig::default().max_shields);
+}
+
+#[test]
+fn a_shield_bounces_a_lone_head_off_the_wall() {
+    let mut game = started();
+    game.eat_kind(1, FoodKind::Shield);
+    assert_eq!(heading(&mut game), Direction::Left);
+
+    hit_wall(&mut game);
+    assert_eq!(heading(&mut game), Direction::Right);
+    game.tick(10);
+    assert_eq!(game.state(), GameState::Playing);
+}
+
+#[test]
+fn a_shield_turns_a_long_snake_around() {
+    let mut game = started();
+    game.eat(3);
+    game.eat_kind(1, FoodKind::Shield);
+
+    hit_wall(&mut game);
+    // The head comes out of the end of the tail, heading away from the body
+    assert_eq!(heading(&mut game), Direction::Right);
+    game.tick(1);
+    let head = game.head_position();
+    let segments = game.segments();
+    for segment in segments {
+        let position = game
+            .app
+            .world
+            .get::<Transform>(segment)
+            .unwrap()
+            .translation;
+        assert!(position.x < head.x);
+    }
+    game.tick(10);
+    assert_eq!(game.state(), GameState::Playing);
+    assert_eq!(game.segments().len(), 3);
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use common::Headless;
+use snake::config::GameConfig;
+use snake::food::{FoodKind, SLOW_MOTION_FACTOR, SLOW_MOTION_SECONDS};
+use snake::game::Difficulty;
+use snake::snake::{GameSpeed, SpeedModifier};
+
+fn ticks(config: &GameConfig, seconds: f32) -> usize {
+    (seconds / config.time_step).ceil() as usize
+}
+
+fn started(config: &GameConfig) -> Headless {
+    // The walls don't kill on Easy, so the snake outlives the slow motion
+    let mut game = Headless::with_setup(config.clone(), |app| {
+        app.insert_resource(Difficulty::Easy);
+    });
+    game.start_game();
+    game
+}
+
+// How far the head gets in `seconds`
+fn distance(game: &mut Headless, config: &GameConfig, seconds: f32) -> f32 {
+    let start = game.head_position();
+    game.tick(ticks(config, seconds));
+    start.distance(game.head_position())
+}
+
+#[test]
+fn slow_motion_halves_the_ramped_speed() {
+    let config = GameConfig {
+        seed: Some(5),
+        ..GameConfig::default()
+    };
+    let mut normal = started(&config);
+    normal.eat(1);
+    let mut slowed = started(&config);
+    slowed.eat(1);
+    slowed.eat_kind(1, FoodKind::SlowMotion);
+    normal.tick(common::TICKS_PER_FOOD + 1);
+
+    // The food eaten while slowed still ramps the speed up underneath
+    slowed.eat(1);
+    normal.eat(1);
+    let ramped = **normal.app.world.resource::<GameSpeed>();
+    assert_eq!(**slowed.app.world.resource::<GameSpeed>(), ramped);
+
+    let full = distance(&mut normal, &config, 0.5);
+    let half = distance(&mut slowed, &config, 0.5);
+    let expected = full * SLOW_MOTION_FACTOR;
+    assert!(
+        (half - expected).abs() <= config.snake_size.x,
+        "moved {half} instead of about {expected}"
+    );
+}
+
+#[test]
+fn a_second_pickup_extends_the_slow_motion() {
+    let config = GameConfig {
+        seed: Some(5),
+        ..GameConfig::default()
+    };
+    let mut game = started(&config);
+    game.eat_kind(1, FoodKind::SlowMotion);
+    game.tick(ticks(&config, SLOW_MOTION_SECONDS / 2.0));
+    game.eat_kind(1, FoodKind::SlowMotion);
+
+    let remaining = game.app.world.resource::<SpeedModifier>().remaining_secs();
+    assert!(remaining > SLOW_MOTION_SECONDS);
+
+    game.tick(ticks(&config, remaining));
+    assert!(!game.app.world.resource::<SpeedModifier>().is_active());
+}
@@ -0,0 +1,131 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::Headless;
+use snake::config::GameConfig;
+use snake::display::DisplaySettings;
+use snake::snake::{Player, Snake, SnakeSegment};
+use snake::theme::{
+    color_distance, recolor_sprites, shade_snake_bodies, switch_theme, SnakeSwatch, Theme,
+    ThemeName, MIN_FOOD_CONTRAST,
+};
+use snake::walls::ArenaConfig;
+
+#[test]
+fn the_swatches_cycle_both_ways_and_wrap_around() {
+    assert_eq!(SnakeSwatch::Theme.cycle(1), SnakeSwatch::Green);
+    assert_eq!(SnakeSwatch::Theme.cycle(-1), SnakeSwatch::White);
+    let all = SnakeSwatch::ALL.len() as isize;
+    assert_eq!(SnakeSwatch::Red.cycle(all), SnakeSwatch::Red);
+}
+
+#[test]
+fn the_theme_swatch_leaves_every_theme_as_it_is() {
+    let config = GameConfig::default();
+    let arena = ArenaConfig::from_config(&config);
+    for name in ThemeName::ALL {
+        let theme = Theme::new(name, &config, &arena);
+        assert_eq!(theme.clone().with_swatch(SnakeSwatch::Theme), theme);
+    }
+}
+
+#[test]
+fn a_swatch_colors_the_first_snake_only() {
+    let config = GameConfig::default();
+    let arena = ArenaConfig::from_config(&config);
+    let (head, tail) = SnakeSwatch::Blue.colors().unwrap();
+    for name in ThemeName::ALL {
+        let plain = Theme::new(name, &config, &arena);
+        let theme = plain.clone().with_swatch(SnakeSwatch::Blue);
+        assert_eq!(theme.snake, head);
+        assert_eq!(theme.snake_tail, tail);
+        assert_eq!(theme.snake_color(Some(&Player::TWO)), plain.player_two);
+        assert_eq!(theme.player_two_tail, plain.player_two_tail);
+    }
+}
+
+#[test]
+fn the_food_is_turned_away_from_a_snake_of_its_color() {
+    let config = GameConfig::default();
+    let arena = ArenaConfig::from_config(&config);
+    let red = Theme::new(ThemeName::Classic, &config, &arena).with_swatch(SnakeSwatch::Red);
+    assert_ne!(red.food, config.food_color);
+    assert!(color_distance(red.food, red.snake) >= MIN_FOOD_CONTRAST);
+    assert!(color_distance(red.food, red.snake_tail) >= MIN_FOOD_CONTRAST);
+
+    for name in ThemeName::ALL {
+        for swatch in SnakeSwatch::ALL {
+            let theme = Theme::new(name, &config, &arena).with_swatch(swatch);
+            let contrast = color_distance(theme.food, theme.snake)
+                .min(color_distance(theme.food, theme.snake_tail));
+            assert!(contrast >= MIN_FOOD_CONTRAST, "{name:?} with {swatch:?}");
+        }
+    }
+}
+
+#[test]
+fn settings_saved_before_there_were_swatches_keep_the_theme_colors() {
+    let settings: DisplaySettings = ron::from_str("(theme: Dark)").unwrap();
+    assert_eq!(settings.snake_swatch, SnakeSwatch::Theme);
+
+    let picked = DisplaySettings {
+        snake_swatch: SnakeSwatch::Orange,
+        ..DisplaySettings::default()
+    };
+    let saved = ron::to_string(&picked).unwrap();
+    assert_eq!(ron::from_str::<DisplaySettings>(&saved).unwrap(), picked);
+}
+
+type NewSnakePart = Or<(Added<Snake>, Added<SnakeSegment>)>;
+
+// Plain white sprites the way `add_sprites` would give them, without the
+// textures the headless app has no assets for
+fn add_snake_sprites(mut commands: Commands, query: Query<Entity, NewSnakePart>) {
+    for part in &query {
+        commands.entity(part).insert(Sprite::default());
+    }
+}
+
+#[test]
+fn picking_a_swatch_recolors_the_snake_right_away() {
+    let mut game = Headless::with_setup(
+        GameConfig {
+            seed: Some(4),
+            obstacle_count: 0,
+            ..GameConfig::default()
+        },
+        |app| {
+            app.init_resource::<DisplaySettings>()
+                .init_resource::<ClearColor>()
+                .init_resource::<Theme>()
+                .add_system(switch_theme)
+                .add_system(recolor_sprites.after(switch_theme))
+                .add_system_to_stage(CoreStage::PostUpdate, add_snake_sprites)
+                .add_system_to_stage(CoreStage::Last, shade_snake_bodies);
+        },
+    );
+    game.start_game();
+    game.tick(1);
+    game.eat(2);
+
+    game.app
+        .world
+        .resource_mut::<DisplaySettings>()
+        .snake_swatch = SnakeSwatch::Purple;
+    game.tick(1);
+    let theme = game.app.world.resource::<Theme>().clone();
+    assert_eq!(theme.swatch, SnakeSwatch::Purple);
+    let head = game.head();
+    assert_eq!(
+        game.app.world.get::<Sprite>(head).unwrap().color,
+        SnakeSwatch::Purple.color().unwrap()
+    );
+    let segments = game.segments();
+    for (index, segment) in segments.iter().enumerate() {
+        assert_eq!(
+            game.app.world.get::<Sprite>(*segment).unwrap().color,
+            theme.segment_color(Some(&Player::ONE), index, segments.len())
+        );
+    }
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use bevy::ecs::system::CommandQueue;
+use bevy::prelude::*;
+
+use common::Headless;
+use snake::collision::Hitbox;
+use snake::config::GameConfig;
+use snake::crates::{Crate, CRATE_HITS};
+use snake::food::{BonusFood, Food, FoodKind, BONUS_FOOD_SCALE};
+use snake::hunter::Hunter;
+use snake::portal::Portal;
+use snake::snake::{Direction, GameSpeed, MovementMode, Player, Snake, Velocity};
+use snake::spawning::{
+    spawn_bonus_food, spawn_crate, spawn_food, spawn_hunter, spawn_ice_patch, spawn_portals,
+    spawn_snake,
+};
+use snake::walls::Obstacle;
+
+// Runs `spawn` on the game's world and hands back what it returned
+fn spawned<T>(game: &mut Headless, spawn: impl FnOnce(&mut Commands, &GameConfig) -> T) -> T {
+    let config = game.app.world.resource::<GameConfig>().clone();
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, &game.app.world);
+    let spawned = spawn(&mut commands, &config);
+    queue.apply(&mut game.app.world);
+    spawned
+}
+
+#[test]
+fn the_helpers_hand_back_what_they_built() {
+    let mut game = Headless::new(GameConfig::default());
+    let config = game.app.world.resource::<GameConfig>().clone();
+    let position = Vec3::new(40.0, -20.0, 0.0);
+
+    let food = spawned(&mut game, |commands, config| {
+        spawn_food(commands, config, position)
+    });
+    let entity = game.app.world.entity(food);
+    assert!(entity.contains::<Food>());
+    assert_eq!(entity.get::<FoodKind>(), Some(&FoodKind::Normal));
+    assert_eq!(**entity.get::<Hitbox>().unwrap(), config.food_size);
+    assert_eq!(entity.get::<Transform>().unwrap().translation, position);
+
+    let bonus = spawned(&mut game, |commands, config| {
+        spawn_bonus_food(commands, config, position, 3.0)
+    });
+    let entity = game.app.world.entity(bonus);
+    assert!(entity.contains::<BonusFood>());
+    assert_eq!(
+        **entity.get::<Hitbox>().unwrap(),
+        config.food_size * BONUS_FOOD_SCALE
+    );
+
+    let head = spawned(&mut game, |commands, config| {
+        spawn_snake(
+            commands,
+            config,
+            MovementMode::Continuous,
+            GameSpeed(1.0),
+            Player::TWO,
+            position,
+            Direction::Up,
+        )
+    });
+    let entity = game.app.world.entity(head);
+    assert!(entity.contains::<Snake>());
+    assert_eq!(entity.get::<Player>(), Some(&Player::TWO));
+    assert_eq!(
+        **entity.get::<Velocity>().unwrap(),
+        Vec2::Y * config.snake_speed
+    );
+}
+
+#[test]
+fn the_pieces_put_down_in_the_arena_are_where_they_were_put() {
+    let mut game = Headless::new(GameConfig::default());
+    let position = Vec3::new(-60.0, 80.0, 0.0);
+    let far = Vec3::new(100.0, -100.0, 0.0);
+
+    let crate_entity = spawned(&mut game, |commands, config| {
+        spawn_crate(commands, config, position)
+    });
+    let entity = game.app.world.entity(crate_entity);
+    assert!(entity.contains::<Obstacle>());
+    assert_eq!(entity.get::<Crate>().unwrap().hits_remaining, CRATE_HITS);
+
+    let (first, second) = spawned(&mut game, |commands, config| {
+        spawn_portals(commands, config, (position, far))
+    });
+    assert_eq!(game.app.world.get::<Portal>(first).unwrap().exit, second);
+    assert_eq!(game.app.world.get::<Portal>(second).unwrap().exit, first);
+
+    let ice = spawned(&mut game, |commands, config| {
+        spawn_ice_patch(commands, position, config.snake_size * 2.0)
+    });
+    let ice = game.app.world.get::<Transform>(ice).unwrap().translation;
+    assert_eq!(ice.truncate(), position.truncate());
+
+    let hunter = spawned(&mut game, |commands, config| {
+        spawn_hunter(commands, config, far)
+    });
+    let entity = game.app.world.entity(hunter);
+    assert!(!entity.get::<Hunter>().unwrap().crashed);
+    assert_eq!(entity.get::<Transform>().unwrap().translation, far);
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use common::Headless;
+use snake::config::GameConfig;
+use snake::food::FoodKind;
+use snake::game::{Difficulty, Paused};
+use snake::snake::GameSpeed;
+use snake::state::GameState;
+use snake::statistics::Statistics;
+
+// The walls don't kill on Easy, so the snake lives as long as a test needs
+fn started() -> Headless {
+    let mut game = Headless::with_setup(
+        GameConfig {
+            seed: Some(21),
+            ..GameConfig::default()
+        },
+        |app| {
+            app.insert_resource(Difficulty::Easy);
+        },
+    );
+    game.start_game();
+    game
+}
+
+fn statistics(game: &Headless) -> Statistics {
+    game.app.world.resource::<Statistics>().clone()
+}
+
+#[test]
+fn the_distance_is_what_the_head_really_moved() {
+    let mut game = started();
+    let start = game.head_position();
+    // Half a second of heading straight for the left wall
+    game.tick(30);
+    let moved = game.head_position().distance(start);
+    assert!(moved > 0.0, "the snake didn't move");
+    assert_eq!(statistics(&game).distance, moved);
+}
+
+#[test]
+fn standing_still_adds_no_distance() {
+    let mut game = started();
+    game.tick(10);
+    let before = statistics(&game).distance;
+
+    **game.app.world.resource_mut::<Paused>() = true;
+    game.tick(30);
+    assert_eq!(statistics(&game).distance, before);
+
+    **game.app.world.resource_mut::<Paused>() = false;
+    game.app.insert_resource(GameSpeed(0.0));
+    game.tick(30);
+    assert_eq!(statistics(&game).distance, before);
+}
+
+#[test]
+fn foods_and_length_add_up_and_a_game_over_counts_the_game() {
+    let mut game = started();
+    game.eat(3);
+    let during = statistics(&game);
+    assert_eq!(during.foods_eaten, 3);
+    assert_eq!(during.longest_snake, 4);
+    assert_eq!(during.games_played, 0);
+    assert!(during.longest_game_secs > 0.0);
+
+    // Poison takes the body first, then the head
+    let mut poisons = 0;
+    while !game.dying() {
+        game.eat_kind(1, FoodKind::Poison);
+        poisons += 1;
+    }
+    game.play_out_death();
+    assert_eq!(game.state(), GameState::GameOver);
+    let after = statistics(&game);
+    assert_eq!(after.foods_eaten, 3 + poisons);
+    assert_eq!(after.longest_snake, 4);
+    assert_eq!(after.games_played, 1);
+}
@@ -0,0 +1,140 @@
+mod common;
+
+use std::time::Duration;
+
+use bevy::{ecs::system::CommandQueue, prelude::*};
+
+use common::Headless;
+use snake::config::GameConfig;
+use snake::food::Food;
+use snake::game::GameMode;
+use snake::snake::{cell_center, grid_cell, GameSpeed};
+use snake::spawning::spawn_food;
+use snake::state::GameState;
+use snake::walls::{ring_bounds, ArenaBounds, InteriorWall, ShrinkingArena, SHRINK_SECONDS};
+
+// A survival run with the snake held still in the middle
+fn survival() -> Headless {
+    let mut game = Headless::with_setup(
+        GameConfig {
+            seed: Some(6),
+            ..GameConfig::default()
+        },
+        |app| {
+            app.insert_resource(GameMode::Survival);
+        },
+    );
+    game.start_game();
+    game.tick(5);
+    *game.app.world.resource_mut::<GameSpeed>() = GameSpeed(0.0);
+    game
+}
+
+fn config(game: &Headless) -> GameConfig {
+    game.app.world.resource::<GameConfig>().clone()
+}
+
+fn bounds(game: &Headless) -> ArenaBounds {
+    *game.app.world.resource::<ArenaBounds>()
+}
+
+// Skips to just before the next ring of walls goes up
+fn almost_shrinking(game: &mut Headless) {
+    let almost = Duration::from_secs_f32(SHRINK_SECONDS - 0.01);
+    game.app
+        .world
+        .resource_mut::<ShrinkingArena>()
+        .next
+        .set_elapsed(almost);
+}
+
+fn rings(game: &Headless) -> i32 {
+    game.app.world.resource::<ShrinkingArena>().rings
+}
+
+fn walls(game: &mut Headless) -> usize {
+    game.app
+        .world
+        .query_filtered::<Entity, With<InteriorWall>>()
+        .iter(&game.app.world)
+        .count()
+}
+
+fn foods(game: &mut Headless) -> usize {
+    game.app
+        .world
+        .query_filtered::<Entity, With<Food>>()
+        .iter(&game.app.world)
+        .count()
+}
+
+#[test]
+fn food_under_the_new_walls_is_put_back_inside() {
+    let mut game = survival();
+    let config = config(&game);
+    let (corner, _) = ring_bounds(&config, &bounds(&game), 0);
+    let mut queue = CommandQueue::default();
+    let buried = spawn_food(
+        &mut Commands::new(&mut queue, &game.app.world),
+        &config,
+        cell_center(&config, corner),
+    );
+    queue.apply(&mut game.app.world);
+    let food_count = foods(&mut game);
+    let wall_count = walls(&mut game);
+
+    almost_shrinking(&mut game);
+    game.tick(2);
+    assert_eq!(rings(&game), 1);
+    assert_eq!(walls(&mut game), wall_count + 4);
+    assert!(game.app.world.get_entity(buried).is_none());
+    assert_eq!(foods(&mut game), food_count);
+
+    let (min, max) = ring_bounds(&config, &bounds(&game), 1);
+    let mut food_query = game.app.world.query_filtered::<&Transform, With<Food>>();
+    for transform in food_query.iter(&game.app.world) {
+        let cell = grid_cell(&config, transform.translation);
+        assert!(cell.cmpge(min).all() && cell.cmple(max).all());
+    }
+}
+
+#[test]
+fn a_snake_under_the_new_walls_is_crushed() {
+    let mut game = survival();
+    let config = config(&game);
+    let (_, corner) = ring_bounds(&config, &bounds(&game), 0);
+    let head = game.head();
+    game.app
+        .world
+        .get_mut::<Transform>(head)
+        .unwrap()
+        .translation = cell_center(&config, corner);
+
+    almost_shrinking(&mut game);
+    game.tick(2);
+    game.play_out_death();
+    assert_eq!(game.state(), GameState::GameOver);
+}
+
+#[test]
+fn the_arena_stops_shrinking_at_its_smallest() {
+    let mut game = survival();
+    let config = config(&game);
+    let mut smallest = 0;
+    while (ShrinkingArena {
+        rings: smallest,
+        ..default()
+    })
+    .can_shrink(&config, &bounds(&game))
+    {
+        smallest += 1;
+    }
+    game.app.world.resource_mut::<ShrinkingArena>().rings = smallest;
+    let wall_count = walls(&mut game);
+
+    almost_shrinking(&mut game);
+    game.tick(30);
+    assert_eq!(rings(&game), smallest);
+    assert_eq!(walls(&mut game), wall_count);
+    assert_eq!(game.state(), GameState::Playing);
+}
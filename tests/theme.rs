@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+
+use snake::config::GameConfig;
+use snake::display::DisplaySettings;
+use snake::food::FoodKind;
+use snake::snake::Player;
+use snake::theme::{Theme, ThemeName};
+use snake::walls::ArenaConfig;
+
+#[test]
+fn the_themes_cycle_both_ways_and_wrap_around() {
+    assert_eq!(ThemeName::Classic.cycle(1), ThemeName::Dark);
+    assert_eq!(ThemeName::Classic.cycle(-1), ThemeName::RetroAmber);
+    assert_eq!(ThemeName::RetroAmber.cycle(1), ThemeName::Classic);
+    let all = ThemeName::ALL.len() as isize;
+    assert_eq!(ThemeName::HighContrast.cycle(all), ThemeName::HighContrast);
+}
+
+#[test]
+fn the_classic_theme_is_the_config_and_the_special_food_keeps_its_colors() {
+    let config = GameConfig {
+        snake_color: Color::rgb(0.1, 0.2, 0.3),
+        ..GameConfig::default()
+    };
+    let arena = ArenaConfig {
+        color: Color::rgb(0.4, 0.5, 0.6),
+        ..ArenaConfig::from_config(&config)
+    };
+    let classic = Theme::new(ThemeName::Classic, &config, &arena);
+    assert_eq!(classic.snake, config.snake_color);
+    assert_eq!(classic.wall, arena.color);
+
+    for name in ThemeName::ALL {
+        let theme = Theme::new(name, &config, &arena);
+        let poison = theme.sprite_color(&config, Some(&FoodKind::Poison), None, false, false);
+        assert_eq!(poison, config.poison_food_color);
+        let player_two = theme.sprite_color(&config, None, Some(&Player::TWO), true, false);
+        assert_eq!(player_two, theme.player_two);
+        assert_eq!(
+            theme.sprite_color(&config, None, None, false, true),
+            theme.obstacle
+        );
+        assert_eq!(
+            theme.sprite_color(&config, None, None, false, false),
+            theme.wall
+        );
+    }
+}
+
+#[test]
+fn settings_saved_before_there_were_themes_load_as_classic() {
+    let settings: DisplaySettings = ron::from_str("(fullscreen: true)").unwrap();
+    assert!(settings.fullscreen);
+    assert_eq!(settings.theme, ThemeName::Classic);
+}
+
+#[test]
+fn the_body_darkens_from_the_head_to_the_tail() {
+    let config = GameConfig::default();
+    let arena = ArenaConfig::from_config(&config);
+    for name in ThemeName::ALL {
+        let theme = Theme::new(name, &config, &arena);
+        for player in [Player::ONE, Player::TWO] {
+            let (head, tail) = match player {
+                Player::TWO => (theme.player_two, theme.player_two_tail),
+                _ => (theme.snake, theme.snake_tail),
+            };
+            let length = 5;
+            let shades: Vec<Vec4> = (0..length)
+                .map(|index| Vec4::from(theme.segment_color(Some(&player), index, length)))
+                .collect();
+            assert!(shades[length - 1].abs_diff_eq(Vec4::from(tail), 1e-6));
+            let distance = |shade: &Vec4| shade.distance(Vec4::from(head));
+            assert!(shades
+                .windows(2)
+                .all(|pair| distance(&pair[0]) < distance(&pair[1])));
+        }
+    }
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use std::time::Duration;
+
+use common::Headless;
+use snake::config::GameConfig;
+use snake::game::{Difficulty, GameMode, Paused, RoundTimer, Scoreboard, TIME_ATTACK_SECONDS};
+use snake::state::GameState;
+
+fn time_attack() -> Headless {
+    // The walls don't kill on Easy, so the snake outlives the clock
+    let mut game = Headless::with_setup(
+        GameConfig {
+            seed: Some(4),
+            ..GameConfig::default()
+        },
+        |app| {
+            app.insert_resource(GameMode::TimeAttack)
+                .insert_resource(Difficulty::Easy);
+        },
+    );
+    game.start_game();
+    game
+}
+
+fn remaining(game: &Headless) -> f32 {
+    game.app.world.resource::<RoundTimer>().remaining_secs()
+}
+
+#[test]
+fn the_run_ends_when_the_clock_runs_out() {
+    let mut game = time_attack();
+    game.eat(2);
+
+    // Skip to the last moment of the run
+    let almost = Duration::from_secs_f32(TIME_ATTACK_SECONDS - 0.1);
+    game.app
+        .world
+        .resource_mut::<RoundTimer>()
+        .set_elapsed(almost);
+    game.tick(3);
+    assert_eq!(game.state(), GameState::Playing);
+    game.tick(10);
+    assert_eq!(game.state(), GameState::GameOver);
+    assert_eq!(game.app.world.resource::<Scoreboard>().score, 2);
+}
+
+#[test]
+fn the_clock_stops_while_paused() {
+    let mut game = time_attack();
+    game.tick(30);
+    let before = remaining(&game);
+    assert!(before < TIME_ATTACK_SECONDS);
+
+    **game.app.world.resource_mut::<Paused>() = true;
+    game.tick(30);
+    assert_eq!(remaining(&game), before);
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::Headless;
+use snake::config::GameConfig;
+use snake::display::DisplaySettings;
+use snake::game::{GameMode, GameTick};
+use snake::state::GameState;
+use snake::tutorial::{
+    advance_tutorial, hold_countdown_for_tutorial, start_tutorial, Tutorial, TUTORIAL_STEPS,
+};
+
+// The tutorial's systems without its overlay, which needs the renderer
+fn started(settings: DisplaySettings, mode: GameMode) -> Headless {
+    let mut game = Headless::with_setup(GameConfig::default(), |app| {
+        app.insert_resource(settings)
+            .insert_resource(mode)
+            .init_resource::<Input<KeyCode>>()
+            .init_resource::<Tutorial>()
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(start_tutorial))
+            .add_system_to_stage(CoreStage::PreUpdate, advance_tutorial)
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                hold_countdown_for_tutorial.after(advance_tutorial),
+            );
+    });
+    game.start_game();
+    game
+}
+
+fn press(game: &mut Headless, key: KeyCode) {
+    game.app.world.resource_mut::<Input<KeyCode>>().press(key);
+    game.tick(1);
+    let mut input = game.app.world.resource_mut::<Input<KeyCode>>();
+    input.release(key);
+    input.clear();
+}
+
+fn tick_count(game: &Headless) -> u64 {
+    **game.app.world.resource::<GameTick>()
+}
+
+fn step(game: &Headless) -> Option<usize> {
+    **game.app.world.resource::<Tutorial>()
+}
+
+#[test]
+fn the_first_game_waits_for_the_tutorial_to_be_read() {
+    let mut game = started(DisplaySettings::default(), GameMode::Classic);
+    assert_eq!(step(&game), Some(0));
+    game.tick(300);
+    assert_eq!(tick_count(&game), 0, "the game ran under the tutorial");
+
+    for shown in 1..TUTORIAL_STEPS.len() {
+        press(&mut game, KeyCode::Return);
+        assert_eq!(step(&game), Some(shown));
+    }
+    press(&mut game, KeyCode::Space);
+    assert_eq!(step(&game), None);
+    assert!(game.app.world.resource::<DisplaySettings>().tutorial_seen);
+
+    // Then the 3-2-1 runs as usual
+    game.skip_countdown();
+    game.tick(10);
+    assert!(tick_count(&game) > 0);
+}
+
+#[test]
+fn the_tutorial_can_be_skipped_and_is_not_shown_again() {
+    let mut game = started(DisplaySettings::default(), GameMode::Classic);
+    press(&mut game, KeyCode::Escape);
+    assert_eq!(step(&game), None);
+    assert!(game.app.world.resource::<DisplaySettings>().tutorial_seen);
+
+    game.app
+        .world
+        .resource_mut::<State<GameState>>()
+        .set(GameState::Menu)
+        .unwrap();
+    game.tick(1);
+    game.start_game();
+    assert_eq!(step(&game), None);
+}
+
+#[test]
+fn versus_leaves_the_tutorial_for_later() {
+    let game = started(DisplaySettings::default(), GameMode::Versus);
+    assert_eq!(step(&game), None);
+    assert!(!game.app.world.resource::<DisplaySettings>().tutorial_seen);
+}
+
+#[test]
+fn settings_saved_before_there_was_a_tutorial_still_get_it() {
+    let settings: DisplaySettings = ron::from_str("(colorblind: true)").unwrap();
+    assert!(!settings.tutorial_seen);
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::{Headless, TICKS_PER_FOOD};
+use snake::config::GameConfig;
+use snake::food::FoodKind;
+use snake::game::{GameMode, Scoreboard, VersusTally};
+use snake::input::ControlScheme;
+use snake::snake::{Dying, GameSpeed, Player, SegmentOrder, Snake};
+use snake::state::GameState;
+
+fn versus() -> Headless {
+    let mut game = Headless::with_setup(
+        GameConfig {
+            seed: Some(9),
+            ..GameConfig::default()
+        },
+        |app| {
+            app.insert_resource(GameMode::Versus);
+        },
+    );
+    game.start_game();
+    game.tick(5);
+    game
+}
+
+fn head_of(game: &mut Headless, player: Player) -> Entity {
+    game.app
+        .world
+        .query_filtered::<(Entity, &Player), With<Snake>>()
+        .iter(&game.app.world)
+        .find(|(_, owner)| **owner == player)
+        .map(|(head, _)| head)
+        .unwrap()
+}
+
+fn feed(game: &mut Headless, player: Player, foods: usize) {
+    for _ in 0..foods {
+        game.tick(TICKS_PER_FOOD);
+        let snake = head_of(game, player);
+        game.eat_by(snake, FoodKind::Normal);
+    }
+}
+
+// Holds both snakes still and puts `player`'s head at `position`
+fn teleport(game: &mut Headless, player: Player, position: Vec3) {
+    *game.app.world.resource_mut::<GameSpeed>() = GameSpeed(0.0);
+    let head = head_of(game, player);
+    game.app
+        .world
+        .get_mut::<Transform>(head)
+        .unwrap()
+        .translation = position;
+}
+
+#[test]
+fn each_player_scores_and_grows_on_their_own() {
+    let mut game = versus();
+    feed(&mut game, Player::TWO, 2);
+
+    let scoreboard = game.app.world.resource::<Scoreboard>();
+    assert_eq!((scoreboard.score, scoreboard.player_two), (0, 2));
+    let heads = [
+        head_of(&mut game, Player::ONE),
+        head_of(&mut game, Player::TWO),
+    ];
+    let lengths = heads.map(|head| game.app.world.get::<SegmentOrder>(head).unwrap().len());
+    assert_eq!(lengths, [0, 2]);
+}
+
+#[test]
+fn running_into_the_other_body_loses_the_round() {
+    let mut game = versus();
+    feed(&mut game, Player::ONE, 3);
+    game.tick(TICKS_PER_FOOD);
+    let head = head_of(&mut game, Player::ONE);
+    let segment = game.app.world.get::<SegmentOrder>(head).unwrap()[1];
+    let position = game
+        .app
+        .world
+        .get::<Transform>(segment)
+        .unwrap()
+        .translation;
+
+    teleport(&mut game, Player::TWO, position);
+    game.tick(1);
+    game.play_out_death();
+    assert_eq!(game.state(), GameState::GameOver);
+    let tally = game.app.world.resource::<VersusTally>();
+    assert_eq!(tally.last_winner, Some(Player::ONE));
+    assert_eq!(tally.wins, [1, 0]);
+}
+
+#[test]
+fn a_head_on_collision_is_a_draw() {
+    let mut game = versus();
+    let head = head_of(&mut game, Player::ONE);
+    let position = game.app.world.get::<Transform>(head).unwrap().translation;
+
+    teleport(&mut game, Player::TWO, position);
+    game.tick(1);
+    // Both snakes play out their death before the round is over
+    let mut dying = game.app.world.query::<&Dying>();
+    assert_eq!(dying.iter(&game.app.world).count(), 2);
+    game.play_out_death();
+    assert_eq!(game.state(), GameState::GameOver);
+    let tally = game.app.world.resource::<VersusTally>();
+    assert_eq!(tally.last_winner, None);
+    assert_eq!(tally.wins, [0, 0]);
+}
+
+#[test]
+fn the_second_player_takes_wasd_away_from_the_first() {
+    let controls = ControlScheme::default();
+    let keys = |player, mode| -> Vec<KeyCode> {
+        controls
+            .player_bindings(player, mode)
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect()
+    };
+    assert!(keys(Player::ONE, GameMode::Classic).contains(&KeyCode::W));
+    assert!(!keys(Player::ONE, GameMode::Versus).contains(&KeyCode::W));
+    assert!(keys(Player::ONE, GameMode::Versus).contains(&KeyCode::Up));
+    assert_eq!(keys(Player::TWO, GameMode::Versus).len(), 4);
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy::sprite::collide_aabb::collide;
+
+use common::Headless;
+use snake::collision::Hitbox;
+use snake::config::GameConfig;
+use snake::food::{Food, SpawnAnimation, WANDER_SPEED};
+use snake::game::Difficulty;
+use snake::snake::{GameSpeed, Velocity};
+use snake::walls::{ArenaBounds, Obstacle};
+
+// The snake holds still, so the food is left to wander
+fn started(wandering_food: bool) -> Headless {
+    let mut game = Headless::with_setup(
+        GameConfig {
+            seed: Some(11),
+            obstacle_count: 8,
+            simultaneous_food: 3,
+            wandering_food,
+            ..GameConfig::default()
+        },
+        |app| {
+            app.insert_resource(Difficulty::Easy)
+                .insert_resource(GameSpeed(0.0));
+        },
+    );
+    game.start_game();
+    game
+}
+
+fn food(game: &mut Headless) -> Vec<(Entity, Vec3, Vec2)> {
+    game.app
+        .world
+        .query_filtered::<(Entity, &Transform, &Hitbox), With<Food>>()
+        .iter(&game.app.world)
+        .map(|(food, transform, hitbox)| (food, transform.translation, **hitbox))
+        .collect()
+}
+
+#[test]
+fn food_wanders_slowly_and_never_into_walls_or_obstacles() {
+    let mut game = started(true);
+    let config = game.app.world.resource::<GameConfig>().clone();
+    let bounds = *game.app.world.resource::<ArenaBounds>();
+    let obstacles: Vec<(Vec3, Vec2)> = game
+        .app
+        .world
+        .query_filtered::<(&Transform, &Hitbox), With<Obstacle>>()
+        .iter(&game.app.world)
+        .map(|(transform, hitbox)| (transform.translation, **hitbox))
+        .collect();
+    assert!(!obstacles.is_empty());
+
+    let start = food(&mut game);
+    let mut moved = false;
+    let mut previous = start.clone();
+    for _ in 0..600 {
+        game.tick(1);
+        let now = food(&mut game);
+        for (entity, position, size) in &now {
+            let (min, max) = bounds.inside(*size);
+            let inside = position.truncate().clamp(min, max) == position.truncate();
+            assert!(inside, "food wandered out to {position}");
+            for (obstacle, obstacle_size) in &obstacles {
+                assert!(collide(*position, *size, *obstacle, *obstacle_size).is_none());
+            }
+            if let Some((_, before, _)) = previous.iter().find(|(food, ..)| food == entity) {
+                let step = before.distance(*position);
+                assert!(step <= WANDER_SPEED * config.time_step + 1e-3);
+                moved |= step > 0.0;
+            }
+        }
+        previous = now;
+    }
+    assert!(moved, "the food never moved");
+}
+
+#[test]
+fn the_same_seed_wanders_the_same_way() {
+    let mut first = started(true);
+    let mut second = started(true);
+    first.tick(300);
+    second.tick(300);
+    let positions = |game: &mut Headless| -> Vec<Vec3> {
+        food(game)
+            .into_iter()
+            .map(|(_, position, _)| position)
+            .collect()
+    };
+    assert_eq!(positions(&mut first), positions(&mut second));
+}
+
+#[test]
+fn food_stays_put_unless_asked_to_wander() {
+    let mut game = started(false);
+    let before = food(&mut game);
+    game.tick(300);
+    assert_eq!(food(&mut game), before);
+}
+
+// Where every food is and which way it is going, in a fixed order
+fn food_motion(game: &mut Headless) -> Vec<(Vec3, Vec2)> {
+    let mut motion: Vec<(Vec3, Vec2)> = game
+        .app
+        .world
+        .query_filtered::<(&Transform, &Velocity), With<Food>>()
+        .iter(&game.app.world)
+        .map(|(transform, velocity)| (transform.translation, **velocity))
+        .collect();
+    motion.sort_by(|(a, _), (b, _)| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    motion
+}
+
+#[test]
+fn a_resumed_run_wanders_on_the_way_it_would_have() {
+    let mut game = started(true);
+    let mut unsaved = started(true);
+    game.tick(40);
+    unsaved.tick(40);
+    assert_eq!(food_motion(&mut game), food_motion(&mut unsaved));
+
+    let config = game.app.world.resource::<GameConfig>().clone();
+    let saved = game.save_and_quit();
+    let mut resumed = Headless::resumed(config, saved, |app| {
+        app.insert_resource(Difficulty::Easy);
+    });
+    assert_eq!(food_motion(&mut resumed), food_motion(&mut unsaved));
+    let popping = resumed
+        .app
+        .world
+        .query_filtered::<(), (With<Food>, With<SpawnAnimation>)>()
+        .iter(&resumed.app.world)
+        .count();
+    assert_eq!(popping, 0, "the food popped up all over again");
+    // Nothing moves yet on the tick the skipped countdown ends on
+    resumed.tick(1);
+    assert_eq!(food_motion(&mut resumed), food_motion(&mut unsaved));
+
+    // Long enough for every food to have turned at least once
+    for _ in 0..150 {
+        resumed.tick(1);
+        unsaved.tick(1);
+        assert_eq!(food_motion(&mut resumed), food_motion(&mut unsaved));
+    }
+}